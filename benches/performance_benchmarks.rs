@@ -3,6 +3,7 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
 use kraken_ws_sdk::{
     parser::{KrakenDataParser, DataParser, MessageHandler},
+    wire::{V1Frame, V2Frame},
     events::EventDispatcher,
     orderbook::{OrderBookManager, OrderBook},
     data::*,
@@ -207,6 +208,7 @@ fn bench_event_dispatching(c: &mut Criterion) {
         last_price: Decimal::from_str("50000.5").unwrap(),
         volume: Decimal::from_str("100.0").unwrap(),
         timestamp: Utc::now(),
+        ..Default::default()
     };
     
     c.bench_function("dispatch_ticker_to_10_callbacks", |b| {
@@ -218,8 +220,6 @@ fn bench_event_dispatching(c: &mut Criterion) {
 
 // Benchmark with different message sizes
 fn bench_message_sizes(c: &mut Criterion) {
-    let parser = KrakenDataParser::new();
-    
     let mut group = c.benchmark_group("message_parsing_by_size");
     
     // Small message
@@ -277,6 +277,61 @@ fn bench_message_sizes(c: &mut Criterion) {
     group.finish();
 }
 
+// Benchmark the zero-copy path against decoding through a `Value` tree
+fn bench_zero_copy_parsing(c: &mut Criterion) {
+    let parser = KrakenDataParser::new();
+    
+    let levels = |start: i32, step: i32| (0..100)
+        .map(|i| format!(r#"["{}.00000","1.00000000","1234567890.123456"]"#, start + i * step))
+        .collect::<Vec<_>>()
+        .join(",");
+    let v1_book = format!(
+        r#"[336,{{"bs":[{}],"as":[{}]}},"book-100","XBT/USD"]"#,
+        levels(50000, -1), levels(50001, 1)
+    );
+    
+    let v2_levels = |start: i32, step: i32| (0..100)
+        .map(|i| format!(r#"{{"price":{}.1,"qty":0.25}}"#, start + i * step))
+        .collect::<Vec<_>>()
+        .join(",");
+    let v2_book = format!(
+        r#"{{"channel":"book","type":"snapshot","data":[{{"symbol":"BTC/USD","bids":[{}],"asks":[{}],"checksum":3310070434}}]}}"#,
+        v2_levels(50000, -1), v2_levels(50001, 1)
+    );
+    
+    let mut group = c.benchmark_group("book_decoding");
+    
+    group.bench_function("v1_value_tree", |b| {
+        b.iter(|| {
+            let frame: Vec<serde_json::Value> = serde_json::from_str(black_box(&v1_book)).unwrap();
+            let _ = parser.parse_v1_frame(&frame, "book-100");
+        })
+    });
+    
+    group.bench_function("v1_zero_copy", |b| {
+        b.iter(|| {
+            let frame = V1Frame::parse(black_box(&v1_book)).unwrap();
+            let _ = parser.parse_v1_raw(&frame, frame.channel_name);
+        })
+    });
+    
+    group.bench_function("v2_value_tree", |b| {
+        b.iter(|| {
+            let json: serde_json::Value = serde_json::from_str(black_box(&v2_book)).unwrap();
+            let _ = parser.parse_v2_value(&json);
+        })
+    });
+    
+    group.bench_function("v2_zero_copy", |b| {
+        b.iter(|| {
+            let frame = V2Frame::parse(black_box(&v2_book)).unwrap();
+            let _ = parser.parse_v2_raw(&frame);
+        })
+    });
+    
+    group.finish();
+}
+
 // Benchmark concurrent operations
 fn bench_concurrent_operations(c: &mut Criterion) {
     let manager = Arc::new(OrderBookManager::new());
@@ -311,6 +366,7 @@ criterion_group!(
     bench_orderbook_calculations,
     bench_event_dispatching,
     bench_message_sizes,
+    bench_zero_copy_parsing,
     bench_concurrent_operations
 );
 
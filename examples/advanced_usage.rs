@@ -8,7 +8,6 @@ use kraken_ws_sdk::{
 };
 use std::sync::{Arc, atomic::{AtomicU64, Ordering}};
 use std::time::Duration;
-use std::collections::HashMap;
 
 /// Advanced callback with metrics and error handling
 struct AdvancedCallback {
@@ -27,14 +26,6 @@ impl AdvancedCallback {
             error_count: AtomicU64::new(0),
         }
     }
-    
-    fn print_stats(&self) {
-        println!("📊 Message Statistics:");
-        println!("   Tickers: {}", self.ticker_count.load(Ordering::Relaxed));
-        println!("   Trades: {}", self.trade_count.load(Ordering::Relaxed));
-        println!("   Order Books: {}", self.orderbook_count.load(Ordering::Relaxed));
-        println!("   Errors: {}", self.error_count.load(Ordering::Relaxed));
-    }
 }
 
 impl EventCallback for AdvancedCallback {
@@ -42,8 +33,8 @@ impl EventCallback for AdvancedCallback {
         self.ticker_count.fetch_add(1, Ordering::Relaxed);
         
         // Advanced processing: calculate spread percentage
-        let spread = &data.ask - &data.bid;
-        let spread_pct = (&spread / &data.last_price) * rust_decimal::Decimal::from(100);
+        let spread = data.ask - data.bid;
+        let spread_pct = (spread / data.last_price) * rust_decimal::Decimal::from(100);
         
         if self.ticker_count.load(Ordering::Relaxed) % 10 == 0 {
             println!("📊 {} - Last: {}, Spread: {} ({:.4}%)", 
@@ -85,7 +76,7 @@ impl EventCallback for AdvancedCallback {
     
     fn on_ohlc(&self, data: OHLCData) {
         // Calculate price change percentage
-        let change = (&data.close - &data.open) / &data.open * rust_decimal::Decimal::from(100);
+        let change = (data.close - data.open) / data.open * rust_decimal::Decimal::from(100);
         println!("📈 {} [{}]: O:{} H:{} L:{} C:{} ({:+.2}%)", 
             data.symbol, data.interval, data.open, data.high, 
             data.low, data.close, change);
@@ -141,23 +132,15 @@ impl EventCallback for AdvancedCallback {
 
 /// Performance monitoring callback
 struct PerformanceCallback {
-    start_time: std::time::Instant,
     message_count: AtomicU64,
 }
 
 impl PerformanceCallback {
     fn new() -> Self {
         Self {
-            start_time: std::time::Instant::now(),
             message_count: AtomicU64::new(0),
         }
     }
-    
-    fn get_throughput(&self) -> f64 {
-        let elapsed = self.start_time.elapsed().as_secs_f64();
-        let count = self.message_count.load(Ordering::Relaxed) as f64;
-        if elapsed > 0.0 { count / elapsed } else { 0.0 }
-    }
 }
 
 impl EventCallback for PerformanceCallback {
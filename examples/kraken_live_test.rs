@@ -25,13 +25,6 @@ impl LiveTestCallback {
             orderbook_count: AtomicU64::new(0),
         }
     }
-    
-    fn print_stats(&self) {
-        println!("📊 Live Data Statistics:");
-        println!("   Tickers received: {}", self.ticker_count.load(Ordering::Relaxed));
-        println!("   Trades received: {}", self.trade_count.load(Ordering::Relaxed));
-        println!("   Order book updates: {}", self.orderbook_count.load(Ordering::Relaxed));
-    }
 }
 
 impl EventCallback for LiveTestCallback {
@@ -40,8 +33,8 @@ impl EventCallback for LiveTestCallback {
         
         // Print every 5th ticker to avoid spam
         if count % 5 == 0 {
-            let spread = &data.ask - &data.bid;
-            let spread_pct = (&spread / &data.last_price) * rust_decimal::Decimal::from(100);
+            let spread = data.ask - data.bid;
+            let spread_pct = (spread / data.last_price) * rust_decimal::Decimal::from(100);
             
             println!("🎯 LIVE TICKER #{}: {} - Last: ${}, Bid: ${}, Ask: ${}, Spread: {:.4}%", 
                 count, data.symbol, data.last_price, data.bid, data.ask, spread_pct);
@@ -76,7 +69,7 @@ impl EventCallback for LiveTestCallback {
     }
     
     fn on_ohlc(&self, data: OHLCData) {
-        let change = (&data.close - &data.open) / &data.open * rust_decimal::Decimal::from(100);
+        let change = (data.close - data.open) / data.open * rust_decimal::Decimal::from(100);
         println!("📈 LIVE OHLC: {} [{}min] O:${} H:${} L:${} C:${} ({:+.2}%) Vol:{}", 
             data.symbol, data.interval, data.open, data.high, 
             data.low, data.close, change, data.volume);
@@ -139,6 +132,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             max_delay: Duration::from_secs(30),
             backoff_multiplier: 2.0,
         },
+        ..Default::default()
     };
     
     // Validate configuration
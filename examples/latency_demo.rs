@@ -25,6 +25,7 @@ fn main() {
         histogram_bucket_us: 5000,  // 5ms buckets
         histogram_buckets: 20,      // Up to 100ms
         rate_window_secs: 5,
+        breakdown_samples: 100,     // Per channel / per symbol
     };
     
     let tracker = LatencyTracker::with_config(config);
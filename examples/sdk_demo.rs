@@ -3,7 +3,7 @@
 //! This example demonstrates the minimal but powerful SDK API.
 //! Run with: cargo run --example sdk_demo
 
-use kraken_ws_sdk::prelude::*;
+use kraken_ws_sdk::KrakenSDK;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
@@ -3,7 +3,6 @@
 //! Production-grade sequence validation and gap detection.
 //! Run with: cargo run --example sequencing_demo
 
-use kraken_ws_sdk::prelude::*;
 use kraken_ws_sdk::{SequenceManager, SequenceConfig};
 
 fn main() {
@@ -24,7 +24,7 @@ use std::time::Duration;
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize logging
     tracing_subscriber::fmt()
-        .with_env_filter("info,kraken_ws_sdk=debug")
+        .with_max_level(tracing::Level::INFO)
         .init();
 
     println!("╔══════════════════════════════════════════════════════════════╗");
@@ -183,6 +183,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                         println!("   💰 BALANCE: {} = {} (available: {})",
                                             update.asset, update.balance, update.available);
                                     }
+                                    Ok(PrivateEvent::Gap(gap)) => {
+                                        println!("   ⚠️  {} missed {} messages", gap.channel, gap.gap_size);
+                                    }
+                                    Ok(PrivateEvent::Resync(resync)) => {
+                                        println!("   🔄 Resyncing {}", resync.channel);
+                                    }
                                     Err(_) => break,
                                 }
                             }
@@ -81,10 +81,10 @@ impl Alert {
                 format!("{} {} {} ${:.2} (threshold: ${:.2})", symbol, direction, price, price, threshold)
             }
             AlertType::OrderFilled { txid, symbol, side, volume, price } => {
-                format!("Order filled: {} {} {} @ ${:.2} [{}]", side.to_uppercase(), volume, symbol, price, &txid[..8])
+                format!("Order filled: {} {} {} @ ${:.2} [{}]", side.to_uppercase(), volume, symbol, price, &txid[..txid.len().min(8)])
             }
             AlertType::OrderCancelled { txid, reason } => {
-                format!("Order cancelled: {} - {}", &txid[..8], reason)
+                format!("Order cancelled: {} - {}", &txid[..txid.len().min(8)], reason)
             }
             AlertType::PnlAlert { symbol, pnl, pnl_percent } => {
                 let sign = if *pnl >= 0.0 { "+" } else { "" };
@@ -236,12 +236,12 @@ impl AlertManager {
     /// Send an alert to all channels
     pub async fn send(&mut self, alert: Alert) {
         // Check severity filter
-        let dominated = match (&alert.severity, &self.min_severity) {
-            (AlertSeverity::Info, AlertSeverity::Warning) => true,
-            (AlertSeverity::Info, AlertSeverity::Critical) => true,
-            (AlertSeverity::Warning, AlertSeverity::Critical) => true,
-            _ => false,
-        };
+        let dominated = matches!(
+            (&alert.severity, &self.min_severity),
+            (AlertSeverity::Info, AlertSeverity::Warning)
+                | (AlertSeverity::Info, AlertSeverity::Critical)
+                | (AlertSeverity::Warning, AlertSeverity::Critical)
+        );
 
         if dominated {
             return;
@@ -0,0 +1,642 @@
+//! Trade and candle gap backfill after reconnects
+//!
+//! Trades and candles published while the socket was down are never resent
+//! by the WebSocket API. [`BackfillTracker`] remembers the last trade and
+//! candle seen for each pair; after a reconnect the client pages through REST
+//! `Trades` and `OHLC` from there and dispatches what was missed, oldest
+//! first, with `backfilled` set.
+//!
+//! Trades are deduplicated against live data by trade id. v1 trades carry
+//! no exchange id, so for them the first live trade after the reconnect
+//! bounds the backfill instead, and trades at the same time are told apart
+//! by price and volume.
+//!
+//! Live data is not held back while a backfill runs. The backfill is its own
+//! task, so live trades and candles received after the reconnect are usually
+//! delivered before the older ones it recovers. Within a pair, backfilled
+//! events arrive oldest first, are marked `backfilled` and never repeat a
+//! live event; consumers that need strict time order should order by
+//! `timestamp`.
+
+use crate::data::{Channel, OHLCData, TradeData};
+use crate::error::SdkError;
+use crate::events::EventDispatcher;
+use crate::rest_client::KrakenPublicClient;
+use crate::symbols::{SymbolFormat, SymbolNormalizer, SymbolRegistry};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// REST pages fetched per pair and channel unless configured otherwise
+pub const DEFAULT_MAX_PAGES: usize = 10;
+
+/// Exchange trade ids remembered per pair for deduplication
+const SEEN_TRADE_IDS: usize = 2048;
+
+/// Trade and candle backfill settings
+#[derive(Debug, Clone)]
+pub struct BackfillConfig {
+    /// Client for REST `Trades` and `OHLC` requests, including its rate limiter
+    pub public_client: KrakenPublicClient,
+    /// REST pages fetched per pair and channel before giving up (up to 1000
+    /// trades or 720 candles each)
+    pub max_pages: usize,
+}
+
+impl BackfillConfig {
+    pub fn new(public_client: KrakenPublicClient) -> Self {
+        Self {
+            public_client,
+            max_pages: DEFAULT_MAX_PAGES,
+        }
+    }
+
+    pub fn with_max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = max_pages;
+        self
+    }
+}
+
+impl Default for BackfillConfig {
+    fn default() -> Self {
+        Self::new(KrakenPublicClient::new())
+    }
+}
+
+/// Last trade seen for a pair
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LastTrade {
+    pub trade_id: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// What to do with one entry of a REST history page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backfilled {
+    /// Missed while disconnected; dispatch it
+    Emit,
+    /// Already dispatched, or older than the gap
+    Skip,
+    /// The live feed covers this and everything after it
+    Stop,
+}
+
+/// Last trades and candles seen per pair, shared by the live feed and the
+/// backfill that follows a reconnect
+///
+/// Clones share the same state.
+#[derive(Clone, Default)]
+pub struct BackfillTracker {
+    state: Arc<Mutex<TrackerState>>,
+}
+
+#[derive(Default)]
+struct TrackerState {
+    trades: HashMap<String, TradeState>,
+    /// Keyed by symbol and interval
+    candles: HashMap<(String, String), CandleState>,
+}
+
+/// Identifies a trade without an exchange id
+type TradeKey = (DateTime<Utc>, Decimal, Decimal);
+
+fn trade_key(trade: &TradeData) -> TradeKey {
+    (trade.timestamp, trade.price, trade.volume)
+}
+
+#[derive(Default)]
+struct TradeState {
+    last: Option<LastTrade>,
+    /// Live trades at the time of `last`
+    last_keys: Vec<TradeKey>,
+    seen: VecDeque<u64>,
+    seen_set: HashSet<u64>,
+    /// Backfilled trades, oldest first, that the live feed may repeat
+    /// without an id; dropped once the live feed moves past them
+    backfilled: VecDeque<TradeKey>,
+    /// Live trades at the start of the gap, which REST history repeats
+    since_keys: Vec<TradeKey>,
+    gap: Gap,
+}
+
+#[derive(Default)]
+struct CandleState {
+    /// Start of the newest candle seen
+    last: Option<DateTime<Utc>>,
+    /// Start of the newest backfilled candle
+    watermark: Option<DateTime<Utc>>,
+    gap: Gap,
+}
+
+/// Bounds of the history missed during a reconnect
+#[derive(Default)]
+struct Gap {
+    /// Last time seen before the reconnect
+    since: Option<DateTime<Utc>>,
+    /// First live time at or after `since` once reconnected
+    resumed_at: Option<DateTime<Utc>>,
+    /// Whether the live feed carries exchange trade ids that REST ids can
+    /// be matched against
+    matched_by_id: bool,
+}
+
+impl Gap {
+    fn observe_live(&mut self, timestamp: DateTime<Utc>) {
+        if self.resumed_at.is_none() && self.since.is_some_and(|since| timestamp >= since) {
+            self.resumed_at = Some(timestamp);
+        }
+    }
+
+    fn resumed_by(&self, timestamp: DateTime<Utc>) -> bool {
+        self.resumed_at.is_some_and(|resumed_at| timestamp >= resumed_at)
+    }
+}
+
+impl TradeState {
+    /// Remember an exchange trade id; false if it was already seen
+    fn remember(&mut self, id: u64) -> bool {
+        if !self.seen_set.insert(id) {
+            return false;
+        }
+        self.seen.push_back(id);
+        if self.seen.len() > SEEN_TRADE_IDS {
+            if let Some(oldest) = self.seen.pop_front() {
+                self.seen_set.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    fn record(&mut self, trade: &TradeData) {
+        match &self.last {
+            Some(last) if trade.timestamp < last.timestamp => return,
+            Some(last) if trade.timestamp == last.timestamp => {}
+            _ => self.last_keys.clear(),
+        }
+        self.last_keys.push(trade_key(trade));
+        self.last = Some(LastTrade {
+            trade_id: trade.trade_id.clone(),
+            timestamp: trade.timestamp,
+        });
+    }
+
+    /// Match an id-less live trade against the backfill; true if it was
+    /// already dispatched from REST history
+    fn take_backfilled(&mut self, trade: &TradeData) -> bool {
+        while self.backfilled.front().is_some_and(|(timestamp, _, _)| *timestamp < trade.timestamp) {
+            self.backfilled.pop_front();
+        }
+        let key = trade_key(trade);
+        match self.backfilled.iter().position(|backfilled| *backfilled == key) {
+            Some(index) => {
+                self.backfilled.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl BackfillTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a live trade; false if it duplicates one already dispatched
+    pub fn observe_trade(&self, trade: &TradeData) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let entry = state.trades.entry(trade.symbol.clone()).or_default();
+
+        let id = exchange_trade_id(&trade.trade_id);
+        let new = match id {
+            Some(id) => entry.remember(id),
+            None => !entry.take_backfilled(trade),
+        };
+        // A live repeat of a backfilled trade still shows where the feed is
+        if new || id.is_none() {
+            entry.gap.observe_live(trade.timestamp);
+            entry.record(trade);
+        }
+        new
+    }
+
+    /// Record a live candle update
+    pub fn observe_candle(&self, candle: &OHLCData) {
+        let begin = candle.interval_begin();
+        let mut state = self.state.lock().unwrap();
+        let entry = state.candles.entry((candle.symbol.clone(), candle.interval.clone())).or_default();
+        entry.gap.observe_live(begin);
+        if entry.last.map_or(true, |last| begin > last) {
+            entry.last = Some(begin);
+        }
+    }
+
+    /// Last trade seen live for a pair
+    pub fn last_trade(&self, symbol: &str) -> Option<LastTrade> {
+        let state = self.state.lock().unwrap();
+        state.trades.get(symbol).and_then(|entry| entry.last.clone())
+    }
+
+    /// Start of the last candle seen for a pair and interval
+    pub fn last_candle(&self, symbol: &str, interval: &str) -> Option<DateTime<Utc>> {
+        let state = self.state.lock().unwrap();
+        state.candles.get(&(symbol.to_string(), interval.to_string())).and_then(|entry| entry.last)
+    }
+
+    /// Mark where each pair's gap starts; call before reading from a new
+    /// connection
+    pub fn begin_gap(&self) {
+        let mut state = self.state.lock().unwrap();
+        for entry in state.trades.values_mut() {
+            entry.gap = Gap {
+                since: entry.last.as_ref().map(|last| last.timestamp),
+                resumed_at: None,
+                matched_by_id: entry.last.as_ref().is_some_and(|last| exchange_trade_id(&last.trade_id).is_some()),
+            };
+            entry.since_keys = entry.last_keys.clone();
+            entry.backfilled.clear();
+        }
+        for entry in state.candles.values_mut() {
+            entry.gap = Gap {
+                since: entry.last,
+                ..Gap::default()
+            };
+            entry.watermark = None;
+        }
+    }
+
+    /// Forget gap bounds once the backfill is done
+    ///
+    /// Backfilled trades the live feed hasn't reached yet stay, so it can
+    /// still repeat them without being dispatched twice.
+    pub fn end_gap(&self) {
+        let mut state = self.state.lock().unwrap();
+        for entry in state.trades.values_mut() {
+            entry.gap = Gap::default();
+            entry.since_keys.clear();
+        }
+        for entry in state.candles.values_mut() {
+            entry.gap = Gap::default();
+            entry.watermark = None;
+        }
+    }
+
+    /// Last trade time before the current gap, if a pair has one
+    pub fn trade_gap(&self, symbol: &str) -> Option<DateTime<Utc>> {
+        let state = self.state.lock().unwrap();
+        state.trades.get(symbol).and_then(|entry| entry.gap.since)
+    }
+
+    /// Start of the last candle before the current gap, if a pair has one
+    pub fn candle_gap(&self, symbol: &str, interval: &str) -> Option<DateTime<Utc>> {
+        let state = self.state.lock().unwrap();
+        state.candles.get(&(symbol.to_string(), interval.to_string())).and_then(|entry| entry.gap.since)
+    }
+
+    /// Classify a trade from REST history, recording it if it is emitted
+    pub fn accept_trade(&self, trade: &TradeData) -> Backfilled {
+        let mut state = self.state.lock().unwrap();
+        let Some(entry) = state.trades.get_mut(&trade.symbol) else {
+            return Backfilled::Stop;
+        };
+        let Some(since) = entry.gap.since else {
+            return Backfilled::Stop;
+        };
+
+        if entry.gap.resumed_by(trade.timestamp) {
+            return Backfilled::Stop;
+        }
+        if trade.timestamp < since {
+            return Backfilled::Skip;
+        }
+        // Without comparable ids, trades at the last live time were
+        // dispatched if one with the same price and volume was seen live
+        if !entry.gap.matched_by_id && trade.timestamp == since {
+            let key = trade_key(trade);
+            if let Some(index) = entry.since_keys.iter().position(|live| *live == key) {
+                entry.since_keys.remove(index);
+                return Backfilled::Skip;
+            }
+        }
+        if exchange_trade_id(&trade.trade_id).is_some_and(|id| !entry.remember(id)) {
+            return Backfilled::Skip;
+        }
+
+        if !entry.gap.matched_by_id {
+            entry.backfilled.push_back(trade_key(trade));
+        }
+        Backfilled::Emit
+    }
+
+    /// Classify a candle from REST history, recording it if it is emitted
+    ///
+    /// The candle that was in progress at the disconnect is emitted with its
+    /// final values.
+    pub fn accept_candle(&self, candle: &OHLCData) -> Backfilled {
+        let begin = candle.interval_begin();
+        let mut state = self.state.lock().unwrap();
+        let Some(entry) = state.candles.get_mut(&(candle.symbol.clone(), candle.interval.clone())) else {
+            return Backfilled::Stop;
+        };
+        let Some(since) = entry.gap.since else {
+            return Backfilled::Stop;
+        };
+
+        if entry.gap.resumed_by(begin) {
+            return Backfilled::Stop;
+        }
+        if begin < since || entry.watermark.is_some_and(|watermark| begin <= watermark) {
+            return Backfilled::Skip;
+        }
+
+        entry.watermark = Some(begin);
+        if entry.last.map_or(true, |last| begin > last) {
+            entry.last = Some(begin);
+        }
+        Backfilled::Emit
+    }
+}
+
+impl fmt::Debug for BackfillTracker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.lock().unwrap();
+        f.debug_struct("BackfillTracker")
+            .field("trade_pairs", &state.trades.len())
+            .field("candle_series", &state.candles.len())
+            .finish()
+    }
+}
+
+/// Replays what a reconnect missed on the subscribed `trade` and `ohlc`
+/// channels
+#[derive(Clone)]
+pub(crate) struct Backfill {
+    pub config: BackfillConfig,
+    pub tracker: BackfillTracker,
+    pub dispatcher: Arc<EventDispatcher>,
+    /// Names pairs in REST requests
+    pub rest_symbols: SymbolRegistry,
+    /// Rewrites pair names on dispatched events
+    pub symbols: Option<SymbolNormalizer>,
+}
+
+impl Backfill {
+    /// Backfill every channel, then close the gap
+    pub async fn run(self, channels: Vec<Channel>) {
+        for channel in channels {
+            let Some(symbol) = &channel.symbol else {
+                continue;
+            };
+            let result = match channel.name.as_str() {
+                "trade" => self.trades(symbol).await,
+                "ohlc" => self.candles(symbol, channel.interval.as_deref().unwrap_or("1")).await,
+                _ => continue,
+            };
+            match result {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Backfilled {} {} events for {}", count, channel.name, symbol),
+                Err(e) => {
+                    tracing::warn!("Backfill of {} for {} failed: {}", channel.name, symbol, e);
+                    self.dispatcher.dispatch_error(e);
+                }
+            }
+        }
+        self.tracker.end_gap();
+    }
+
+    async fn trades(&self, symbol: &str) -> Result<usize, SdkError> {
+        let Some(since) = self.tracker.trade_gap(symbol) else {
+            return Ok(0);
+        };
+        let pair = self.rest_symbols.convert(symbol, SymbolFormat::AltName);
+        let mut cursor = since.timestamp_nanos_opt().unwrap_or_default().max(0) as u64;
+        let mut count = 0;
+
+        for _ in 0..self.config.max_pages {
+            let page = self.config.public_client.get_recent_trades(&pair, Some(cursor)).await?;
+            let done = page.entries.is_empty() || page.last == cursor;
+            for mut trade in page.entries {
+                // Kraken keys history by its legacy pair name
+                trade.symbol = symbol.to_string();
+                match self.tracker.accept_trade(&trade) {
+                    Backfilled::Emit => {
+                        trade.backfilled = true;
+                        self.normalize(&mut trade.symbol);
+                        self.dispatcher.dispatch_trade(trade);
+                        count += 1;
+                    }
+                    Backfilled::Skip => {}
+                    Backfilled::Stop => return Ok(count),
+                }
+            }
+            if done {
+                return Ok(count);
+            }
+            cursor = page.last;
+        }
+
+        tracing::warn!("Trade backfill for {} stopped after {} pages", symbol, self.config.max_pages);
+        Ok(count)
+    }
+
+    async fn candles(&self, symbol: &str, interval: &str) -> Result<usize, SdkError> {
+        let Some(since) = self.tracker.candle_gap(symbol, interval) else {
+            return Ok(0);
+        };
+        let Ok(minutes) = interval.parse::<u32>() else {
+            return Ok(0);
+        };
+        let pair = self.rest_symbols.convert(symbol, SymbolFormat::AltName);
+        // `since` is exclusive; start just before the candle in progress at the disconnect
+        let mut cursor = (since.timestamp() - 1).max(0) as u64;
+        let mut count = 0;
+
+        for _ in 0..self.config.max_pages {
+            let page = self.config.public_client.get_ohlc(&pair, minutes, Some(cursor)).await?;
+            let done = page.entries.is_empty() || page.last == cursor;
+            for mut candle in page.entries {
+                candle.symbol = symbol.to_string();
+                candle.interval = interval.to_string();
+                match self.tracker.accept_candle(&candle) {
+                    Backfilled::Emit => {
+                        candle.backfilled = true;
+                        self.normalize(&mut candle.symbol);
+                        self.dispatcher.dispatch_ohlc(candle);
+                        count += 1;
+                    }
+                    Backfilled::Skip => {}
+                    Backfilled::Stop => return Ok(count),
+                }
+            }
+            if done {
+                return Ok(count);
+            }
+            cursor = page.last;
+        }
+
+        tracing::warn!("Candle backfill for {} stopped after {} pages", symbol, self.config.max_pages);
+        Ok(count)
+    }
+
+    fn normalize(&self, symbol: &mut String) {
+        if let Some(symbols) = &self.symbols {
+            symbols.apply(symbol);
+        }
+    }
+}
+
+/// Exchange-assigned trade id; v1 trades carry an id derived from the
+/// frame instead (see `wire::v1_trade_id`)
+fn exchange_trade_id(trade_id: &str) -> Option<u64> {
+    trade_id.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::TradeSide;
+    use rust_decimal::Decimal;
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_699_999_980 + seconds, 0).unwrap()
+    }
+
+    fn trade(trade_id: &str, seconds: i64) -> TradeData {
+        TradeData {
+            symbol: "XBT/USD".to_string(),
+            price: Decimal::ONE,
+            volume: Decimal::ONE,
+            side: TradeSide::Buy,
+            timestamp: at(seconds),
+            trade_id: trade_id.to_string(),
+            backfilled: false,
+        }
+    }
+
+    fn candle(seconds: i64) -> OHLCData {
+        OHLCData {
+            symbol: "XBT/USD".to_string(),
+            open: Decimal::ONE,
+            high: Decimal::ONE,
+            low: Decimal::ONE,
+            close: Decimal::ONE,
+            volume: Decimal::ONE,
+            timestamp: at(seconds),
+            interval: "1".to_string(),
+            backfilled: false,
+        }
+    }
+
+    #[test]
+    fn test_trades_deduplicated_by_id() {
+        let tracker = BackfillTracker::new();
+        assert!(tracker.observe_trade(&trade("100", 0)));
+        assert!(!tracker.observe_trade(&trade("100", 0)));
+        tracker.begin_gap();
+        assert_eq!(tracker.trade_gap("XBT/USD"), Some(at(0)));
+
+        // Live trades arrive again from 30s on, one of them a v2 snapshot repeat
+        assert!(tracker.observe_trade(&trade("103", 30)));
+        assert!(!tracker.observe_trade(&trade("100", 0)));
+
+        let history = [("100", 0), ("101", 10), ("102", 20), ("103", 30)];
+        let decisions: Vec<_> = history.iter().map(|(id, s)| tracker.accept_trade(&trade(id, *s))).collect();
+        assert_eq!(decisions, vec![Backfilled::Skip, Backfilled::Emit, Backfilled::Emit, Backfilled::Stop]);
+
+        tracker.end_gap();
+        assert_eq!(tracker.last_trade("XBT/USD").unwrap().trade_id, "103");
+        assert_eq!(tracker.accept_trade(&trade("104", 40)), Backfilled::Stop);
+    }
+
+    #[test]
+    fn test_trades_without_exchange_ids_bounded_by_time() {
+        let tracker = BackfillTracker::new();
+        tracker.observe_trade(&trade("local-a", 0));
+        tracker.begin_gap();
+
+        assert_eq!(tracker.accept_trade(&trade("100", 0)), Backfilled::Skip);
+        assert_eq!(tracker.accept_trade(&trade("101", 10)), Backfilled::Emit);
+        assert_eq!(tracker.accept_trade(&trade("101", 10)), Backfilled::Skip);
+        // The live feed caught up with the backfill
+        assert!(!tracker.observe_trade(&trade("local-b", 10)));
+        assert!(tracker.observe_trade(&trade("local-c", 11)));
+    }
+
+    #[test]
+    fn test_trades_without_exchange_ids_at_the_same_time() {
+        let priced = |trade_id: &str, seconds: i64, price: i64| TradeData {
+            price: Decimal::from(price),
+            ..trade(trade_id, seconds)
+        };
+        let tracker = BackfillTracker::new();
+        tracker.observe_trade(&priced("local-a", 0, 1));
+        tracker.begin_gap();
+
+        // A second trade at the last live time was missed
+        assert_eq!(tracker.accept_trade(&priced("100", 0, 1)), Backfilled::Skip);
+        assert_eq!(tracker.accept_trade(&priced("101", 0, 2)), Backfilled::Emit);
+        assert_eq!(tracker.accept_trade(&priced("102", 10, 1)), Backfilled::Emit);
+        tracker.end_gap();
+
+        // The live feed repeats the backfilled trade, then one it hasn't seen
+        assert!(!tracker.observe_trade(&priced("local-b", 10, 1)));
+        assert!(tracker.observe_trade(&priced("local-c", 10, 3)));
+        assert!(tracker.observe_trade(&priced("local-d", 10, 1)));
+
+        // The next gap starts from every trade seen last, repeats included
+        tracker.begin_gap();
+        let decisions: Vec<_> = [("102", 1), ("103", 3), ("104", 1), ("105", 4)].iter()
+            .map(|(id, price)| tracker.accept_trade(&priced(id, 10, *price)))
+            .collect();
+        assert_eq!(decisions, vec![Backfilled::Skip, Backfilled::Skip, Backfilled::Skip, Backfilled::Emit]);
+    }
+
+    #[test]
+    fn test_live_trades_are_not_held_for_the_backfill() {
+        let tracker = BackfillTracker::new();
+        let mut delivered = Vec::new();
+        tracker.observe_trade(&trade("100", 0));
+        tracker.begin_gap();
+
+        // Live trades after the reconnect go out before the backfill runs
+        for live in [trade("103", 30), trade("104", 31)] {
+            if tracker.observe_trade(&live) {
+                delivered.push((live.trade_id, false));
+            }
+        }
+        // The backfill then fills the gap, oldest first, up to the live feed
+        for history in [trade("101", 10), trade("102", 20), trade("103", 30)] {
+            match tracker.accept_trade(&history) {
+                Backfilled::Emit => delivered.push((history.trade_id, true)),
+                Backfilled::Skip => {}
+                Backfilled::Stop => break,
+            }
+        }
+        tracker.end_gap();
+
+        let expected = [("103", false), ("104", false), ("101", true), ("102", true)];
+        assert_eq!(delivered, expected.map(|(id, backfilled)| (id.to_string(), backfilled)));
+    }
+
+    #[test]
+    fn test_candles_resume_from_the_one_in_progress() {
+        let tracker = BackfillTracker::new();
+        // v1 stamps candles with their last update
+        tracker.observe_candle(&candle(90));
+        assert_eq!(tracker.last_candle("XBT/USD", "1"), Some(at(60)));
+        tracker.begin_gap();
+        tracker.observe_candle(&candle(245));
+
+        let decisions: Vec<_> = [0, 60, 60, 120, 180, 240].iter().map(|s| tracker.accept_candle(&candle(*s))).collect();
+        assert_eq!(decisions, vec![
+            Backfilled::Skip,
+            Backfilled::Emit,
+            Backfilled::Skip,
+            Backfilled::Emit,
+            Backfilled::Emit,
+            Backfilled::Stop,
+        ]);
+    }
+}
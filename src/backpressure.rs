@@ -10,9 +10,10 @@ use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
 
 /// Drop policy when buffer is full or rate exceeded
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum DropPolicy {
     /// Drop oldest messages first (FIFO overflow)
+    #[default]
     Oldest,
     /// Drop newest messages (reject new)
     Latest,
@@ -22,12 +23,6 @@ pub enum DropPolicy {
     Block,
 }
 
-impl Default for DropPolicy {
-    fn default() -> Self {
-        DropPolicy::Oldest
-    }
-}
-
 /// Backpressure configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BackpressureConfig {
@@ -102,30 +97,55 @@ pub struct BackpressureStats {
     pub coalesce_rate: f64,
 }
 
+/// Payloads that can be folded together when updates are coalesced
+///
+/// The default keeps only the newer payload; types carrying incremental
+/// updates (such as order book deltas) override it to merge instead.
+pub trait Coalesce {
+    /// Fold a newer payload for the same symbol into this one
+    fn coalesce(&mut self, newer: Self)
+    where
+        Self: Sized,
+    {
+        *self = newer;
+    }
+}
+
+impl Coalesce for String {}
+
 /// Message wrapper with metadata
 #[derive(Debug, Clone)]
-pub struct BufferedMessage {
+pub struct BufferedMessage<T = String> {
     pub channel: String,
     pub symbol: String,
-    pub data: String,
+    pub data: T,
     pub sequence: Option<u64>,
     pub received_at: Instant,
     pub timestamp: DateTime<Utc>,
 }
 
+impl<T: Coalesce> BufferedMessage<T> {
+    /// Merge a newer message into this one, keeping the original receive time
+    fn coalesce(&mut self, newer: BufferedMessage<T>) {
+        self.data.coalesce(newer.data);
+        self.sequence = newer.sequence;
+        self.timestamp = newer.timestamp;
+    }
+}
+
 /// Per-channel state
 #[derive(Debug)]
-struct ChannelState {
-    buffer: VecDeque<BufferedMessage>,
+struct ChannelState<T> {
+    buffer: VecDeque<BufferedMessage<T>>,
     /// Coalesced messages by symbol (latest only)
-    coalesced: HashMap<String, BufferedMessage>,
+    coalesced: HashMap<String, BufferedMessage<T>>,
     /// Timestamps of recent messages for rate calculation
     recent_timestamps: VecDeque<Instant>,
     /// Stats for this channel
     stats: BackpressureStats,
 }
 
-impl Default for ChannelState {
+impl<T> Default for ChannelState<T> {
     fn default() -> Self {
         Self {
             buffer: VecDeque::new(),
@@ -136,6 +156,19 @@ impl Default for ChannelState {
     }
 }
 
+impl<T> ChannelState<T> {
+    /// Forget arrivals older than the rate window
+    fn prune_timestamps(&mut self, now: Instant, window: Duration) {
+        while let Some(ts) = self.recent_timestamps.front() {
+            if now.duration_since(*ts) > window {
+                self.recent_timestamps.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+}
+
 /// Callback for drop events
 pub type DropCallback = Arc<dyn Fn(DropEvent) + Send + Sync>;
 
@@ -187,16 +220,19 @@ pub struct RateLimitEvent {
 }
 
 /// Production-grade backpressure manager
-pub struct BackpressureManager {
+///
+/// Generic over the message payload so it can sit in front of raw frames
+/// (`String`, the default) or parsed events.
+pub struct BackpressureManager<T = String> {
     config: BackpressureConfig,
-    channels: Mutex<HashMap<String, ChannelState>>,
+    channels: Mutex<HashMap<String, ChannelState<T>>>,
     global_stats: Mutex<BackpressureStats>,
     on_drop: Mutex<Option<DropCallback>>,
     on_coalesce: Mutex<Option<CoalesceCallback>>,
     on_rate_limit: Mutex<Option<RateLimitCallback>>,
 }
 
-impl BackpressureManager {
+impl<T: Coalesce> BackpressureManager<T> {
     /// Create with default config
     pub fn new() -> Self {
         Self::with_config(BackpressureConfig::default())
@@ -242,7 +278,12 @@ impl BackpressureManager {
     }
     
     /// Process incoming message through backpressure control
-    pub fn process(&self, message: BufferedMessage) -> BackpressureResult {
+    pub fn process(&self, message: BufferedMessage<T>) -> BackpressureResult {
+        self.process_evicting(message, &|_| true)
+    }
+    
+    /// Process a message, making room only by dropping `evictable` messages
+    fn process_evicting(&self, message: BufferedMessage<T>, evictable: &dyn Fn(&T) -> bool) -> BackpressureResult {
         let mut channels = self.channels.lock().unwrap();
         let state = channels.entry(message.channel.clone()).or_default();
         
@@ -256,14 +297,7 @@ impl BackpressureManager {
         let now = Instant::now();
         
         // Clean old timestamps outside rate window
-        let window = Duration::from_millis(self.config.rate_window_ms);
-        while let Some(ts) = state.recent_timestamps.front() {
-            if now.duration_since(*ts) > window {
-                state.recent_timestamps.pop_front();
-            } else {
-                break;
-            }
-        }
+        state.prune_timestamps(now, Duration::from_millis(self.config.rate_window_ms));
         
         // Calculate current rate
         let current_rate = state.recent_timestamps.len() as f64 
@@ -314,7 +348,8 @@ impl BackpressureManager {
                 }
                 DropPolicy::Oldest => {
                     // Drop oldest from buffer
-                    if let Some(dropped) = state.buffer.pop_front() {
+                    let oldest = state.buffer.iter().position(|queued| evictable(&queued.data));
+                    if let Some(dropped) = oldest.and_then(|index| state.buffer.remove(index)) {
                         state.stats.total_dropped += 1;
                         self.emit_drop(DropEvent {
                             channel: dropped.channel,
@@ -329,8 +364,13 @@ impl BackpressureManager {
                 }
                 DropPolicy::Random => {
                     // Drop random message
-                    if !state.buffer.is_empty() {
-                        let idx = (now.elapsed().as_nanos() as usize) % state.buffer.len();
+                    let candidates: Vec<usize> = state.buffer.iter()
+                        .enumerate()
+                        .filter(|(_, queued)| evictable(&queued.data))
+                        .map(|(index, _)| index)
+                        .collect();
+                    if !candidates.is_empty() {
+                        let idx = candidates[(now.elapsed().as_nanos() as usize) % candidates.len()];
                         if let Some(dropped) = state.buffer.remove(idx) {
                             state.stats.total_dropped += 1;
                             self.emit_drop(DropEvent {
@@ -377,7 +417,8 @@ impl BackpressureManager {
                     };
                 }
                 DropPolicy::Oldest => {
-                    if let Some(dropped) = state.buffer.pop_front() {
+                    let oldest = state.buffer.iter().position(|queued| evictable(&queued.data));
+                    if let Some(dropped) = oldest.and_then(|index| state.buffer.remove(index)) {
                         state.stats.total_dropped += 1;
                         self.emit_drop(DropEvent {
                             channel: dropped.channel,
@@ -397,8 +438,8 @@ impl BackpressureManager {
         // Handle coalescing
         let mut coalesced = false;
         if self.config.coalesce_updates {
-            if let Some(existing) = state.coalesced.get(&message.symbol) {
-                // Coalesce - replace with newer
+            if let Some(existing) = state.coalesced.get_mut(&message.symbol) {
+                // Coalesce - fold the newer message into the pending one
                 state.stats.total_coalesced += 1;
                 coalesced = true;
                 
@@ -410,11 +451,13 @@ impl BackpressureManager {
                     coalesced_count: state.stats.total_coalesced,
                     timestamp: Utc::now(),
                 });
+                existing.coalesce(message);
+            } else {
+                state.coalesced.insert(message.symbol.clone(), message);
             }
-            state.coalesced.insert(message.symbol.clone(), message.clone());
         } else {
             // No coalescing, add to buffer
-            state.buffer.push_back(message.clone());
+            state.buffer.push_back(message);
         }
         
         // Record timestamp for rate calculation
@@ -453,20 +496,108 @@ impl BackpressureManager {
         }
     }
     
+    /// Process a message while keeping its channel in arrival order
+    ///
+    /// A message folds into the one queued for its symbol when
+    /// `merges(pending, newer)` allows. The merged message moves to the
+    /// newer one's place, so it never overtakes anything queued before it.
+    /// Only messages `droppable` accepts are rate limited or dropped to make
+    /// room; the others are always queued, even past `max_buffer_size`.
+    pub fn process_in_order<F, D>(&self, message: BufferedMessage<T>, merges: F, droppable: D) -> BackpressureResult
+    where
+        F: Fn(&T, &T) -> bool,
+        D: Fn(&T) -> bool,
+    {
+        {
+            let mut channels = self.channels.lock().unwrap();
+            let state = channels.entry(message.channel.clone()).or_default();
+            let pending = state.buffer.iter()
+                .rposition(|pending| pending.symbol == message.symbol && merges(&pending.data, &message.data));
+            if let Some(mut pending) = pending.and_then(|index| state.buffer.remove(index)) {
+                let old_sequence = pending.sequence;
+                let (channel, symbol, new_sequence) = (message.channel.clone(), message.symbol.clone(), message.sequence);
+                pending.coalesce(message);
+                state.buffer.push_back(pending);
+                
+                state.stats.total_received += 1;
+                state.stats.total_accepted += 1;
+                state.stats.total_coalesced += 1;
+                {
+                    let mut global = self.global_stats.lock().unwrap();
+                    global.total_received += 1;
+                    global.total_accepted += 1;
+                    global.total_coalesced += 1;
+                }
+                self.emit_coalesce(CoalesceEvent {
+                    channel,
+                    symbol,
+                    old_sequence,
+                    new_sequence,
+                    coalesced_count: state.stats.total_coalesced,
+                    timestamp: Utc::now(),
+                });
+                
+                return BackpressureResult {
+                    accepted: true,
+                    dropped: false,
+                    coalesced: true,
+                    queue_depth: state.stats.current_queue_depth,
+                    current_rate: state.stats.current_rate,
+                    dropped_count: state.stats.total_dropped,
+                    coalesced_count: state.stats.total_coalesced,
+                };
+            }
+            
+            if !droppable(&message.data) {
+                // Still counts towards the rate the droppable messages are held to
+                let now = Instant::now();
+                state.prune_timestamps(now, Duration::from_millis(self.config.rate_window_ms));
+                state.recent_timestamps.push_back(now);
+                state.buffer.push_back(message);
+                
+                state.stats.total_received += 1;
+                state.stats.total_accepted += 1;
+                state.stats.current_queue_depth = state.buffer.len() + state.coalesced.len();
+                state.stats.peak_queue_depth = state.stats.peak_queue_depth.max(state.stats.current_queue_depth);
+                {
+                    let mut global = self.global_stats.lock().unwrap();
+                    global.total_received += 1;
+                    global.total_accepted += 1;
+                }
+                
+                return BackpressureResult {
+                    accepted: true,
+                    dropped: false,
+                    coalesced: false,
+                    queue_depth: state.stats.current_queue_depth,
+                    current_rate: state.stats.current_rate,
+                    dropped_count: state.stats.total_dropped,
+                    coalesced_count: state.stats.total_coalesced,
+                };
+            }
+        }
+        self.process_evicting(message, &droppable)
+    }
+    
     /// Get next message from buffer (for processing)
-    pub fn pop(&self, channel: &str) -> Option<BufferedMessage> {
+    pub fn pop(&self, channel: &str) -> Option<BufferedMessage<T>> {
         let mut channels = self.channels.lock().unwrap();
-        if let Some(state) = channels.get_mut(channel) {
-            // If coalescing, drain coalesced first
-            if self.config.coalesce_updates && !state.coalesced.is_empty() {
-                let key = state.coalesced.keys().next().cloned();
-                if let Some(k) = key {
-                    return state.coalesced.remove(&k);
-                }
+        let state = channels.get_mut(channel)?;
+        
+        // If coalescing, drain coalesced first
+        let mut message = None;
+        if self.config.coalesce_updates && !state.coalesced.is_empty() {
+            let key = state.coalesced.keys().next().cloned();
+            if let Some(k) = key {
+                message = state.coalesced.remove(&k);
             }
-            return state.buffer.pop_front();
         }
-        None
+        if message.is_none() {
+            message = state.buffer.pop_front();
+        }
+        
+        state.stats.current_queue_depth = state.buffer.len() + state.coalesced.len();
+        message
     }
     
     /// Get stats for a channel
@@ -485,7 +616,9 @@ impl BackpressureManager {
             if state.stats.peak_rate > stats.peak_rate {
                 stats.peak_rate = state.stats.peak_rate;
             }
+            stats.current_rate += state.stats.current_rate;
             stats.current_queue_depth += state.stats.current_queue_depth;
+            stats.peak_queue_depth = stats.peak_queue_depth.max(state.stats.peak_queue_depth);
         }
         
         if stats.total_received > 0 {
@@ -536,7 +669,7 @@ impl BackpressureManager {
     }
 }
 
-impl Default for BackpressureManager {
+impl<T: Coalesce> Default for BackpressureManager<T> {
     fn default() -> Self {
         Self::new()
     }
@@ -649,6 +782,97 @@ mod tests {
         assert_eq!(result.dropped_count, 1);
     }
     
+    #[derive(Debug)]
+    struct Deltas(Vec<u64>);
+    
+    impl Coalesce for Deltas {
+        fn coalesce(&mut self, newer: Self) {
+            self.0.extend(newer.0);
+        }
+    }
+    
+    #[test]
+    fn test_coalescing_merges_payloads() {
+        let manager: BackpressureManager<Deltas> = BackpressureManager::new();
+        
+        for i in 1..=3 {
+            manager.process(BufferedMessage {
+                channel: "book".to_string(),
+                symbol: "BTC/USD".to_string(),
+                data: Deltas(vec![i]),
+                sequence: Some(i),
+                received_at: Instant::now(),
+                timestamp: Utc::now(),
+            });
+        }
+        
+        let merged = manager.pop("book").unwrap();
+        assert_eq!(merged.data.0, vec![1, 2, 3]);
+        assert_eq!(merged.sequence, Some(3));
+        assert!(manager.pop("book").is_none());
+        assert_eq!(manager.global_stats().current_queue_depth, 0);
+        assert_eq!(manager.global_stats().total_coalesced, 2);
+    }
+    
+    #[test]
+    fn test_in_order_merges_move_to_the_newer_place() {
+        let manager: BackpressureManager<Deltas> = BackpressureManager::with_config(BackpressureConfig {
+            coalesce_updates: false,
+            ..Default::default()
+        });
+        let message = |symbol: &str, i: u64| BufferedMessage {
+            channel: "events".to_string(),
+            symbol: symbol.to_string(),
+            data: Deltas(vec![i]),
+            sequence: Some(i),
+            received_at: Instant::now(),
+            timestamp: Utc::now(),
+        };
+        let merges = |pending: &Deltas, newer: &Deltas| pending.0[0] % 2 == 1 && newer.0[0] % 2 == 1;
+        
+        manager.process_in_order(message("BTC/USD", 1), merges, |_| true);
+        manager.process_in_order(message("ETH/USD", 2), merges, |_| true);
+        assert!(manager.process_in_order(message("BTC/USD", 3), merges, |_| true).coalesced);
+        manager.process_in_order(message("BTC/USD", 4), merges, |_| true);
+        // Merged past 4, which then leaves ahead of it
+        assert!(manager.process_in_order(message("BTC/USD", 5), merges, |_| true).coalesced);
+        
+        let drained: Vec<Vec<u64>> = std::iter::from_fn(|| manager.pop("events")).map(|m| m.data.0).collect();
+        assert_eq!(drained, vec![vec![2], vec![4], vec![1, 3, 5]]);
+        assert_eq!(manager.global_stats().total_coalesced, 2);
+        assert_eq!(manager.global_stats().total_received, 5);
+    }
+    
+    #[test]
+    fn test_in_order_only_drops_droppable_messages() {
+        let manager: BackpressureManager<Deltas> = BackpressureManager::with_config(BackpressureConfig {
+            max_messages_per_second: 2,
+            burst_allowance: 0,
+            max_buffer_size: 3,
+            coalesce_updates: false,
+            ..Default::default()
+        });
+        let message = |symbol: &str, i: u64| BufferedMessage {
+            channel: "events".to_string(),
+            symbol: symbol.to_string(),
+            data: Deltas(vec![i]),
+            sequence: Some(i),
+            received_at: Instant::now(),
+            timestamp: Utc::now(),
+        };
+        // Odd messages may be dropped, even ones never
+        let droppable = |data: &Deltas| data.0[0] % 2 == 1;
+        
+        for i in 1..=10 {
+            manager.process_in_order(message(&format!("PAIR{}", i), i), |_, _| false, droppable);
+        }
+        
+        let drained: Vec<u64> = std::iter::from_fn(|| manager.pop("events")).map(|m| m.data.0[0]).collect();
+        assert!(drained.len() < 10);
+        assert_eq!(drained.iter().filter(|i| *i % 2 == 0).copied().collect::<Vec<_>>(), vec![2, 4, 6, 8, 10]);
+        assert_eq!(manager.global_stats().total_dropped, 10 - drained.len() as u64);
+    }
+    
     #[test]
     fn test_stats() {
         let manager = BackpressureManager::new();
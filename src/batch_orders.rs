@@ -2,10 +2,9 @@
 //!
 //! Provides atomic batch order placement and OCO (one-cancels-other) orders.
 
-use crate::trading::{OrderRequest, OrderResponse, OrderSide, CancelResponse};
-use crate::error::SdkError;
+use crate::trading::{OrderRequest, OrderResponse, OrderSide};
 use rust_decimal::Decimal;
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 
 /// Batch order request - multiple orders submitted together
 #[derive(Debug, Clone)]
@@ -15,6 +14,12 @@ pub struct BatchOrderRequest {
     pub atomic: bool,
 }
 
+impl Default for BatchOrderRequest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BatchOrderRequest {
     pub fn new() -> Self {
         Self {
@@ -23,6 +28,7 @@ impl BatchOrderRequest {
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn add(mut self, order: OrderRequest) -> Self {
         self.orders.push(order);
         self
@@ -174,6 +180,7 @@ pub struct BracketOrderResult {
 
 /// Position sizing helpers
 pub mod sizing {
+    use crate::data::InstrumentData;
     use rust_decimal::Decimal;
 
     /// Calculate position size based on percentage of balance
@@ -209,6 +216,12 @@ pub mod sizing {
         }
         (size / lot_size).floor() * lot_size
     }
+
+    /// Round to a pair's lot size, e.g. from an
+    /// [`InstrumentRegistry`](crate::instruments::InstrumentRegistry)
+    pub fn round_to_instrument(size: Decimal, instrument: &InstrumentData) -> Decimal {
+        round_to_lot(size, instrument.qty_increment)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,572 @@
+//! Local candle aggregation from trades
+//!
+//! Kraken's `ohlc` channel only offers fixed intervals and sends the candle
+//! in progress without ever saying when it closed. [`CandleBuilder`] builds
+//! bars from [`TradeData`] instead:
+//! - Time bars of any length, aligned to the Unix epoch (e.g. 15s, 7m)
+//! - Volume, tick (trade count) and dollar (notional) bars
+//! - Separate [`CandleEvent::Updated`] and [`CandleEvent::Closed`] events
+//! - Reconciliation of time bars against the exchange's `ohlc` stream
+//!
+//! With [`ClientConfig::candles`] set, the client feeds its own builder and
+//! publishes each event as [`SdkEvent::Candle`]. A builder can also be fed
+//! by hand, e.g. from a replay or another trade source.
+//!
+//! [`ClientConfig::candles`]: crate::data::ClientConfig::candles
+//! [`SdkEvent::Candle`]: crate::events::SdkEvent::Candle
+//!
+//! ## Example: Acting on Candle Close
+//!
+//! ```rust,ignore
+//! use kraken_ws_sdk::candles::{BarSpec, CandleBuilder, CandleConfig, CandleEvent};
+//! use std::time::Duration;
+//!
+//! let builder = CandleBuilder::with_config(CandleConfig {
+//!     bars: vec![BarSpec::Time(Duration::from_secs(15)), BarSpec::Volume(dec!(10))],
+//!     ..Default::default()
+//! });
+//! builder.on_event(|event| {
+//!     if let CandleEvent::Closed(candle) = event {
+//!         println!("{:?} closed at {}", candle.bar, candle.close);
+//!     }
+//! });
+//!
+//! while let Some(event) = events.recv().await {
+//!     match event {
+//!         SdkEvent::Trade(trade) => { builder.add_trade(&trade); }
+//!         SdkEvent::Ohlc(candle) => { builder.reconcile(&candle); }
+//!         _ => {}
+//!     }
+//! }
+//! ```
+
+use crate::data::{OHLCData, TradeData};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// CONFIGURATION
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// How trades are grouped into bars
+///
+/// A trade is never split between bars; the trade that reaches a volume,
+/// tick or dollar threshold closes its bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BarSpec {
+    /// Fixed time buckets aligned to the Unix epoch
+    Time(Duration),
+    /// Closes once traded volume reaches the threshold
+    Volume(Decimal),
+    /// Closes after this many trades
+    Tick(u64),
+    /// Closes once traded notional (price × volume) reaches the threshold
+    Dollar(Decimal),
+}
+
+impl BarSpec {
+    /// Time bar of `seconds`
+    pub fn seconds(seconds: u64) -> Self {
+        BarSpec::Time(Duration::from_secs(seconds))
+    }
+
+    /// Time bar of `minutes`
+    pub fn minutes(minutes: u64) -> Self {
+        BarSpec::Time(Duration::from_secs(minutes * 60))
+    }
+
+    /// Bucket length in milliseconds, for time bars
+    fn bucket_millis(&self) -> Option<i64> {
+        match self {
+            BarSpec::Time(duration) => Some((duration.as_millis() as i64).max(1)),
+            _ => None,
+        }
+    }
+}
+
+/// Configuration for candle aggregation
+#[derive(Debug, Clone)]
+pub struct CandleConfig {
+    /// Bars built for every symbol
+    pub bars: Vec<BarSpec>,
+    /// Closed candles kept per symbol and bar
+    pub max_history: usize,
+}
+
+impl Default for CandleConfig {
+    fn default() -> Self {
+        Self {
+            bars: vec![BarSpec::minutes(1)],
+            max_history: 500,
+        }
+    }
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// CANDLES
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// A locally built candle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Candle {
+    pub symbol: String,
+    pub bar: BarSpec,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    pub volume: Decimal,
+    /// Traded notional (price × volume)
+    pub notional: Decimal,
+    pub trade_count: u64,
+    /// Bucket start for time bars, first trade otherwise
+    pub open_time: DateTime<Utc>,
+    /// Bucket end for time bars, last trade otherwise
+    pub close_time: DateTime<Utc>,
+    /// Prices and volume were replaced with the exchange's `ohlc` candle
+    pub reconciled: bool,
+}
+
+impl Candle {
+    fn open_with(symbol: &str, bar: BarSpec, trade: &TradeData) -> Self {
+        let (open_time, close_time) = match bar.bucket_millis() {
+            Some(millis) => {
+                let start = trade.timestamp.timestamp_millis().div_euclid(millis) * millis;
+                (from_millis(start), from_millis(start + millis))
+            }
+            None => (trade.timestamp, trade.timestamp),
+        };
+        Self {
+            symbol: symbol.to_string(),
+            bar,
+            open: trade.price,
+            high: trade.price,
+            low: trade.price,
+            close: trade.price,
+            volume: Decimal::ZERO,
+            notional: Decimal::ZERO,
+            trade_count: 0,
+            open_time,
+            close_time,
+            reconciled: false,
+        }
+    }
+
+    fn add(&mut self, trade: &TradeData) {
+        self.high = self.high.max(trade.price);
+        self.low = self.low.min(trade.price);
+        self.close = trade.price;
+        self.volume += trade.volume;
+        self.notional += trade.price * trade.volume;
+        self.trade_count += 1;
+        if self.bar.bucket_millis().is_none() {
+            self.close_time = trade.timestamp;
+        }
+    }
+
+    /// Whether the bar's threshold has been reached
+    fn is_full(&self) -> bool {
+        match self.bar {
+            BarSpec::Time(_) => false,
+            BarSpec::Volume(threshold) => self.volume >= threshold,
+            BarSpec::Tick(threshold) => self.trade_count >= threshold,
+            BarSpec::Dollar(threshold) => self.notional >= threshold,
+        }
+    }
+
+    /// Volume-weighted average price
+    pub fn vwap(&self) -> Option<Decimal> {
+        if self.volume.is_zero() {
+            None
+        } else {
+            Some(self.notional / self.volume)
+        }
+    }
+}
+
+/// Candle lifecycle events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CandleEvent {
+    /// A trade or exchange candle changed the open candle
+    Updated(Candle),
+    /// The candle is final and will not change again
+    Closed(Candle),
+}
+
+impl CandleEvent {
+    pub fn candle(&self) -> &Candle {
+        match self {
+            CandleEvent::Updated(candle) | CandleEvent::Closed(candle) => candle,
+        }
+    }
+}
+
+/// Callback for candle events
+pub type CandleCallback = Arc<dyn Fn(CandleEvent) + Send + Sync>;
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// CANDLE BUILDER
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Symbol and bar a candle series is kept for
+type SeriesKey = (String, BarSpec);
+
+/// Builds candles from trades for every configured bar
+///
+/// Time bars close when a trade lands in a later bucket, when [`tick`]
+/// passes the bucket end, or when the exchange's `ohlc` stream moves on.
+/// Buckets without trades produce no candle. Trades older than the open
+/// time bar (e.g. backfilled ones) are not added to it.
+///
+/// [`tick`]: CandleBuilder::tick
+pub struct CandleBuilder {
+    config: CandleConfig,
+    /// Candle in progress per series
+    open: Mutex<HashMap<SeriesKey, Candle>>,
+    /// Closed candles per series, oldest first
+    history: Mutex<HashMap<SeriesKey, VecDeque<Candle>>>,
+    callbacks: Mutex<Vec<CandleCallback>>,
+}
+
+impl CandleBuilder {
+    /// Create a builder for one-minute bars
+    pub fn new() -> Self {
+        Self::with_config(CandleConfig::default())
+    }
+
+    /// Create with custom config
+    pub fn with_config(config: CandleConfig) -> Self {
+        Self {
+            config,
+            open: Mutex::new(HashMap::new()),
+            history: Mutex::new(HashMap::new()),
+            callbacks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Register callback for candle events
+    pub fn on_event<F>(&self, callback: F)
+    where
+        F: Fn(CandleEvent) + Send + Sync + 'static,
+    {
+        self.callbacks.lock().unwrap().push(Arc::new(callback));
+    }
+
+    /// Add a trade to every bar for its symbol
+    ///
+    /// Returns the events it caused, in order: a `Closed` for any bar it
+    /// rolled over, then an `Updated`, then a `Closed` if it filled a
+    /// threshold bar.
+    pub fn add_trade(&self, trade: &TradeData) -> Vec<CandleEvent> {
+        let mut events = Vec::new();
+        {
+            let mut open = self.open.lock().unwrap();
+            for &bar in &self.config.bars {
+                let key = (trade.symbol.clone(), bar);
+
+                if bar.bucket_millis().is_some() {
+                    let late = match open.get(&key) {
+                        Some(current) => trade.timestamp < current.open_time,
+                        None => self.last_closed(&key).is_some_and(|last| trade.timestamp < last.close_time),
+                    };
+                    if late {
+                        tracing::debug!("Skipping late trade for {} {:?}", trade.symbol, bar);
+                        continue;
+                    }
+                    if open.get(&key).is_some_and(|current| trade.timestamp >= current.close_time) {
+                        if let Some(closed) = open.remove(&key) {
+                            events.push(CandleEvent::Closed(closed));
+                        }
+                    }
+                }
+
+                let candle = open.entry(key.clone())
+                    .or_insert_with(|| Candle::open_with(&trade.symbol, bar, trade));
+                candle.add(trade);
+                events.push(CandleEvent::Updated(candle.clone()));
+
+                if candle.is_full() {
+                    if let Some(closed) = open.remove(&key) {
+                        events.push(CandleEvent::Closed(closed));
+                    }
+                }
+            }
+        }
+        self.publish(&events);
+        events
+    }
+
+    /// Close time bars whose bucket ended by `now`
+    ///
+    /// Call periodically so bars close on time even when no trade follows.
+    pub fn tick(&self, now: DateTime<Utc>) -> Vec<CandleEvent> {
+        let mut events = Vec::new();
+        {
+            let mut open = self.open.lock().unwrap();
+            let mut expired: Vec<SeriesKey> = open.iter()
+                .filter(|(_, candle)| candle.bar.bucket_millis().is_some() && candle.close_time <= now)
+                .map(|(key, _)| key.clone())
+                .collect();
+            expired.sort_by_key(|key| open[key].close_time);
+            for key in expired {
+                if let Some(closed) = open.remove(&key) {
+                    events.push(CandleEvent::Closed(closed));
+                }
+            }
+        }
+        self.publish(&events);
+        events
+    }
+
+    /// Reconcile time bars matching an exchange `ohlc` candle
+    ///
+    /// The exchange saw every trade, so its prices and volume replace the
+    /// local ones for the same bucket. A candle for a later bucket closes
+    /// the local one. Updates for buckets already closed are ignored.
+    pub fn reconcile(&self, exchange: &OHLCData) -> Vec<CandleEvent> {
+        let Ok(minutes) = exchange.interval.parse::<u64>() else {
+            return Vec::new();
+        };
+        let bar = BarSpec::minutes(minutes);
+        if !self.config.bars.contains(&bar) {
+            return Vec::new();
+        }
+
+        let begin = exchange.interval_begin();
+        let key = (exchange.symbol.clone(), bar);
+        let mut events = Vec::new();
+        {
+            let mut open = self.open.lock().unwrap();
+            if let Some(current) = open.get(&key) {
+                if begin < current.open_time {
+                    tracing::debug!("Ignoring exchange candle for closed {} bar at {}", exchange.symbol, begin);
+                    return events;
+                }
+                if begin > current.open_time {
+                    if let Some(closed) = open.remove(&key) {
+                        events.push(CandleEvent::Closed(closed));
+                    }
+                }
+            } else if self.last_closed(&key).is_some_and(|last| begin <= last.open_time) {
+                tracing::debug!("Ignoring exchange candle for closed {} bar at {}", exchange.symbol, begin);
+                return events;
+            }
+
+            let millis = bar.bucket_millis().unwrap_or(1);
+            let start = begin.timestamp_millis();
+            let candle = open.entry(key).or_insert_with(|| Candle {
+                symbol: exchange.symbol.clone(),
+                bar,
+                open: exchange.open,
+                high: exchange.high,
+                low: exchange.low,
+                close: exchange.close,
+                volume: Decimal::ZERO,
+                notional: Decimal::ZERO,
+                trade_count: 0,
+                open_time: from_millis(start),
+                close_time: from_millis(start + millis),
+                reconciled: false,
+            });
+            candle.open = exchange.open;
+            candle.high = exchange.high;
+            candle.low = exchange.low;
+            candle.close = exchange.close;
+            candle.volume = exchange.volume;
+            candle.reconciled = true;
+            events.push(CandleEvent::Updated(candle.clone()));
+        }
+        self.publish(&events);
+        events
+    }
+
+    /// Candle in progress for a symbol and bar
+    pub fn current(&self, symbol: &str, bar: &BarSpec) -> Option<Candle> {
+        self.open.lock().unwrap().get(&(symbol.to_string(), *bar)).cloned()
+    }
+
+    /// Most recent closed candles for a symbol and bar, oldest first
+    pub fn closed(&self, symbol: &str, bar: &BarSpec, limit: usize) -> Vec<Candle> {
+        let history = self.history.lock().unwrap();
+        history.get(&(symbol.to_string(), *bar))
+            .map(|candles| candles.iter().skip(candles.len().saturating_sub(limit)).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Clear candles for a symbol
+    pub fn clear_symbol(&self, symbol: &str) {
+        self.open.lock().unwrap().retain(|(s, _), _| s != symbol);
+        self.history.lock().unwrap().retain(|(s, _), _| s != symbol);
+    }
+
+    /// Clear all candles
+    pub fn clear_all(&self) {
+        self.open.lock().unwrap().clear();
+        self.history.lock().unwrap().clear();
+    }
+
+    fn last_closed(&self, key: &SeriesKey) -> Option<Candle> {
+        self.history.lock().unwrap().get(key).and_then(|candles| candles.back().cloned())
+    }
+
+    /// Record closed candles and notify callbacks
+    fn publish(&self, events: &[CandleEvent]) {
+        {
+            let mut history = self.history.lock().unwrap();
+            for event in events {
+                if let CandleEvent::Closed(candle) = event {
+                    let series = history.entry((candle.symbol.clone(), candle.bar)).or_default();
+                    series.push_back(candle.clone());
+                    while series.len() > self.config.max_history {
+                        series.pop_front();
+                    }
+                }
+            }
+        }
+
+        let callbacks = self.callbacks.lock().unwrap().clone();
+        for event in events {
+            for callback in &callbacks {
+                callback(event.clone());
+            }
+        }
+    }
+}
+
+impl Default for CandleBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn from_millis(millis: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(millis).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::TradeSide;
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_699_999_980 + seconds, 0).unwrap()
+    }
+
+    fn trade(price: &str, volume: &str, seconds: i64) -> TradeData {
+        TradeData {
+            symbol: "XBT/USD".to_string(),
+            price: dec(price),
+            volume: dec(volume),
+            side: TradeSide::Buy,
+            timestamp: at(seconds),
+            trade_id: String::new(),
+            backfilled: false,
+        }
+    }
+
+    fn closed(events: &[CandleEvent]) -> Vec<&Candle> {
+        events.iter()
+            .filter_map(|event| match event {
+                CandleEvent::Closed(candle) => Some(candle),
+                CandleEvent::Updated(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_time_bars_close_on_rollover_and_tick() {
+        let builder = CandleBuilder::with_config(CandleConfig {
+            bars: vec![BarSpec::seconds(15)],
+            ..Default::default()
+        });
+
+        builder.add_trade(&trade("100", "1", 1));
+        builder.add_trade(&trade("105", "1", 5));
+        let events = builder.add_trade(&trade("99", "2", 16));
+        let candle = closed(&events)[0];
+        assert_eq!((candle.open, candle.high, candle.close, candle.volume), (dec("100"), dec("105"), dec("105"), dec("2")));
+        assert_eq!((candle.open_time, candle.close_time), (at(0), at(15)));
+
+        // Late trades don't reopen the closed bar
+        assert!(builder.add_trade(&trade("1", "1", 10)).is_empty());
+        assert!(builder.tick(at(29)).is_empty());
+        let events = builder.tick(at(30));
+        assert_eq!(closed(&events)[0].low, dec("99"));
+        assert_eq!(builder.closed("XBT/USD", &BarSpec::seconds(15), 10).len(), 2);
+    }
+
+    #[test]
+    fn test_trade_after_tick_close() {
+        let builder = CandleBuilder::with_config(CandleConfig {
+            bars: vec![BarSpec::seconds(15)],
+            ..Default::default()
+        });
+
+        builder.add_trade(&trade("100", "1", 1));
+        assert_eq!(closed(&builder.tick(at(15))).len(), 1);
+
+        // The bucket closed on the tick, so a trade inside it is late
+        assert!(builder.add_trade(&trade("101", "1", 14)).is_empty());
+        assert!(builder.current("XBT/USD", &BarSpec::seconds(15)).is_none());
+        assert_eq!(builder.closed("XBT/USD", &BarSpec::seconds(15), 10).len(), 1);
+
+        let events = builder.add_trade(&trade("102", "1", 15));
+        assert_eq!(builder.current("XBT/USD", &BarSpec::seconds(15)).unwrap().open_time, at(15));
+        assert!(closed(&events).is_empty());
+    }
+
+    #[test]
+    fn test_threshold_bars() {
+        let builder = CandleBuilder::with_config(CandleConfig {
+            bars: vec![BarSpec::Volume(dec("3")), BarSpec::Tick(2), BarSpec::Dollar(dec("500"))],
+            ..Default::default()
+        });
+
+        let events = builder.add_trade(&trade("100", "2", 0));
+        assert!(closed(&events).is_empty());
+        let events = builder.add_trade(&trade("110", "2", 1));
+        let bars: Vec<BarSpec> = closed(&events).iter().map(|candle| candle.bar).collect();
+        assert_eq!(bars, vec![BarSpec::Volume(dec("3")), BarSpec::Tick(2)]);
+        assert_eq!(closed(&events)[0].vwap(), Some(dec("105")));
+
+        let events = builder.add_trade(&trade("100", "1", 2));
+        assert_eq!(closed(&events)[0].bar, BarSpec::Dollar(dec("500")));
+        assert_eq!(closed(&events)[0].notional, dec("520"));
+    }
+
+    #[test]
+    fn test_reconcile_with_exchange_candles() {
+        let builder = CandleBuilder::new();
+        let exchange = |close: &str, seconds: i64| OHLCData {
+            symbol: "XBT/USD".to_string(),
+            open: dec("100"),
+            high: dec("120"),
+            low: dec("90"),
+            close: dec(close),
+            volume: dec("7"),
+            timestamp: at(seconds),
+            interval: "1".to_string(),
+            backfilled: false,
+        };
+
+        builder.add_trade(&trade("100", "1", 5));
+        let events = builder.reconcile(&exchange("110", 30));
+        let candle = events[0].candle();
+        assert!(candle.reconciled);
+        assert_eq!((candle.high, candle.volume, candle.trade_count), (dec("120"), dec("7"), 1));
+
+        // The exchange moving to the next minute closes the local bar
+        let events = builder.reconcile(&exchange("111", 61));
+        assert_eq!(closed(&events)[0].close, dec("110"));
+        assert_eq!(builder.current("XBT/USD", &BarSpec::minutes(1)).unwrap().open_time, at(60));
+        assert!(builder.reconcile(&exchange("112", 30)).is_empty());
+    }
+}
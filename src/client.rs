@@ -1,18 +1,33 @@
 //! Main client interface for the Kraken WebSocket SDK
 
 use crate::{
+    backfill::{Backfill, BackfillConfig, BackfillTracker},
+    backpressure::{BackpressureConfig, BackpressureStats},
+    candles::{CandleBuilder, CandleConfig},
     connection::{ConnectionManager, WebSocketMessage},
     data::*,
-    error::{ProcessingError, SdkError},
-    events::{EventCallback, EventDispatcher},
-    orderbook::OrderBookManager,
-    parser::{DataParser, KrakenDataParser, MessageHandler},
-    subscription::SubscriptionManager,
+    error::{ConnectionError, SdkError},
+    events::{EventCallback, EventDispatcher, EventFilter, EventStream},
+    instruments::InstrumentRegistry,
+    latency::{LatencyAlert, LatencyAlertConfig, LatencyConfig, LatencyStats, LatencyTracker},
+    orderbook::{OrderBook, OrderBookManager, DEFAULT_BOOK_DEPTH},
+    parser::{KrakenDataParser, MessageHandler},
+    recorder::{RecordingSink, SessionRecorder},
+    rest_client::KrakenPublicClient,
+    sequencing::{SequenceConfig, SequenceManager, SequenceStats},
+    state::{self, StateError, StateMachine, StateMachineConfig, StateTransition},
+    subscription::{SubscriptionAck, SubscriptionManager},
+    symbols::{SymbolFormat, SymbolNormalizer, SymbolRegistry},
+    system_status::{ExchangeStatus, SystemStatus, SystemStatusMonitor},
 };
 use futures_util::{SinkExt, StreamExt};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::sync::mpsc;
-use tokio_tungstenite::{tungstenite::Message, WebSocketStream};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::{tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+type WsStream = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
 
 /// Main WebSocket client for Kraken API
 pub struct KrakenWsClient {
@@ -20,15 +35,50 @@ pub struct KrakenWsClient {
     event_dispatcher: Arc<EventDispatcher>,
     subscription_manager: SubscriptionManager,
     orderbook_manager: OrderBookManager,
+    sequence_manager: Arc<SequenceManager>,
+    latency_tracker: Arc<LatencyTracker>,
+    status_monitor: SystemStatusMonitor,
+    instruments: InstrumentRegistry,
+    backfill: BackfillTracker,
+    candles: Option<Arc<CandleBuilder>>,
     message_handler: MessageHandler,
     config: ClientConfig,
-    pending_subscriptions: Option<Vec<Channel>>,
+    lifecycle: Lifecycle,
+    session: Option<Session>,
+}
+
+/// Handles to the background task that owns the socket
+struct Session {
+    outgoing: mpsc::Sender<Message>,
+    task: JoinHandle<()>,
 }
 
 impl KrakenWsClient {
     /// Create a new Kraken WebSocket client
     pub fn new(config: ClientConfig) -> Self {
-        // Validate configuration
+        let event_dispatcher = Arc::new(
+            EventDispatcher::new()
+                .with_backpressure(config.backpressure_config.clone())
+                .with_stream_buffer(config.buffer_size),
+        );
+        let latency_tracker = Arc::new(LatencyTracker::with_config(config.latency_config.clone()));
+        if let Some(alerts) = &config.latency_alerts {
+            latency_tracker.set_alerts(alerts);
+        }
+        
+        Self::with_shared(config, event_dispatcher, latency_tracker, SystemStatusMonitor::new(), InstrumentRegistry::new())
+    }
+    
+    /// Client that publishes into shared components, so several connections
+    /// can present one event stream (see [`KrakenWsPool`](crate::pool::KrakenWsPool))
+    pub(crate) fn with_shared(
+        config: ClientConfig,
+        event_dispatcher: Arc<EventDispatcher>,
+        latency_tracker: Arc<LatencyTracker>,
+        status_monitor: SystemStatusMonitor,
+        instruments: InstrumentRegistry,
+    ) -> Self {
+        // Invalid configurations are refused on connect
         if let Err(e) = config.validate() {
             tracing::error!("Invalid configuration: {}", e);
         }
@@ -36,68 +86,216 @@ impl KrakenWsClient {
         let connection_config = ConnectionConfig {
             endpoint: config.endpoint.clone(),
             timeout: config.timeout,
-            ping_interval: std::time::Duration::from_secs(30),
+            ping_interval: config.ping_interval,
         };
         
         let connection_manager = ConnectionManager::new(connection_config, config.reconnect_config.clone());
-        let event_dispatcher = Arc::new(EventDispatcher::new());
-        let subscription_manager = SubscriptionManager::new();
+        let subscription_manager = SubscriptionManager::with_protocol(config.protocol);
         let orderbook_manager = OrderBookManager::new();
+        let sequence_manager = Arc::new(SequenceManager::with_config(config.sequence_config.clone()));
+        {
+            let dispatcher = Arc::clone(&event_dispatcher);
+            sequence_manager.on_gap(move |gap| dispatcher.dispatch_gap(gap));
+            let dispatcher = Arc::clone(&event_dispatcher);
+            sequence_manager.on_resync(move |resync| dispatcher.dispatch_resync(resync));
+        }
         let parser = Arc::new(KrakenDataParser::new());
-        let message_handler = MessageHandler::new(parser, Arc::clone(&event_dispatcher));
+        let message_handler = MessageHandler::new(parser, Arc::clone(&event_dispatcher))
+            .with_orderbook_manager(orderbook_manager.clone())
+            .with_subscription_manager(subscription_manager.clone())
+            .with_sequence_manager(Arc::clone(&sequence_manager))
+            .with_latency_tracker(Arc::clone(&latency_tracker))
+            .with_status_monitor(status_monitor.clone())
+            .with_instrument_registry(instruments.clone())
+            .with_connection_manager(connection_manager.clone());
+        let message_handler = match &config.symbols {
+            Some(symbols) => message_handler.with_symbol_normalizer(symbols.clone()),
+            None => message_handler,
+        };
+        let backfill = BackfillTracker::new();
+        let message_handler = match &config.backfill {
+            Some(_) => message_handler.with_backfill_tracker(backfill.clone()),
+            None => message_handler,
+        };
+        let candles = config.candles.clone().map(|config| Arc::new(CandleBuilder::with_config(config)));
+        let message_handler = match &candles {
+            Some(candles) => message_handler.with_candle_builder(Arc::clone(candles)),
+            None => message_handler,
+        };
+        let lifecycle = Lifecycle {
+            machine: Arc::new(Mutex::new(StateMachine::new(StateMachineConfig {
+                max_retries: config.reconnect_config.max_attempts,
+                initial_retry_delay: config.reconnect_config.initial_delay,
+                max_retry_delay: config.reconnect_config.max_delay,
+                backoff_multiplier: config.reconnect_config.backoff_multiplier,
+                heartbeat_interval: config.ping_interval,
+                heartbeat_timeout: config.liveness_timeout,
+                requires_auth: false,
+            }))),
+            event_dispatcher: Arc::clone(&event_dispatcher),
+        };
         
         Self {
             connection_manager,
             event_dispatcher,
             subscription_manager,
             orderbook_manager,
+            sequence_manager,
+            latency_tracker,
+            status_monitor,
+            instruments,
+            backfill,
+            candles,
             message_handler,
             config,
-            pending_subscriptions: None,
+            lifecycle,
+            session: None,
         }
     }
     
     /// Connect to the WebSocket API
+    ///
+    /// Returns once the socket is open. A background task then processes
+    /// messages and, when the connection drops, reconnects with exponential
+    /// backoff and replays every subscription.
     pub async fn connect(&mut self) -> Result<(), SdkError> {
+        if let Some(session) = &self.session {
+            if !session.task.is_finished() {
+                tracing::debug!("Already connected");
+                return Ok(());
+            }
+            // Reconnection gave up; start over
+            self.session = None;
+        }
+        
+        // A zero ping interval or liveness window would break the session loop
+        self.config.validate().map_err(SdkError::Configuration)?;
+        
+        // Slow consumers back up the delivery queue, not the socket
+        self.event_dispatcher.start_delivery();
+        
         tracing::info!("Connecting to Kraken WebSocket API");
+        self.lifecycle.transition(|sm| sm.connect());
         
-        let ws_stream = self.connection_manager.connect().await?;
+        let ws_stream = match self.connection_manager.connect().await {
+            Ok(stream) => stream,
+            Err(e) => {
+                self.lifecycle.transition(|sm| sm.connection_failed(e.to_string()));
+                return Err(e.into());
+            }
+        };
+        self.lifecycle.transition(|sm| sm.connection_established());
         
         // Notify connection state change
         self.event_dispatcher.dispatch_connection_state_change(ConnectionState::Connected);
         
         // Start message processing loop
-        self.start_message_loop(ws_stream).await?;
+        let (outgoing, outgoing_rx) = mpsc::channel::<Message>(self.config.outgoing_buffer_size);
+        let (recovery_fallback, recovery_fallback_rx) = mpsc::unbounded_channel();
+        let supervisor = ConnectionSupervisor {
+            connection_manager: self.connection_manager.clone(),
+            event_dispatcher: Arc::clone(&self.event_dispatcher),
+            subscription_manager: self.subscription_manager.clone(),
+            orderbook_manager: self.orderbook_manager.clone(),
+            sequence_manager: Arc::clone(&self.sequence_manager),
+            message_handler: self.message_handler.clone(),
+            lifecycle: self.lifecycle.clone(),
+            protocol: self.config.protocol,
+            auto_reconnect: self.config.auto_reconnect,
+            reconnect_config: self.config.reconnect_config.clone(),
+            liveness_timeout: self.config.liveness_timeout,
+            book_recovery: self.config.book_recovery.clone(),
+            rest_symbols: self.rest_symbols(),
+            recovery_fallback,
+            backfill: self.config.backfill.clone().map(|config| Backfill {
+                config,
+                tracker: self.backfill.clone(),
+                dispatcher: Arc::clone(&self.event_dispatcher),
+                rest_symbols: self.rest_symbols(),
+                symbols: self.config.symbols.clone(),
+            }),
+            recorder: self.config.recorder.clone(),
+        };
+        let task = tokio::spawn(supervisor.run(ws_stream, outgoing_rx, recovery_fallback_rx));
+        self.session = Some(Session { outgoing, task });
         
         Ok(())
     }
     
     /// Subscribe to market data channels
+    ///
+    /// When connected, resolves once Kraken confirms every channel and fails
+    /// with [`SubscriptionError::UnknownPair`], [`SubscriptionError::InvalidDepth`]
+    /// or [`SubscriptionError::Timeout`] otherwise. Before [`connect`] the
+    /// channels are only queued and sent once the connection is up.
+    ///
+    /// [`SubscriptionError::UnknownPair`]: crate::error::SubscriptionError::UnknownPair
+    /// [`SubscriptionError::InvalidDepth`]: crate::error::SubscriptionError::InvalidDepth
+    /// [`SubscriptionError::Timeout`]: crate::error::SubscriptionError::Timeout
+    /// [`connect`]: KrakenWsClient::connect
     pub async fn subscribe(&mut self, channels: Vec<Channel>) -> Result<(), SdkError> {
+        if let Some(ack) = self.request_subscription(&channels).await? {
+            ack.wait(self.config.subscription_timeout).await?;
+            tracing::info!("Subscription confirmed for {} channels", channels.len());
+        }
+        Ok(())
+    }
+    
+    /// Send (or queue) a subscription without waiting for Kraken
+    ///
+    /// Returns the handle to wait on when the request went out on a live session.
+    pub(crate) async fn request_subscription(&mut self, channels: &[Channel]) -> Result<Option<SubscriptionAck>, SdkError> {
         tracing::info!("Subscribing to channels: {:?}", channels);
         
-        // Validate channels and create subscription message
-        let subscription_message = self.subscription_manager.create_subscription_message(&channels)?;
-        
-        // Store channels for later subscription after connection
-        self.pending_subscriptions = Some(channels);
+        // Validate channels and create subscription messages
+        let (messages, ack) = self.subscription_manager.create_subscription_request(channels)?;
         
-        tracing::info!("Subscription message prepared for {} channels", 
-            self.pending_subscriptions.as_ref().map(|c| c.len()).unwrap_or(0));
+        set_book_depths(&self.orderbook_manager, channels);
         
-        Ok(())
+        // Channels stay pending in the subscription manager and are sent on
+        // connect (and replayed on every reconnect)
+        match &self.session {
+            Some(session) => {
+                for message in messages {
+                    session.outgoing.send(message).await
+                        .map_err(|_| SdkError::Network("Connection task has stopped".to_string()))?;
+                }
+                tracing::info!("Subscription sent for {} channels", channels.len());
+                Ok(Some(ack))
+            }
+            None => {
+                tracing::info!("Subscription for {} channels will be sent on connect", channels.len());
+                Ok(None)
+            }
+        }
     }
     
     /// Unsubscribe from market data channels
+    ///
+    /// When connected, resolves once Kraken confirms the unsubscription.
     pub async fn unsubscribe(&mut self, channels: Vec<Channel>) -> Result<(), SdkError> {
+        if let Some(ack) = self.request_unsubscription(&channels).await? {
+            ack.wait(self.config.subscription_timeout).await?;
+            tracing::info!("Unsubscription confirmed for {} channels", channels.len());
+        }
+        Ok(())
+    }
+    
+    /// Send an unsubscription without waiting for Kraken
+    pub(crate) async fn request_unsubscription(&mut self, channels: &[Channel]) -> Result<Option<SubscriptionAck>, SdkError> {
         tracing::info!("Unsubscribing from channels: {:?}", channels);
         
-        let unsubscription_message = self.subscription_manager.create_unsubscription_message(&channels)?;
-        
-        // TODO: Send unsubscription message through WebSocket connection
-        tracing::info!("Unsubscription message created successfully for {} channels", channels.len());
+        let (message, ack) = self.subscription_manager.create_unsubscription_request(channels)?;
         
-        Ok(())
+        match &self.session {
+            Some(session) => {
+                session.outgoing.send(message).await
+                    .map_err(|_| SdkError::Network("Connection task has stopped".to_string()))?;
+                tracing::info!("Unsubscription sent for {} channels", channels.len());
+                Ok(Some(ack))
+            }
+            None => Ok(None),
+        }
     }
     
     /// Register a callback for market data events
@@ -114,6 +312,8 @@ impl KrakenWsClient {
     /// 
     /// Returns a receiver that will receive all SDK events (ticker, trade, book, ohlc, state, error).
     /// This is the recommended API for new code - simpler than callbacks and better for testing.
+    /// Each call returns an independent stream buffering up to `buffer_size` events;
+    /// a stream that falls behind reports what it missed with `SdkEvent::Lagged`.
     /// 
     /// # Example
     /// ```rust,ignore
@@ -131,11 +331,37 @@ impl KrakenWsClient {
         self.event_dispatcher.create_event_stream()
     }
     
+    /// Get an independent event stream that only receives events passing `filter`
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// let mut btc_books = client.events_with_filter(
+    ///     EventFilter::new().symbol("XBT/USD").data_type(DataType::OrderBook),
+    /// );
+    /// ```
+    pub fn events_with_filter(&self, filter: EventFilter) -> EventStream {
+        self.event_dispatcher.subscribe(filter)
+    }
+    
+    /// Get a filtered event stream with its own buffer capacity
+    pub fn events_with_buffer(&self, filter: EventFilter, capacity: usize) -> EventStream {
+        self.event_dispatcher.subscribe_with_buffer(filter, capacity)
+    }
+    
     /// Disconnect from the WebSocket API
     pub async fn disconnect(&mut self) -> Result<(), SdkError> {
         tracing::info!("Disconnecting from Kraken WebSocket API");
         
+        if let Some(Session { outgoing, mut task }) = self.session.take() {
+            // Closing the outgoing channel makes the task close the socket
+            drop(outgoing);
+            if tokio::time::timeout(Duration::from_secs(1), &mut task).await.is_err() {
+                task.abort();
+            }
+        }
+        
         self.connection_manager.disconnect().await?;
+        self.lifecycle.transition(|sm| sm.close());
         self.event_dispatcher.dispatch_connection_state_change(ConnectionState::Disconnected);
         
         Ok(())
@@ -151,147 +377,618 @@ impl KrakenWsClient {
         self.connection_manager.connection_state()
     }
     
-    /// Start the message processing loop
-    async fn start_message_loop(&mut self, mut ws_stream: WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>) -> Result<(), SdkError> {
-        let (mut ws_sender, mut ws_receiver) = ws_stream.split();
-        let (tx, mut rx) = mpsc::channel::<Message>(100);
+    /// Get the lifecycle state, including why a connection is degraded or closed
+    pub fn state(&self) -> state::ConnectionState {
+        self.lifecycle.machine.lock().unwrap().state().clone()
+    }
+    
+    /// Get recent lifecycle transitions, oldest first
+    pub fn state_history(&self) -> Vec<StateTransition> {
+        self.lifecycle.machine.lock().unwrap().history().to_vec()
+    }
+    
+    /// Get the backoff delay before the next reconnection attempt
+    pub fn retry_delay(&self) -> Duration {
+        self.lifecycle.machine.lock().unwrap().retry_delay()
+    }
+    
+    /// Whether a connection task is running, i.e. connected or still
+    /// reconnecting (false before `connect` and once reconnection gave up)
+    pub(crate) fn is_running(&self) -> bool {
+        self.session.as_ref().is_some_and(|session| !session.task.is_finished())
+    }
+    
+    /// Handle for (un)subscribing from code that can't hold the client
+    /// across an `await`, bound to the current session
+    pub(crate) fn handle(&self) -> ClientHandle {
+        ClientHandle {
+            subscription_manager: self.subscription_manager.clone(),
+            orderbook_manager: self.orderbook_manager.clone(),
+            connection_manager: self.connection_manager.clone(),
+            outgoing: self.session.as_ref().map(|session| session.outgoing.clone()),
+        }
+    }
+    
+    /// Check whether the last application-level ping was answered
+    pub fn is_healthy(&self) -> bool {
+        self.connection_manager.is_healthy()
+    }
+    
+    /// Exchange status from the latest `systemStatus` message
+    ///
+    /// `None` until Kraken has sent one (it does on every new connection).
+    pub fn exchange_status(&self) -> Option<ExchangeStatus> {
+        self.status_monitor.status()
+    }
+    
+    /// Latest `systemStatus` message, including API version and connection id
+    pub fn system_status(&self) -> Option<SystemStatus> {
+        self.status_monitor.current()
+    }
+    
+    /// Shared handle to the exchange status
+    ///
+    /// Pass it to [`KrakenRestClient::with_status_monitor`] to block orders
+    /// the engine would refuse, or `watch()` it to react to maintenance.
+    ///
+    /// [`KrakenRestClient::with_status_monitor`]: crate::rest_client::KrakenRestClient::with_status_monitor
+    pub fn status_monitor(&self) -> SystemStatusMonitor {
+        self.status_monitor.clone()
+    }
+    
+    /// Shared handle to pair rules received on the v2 `instrument` channel
+    ///
+    /// Pass it to [`KrakenRestClient::with_instruments`] to validate orders.
+    ///
+    /// [`KrakenRestClient::with_instruments`]: crate::rest_client::KrakenRestClient::with_instruments
+    pub fn instrument_registry(&self) -> InstrumentRegistry {
+        self.instruments.clone()
+    }
+    
+    /// Last trade and candle seen per pair
+    ///
+    /// Only tracked when [`ClientConfig::backfill`] is set.
+    pub fn backfill_tracker(&self) -> BackfillTracker {
+        self.backfill.clone()
+    }
+    
+    /// Candles built from this client's trades
+    ///
+    /// Only built when [`ClientConfig::candles`] is set.
+    pub fn candle_builder(&self) -> Option<Arc<CandleBuilder>> {
+        self.candles.clone()
+    }
+    
+    /// Sequence validation statistics for sequenced channels
+    pub fn sequence_stats(&self) -> SequenceStats {
+        self.sequence_manager.stats()
+    }
+    
+    /// Latency statistics across every dispatched market data message
+    ///
+    /// `processing` is receive -> dispatch; `network` and `total` cover only
+    /// messages carrying an exchange timestamp.
+    pub fn latency_stats(&self) -> LatencyStats {
+        self.latency_tracker.stats()
+    }
+    
+    /// Latency statistics for one channel (`ticker`, `book`, `trade`, `ohlc`, `instrument`)
+    pub fn channel_latency_stats(&self, channel: &str) -> Option<LatencyStats> {
+        self.latency_tracker.channel_stats(channel)
+    }
+    
+    /// Latency statistics for one symbol
+    pub fn symbol_latency_stats(&self, symbol: &str) -> Option<LatencyStats> {
+        self.latency_tracker.symbol_stats(symbol)
+    }
+    
+    /// Backpressure statistics for outbound events (drops, coalesced
+    /// book updates and queue depth)
+    pub fn backpressure_stats(&self) -> BackpressureStats {
+        self.event_dispatcher.backpressure_stats()
+    }
+    
+    /// Get a snapshot of the order book for a symbol
+    ///
+    /// With [`ClientConfig::symbols`] set, the symbol may be given in any form.
+    pub fn get_order_book(&self, symbol: &str) -> Option<Arc<crate::orderbook::OrderBook>> {
+        self.orderbook_manager.get_order_book(&self.native_symbol(symbol))
+    }
+    
+    /// Seed or resync the order book for a symbol from a REST `Depth` snapshot
+    ///
+    /// Deltas received while the snapshot is fetched are buffered and
+    /// replayed on top of it. Uses the client from
+    /// [`BookRecovery::RestSnapshot`] when configured.
+    pub async fn seed_order_book(&self, symbol: &str) -> Result<Arc<OrderBook>, SdkError> {
+        let public_client = match &self.config.book_recovery {
+            BookRecovery::RestSnapshot(public_client) => public_client.clone(),
+            BookRecovery::Resubscribe => KrakenPublicClient::new(),
+        };
+        recover_book(&self.orderbook_manager, &public_client, &self.rest_symbols(), &self.native_symbol(symbol)).await
+    }
+    
+    /// Get best bid and ask prices for a symbol
+    pub fn get_best_bid_ask(&self, symbol: &str) -> Option<(Option<rust_decimal::Decimal>, Option<rust_decimal::Decimal>)> {
+        self.orderbook_manager.get_best_bid_ask(&self.native_symbol(symbol))
+    }
+    
+    /// Name this connection's protocol uses for a pair given in any form
+    ///
+    /// v1 uses WebSocket names (`XBT/USD`), v2 common names (`BTC/USD`).
+    /// Unchanged unless symbol normalization is configured.
+    fn native_symbol(&self, symbol: &str) -> String {
+        let Some(symbols) = &self.config.symbols else {
+            return symbol.to_string();
+        };
+        let format = match self.config.protocol {
+            ProtocolVersion::V1 => SymbolFormat::WsName,
+            ProtocolVersion::V2 => SymbolFormat::Canonical,
+        };
+        symbols.registry.convert(symbol, format)
+    }
+    
+    /// Registry used to name pairs in REST requests
+    fn rest_symbols(&self) -> SymbolRegistry {
+        self.config.symbols.as_ref()
+            .map(|symbols| symbols.registry.clone())
+            .unwrap_or_default()
+    }
+    
+    /// Get active subscriptions
+    pub fn get_active_subscriptions(&self) -> Vec<String> {
+        self.subscription_manager.get_active_subscriptions()
+    }
+    
+    /// Channels that are subscribed or awaiting Kraken's confirmation
+    pub fn subscribed_channels(&self) -> Vec<Channel> {
+        self.subscription_manager.get_subscribed_channels()
+    }
+    
+    /// Check if subscribed to a channel
+    pub fn is_subscribed(&self, channel: &Channel) -> bool {
+        self.subscription_manager.is_subscribed(channel)
+    }
+    
+    /// Get callback count for a data type
+    pub fn get_callback_count(&self, data_type: &DataType) -> usize {
+        self.event_dispatcher.get_callback_count(data_type)
+    }
+    
+    /// Cleanup resources
+    pub async fn cleanup(&mut self) -> Result<(), SdkError> {
+        tracing::info!("Cleaning up SDK resources");
         
-        // Send pending subscriptions if any
-        if let Some(channels) = &self.pending_subscriptions {
-            tracing::info!("Sending subscription for {} channels", channels.len());
-            if let Ok(subscription_msg) = self.subscription_manager.create_subscription_message(channels) {
-                if let Err(e) = ws_sender.send(subscription_msg).await {
-                    tracing::error!("Failed to send subscription message: {}", e);
-                } else {
-                    tracing::info!("Subscription message sent successfully");
-                }
-            }
+        // Disconnect if connected (or still reconnecting)
+        if self.session.is_some() || self.is_connected() {
+            self.disconnect().await?;
         }
         
-        // Store sender for future messages
-        let sender_clone = tx.clone();
+        // Clear order books
+        for symbol in self.orderbook_manager.get_symbols() {
+            self.orderbook_manager.clear_order_book(&symbol);
+        }
         
-        // Spawn task to handle outgoing messages
-        let sender_task = tokio::spawn(async move {
-            while let Some(message) = rx.recv().await {
-                if let Err(e) = ws_sender.send(message).await {
-                    tracing::error!("Failed to send WebSocket message: {}", e);
-                    break;
+        tracing::info!("SDK cleanup completed");
+        Ok(())
+    }
+}
+
+impl Drop for KrakenWsClient {
+    fn drop(&mut self) {
+        if let Some(session) = self.session.take() {
+            session.task.abort();
+        }
+    }
+}
+
+/// Synchronous access to a client's subscriptions and connection state
+///
+/// Taken with [`KrakenWsClient::handle`]; a handle taken before `connect`
+/// only queues subscriptions, which are sent once the connection is up.
+#[derive(Clone)]
+pub(crate) struct ClientHandle {
+    subscription_manager: SubscriptionManager,
+    orderbook_manager: OrderBookManager,
+    connection_manager: ConnectionManager,
+    outgoing: Option<mpsc::Sender<Message>>,
+}
+
+impl ClientHandle {
+    /// Send (or queue) a subscription without waiting for Kraken
+    pub(crate) fn subscribe(&self, channels: &[Channel]) -> Result<(), SdkError> {
+        let messages = self.subscription_manager.create_subscription_messages(channels)?;
+        set_book_depths(&self.orderbook_manager, channels);
+        self.send(messages)
+    }
+    
+    /// Send an unsubscription without waiting for Kraken
+    ///
+    /// Channels not yet confirmed are dropped from the queue instead.
+    pub(crate) fn unsubscribe(&self, channels: &[Channel]) -> Result<(), SdkError> {
+        self.subscription_manager.remove_pending(channels);
+        if channels.iter().all(|channel| !self.subscription_manager.is_subscribed(channel)) {
+            return Ok(());
+        }
+        let message = self.subscription_manager.create_unsubscription_message(channels)?;
+        self.send(vec![message])
+    }
+    
+    pub(crate) fn connection_state(&self) -> ConnectionState {
+        self.connection_manager.connection_state()
+    }
+    
+    fn send(&self, messages: Vec<Message>) -> Result<(), SdkError> {
+        let Some(outgoing) = &self.outgoing else {
+            return Ok(());
+        };
+        for message in messages {
+            match outgoing.try_send(message) {
+                Ok(()) => {}
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    return Err(SdkError::Network("Outgoing message queue is full".to_string()));
                 }
+                // The channels stay queued for the next connection
+                Err(mpsc::error::TrySendError::Closed(_)) => return Ok(()),
             }
-        });
+        }
+        Ok(())
+    }
+}
+
+/// Truncate local books to the subscribed depth so checksums match
+fn set_book_depths(orderbook_manager: &OrderBookManager, channels: &[Channel]) {
+    for channel in channels.iter().filter(|c| c.name == "book") {
+        if let Some(symbol) = &channel.symbol {
+            let depth = channel.depth.unwrap_or(DEFAULT_BOOK_DEPTH);
+            orderbook_manager.set_depth(symbol, depth as usize);
+        }
+    }
+}
+
+type WsSink = RecordingSink<futures_util::stream::SplitSink<WsStream, Message>>;
+
+/// Why a connection's message loop stopped
+enum SessionEnd {
+    /// The client disconnected (or was dropped)
+    Shutdown,
+    /// The socket closed or failed
+    ConnectionLost { code: u16, reason: String },
+    /// Nothing arrived within the liveness window
+    HeartbeatTimeout,
+}
+
+impl SessionEnd {
+    /// Abnormal closure (no close frame), as defined by RFC 6455
+    fn abnormal(reason: impl ToString) -> Self {
+        SessionEnd::ConnectionLost { code: 1006, reason: reason.to_string() }
+    }
+}
+
+/// Connection lifecycle shared by the client and its connection task
+///
+/// Every transition of the [`StateMachine`] is published on the event stream.
+#[derive(Clone)]
+struct Lifecycle {
+    machine: Arc<Mutex<StateMachine>>,
+    event_dispatcher: Arc<EventDispatcher>,
+}
+
+impl Lifecycle {
+    fn transition<F>(&self, apply: F) -> Option<StateTransition>
+    where
+        F: FnOnce(&mut StateMachine) -> Result<StateTransition, StateError>,
+    {
+        let result = apply(&mut self.machine.lock().unwrap());
+        match result {
+            Ok(transition) => {
+                self.event_dispatcher.dispatch_transition(transition.clone());
+                Some(transition)
+            }
+            Err(e) => {
+                tracing::debug!("Lifecycle transition skipped: {}", e);
+                None
+            }
+        }
+    }
+    
+    fn state(&self) -> state::ConnectionState {
+        self.machine.lock().unwrap().state().clone()
+    }
+    
+    fn retry_count(&self) -> u32 {
+        self.machine.lock().unwrap().retry_count()
+    }
+    
+    fn retry_delay(&self) -> Duration {
+        self.machine.lock().unwrap().retry_delay()
+    }
+}
+
+/// Background task that owns the socket and restores it after failures
+struct ConnectionSupervisor {
+    connection_manager: ConnectionManager,
+    event_dispatcher: Arc<EventDispatcher>,
+    subscription_manager: SubscriptionManager,
+    orderbook_manager: OrderBookManager,
+    sequence_manager: Arc<SequenceManager>,
+    message_handler: MessageHandler,
+    lifecycle: Lifecycle,
+    protocol: ProtocolVersion,
+    /// Whether a dropped connection is retried at all
+    auto_reconnect: bool,
+    reconnect_config: ReconnectConfig,
+    liveness_timeout: Duration,
+    book_recovery: BookRecovery,
+    /// Names pairs in REST `Depth` requests
+    rest_symbols: SymbolRegistry,
+    /// Books whose REST recovery failed and need resubscribing
+    recovery_fallback: mpsc::UnboundedSender<String>,
+    /// Refills trades and candles missed while reconnecting
+    backfill: Option<Backfill>,
+    /// Records frames; each session is recorded as a new connection
+    recorder: Option<SessionRecorder>,
+}
+
+impl ConnectionSupervisor {
+    async fn run(
+        mut self,
+        ws_stream: WsStream,
+        mut outgoing: mpsc::Receiver<Message>,
+        mut recovery_fallback: mpsc::UnboundedReceiver<String>,
+    ) {
+        let mut ws_stream = ws_stream;
         
-        // Handle incoming messages
-        let receiver_task = tokio::spawn({
-            let message_handler = self.message_handler.clone();
-            let event_dispatcher = Arc::clone(&self.event_dispatcher);
-            let orderbook_manager = self.orderbook_manager.clone();
-            
-            async move {
-                while let Some(message) = ws_receiver.next().await {
-                    match message {
-                        Ok(msg) => {
-                            if let Err(e) = Self::handle_message_static(msg, &message_handler, &event_dispatcher, &orderbook_manager).await {
-                                tracing::error!("Error handling message: {}", e);
-                                event_dispatcher.dispatch_error(e);
-                            }
-                        }
-                        Err(e) => {
-                            tracing::error!("WebSocket error: {}", e);
-                            event_dispatcher.dispatch_error(SdkError::Network(format!("WebSocket error: {}", e)));
-                            break;
-                        }
-                    }
+        loop {
+            match self.run_session(ws_stream, &mut outgoing, &mut recovery_fallback).await {
+                SessionEnd::Shutdown => return,
+                SessionEnd::ConnectionLost { code, reason } => {
+                    self.lifecycle.transition(|sm| sm.server_disconnect(code, reason));
+                }
+                SessionEnd::HeartbeatTimeout => {
+                    self.lifecycle.transition(|sm| sm.heartbeat_timeout());
                 }
             }
-        });
-        
-        // Wait for either task to complete
-        tokio::select! {
-            _ = sender_task => {
-                tracing::info!("Sender task completed");
+            
+            if !self.auto_reconnect {
+                tracing::warn!("Connection lost and reconnecting is disabled");
+                self.give_up("Connection lost and reconnecting is disabled".to_string());
+                return;
             }
-            _ = receiver_task => {
-                tracing::info!("Receiver task completed");
+            match self.reconnect().await {
+                Some(stream) => ws_stream = stream,
+                None => return,
             }
         }
-        
-        Ok(())
     }
     
-    /// Static method to handle messages (for use in async tasks)
-    async fn handle_message_static(
-        message: WebSocketMessage,
-        message_handler: &MessageHandler,
-        event_dispatcher: &Arc<EventDispatcher>,
-        orderbook_manager: &OrderBookManager,
-    ) -> Result<(), SdkError> {
-        match message {
-            Message::Text(text) => {
-                tracing::debug!("Received message: {}", text);
-                
-                // Process the message
-                if let Err(e) = message_handler.handle_message(&text).await {
-                    tracing::warn!("Failed to process message, continuing: {}", e);
+    /// Process one connection until it drops or the client disconnects
+    async fn run_session(
+        &self,
+        ws_stream: WsStream,
+        outgoing: &mut mpsc::Receiver<Message>,
+        recovery_fallback: &mut mpsc::UnboundedReceiver<String>,
+    ) -> SessionEnd {
+        let recorder = self.recorder.as_ref().map(SessionRecorder::connection);
+        let (ws_sender, ws_receiver) = ws_stream.split();
+        let mut ws_sender = RecordingSink::new(ws_sender, recorder.clone());
+        let mut ws_receiver = ws_receiver.inspect(move |message| {
+            if let (Some(recorder), Ok(message)) = (&recorder, message) {
+                recorder.record_inbound(message);
+            }
+        });
+        
+        // Send (or replay) every subscription
+        match self.subscription_manager.create_replay_messages() {
+            Ok(messages) => {
+                if !messages.is_empty() {
+                    tracing::info!("Sending {} subscription messages", messages.len());
                 }
-                
-                // Try to update order book if it's order book data
-                if text.contains("book") {
-                    if let Ok(parser) = KrakenDataParser::new().parse_orderbook(&text) {
-                        if let Err(e) = orderbook_manager.apply_update(parser) {
-                            tracing::warn!("Failed to update order book: {}", e);
-                        }
+                for message in messages {
+                    if let Err(e) = ws_sender.send(message).await {
+                        tracing::error!("Failed to send subscription message: {}", e);
                     }
                 }
             }
-            Message::Binary(data) => {
-                tracing::debug!("Received binary message: {} bytes", data.len());
-            }
-            Message::Ping(data) => {
-                tracing::debug!("Received ping, sending pong");
-                // Connection manager should handle pong response
+            Err(e) => {
+                tracing::error!("Failed to create subscription messages: {}", e);
+                self.event_dispatcher.dispatch_error(e.into());
             }
-            Message::Pong(_) => {
-                tracing::debug!("Received pong");
+        }
+        self.confirm_subscriptions_if_done();
+        
+        // Pings keep quiet connections alive; anything received counts as a
+        // sign of life, so a silent socket is treated as half-open
+        let ping_interval = self.connection_manager.ping_interval();
+        let mut ping_timer = tokio::time::interval_at(tokio::time::Instant::now() + ping_interval, ping_interval);
+        let mut last_activity = tokio::time::Instant::now();
+        
+        loop {
+            let liveness_deadline = last_activity + self.liveness_timeout;
+            
+            tokio::select! {
+                message = ws_receiver.next() => match message {
+                    Some(Ok(Message::Close(frame))) => {
+                        tracing::info!("WebSocket connection closed: {:?}", frame);
+                        return match frame {
+                            Some(frame) => SessionEnd::ConnectionLost {
+                                code: frame.code.into(),
+                                reason: frame.reason.to_string(),
+                            },
+                            // No status code present
+                            None => SessionEnd::ConnectionLost { code: 1005, reason: String::new() },
+                        };
+                    }
+                    None => {
+                        tracing::info!("WebSocket connection closed");
+                        return SessionEnd::abnormal("Connection closed without a close frame");
+                    }
+                    Some(Ok(Message::Ping(data))) => {
+                        last_activity = tokio::time::Instant::now();
+                        if let Err(e) = ws_sender.send(Message::Pong(data)).await {
+                            tracing::error!("Failed to answer ping: {}", e);
+                            return SessionEnd::abnormal(e);
+                        }
+                    }
+                    Some(Ok(msg)) => {
+                        last_activity = tokio::time::Instant::now();
+                        if let Err(e) = self.handle_message(msg).await {
+                            tracing::error!("Error handling message: {}", e);
+                            self.event_dispatcher.dispatch_error(e);
+                        }
+                        
+                        for symbol in self.orderbook_manager.take_resync_requests() {
+                            self.resync_book(symbol, &mut ws_sender).await;
+                        }
+                    }
+                    Some(Err(e)) => {
+                        tracing::error!("WebSocket error: {}", e);
+                        self.event_dispatcher.dispatch_error(SdkError::Network(format!("WebSocket error: {}", e)));
+                        return SessionEnd::abnormal(e);
+                    }
+                },
+                _ = ping_timer.tick() => {
+                    let ping = self.connection_manager.create_ping_message(self.protocol);
+                    if let Err(e) = ws_sender.send(ping).await {
+                        tracing::error!("Failed to send ping: {}", e);
+                        return SessionEnd::abnormal(e);
+                    }
+                }
+                _ = tokio::time::sleep_until(liveness_deadline) => {
+                    tracing::warn!("No data, heartbeat or pong for {:?}; connection is dead", self.liveness_timeout);
+                    self.event_dispatcher.dispatch_error(SdkError::Connection(ConnectionError::ConnectionLost(
+                        format!("No data, heartbeat or pong received for {:?}", self.liveness_timeout)
+                    )));
+                    return SessionEnd::HeartbeatTimeout;
+                }
+                Some(symbol) = recovery_fallback.recv() => {
+                    self.request_book_resync(&symbol, &mut ws_sender).await;
+                }
+                message = outgoing.recv() => match message {
+                    Some(message) => {
+                        if let Err(e) = ws_sender.send(message).await {
+                            tracing::error!("Failed to send WebSocket message: {}", e);
+                            return SessionEnd::abnormal(e);
+                        }
+                    }
+                    None => {
+                        let _ = ws_sender.close().await;
+                        return SessionEnd::Shutdown;
+                    }
+                },
             }
-            Message::Close(_) => {
-                tracing::info!("WebSocket connection closed");
-                event_dispatcher.dispatch_connection_state_change(ConnectionState::Disconnected);
+        }
+    }
+    
+    /// Reconnect with exponential backoff, reporting each attempt
+    ///
+    /// The backoff comes from the state machine, so it matches what
+    /// [`KrakenWsClient::retry_delay`] reports.
+    async fn reconnect(&mut self) -> Option<WsStream> {
+        let max_attempts = self.reconnect_config.max_attempts;
+        self.connection_manager.set_state(ConnectionState::Reconnecting);
+        self.event_dispatcher.dispatch_connection_state_change(ConnectionState::Reconnecting);
+        
+        loop {
+            // The state machine decides when to give up (and records why);
+            // a retry it rejects outright is giving up too
+            let delay = self.lifecycle.retry_delay();
+            let Some(transition) = self.lifecycle.transition(|sm| sm.retry()) else {
+                break;
+            };
+            if matches!(transition.to, state::ConnectionState::Closed { .. }) {
+                break;
             }
-            _ => {
-                tracing::debug!("Received other message type");
+            
+            let attempt = self.lifecycle.retry_count();
+            tracing::info!("Reconnection attempt {} of {} in {:?}", attempt, max_attempts, delay);
+            self.event_dispatcher.dispatch_reconnect(ReconnectEvent { attempt, max_attempts, delay });
+            tokio::time::sleep(delay).await;
+            
+            match self.connection_manager.connect().await {
+                Ok(stream) => {
+                    tracing::info!("Reconnected after {} attempts", attempt);
+                    self.lifecycle.transition(|sm| sm.connection_established());
+                    self.reset_order_books();
+                    self.start_backfill();
+                    self.event_dispatcher.dispatch_connection_state_change(ConnectionState::Connected);
+                    return Some(stream);
+                }
+                Err(e) => {
+                    tracing::warn!("Reconnection attempt {} failed: {}", attempt, e);
+                    self.lifecycle.transition(|sm| sm.connection_failed(e.to_string()));
+                }
             }
         }
         
-        Ok(())
+        tracing::error!("All {} reconnection attempts failed", max_attempts);
+        self.give_up(format!("Gave up after {} reconnection attempts", max_attempts));
+        None
     }
     
-    /// Handle incoming WebSocket messages
+    /// Leave the client `Failed`, reporting why
+    fn give_up(&self, reason: String) {
+        self.connection_manager.set_state(ConnectionState::Failed);
+        self.event_dispatcher.dispatch_connection_state_change(ConnectionState::Failed);
+        self.event_dispatcher.dispatch_error(SdkError::Connection(ConnectionError::ConnectionLost(reason)));
+    }
+    
+    /// Drop local books and sequence state; the replayed subscriptions
+    /// deliver fresh snapshots
+    fn reset_order_books(&self) {
+        for symbol in self.orderbook_manager.get_symbols() {
+            self.orderbook_manager.clear_order_book(&symbol);
+        }
+        self.orderbook_manager.take_resync_requests();
+        self.sequence_manager.reset_all();
+    }
+    
+    /// Refill the trades and candles missed while disconnected
+    ///
+    /// The gap is marked before the new connection is read, so live data
+    /// bounds the backfill and duplicates are dropped. The backfill runs
+    /// alongside the live feed, which isn't held back, so older backfilled
+    /// events may be delivered after newer live ones.
+    fn start_backfill(&self) {
+        let Some(backfill) = &self.backfill else {
+            return;
+        };
+        backfill.tracker.begin_gap();
+        let channels = self.subscription_manager.get_subscribed_channels();
+        tokio::spawn(backfill.clone().run(channels));
+    }
+    
+    /// Move from SUBSCRIBING to SUBSCRIBED once nothing is awaiting confirmation
+    fn confirm_subscriptions_if_done(&self) {
+        if self.lifecycle.state() == state::ConnectionState::Subscribing
+            && self.subscription_manager.pending_count() == 0
+        {
+            self.lifecycle.transition(|sm| sm.subscriptions_confirmed());
+        }
+    }
+    
+    /// Handle one incoming frame
     async fn handle_message(&self, message: WebSocketMessage) -> Result<(), SdkError> {
         match message {
             Message::Text(text) => {
                 tracing::debug!("Received message: {}", text);
                 
-                // Try to determine message type and parse accordingly
-                if let Err(e) = self.process_message(&text).await {
-                    // Handle malformed data gracefully - continue processing
+                // The handler tracks subscription status and pongs, updates
+                // local books and dispatches market data
+                if let Err(e) = self.message_handler.handle_message(&text).await {
                     tracing::warn!("Failed to process message, continuing: {}", e);
                 }
+                self.confirm_subscriptions_if_done();
             }
             Message::Binary(data) => {
                 tracing::debug!("Received binary message: {} bytes", data.len());
-                // Handle binary messages if needed
-            }
-            Message::Ping(_data) => {
-                tracing::debug!("Received ping, sending pong");
-                // Handle ping/pong for connection health
             }
             Message::Pong(_) => {
                 tracing::debug!("Received pong");
+                self.connection_manager.update_pong();
             }
             Message::Close(_) => {
                 tracing::info!("WebSocket connection closed");
-                self.event_dispatcher.dispatch_connection_state_change(ConnectionState::Disconnected);
             }
             _ => {
                 tracing::debug!("Received other message type");
@@ -301,75 +998,78 @@ impl KrakenWsClient {
         Ok(())
     }
     
-    /// Process and route messages to appropriate parsers
-    async fn process_message(&self, message: &str) -> Result<(), ProcessingError> {
-        // Check if it's a subscription status message
-        if message.contains("subscriptionStatus") {
-            if let Err(e) = self.subscription_manager.handle_subscription_confirmation(message) {
-                tracing::warn!("Subscription confirmation error: {}", e);
-            }
-            return Ok(());
-        }
-        
-        // Use the message handler to process the message
-        self.message_handler.handle_message(message).await
-    }
-    
-    /// Handle reconnection logic
-    async fn handle_reconnection(&self) -> Result<(), SdkError> {
-        tracing::info!("Attempting to reconnect...");
-        
-        // This would trigger the reconnection logic in ConnectionManager
-        // For now, we'll just log the attempt
-        self.event_dispatcher.dispatch_connection_state_change(ConnectionState::Reconnecting);
+    /// Restore an out-of-sync book as configured by [`BookRecovery`]
+    async fn resync_book(&self, symbol: String, sink: &mut WsSink) {
+        let BookRecovery::RestSnapshot(public_client) = &self.book_recovery else {
+            self.request_book_resync(&symbol, sink).await;
+            return;
+        };
         
-        Ok(())
-    }
-    
-    /// Get order book for a symbol
-    pub fn get_order_book(&self, symbol: &str) -> Option<crate::orderbook::OrderBook> {
-        self.orderbook_manager.get_order_book(symbol)
-    }
-    
-    /// Get best bid and ask prices for a symbol
-    pub fn get_best_bid_ask(&self, symbol: &str) -> Option<(Option<rust_decimal::Decimal>, Option<rust_decimal::Decimal>)> {
-        self.orderbook_manager.get_best_bid_ask(symbol)
-    }
-    
-    /// Get active subscriptions
-    pub fn get_active_subscriptions(&self) -> Vec<String> {
-        self.subscription_manager.get_active_subscriptions()
-    }
-    
-    /// Check if subscribed to a channel
-    pub fn is_subscribed(&self, channel: &Channel) -> bool {
-        self.subscription_manager.is_subscribed(channel)
-    }
-    
-    /// Get callback count for a data type
-    pub fn get_callback_count(&self, data_type: &DataType) -> usize {
-        self.event_dispatcher.get_callback_count(data_type)
+        // The socket keeps flowing while the snapshot is fetched; its deltas
+        // are buffered by the order book manager
+        let orderbook_manager = self.orderbook_manager.clone();
+        let public_client = public_client.clone();
+        let rest_symbols = self.rest_symbols.clone();
+        let fallback = self.recovery_fallback.clone();
+        tokio::spawn(async move {
+            if let Err(e) = recover_book(&orderbook_manager, &public_client, &rest_symbols, &symbol).await {
+                tracing::warn!("REST recovery failed for {}, resubscribing: {}", symbol, e);
+                let _ = fallback.send(symbol);
+            }
+        });
     }
     
-    /// Cleanup resources
-    pub async fn cleanup(&mut self) -> Result<(), SdkError> {
-        tracing::info!("Cleaning up SDK resources");
-        
-        // Disconnect if connected
-        if self.is_connected() {
-            self.disconnect().await?;
+    /// Resubscribe to a book channel to get a fresh snapshot
+    async fn request_book_resync(&self, symbol: &str, sink: &mut WsSink) {
+        let channels: Vec<Channel> = self.subscription_manager.get_subscribed_channels()
+            .into_iter()
+            .filter(|c| c.name == "book" && c.symbol.as_deref() == Some(symbol))
+            .collect();
+        if channels.is_empty() {
+            tracing::warn!("No book subscription found for {}, cannot resync", symbol);
+            return;
         }
         
-        // Clear order books
-        for symbol in self.orderbook_manager.get_symbols() {
-            self.orderbook_manager.clear_order_book(&symbol);
+        match self.subscription_manager.create_resubscription_messages(&channels) {
+            Ok(messages) => {
+                tracing::info!("Resubscribing to book for {} after checksum mismatch", symbol);
+                for message in messages {
+                    if let Err(e) = sink.send(message).await {
+                        tracing::error!("Failed to send resync message: {}", e);
+                        return;
+                    }
+                }
+            }
+            Err(e) => tracing::error!("Failed to create resync messages for {}: {}", symbol, e),
         }
-        
-        tracing::info!("SDK cleanup completed");
-        Ok(())
     }
 }
 
+/// Install a REST `Depth` snapshot for `symbol`, replaying the deltas
+/// received while it was fetched
+async fn recover_book(
+    orderbook_manager: &OrderBookManager,
+    public_client: &KrakenPublicClient,
+    rest_symbols: &SymbolRegistry,
+    symbol: &str,
+) -> Result<Arc<OrderBook>, SdkError> {
+    orderbook_manager.begin_recovery(symbol);
+    
+    let pair = rest_symbols.convert(symbol, SymbolFormat::AltName);
+    let depth = orderbook_manager.depth(symbol).map(|depth| depth as u32);
+    let mut snapshot = match public_client.get_order_book(&pair, depth).await {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            orderbook_manager.abandon_recovery(symbol);
+            return Err(e);
+        }
+    };
+    
+    // Kraken keys the result by its legacy pair name
+    snapshot.symbol = symbol.to_string();
+    Ok(orderbook_manager.apply_recovery_snapshot(snapshot)?)
+}
+
 /// Builder pattern for client configuration
 pub struct ClientConfigBuilder {
     config: ClientConfig,
@@ -387,6 +1087,16 @@ impl ClientConfigBuilder {
         self
     }
     
+    /// Select the protocol version, switching the endpoint to that version's
+    /// default unless a custom endpoint was configured
+    pub fn protocol(mut self, protocol: ProtocolVersion) -> Self {
+        if self.config.endpoint == self.config.protocol.default_endpoint() {
+            self.config.endpoint = protocol.default_endpoint().to_string();
+        }
+        self.config.protocol = protocol;
+        self
+    }
+    
     pub fn api_credentials(mut self, api_key: &str, api_secret: &str) -> Self {
         self.config.api_key = Some(api_key.to_string());
         self.config.api_secret = Some(api_secret.to_string());
@@ -403,11 +1113,111 @@ impl ClientConfigBuilder {
         self
     }
     
+    /// Interval between application-level `ping` requests
+    pub fn ping_interval(mut self, interval: std::time::Duration) -> Self {
+        self.config.ping_interval = interval;
+        self
+    }
+    
+    /// How long the connection may stay silent before it is declared dead
+    pub fn liveness_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.config.liveness_timeout = timeout;
+        self
+    }
+    
+    /// How long `subscribe`/`unsubscribe` wait for Kraken's confirmation
+    pub fn subscription_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.config.subscription_timeout = timeout;
+        self
+    }
+    
+    /// Capacity of the queue of outbound requests waiting for the socket
+    pub fn outgoing_buffer_size(mut self, size: usize) -> Self {
+        self.config.outgoing_buffer_size = size;
+        self
+    }
+    
+    /// Set sequence validation for sequenced channels
+    pub fn sequence_config(mut self, sequence_config: SequenceConfig) -> Self {
+        self.config.sequence_config = sequence_config;
+        self
+    }
+    
+    /// Set sample retention for latency tracking
+    pub fn latency_config(mut self, latency_config: LatencyConfig) -> Self {
+        self.config.latency_config = latency_config;
+        self
+    }
+    
+    /// Set latency alert thresholds and callback
+    pub fn latency_alerts(mut self, latency_alerts: LatencyAlertConfig) -> Self {
+        self.config.latency_alerts = Some(latency_alerts);
+        self
+    }
+    
+    /// Receive latency alerts, keeping any thresholds already configured
+    pub fn on_latency_alert<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(LatencyAlert) + Send + Sync + 'static
+    {
+        let alerts = match self.config.latency_alerts.take() {
+            Some(mut alerts) => {
+                alerts.callback = Arc::new(callback);
+                alerts
+            }
+            None => LatencyAlertConfig::new(callback),
+        };
+        self.config.latency_alerts = Some(alerts);
+        self
+    }
+    
+    /// Set flow control for outbound events
+    pub fn backpressure_config(mut self, backpressure_config: BackpressureConfig) -> Self {
+        self.config.backpressure_config = backpressure_config;
+        self
+    }
+    
+    /// Reconnect after the connection drops (on by default)
+    pub fn auto_reconnect(mut self, enabled: bool) -> Self {
+        self.config.auto_reconnect = enabled;
+        self
+    }
+    
     pub fn reconnect_config(mut self, reconnect_config: ReconnectConfig) -> Self {
         self.config.reconnect_config = reconnect_config;
         self
     }
     
+    /// Rewrite pair names on dispatched events, e.g. to canonical `BTC/USD`
+    pub fn symbols(mut self, symbols: SymbolNormalizer) -> Self {
+        self.config.symbols = Some(symbols);
+        self
+    }
+    
+    /// Restore out-of-sync books from REST snapshots instead of resubscribing
+    pub fn book_recovery(mut self, recovery: BookRecovery) -> Self {
+        self.config.book_recovery = recovery;
+        self
+    }
+    
+    /// Refill trades and candles missed during reconnects from REST history
+    pub fn backfill(mut self, backfill: BackfillConfig) -> Self {
+        self.config.backfill = Some(backfill);
+        self
+    }
+    
+    /// Record every frame sent and received
+    pub fn recorder(mut self, recorder: SessionRecorder) -> Self {
+        self.config.recorder = Some(recorder);
+        self
+    }
+    
+    /// Build candles from trades and publish them as events
+    pub fn candles(mut self, candles: CandleConfig) -> Self {
+        self.config.candles = Some(candles);
+        self
+    }
+    
     pub fn build(self) -> ClientConfig {
         self.config
     }
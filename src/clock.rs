@@ -0,0 +1,90 @@
+//! Time source for time-dependent components
+//!
+//! Parsers stamp frames that carry no exchange time, and analytics age
+//! their state, by the current time. Live, that is the system clock. A
+//! replay drives a [`Clock::manual`] clock instead, set to each recorded
+//! frame's receive time, so the output is the same however fast the
+//! frames are fed.
+
+use chrono::{DateTime, Duration, Utc};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// System or manually driven clock
+///
+/// Clones of a manual clock share its time.
+#[derive(Clone, Default)]
+pub struct Clock {
+    manual: Option<Arc<Mutex<DateTime<Utc>>>>,
+}
+
+impl Clock {
+    /// The system clock
+    pub fn system() -> Self {
+        Self::default()
+    }
+
+    /// A clock that only moves when set
+    pub fn manual(start: DateTime<Utc>) -> Self {
+        Self {
+            manual: Some(Arc::new(Mutex::new(start))),
+        }
+    }
+
+    pub fn is_manual(&self) -> bool {
+        self.manual.is_some()
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        match &self.manual {
+            Some(time) => *time.lock().unwrap(),
+            None => Utc::now(),
+        }
+    }
+
+    /// Move a manual clock to `at`; the system clock ignores this
+    pub fn set(&self, at: DateTime<Utc>) {
+        if let Some(time) = &self.manual {
+            *time.lock().unwrap() = at;
+        }
+    }
+
+    /// Move a manual clock forward; the system clock ignores this
+    pub fn advance(&self, by: Duration) {
+        if let Some(time) = &self.manual {
+            let mut time = time.lock().unwrap();
+            *time += by;
+        }
+    }
+}
+
+impl fmt::Debug for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.manual {
+            Some(time) => f.debug_tuple("Clock::Manual").field(&*time.lock().unwrap()).finish(),
+            None => f.write_str("Clock::System"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock_is_shared_by_clones() {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let clock = Clock::manual(start);
+        let shared = clock.clone();
+
+        shared.advance(Duration::milliseconds(250));
+        assert_eq!(clock.now(), start + Duration::milliseconds(250));
+        clock.set(start);
+        assert_eq!(shared.now(), start);
+
+        let system = Clock::system();
+        system.set(start);
+        assert!(!system.is_manual());
+        assert!(system.now() > start);
+    }
+}
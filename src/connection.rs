@@ -1,10 +1,11 @@
 //! WebSocket connection management
 
 use crate::{
-    data::{ConnectionConfig, ConnectionState, ReconnectConfig},
+    data::{ConnectionConfig, ConnectionState, ProtocolVersion, ReconnectConfig},
     error::ConnectionError,
 };
 // use futures_util::{SinkExt, StreamExt};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::time::{sleep, Instant};
@@ -21,6 +22,7 @@ pub struct ConnectionManager {
     reconnect_strategy: ReconnectStrategy,
     last_ping: Arc<Mutex<Option<Instant>>>,
     last_pong: Arc<Mutex<Option<Instant>>>,
+    next_ping_id: Arc<AtomicU64>,
 }
 
 impl ConnectionManager {
@@ -31,9 +33,15 @@ impl ConnectionManager {
             reconnect_strategy: ReconnectStrategy::new(reconnect_config),
             last_ping: Arc::new(Mutex::new(None)),
             last_pong: Arc::new(Mutex::new(None)),
+            next_ping_id: Arc::new(AtomicU64::new(1)),
         }
     }
     
+    /// Interval between application-level pings
+    pub fn ping_interval(&self) -> Duration {
+        self.config.ping_interval
+    }
+    
     /// Establish WebSocket connection
     pub async fn connect(&mut self) -> Result<WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>, ConnectionError> {
         self.set_state(ConnectionState::Connecting);
@@ -50,6 +58,8 @@ impl ConnectionManager {
                     Ok((ws_stream, _)) => {
                         self.set_state(ConnectionState::Connected);
                         self.reconnect_strategy.reset();
+                        *self.last_ping.lock().unwrap() = None;
+                        *self.last_pong.lock().unwrap() = None;
                         tracing::info!("WebSocket connection established");
                         Ok(ws_stream)
                     }
@@ -215,10 +225,24 @@ impl ConnectionManager {
         Ok(false)
     }
     
-    fn set_state(&self, state: ConnectionState) {
+    pub(crate) fn set_state(&self, state: ConnectionState) {
         *self.state.lock().unwrap() = state;
     }
     
+    /// Create an application-level ping request and record the ping time
+    ///
+    /// Kraken answers with a `pong` event (v1) or method response (v2).
+    pub fn create_ping_message(&self, protocol: ProtocolVersion) -> Message {
+        let req_id = self.next_ping_id.fetch_add(1, Ordering::Relaxed);
+        let ping = match protocol {
+            ProtocolVersion::V1 => serde_json::json!({ "event": "ping", "reqid": req_id }),
+            ProtocolVersion::V2 => serde_json::json!({ "method": "ping", "req_id": req_id }),
+        };
+        
+        self.update_ping();
+        Message::Text(ping.to_string())
+    }
+    
     /// Update ping timestamp
     pub fn update_ping(&self) {
         *self.last_ping.lock().unwrap() = Some(Instant::now());
@@ -247,7 +271,22 @@ impl ConnectionManager {
     }
 }
 
+impl Clone for ConnectionManager {
+    /// Clones share connection state and ping/pong timestamps
+    fn clone(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            state: Arc::clone(&self.state),
+            reconnect_strategy: self.reconnect_strategy.clone(),
+            last_ping: Arc::clone(&self.last_ping),
+            last_pong: Arc::clone(&self.last_pong),
+            next_ping_id: Arc::clone(&self.next_ping_id),
+        }
+    }
+}
+
 /// Reconnection strategy with exponential backoff
+#[derive(Clone)]
 pub struct ReconnectStrategy {
     config: ReconnectConfig,
     current_delay: Duration,
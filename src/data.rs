@@ -1,19 +1,67 @@
 //! Data models for market data structures
 
+use crate::backfill::BackfillConfig;
+use crate::backpressure::BackpressureConfig;
+use crate::candles::CandleConfig;
+use crate::latency::{LatencyAlertConfig, LatencyConfig};
+use crate::recorder::SessionRecorder;
+use crate::rest_client::KrakenPublicClient;
+use crate::sequencing::SequenceConfig;
+use crate::symbols::SymbolNormalizer;
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// Ticker data structure
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+///
+/// v1 tickers report both "today" (since midnight UTC) and rolling 24h
+/// statistics; v2 tickers only the 24h ones, so the `*_today` fields are
+/// `None` for v2.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
 pub struct TickerData {
     pub symbol: String,
     pub bid: Decimal,
     pub ask: Decimal,
     pub last_price: Decimal,
+    /// Volume today for v1, over the last 24h for v2
     pub volume: Decimal,
     pub timestamp: DateTime<Utc>,
+    /// Quantity at the best bid
+    pub bid_qty: Decimal,
+    /// Quantity at the best ask
+    pub ask_qty: Decimal,
+    /// Quantity of the last trade (v1 only)
+    pub last_qty: Option<Decimal>,
+    pub volume_24h: Decimal,
+    /// Volume-weighted average price today (v1 only)
+    pub vwap_today: Option<Decimal>,
+    pub vwap_24h: Decimal,
+    /// Number of trades today (v1 only)
+    pub trades_today: Option<u64>,
+    /// Number of trades over the last 24h (v1 only)
+    pub trades_24h: Option<u64>,
+    pub low_today: Option<Decimal>,
+    pub low_24h: Decimal,
+    pub high_today: Option<Decimal>,
+    pub high_24h: Decimal,
+    /// Opening price today (v1 only)
+    pub open_today: Option<Decimal>,
+    /// Price 24h ago (v2 derives it from `last` and `change`)
+    pub open_24h: Decimal,
+}
+
+impl TickerData {
+    /// Midpoint between best bid and ask
+    pub fn mid_price(&self) -> Decimal {
+        (self.bid + self.ask) / Decimal::TWO
+    }
+    
+    /// Best ask minus best bid
+    pub fn spread(&self) -> Decimal {
+        self.ask - self.bid
+    }
 }
 
 impl fmt::Display for TickerData {
@@ -36,6 +84,30 @@ pub struct OrderBookUpdate {
     pub checksum: Option<u32>,
 }
 
+impl OrderBookUpdate {
+    /// Fold a later update for the same book into this one
+    ///
+    /// Levels at a price the later update touches are replaced by the later
+    /// level (a zero volume still means removal), so applying the merged
+    /// update leaves the book in the same state as applying both in order.
+    /// The later timestamp and checksum win.
+    pub fn merge(&mut self, later: OrderBookUpdate) {
+        merge_levels(&mut self.bids, later.bids);
+        merge_levels(&mut self.asks, later.asks);
+        self.timestamp = later.timestamp;
+        self.checksum = later.checksum;
+    }
+}
+
+fn merge_levels(levels: &mut Vec<PriceLevel>, later: Vec<PriceLevel>) {
+    for level in later {
+        match levels.iter_mut().find(|existing| existing.price == level.price) {
+            Some(existing) => *existing = level,
+            None => levels.push(level),
+        }
+    }
+}
+
 impl fmt::Display for OrderBookUpdate {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -55,6 +127,9 @@ pub struct TradeData {
     pub side: TradeSide,
     pub timestamp: DateTime<Utc>,
     pub trade_id: String,
+    /// Recovered from REST history after a reconnect rather than received live
+    #[serde(default)]
+    pub backfilled: bool,
 }
 
 impl fmt::Display for TradeData {
@@ -92,6 +167,21 @@ pub struct OHLCData {
     pub volume: Decimal,
     pub timestamp: DateTime<Utc>,
     pub interval: String,
+    /// Recovered from REST history after a reconnect rather than received live
+    #[serde(default)]
+    pub backfilled: bool,
+}
+
+impl OHLCData {
+    /// Start of the candle's interval
+    ///
+    /// v1 candles are stamped with the time of their last update, v2 and
+    /// REST candles with the interval start.
+    pub fn interval_begin(&self) -> DateTime<Utc> {
+        let seconds = self.interval.parse::<i64>().unwrap_or(1).max(1) * 60;
+        let begin = self.timestamp.timestamp().div_euclid(seconds) * seconds;
+        DateTime::from_timestamp(begin, 0).unwrap_or(self.timestamp)
+    }
 }
 
 impl fmt::Display for OHLCData {
@@ -104,6 +194,64 @@ impl fmt::Display for OHLCData {
     }
 }
 
+/// Best bid and offer from the v1 `spread` channel
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SpreadData {
+    pub symbol: String,
+    pub bid: Decimal,
+    pub ask: Decimal,
+    pub bid_volume: Decimal,
+    pub ask_volume: Decimal,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl SpreadData {
+    /// Best ask minus best bid
+    pub fn spread(&self) -> Decimal {
+        self.ask - self.bid
+    }
+    
+    /// Midpoint between best bid and ask
+    pub fn mid_price(&self) -> Decimal {
+        (self.bid + self.ask) / Decimal::TWO
+    }
+}
+
+impl fmt::Display for SpreadData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Spread[{}]: bid={}x{}, ask={}x{} @ {}",
+            self.symbol, self.bid, self.bid_volume, self.ask, self.ask_volume, self.timestamp
+        )
+    }
+}
+
+/// Tradable instrument reference data (v2 `instrument` channel)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InstrumentData {
+    pub symbol: String,
+    pub base: String,
+    pub quote: String,
+    pub status: String,
+    pub price_precision: u32,
+    pub price_increment: Decimal,
+    pub qty_precision: u32,
+    pub qty_increment: Decimal,
+    pub qty_min: Decimal,
+    pub cost_min: Decimal,
+}
+
+impl fmt::Display for InstrumentData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Instrument[{}]: {} tick={} lot={} min={}",
+            self.symbol, self.status, self.price_increment, self.qty_increment, self.qty_min
+        )
+    }
+}
+
 /// Trade side enumeration
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum TradeSide {
@@ -118,6 +266,51 @@ pub enum DataType {
     OrderBook,
     Trade,
     OHLC,
+    Spread,
+}
+
+/// Kraken public WebSocket v1 endpoint
+pub const KRAKEN_WS_V1_URL: &str = "wss://ws.kraken.com";
+
+/// Kraken public WebSocket v2 endpoint
+pub const KRAKEN_WS_V2_URL: &str = "wss://ws.kraken.com/v2";
+
+/// Kraken WebSocket protocol version
+///
+/// V1 uses `event`/`pair` requests and `[channelID, data, channelName, pair]`
+/// frames; V2 uses `method`/`params`/`req_id` requests and
+/// `{"channel", "type", "data"}` frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum ProtocolVersion {
+    #[default]
+    V1,
+    V2,
+}
+
+impl ProtocolVersion {
+    /// Default public endpoint for this protocol version
+    pub fn default_endpoint(&self) -> &'static str {
+        match self {
+            ProtocolVersion::V1 => KRAKEN_WS_V1_URL,
+            ProtocolVersion::V2 => KRAKEN_WS_V2_URL,
+        }
+    }
+}
+
+/// How an out-of-sync order book is restored
+///
+/// Books fall out of sync on a checksum mismatch or a sequence gap.
+#[derive(Debug, Clone, Default)]
+pub enum BookRecovery {
+    /// Resubscribe to the book channel for a fresh WebSocket snapshot
+    #[default]
+    Resubscribe,
+    /// Fetch a REST `Depth` snapshot and replay the deltas received while
+    /// it was in flight, falling back to resubscribing if that fails
+    ///
+    /// REST requests go through the client's rate limiter, so recovering
+    /// many books at once is throttled locally instead of by Kraken.
+    RestSnapshot(KrakenPublicClient),
 }
 
 /// Client configuration
@@ -126,9 +319,47 @@ pub struct ClientConfig {
     pub api_key: Option<String>,
     pub api_secret: Option<String>,
     pub endpoint: String,
+    pub protocol: ProtocolVersion,
+    /// Reconnect with `reconnect_config` after the connection drops; when
+    /// off, a dropped connection leaves the client `Failed`
+    pub auto_reconnect: bool,
     pub reconnect_config: ReconnectConfig,
     pub buffer_size: usize,
     pub timeout: std::time::Duration,
+    /// Interval between application-level `ping` requests
+    pub ping_interval: std::time::Duration,
+    /// The connection is declared dead when nothing (data, heartbeat or
+    /// pong) arrives within this window
+    pub liveness_timeout: std::time::Duration,
+    /// Sequence validation for sequenced channels (v2 book updates that
+    /// carry a `sequence`); gaps beyond `max_gap_size` trigger a resync
+    pub sequence_config: SequenceConfig,
+    /// Flow control between dispatch and consumers: rate limits, drop policy
+    /// and per-symbol coalescing of order book updates. Events are queued
+    /// here while the delivery task is behind.
+    pub backpressure_config: BackpressureConfig,
+    /// Sample retention for the per-message latency tracker
+    pub latency_config: LatencyConfig,
+    /// Thresholds and callback for latency alerts (none by default)
+    pub latency_alerts: Option<LatencyAlertConfig>,
+    /// How long `subscribe`/`unsubscribe` wait for Kraken to confirm
+    pub subscription_timeout: std::time::Duration,
+    /// Capacity of the queue of outbound requests (subscriptions, resyncs)
+    /// waiting for the socket; senders wait while it is full
+    pub outgoing_buffer_size: usize,
+    /// Rewrites pair names on dispatched events (names are left as Kraken
+    /// sent them by default)
+    pub symbols: Option<SymbolNormalizer>,
+    /// How books are restored after a checksum mismatch or sequence gap
+    pub book_recovery: BookRecovery,
+    /// Refill trades and candles missed during reconnects from REST history
+    /// (off by default); backfilled events may arrive after newer live ones
+    pub backfill: Option<BackfillConfig>,
+    /// Writes every frame sent and received to disk (off by default)
+    pub recorder: Option<SessionRecorder>,
+    /// Build candles from dispatched trades and publish them as
+    /// [`SdkEvent::Candle`](crate::events::SdkEvent::Candle) (off by default)
+    pub candles: Option<CandleConfig>,
 }
 
 impl ClientConfig {
@@ -142,14 +373,45 @@ impl ClientConfig {
             return Err("Endpoint must be a valid WebSocket URL".to_string());
         }
         
+        // Kraken's own endpoints only speak one protocol version each
+        let endpoint = self.endpoint.trim_end_matches('/');
+        for version in [ProtocolVersion::V1, ProtocolVersion::V2] {
+            if version != self.protocol && endpoint == version.default_endpoint() {
+                return Err(format!(
+                    "Endpoint {} is Kraken's {:?} endpoint but protocol is {:?}",
+                    self.endpoint, version, self.protocol
+                ));
+            }
+        }
+        
         if self.buffer_size == 0 {
             return Err("Buffer size must be greater than 0".to_string());
         }
         
+        if self.outgoing_buffer_size == 0 {
+            return Err("Outgoing buffer size must be greater than 0".to_string());
+        }
+        
+        if self.backpressure_config.max_buffer_size == 0 {
+            return Err("Backpressure buffer size must be greater than 0".to_string());
+        }
+        
         if self.timeout.as_secs() == 0 {
             return Err("Timeout must be greater than 0".to_string());
         }
         
+        if self.subscription_timeout.is_zero() {
+            return Err("Subscription timeout must be greater than 0".to_string());
+        }
+        
+        if self.ping_interval.is_zero() {
+            return Err("Ping interval must be greater than 0".to_string());
+        }
+        
+        if self.liveness_timeout <= self.ping_interval {
+            return Err("Liveness timeout must be greater than ping interval".to_string());
+        }
+        
         self.reconnect_config.validate()?;
         
         Ok(())
@@ -161,10 +423,25 @@ impl Default for ClientConfig {
         Self {
             api_key: None,
             api_secret: None,
-            endpoint: "wss://ws.kraken.com".to_string(),
+            endpoint: KRAKEN_WS_V1_URL.to_string(),
+            protocol: ProtocolVersion::V1,
+            auto_reconnect: true,
             reconnect_config: ReconnectConfig::default(),
             buffer_size: 1024,
             timeout: std::time::Duration::from_secs(30),
+            ping_interval: std::time::Duration::from_secs(10),
+            liveness_timeout: std::time::Duration::from_secs(30),
+            sequence_config: SequenceConfig::strict(),
+            backpressure_config: BackpressureConfig::default(),
+            latency_config: LatencyConfig::default(),
+            latency_alerts: None,
+            subscription_timeout: std::time::Duration::from_secs(10),
+            outgoing_buffer_size: 100,
+            symbols: None,
+            book_recovery: BookRecovery::default(),
+            backfill: None,
+            recorder: None,
+            candles: None,
         }
     }
 }
@@ -212,6 +489,17 @@ impl Default for ReconnectConfig {
     }
 }
 
+/// A reconnection attempt reported by the client
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectEvent {
+    /// Attempt number, starting at 1
+    pub attempt: u32,
+    /// Attempts allowed before the client gives up
+    pub max_attempts: u32,
+    /// Backoff delay before this attempt
+    pub delay: std::time::Duration,
+}
+
 /// Connection configuration
 #[derive(Debug, Clone)]
 pub struct ConnectionConfig {
@@ -223,7 +511,7 @@ pub struct ConnectionConfig {
 impl Default for ConnectionConfig {
     fn default() -> Self {
         Self {
-            endpoint: "wss://ws.kraken.com".to_string(),
+            endpoint: KRAKEN_WS_V1_URL.to_string(),
             timeout: std::time::Duration::from_secs(30),
             ping_interval: std::time::Duration::from_secs(30),
         }
@@ -246,6 +534,7 @@ pub struct Channel {
     pub name: String,
     pub symbol: Option<String>,
     pub interval: Option<String>,
+    pub depth: Option<u32>,
 }
 
 impl Channel {
@@ -254,6 +543,7 @@ impl Channel {
             name: name.to_string(),
             symbol: None,
             interval: None,
+            depth: None,
         }
     }
     
@@ -266,4 +556,9 @@ impl Channel {
         self.interval = Some(interval.to_string());
         self
     }
-}
\ No newline at end of file
+    
+    pub fn with_depth(mut self, depth: u32) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+}
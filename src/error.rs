@@ -3,7 +3,6 @@
 use thiserror::Error;
 use std::fmt;
 use chrono;
-use std::collections;
 
 /// Main error type for the SDK
 #[derive(Error, Debug, Clone)]
@@ -28,6 +27,18 @@ pub enum SdkError {
     
     #[error("Not implemented: {0}")]
     NotImplemented(String),
+    
+    #[error("Order rejected while exchange is {status}: {reason}")]
+    OrderRejected {
+        status: crate::system_status::ExchangeStatus,
+        reason: String,
+    },
+    
+    #[error("Invalid order for {pair}: {reason}")]
+    InvalidOrder {
+        pair: String,
+        reason: String,
+    },
 }
 
 /// Connection-specific errors
@@ -60,6 +71,13 @@ pub enum ParseError {
     
     #[error("Malformed message: {0}")]
     MalformedMessage(String),
+    
+    #[error("Checksum mismatch for {symbol}: expected {expected}, calculated {calculated}")]
+    ChecksumMismatch {
+        symbol: String,
+        expected: u32,
+        calculated: u32,
+    },
 }
 
 /// Subscription-specific errors
@@ -76,6 +94,18 @@ pub enum SubscriptionError {
     
     #[error("Not subscribed: {0}")]
     NotSubscribed(String),
+    
+    #[error("Unknown pair: {0}")]
+    UnknownPair(String),
+    
+    #[error("Invalid book depth: {0}. Valid depths: [10, 25, 100, 500, 1000]")]
+    InvalidDepth(u32),
+    
+    #[error("No response to request {req_id} within {timeout:?}")]
+    Timeout { req_id: u64, timeout: std::time::Duration },
+    
+    #[error("No pooled connection has room for {channel} ({connections} connections, all full)")]
+    PoolExhausted { channel: String, connections: usize },
 }
 
 /// Processing-specific errors
@@ -178,6 +208,8 @@ impl ErrorSeverity {
             SdkError::Subscription(_) => ErrorSeverity::Medium,
             SdkError::Network(_) => ErrorSeverity::Medium,
             SdkError::NotImplemented(_) => ErrorSeverity::Low,
+            SdkError::OrderRejected { .. } => ErrorSeverity::Medium,
+            SdkError::InvalidOrder { .. } => ErrorSeverity::Medium,
         }
     }
 }
@@ -3,7 +3,12 @@
 //! This module provides two APIs for consuming SDK events:
 //!
 //! 1. **Callback API** - Register callbacks per data type (traditional approach)
-//! 2. **Stream API** - Single unified event stream (recommended for new code)
+//! 2. **Stream API** - Independent, filterable event streams (recommended for new code)
+//!
+//! Each stream has its own bounded buffer. A stream that falls behind has
+//! its pending book updates merged per symbol, then loses its oldest events
+//! and is told how many with [`SdkEvent::Lagged`]; other streams and
+//! callbacks are unaffected.
 //!
 //! # Stream API Example
 //!
@@ -19,16 +24,53 @@
 //!         SdkEvent::Trade(data) => println!("Trade: {}", data.symbol),
 //!         SdkEvent::OrderBook(data) => println!("Book: {}", data.symbol),
 //!         SdkEvent::Ohlc(data) => println!("OHLC: {}", data.symbol),
+//!         SdkEvent::Spread(data) => println!("Spread: {} {}", data.symbol, data.spread()),
+//!         SdkEvent::Instrument(data) => println!("Instrument: {}", data.symbol),
+//!         SdkEvent::Candle(event) => println!("Candle: {:?}", event.candle().bar),
+//!         SdkEvent::ChecksumMismatch(m) => println!("Resyncing book: {}", m.symbol),
+//!         SdkEvent::Gap(g) => println!("{} missed {} messages", g.channel, g.gap_size),
+//!         SdkEvent::Resync(r) => println!("Resyncing {}: {:?}", r.channel, r.reason),
+//!         SdkEvent::Reconnecting(r) => println!("Reconnect attempt {}", r.attempt),
 //!         SdkEvent::State(state) => println!("State: {:?}", state),
+//!         SdkEvent::Transition(t) => println!("{} -> {} ({:?})", t.from, t.to, t.trigger),
+//!         SdkEvent::SystemStatus(s) => println!("Exchange is {}", s.status),
+//!         SdkEvent::Heartbeat(_) => {}
 //!         SdkEvent::Error(err) => eprintln!("Error: {}", err),
+//!         SdkEvent::Lagged(missed) => eprintln!("Fell behind, missed {} events", missed),
 //!     }
 //! }
+//!
+//! // A second, independent view: only BTC trades and books
+//! let btc = client.events_with_filter(
+//!     EventFilter::new()
+//!         .symbol("XBT/USD")
+//!         .kind(EventKind::Trade)
+//!         .kind(EventKind::OrderBook),
+//! );
 //! ```
 
-use crate::{data::*, error::SdkError};
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
-use tokio::sync::mpsc;
+use crate::{
+    backpressure::{BackpressureConfig, BackpressureManager, BackpressureStats, BufferedMessage, Coalesce},
+    candles::CandleEvent,
+    data::*,
+    error::{ParseError, SdkError},
+    orderbook::ChecksumMismatch,
+    sequencing::{GapEvent, ResyncEvent},
+    state::StateTransition,
+    system_status::{Heartbeat, SystemStatus},
+};
+use futures_util::Stream;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, TryLockError};
+use std::task::{Context, Poll, Waker};
+use std::time::Instant;
+use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::Notify;
+
+/// Default capacity of each event stream
+pub const DEFAULT_STREAM_BUFFER: usize = 1024;
 
 /// Unified SDK event enum - single type for all events
 ///
@@ -44,13 +86,94 @@ pub enum SdkEvent {
     OrderBook(OrderBookUpdate),
     /// OHLC candle update
     Ohlc(OHLCData),
+    /// Best bid/ask update (v1 `spread` channel)
+    Spread(SpreadData),
+    /// Instrument reference data (v2 `instrument` channel)
+    Instrument(InstrumentData),
+    /// Locally built candle changed or closed (see [`ClientConfig::candles`])
+    Candle(CandleEvent),
+    /// Local order book failed checksum validation and is being resynced
+    ChecksumMismatch(ChecksumMismatch),
+    /// Sequence gap detected on a sequenced channel
+    Gap(GapEvent),
+    /// Sequenced channel is being resynced from a fresh snapshot
+    Resync(ResyncEvent),
+    /// Reconnection attempt after the connection dropped
+    Reconnecting(ReconnectEvent),
     /// Connection state change
     State(ConnectionState),
+    /// Connection lifecycle transition, including why it happened
+    Transition(StateTransition),
+    /// Exchange status announced by Kraken (online, maintenance, cancel_only, ...)
+    SystemStatus(SystemStatus),
+    /// Kraken heartbeat on an otherwise quiet connection
+    Heartbeat(Heartbeat),
     /// Error occurred
     Error(SdkError),
+    /// This stream fell behind and missed this many events
+    ///
+    /// Only ever produced by the stream that lagged; never dispatched.
+    Lagged(u64),
+}
+
+/// Event kind, for filtering streams
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    Ticker,
+    Trade,
+    OrderBook,
+    Ohlc,
+    Spread,
+    Instrument,
+    Candle,
+    ChecksumMismatch,
+    Gap,
+    Resync,
+    Reconnecting,
+    State,
+    Transition,
+    SystemStatus,
+    Heartbeat,
+    Error,
+    Lagged,
 }
 
 impl SdkEvent {
+    /// Kind of this event
+    pub fn kind(&self) -> EventKind {
+        match self {
+            SdkEvent::Ticker(_) => EventKind::Ticker,
+            SdkEvent::Trade(_) => EventKind::Trade,
+            SdkEvent::OrderBook(_) => EventKind::OrderBook,
+            SdkEvent::Ohlc(_) => EventKind::Ohlc,
+            SdkEvent::Spread(_) => EventKind::Spread,
+            SdkEvent::Instrument(_) => EventKind::Instrument,
+            SdkEvent::Candle(_) => EventKind::Candle,
+            SdkEvent::ChecksumMismatch(_) => EventKind::ChecksumMismatch,
+            SdkEvent::Gap(_) => EventKind::Gap,
+            SdkEvent::Resync(_) => EventKind::Resync,
+            SdkEvent::Reconnecting(_) => EventKind::Reconnecting,
+            SdkEvent::State(_) => EventKind::State,
+            SdkEvent::Transition(_) => EventKind::Transition,
+            SdkEvent::SystemStatus(_) => EventKind::SystemStatus,
+            SdkEvent::Heartbeat(_) => EventKind::Heartbeat,
+            SdkEvent::Error(_) => EventKind::Error,
+            SdkEvent::Lagged(_) => EventKind::Lagged,
+        }
+    }
+    
+    /// Market data type of this event, if it is market data
+    pub fn data_type(&self) -> Option<DataType> {
+        match self {
+            SdkEvent::Ticker(_) => Some(DataType::Ticker),
+            SdkEvent::Trade(_) => Some(DataType::Trade),
+            SdkEvent::OrderBook(_) => Some(DataType::OrderBook),
+            SdkEvent::Ohlc(_) => Some(DataType::OHLC),
+            SdkEvent::Spread(_) => Some(DataType::Spread),
+            _ => None,
+        }
+    }
+    
     /// Get the symbol associated with this event, if any
     pub fn symbol(&self) -> Option<&str> {
         match self {
@@ -58,13 +181,20 @@ impl SdkEvent {
             SdkEvent::Trade(d) => Some(&d.symbol),
             SdkEvent::OrderBook(d) => Some(&d.symbol),
             SdkEvent::Ohlc(d) => Some(&d.symbol),
-            SdkEvent::State(_) | SdkEvent::Error(_) => None,
+            SdkEvent::Spread(d) => Some(&d.symbol),
+            SdkEvent::Instrument(d) => Some(&d.symbol),
+            SdkEvent::Candle(d) => Some(&d.candle().symbol),
+            SdkEvent::ChecksumMismatch(d) => Some(&d.symbol),
+            SdkEvent::Gap(_) | SdkEvent::Resync(_) => None,
+            SdkEvent::Reconnecting(_) | SdkEvent::State(_) | SdkEvent::Transition(_) | SdkEvent::Error(_) => None,
+            SdkEvent::SystemStatus(_) | SdkEvent::Heartbeat(_) => None,
+            SdkEvent::Lagged(_) => None,
         }
     }
     
     /// Check if this is a market data event
     pub fn is_market_data(&self) -> bool {
-        matches!(self, SdkEvent::Ticker(_) | SdkEvent::Trade(_) | SdkEvent::OrderBook(_) | SdkEvent::Ohlc(_))
+        matches!(self, SdkEvent::Ticker(_) | SdkEvent::Trade(_) | SdkEvent::OrderBook(_) | SdkEvent::Ohlc(_) | SdkEvent::Spread(_))
     }
     
     /// Check if this is an error event
@@ -73,11 +203,331 @@ impl SdkEvent {
     }
 }
 
+/// Queued order book updates for a symbol merge into a single delta;
+/// every other event keeps only the newest value.
+impl Coalesce for SdkEvent {
+    fn coalesce(&mut self, newer: Self) {
+        match (self, newer) {
+            (SdkEvent::OrderBook(pending), SdkEvent::OrderBook(update)) => pending.merge(update),
+            (pending, newer) => *pending = newer,
+        }
+    }
+}
+
 /// Event stream receiver - use this to consume events
-pub type EventReceiver = mpsc::UnboundedReceiver<SdkEvent>;
+pub type EventReceiver = EventStream;
+
+/// Which events a stream receives
+///
+/// An empty filter passes everything. Symbol and data type filters only
+/// apply to events that have a symbol or data type, so connection state
+/// and errors still reach a symbol-filtered stream unless excluded by kind.
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    symbols: Option<HashSet<String>>,
+    data_types: Option<HashSet<DataType>>,
+    kinds: Option<HashSet<EventKind>>,
+}
+
+impl EventFilter {
+    /// Filter that passes every event
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Also pass events for `symbol`
+    pub fn symbol(mut self, symbol: &str) -> Self {
+        self.symbols.get_or_insert_with(HashSet::new).insert(symbol.to_string());
+        self
+    }
+    
+    /// Also pass market data of `data_type`
+    pub fn data_type(mut self, data_type: DataType) -> Self {
+        self.data_types.get_or_insert_with(HashSet::new).insert(data_type);
+        self
+    }
+    
+    /// Also pass events of `kind`
+    pub fn kind(mut self, kind: EventKind) -> Self {
+        self.kinds.get_or_insert_with(HashSet::new).insert(kind);
+        self
+    }
+    
+    /// Whether `event` passes this filter
+    pub fn matches(&self, event: &SdkEvent) -> bool {
+        if let (Some(kinds), kind) = (&self.kinds, event.kind()) {
+            if !kinds.contains(&kind) {
+                return false;
+            }
+        }
+        if let (Some(data_types), Some(data_type)) = (&self.data_types, event.data_type()) {
+            if !data_types.contains(&data_type) {
+                return false;
+            }
+        }
+        if let (Some(symbols), Some(symbol)) = (&self.symbols, event.symbol()) {
+            if !symbols.contains(symbol) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Buffer shared between the dispatcher and one stream
+struct StreamShared {
+    filter: EventFilter,
+    capacity: usize,
+    state: Mutex<StreamState>,
+    /// The stream was dropped; the dispatcher prunes it
+    closed: AtomicBool,
+}
+
+#[derive(Default)]
+struct StreamState {
+    queue: VecDeque<SdkEvent>,
+    /// Dropped since the last `Lagged` report
+    unreported: u64,
+    missed: u64,
+    coalesced: u64,
+    /// Every dispatcher is gone; no more events will arrive
+    disconnected: bool,
+    waker: Option<Waker>,
+}
+
+impl StreamShared {
+    fn push(&self, event: &SdkEvent) {
+        if !self.filter.matches(event) {
+            return;
+        }
+        
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.queue.len() >= self.capacity {
+            // Merge a book update into the pending one for its symbol
+            // before giving anything up
+            if let SdkEvent::OrderBook(update) = event {
+                let pending = state.queue.iter_mut().rev().find_map(|queued| match queued {
+                    SdkEvent::OrderBook(book) if book.symbol == update.symbol => Some(book),
+                    _ => None,
+                });
+                if let Some(pending) = pending {
+                    pending.merge(update.clone());
+                    state.coalesced += 1;
+                    return;
+                }
+            }
+            state.queue.pop_front();
+            state.missed += 1;
+            state.unreported += 1;
+        }
+        
+        state.queue.push_back(event.clone());
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+    
+    fn disconnect(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.disconnected = true;
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Streams registered with a dispatcher; disconnects them when the last
+/// dispatcher clone is dropped
+#[derive(Default)]
+struct StreamRegistry {
+    streams: Mutex<Vec<Arc<StreamShared>>>,
+}
+
+impl Drop for StreamRegistry {
+    fn drop(&mut self) {
+        if let Ok(streams) = self.streams.get_mut() {
+            for stream in streams.iter() {
+                stream.disconnect();
+            }
+        }
+    }
+}
+
+/// One subscriber's view of the event feed
+///
+/// Implements [`Stream`]; `recv()` / `try_recv()` mirror a channel
+/// receiver. When the buffer overflows, pending book updates for the same
+/// symbol are merged first; otherwise the oldest event is dropped and the
+/// next item is [`SdkEvent::Lagged`] with the number missed.
+pub struct EventStream {
+    shared: Arc<StreamShared>,
+}
 
-/// Event stream sender - internal use
-pub type EventSender = mpsc::UnboundedSender<SdkEvent>;
+impl EventStream {
+    /// Receive the next event, or `None` once the dispatcher is gone
+    pub async fn recv(&mut self) -> Option<SdkEvent> {
+        std::future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+    
+    /// Receive an event if one is buffered
+    pub fn try_recv(&mut self) -> Result<SdkEvent, TryRecvError> {
+        let mut state = self.shared.state.lock().unwrap_or_else(|e| e.into_inner());
+        match Self::next_event(&mut state) {
+            Some(event) => Ok(event),
+            None if state.disconnected => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+    
+    /// Poll for the next event
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<SdkEvent>> {
+        let mut state = self.shared.state.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(event) = Self::next_event(&mut state) {
+            return Poll::Ready(Some(event));
+        }
+        if state.disconnected {
+            return Poll::Ready(None);
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+    
+    fn next_event(state: &mut StreamState) -> Option<SdkEvent> {
+        if state.unreported > 0 {
+            return Some(SdkEvent::Lagged(std::mem::take(&mut state.unreported)));
+        }
+        state.queue.pop_front()
+    }
+    
+    /// Filter this stream was created with
+    pub fn filter(&self) -> &EventFilter {
+        &self.shared.filter
+    }
+    
+    /// Buffer capacity of this stream
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+    
+    /// Events currently buffered
+    pub fn len(&self) -> usize {
+        self.shared.state.lock().map(|state| state.queue.len()).unwrap_or(0)
+    }
+    
+    /// Whether no events are buffered
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    
+    /// Total events this stream has missed by falling behind
+    pub fn missed(&self) -> u64 {
+        self.shared.state.lock().map(|state| state.missed).unwrap_or(0)
+    }
+    
+    /// Book updates merged into a pending update while this stream was full
+    pub fn coalesced(&self) -> u64 {
+        self.shared.state.lock().map(|state| state.coalesced).unwrap_or(0)
+    }
+}
+
+impl Stream for EventStream {
+    type Item = SdkEvent;
+    
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<SdkEvent>> {
+        self.get_mut().poll_recv(cx)
+    }
+}
+
+impl Drop for EventStream {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, Ordering::Relaxed);
+    }
+}
+
+impl std::fmt::Debug for EventStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventStream")
+            .field("filter", &self.shared.filter)
+            .field("capacity", &self.shared.capacity)
+            .field("len", &self.len())
+            .field("missed", &self.missed())
+            .finish()
+    }
+}
+
+/// Backpressure channel every outbound event is queued on
+const QUEUE_CHANNEL: &str = "events";
+
+/// Backpressure stage between dispatch and delivery
+///
+/// Events wait here in arrival order until they are delivered. A book
+/// update is merged into the one still queued for its symbol, which moves
+/// up to the newer update's place, so at most one book per symbol waits.
+/// Only book updates are rate limited or dropped under the backpressure
+/// policy; trades, state changes, gaps and errors are always delivered.
+struct DeliveryQueue {
+    events: BackpressureManager<SdkEvent>,
+    coalesce_books: bool,
+    /// Held while events are being delivered so they leave in order
+    draining: Mutex<()>,
+    /// A delivery task drains the queue instead of the publisher
+    background: AtomicBool,
+    /// Wakes the delivery task
+    wake: Arc<Notify>,
+}
+
+impl DeliveryQueue {
+    fn new(config: BackpressureConfig) -> Self {
+        let coalesce_books = config.coalesce_updates;
+        let ordered = BackpressureConfig {
+            coalesce_updates: false,
+            ..config
+        };
+        Self {
+            events: BackpressureManager::with_config(ordered),
+            coalesce_books,
+            draining: Mutex::new(()),
+            background: AtomicBool::new(false),
+            wake: Arc::new(Notify::new()),
+        }
+    }
+    
+    fn push(&self, event: SdkEvent) {
+        let message = BufferedMessage {
+            channel: QUEUE_CHANNEL.to_string(),
+            symbol: event.symbol().unwrap_or_default().to_string(),
+            data: event,
+            sequence: None,
+            received_at: Instant::now(),
+            timestamp: chrono::Utc::now(),
+        };
+        let coalesce_books = self.coalesce_books;
+        self.events.process_in_order(
+            message,
+            |pending, newer| coalesce_books && matches!((pending, newer), (SdkEvent::OrderBook(_), SdkEvent::OrderBook(_))),
+            |event| matches!(event, SdkEvent::OrderBook(_)),
+        );
+    }
+    
+    fn pop(&self) -> Option<SdkEvent> {
+        self.events.pop(QUEUE_CHANNEL).map(|message| message.data)
+    }
+    
+    fn is_empty(&self) -> bool {
+        self.events.global_stats().current_queue_depth == 0
+    }
+    
+    fn stats(&self) -> BackpressureStats {
+        self.events.global_stats()
+    }
+}
+
+impl Drop for DeliveryQueue {
+    fn drop(&mut self) {
+        // Lets the delivery task see that its dispatcher is gone
+        self.wake.notify_one();
+    }
+}
 
 /// Trait for event callbacks
 pub trait EventCallback: Send + Sync {
@@ -87,6 +537,18 @@ pub trait EventCallback: Send + Sync {
     fn on_ohlc(&self, data: OHLCData);
     fn on_error(&self, error: SdkError);
     fn on_connection_state_change(&self, state: ConnectionState);
+    
+    /// Called for best bid/ask updates from the `spread` channel
+    fn on_spread(&self, _data: SpreadData) {}
+    
+    /// Called by connection listeners before each reconnection attempt
+    fn on_reconnect(&self, _event: ReconnectEvent) {}
+    
+    /// Called by connection listeners on every lifecycle state transition
+    fn on_state_transition(&self, _transition: StateTransition) {}
+    
+    /// Called by connection listeners when Kraken announces the exchange status
+    fn on_system_status(&self, _status: SystemStatus) {}
 }
 
 /// Event dispatcher for managing callbacks and event streams
@@ -95,8 +557,12 @@ pub struct EventDispatcher {
     connection_listeners: Arc<Mutex<Vec<CallbackEntry>>>,
     error_callbacks: Arc<Mutex<Vec<CallbackEntry>>>,
     next_id: Arc<Mutex<u64>>,
-    /// Event stream senders for the unified stream API
-    event_streams: Arc<Mutex<Vec<EventSender>>>,
+    /// Subscribers of the stream API
+    event_streams: Arc<StreamRegistry>,
+    /// Capacity of each new event stream
+    stream_buffer: usize,
+    /// Every outbound event passes through here before delivery
+    queue: Arc<DeliveryQueue>,
 }
 
 /// Callback entry with unique ID for management
@@ -113,14 +579,34 @@ impl EventDispatcher {
             connection_listeners: Arc::new(Mutex::new(Vec::new())),
             error_callbacks: Arc::new(Mutex::new(Vec::new())),
             next_id: Arc::new(Mutex::new(0)),
-            event_streams: Arc::new(Mutex::new(Vec::new())),
+            event_streams: Arc::new(StreamRegistry::default()),
+            stream_buffer: DEFAULT_STREAM_BUFFER,
+            queue: Arc::new(DeliveryQueue::new(BackpressureConfig::default())),
         }
     }
     
+    /// Use a custom backpressure configuration for outbound events
+    pub fn with_backpressure(mut self, config: BackpressureConfig) -> Self {
+        self.queue = Arc::new(DeliveryQueue::new(config));
+        self
+    }
+    
+    /// Set the capacity of event streams created from now on
+    pub fn with_stream_buffer(mut self, capacity: usize) -> Self {
+        self.stream_buffer = capacity.max(1);
+        self
+    }
+    
+    /// Backpressure statistics for outbound events
+    pub fn backpressure_stats(&self) -> BackpressureStats {
+        self.queue.stats()
+    }
+    
     /// Create a new event stream receiver
     /// 
-    /// Returns an unbounded receiver that will receive all SDK events.
-    /// Multiple streams can be created - each receives all events.
+    /// Returns a bounded stream that will receive all SDK events.
+    /// Multiple streams can be created - each receives all events into its
+    /// own buffer, and a slow stream never holds back the others.
     /// 
     /// # Example
     /// ```rust,ignore
@@ -133,18 +619,120 @@ impl EventDispatcher {
     /// }
     /// ```
     pub fn create_event_stream(&self) -> EventReceiver {
-        let (tx, rx) = mpsc::unbounded_channel();
-        if let Ok(mut streams) = self.event_streams.lock() {
-            streams.push(tx);
+        self.subscribe(EventFilter::new())
+    }
+    
+    /// Create a stream that only receives events passing `filter`
+    pub fn subscribe(&self, filter: EventFilter) -> EventStream {
+        self.subscribe_with_buffer(filter, self.stream_buffer)
+    }
+    
+    /// Create a filtered stream with its own buffer capacity
+    pub fn subscribe_with_buffer(&self, filter: EventFilter, capacity: usize) -> EventStream {
+        let shared = Arc::new(StreamShared {
+            filter,
+            capacity: capacity.max(1),
+            state: Mutex::new(StreamState::default()),
+            closed: AtomicBool::new(false),
+        });
+        if let Ok(mut streams) = self.event_streams.streams.lock() {
+            streams.push(Arc::clone(&shared));
+        }
+        EventStream { shared }
+    }
+    
+    /// Number of live event streams
+    pub fn stream_count(&self) -> usize {
+        self.event_streams.streams.lock()
+            .map(|streams| streams.iter().filter(|s| !s.closed.load(Ordering::Relaxed)).count())
+            .unwrap_or(0)
+    }
+    
+    /// Deliver events from a background task from now on
+    ///
+    /// Publishing then only queues an event, so a slow callback or stream
+    /// consumer never holds up the publisher: events wait in the queue,
+    /// bounded by the backpressure configuration, with book updates merged
+    /// per symbol. Without a delivery task, events are delivered before
+    /// `dispatch_*` returns. Must be called within a Tokio runtime; calling
+    /// it again has no effect.
+    pub fn start_delivery(self: &Arc<Self>) {
+        if self.queue.background.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let dispatcher = Arc::downgrade(self);
+        let wake = Arc::clone(&self.queue.wake);
+        tokio::spawn(async move {
+            loop {
+                wake.notified().await;
+                let Some(dispatcher) = dispatcher.upgrade() else {
+                    return;
+                };
+                dispatcher.drain();
+            }
+        });
+    }
+    
+    /// Queue an event and deliver it, or wake the delivery task
+    fn publish(&self, event: SdkEvent) {
+        self.queue.push(event);
+        if self.queue.background.load(Ordering::SeqCst) {
+            self.queue.wake.notify_one();
+        } else {
+            self.drain();
+        }
+    }
+    
+    /// Deliver queued events until the queue is empty
+    fn drain(&self) {
+        loop {
+            {
+                // Whoever holds the lock delivers; events queued meanwhile
+                // are picked up by the re-check below
+                let _guard = match self.queue.draining.try_lock() {
+                    Ok(guard) => guard,
+                    Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+                    Err(TryLockError::WouldBlock) => return,
+                };
+                while let Some(event) = self.queue.pop() {
+                    self.deliver(event);
+                }
+            }
+            
+            if self.queue.is_empty() {
+                return;
+            }
+        }
+    }
+    
+    /// Hand an event that left the queue to streams and callbacks
+    fn deliver(&self, event: SdkEvent) {
+        self.send_to_streams(&event);
+        
+        match event {
+            SdkEvent::Ticker(data) => self.notify_ticker(data),
+            SdkEvent::OrderBook(data) => self.notify_orderbook(data),
+            SdkEvent::Trade(data) => self.notify_trade(data),
+            SdkEvent::Ohlc(data) => self.notify_ohlc(data),
+            SdkEvent::Spread(data) => self.notify_spread(data),
+            SdkEvent::Error(error) => self.notify_error(error),
+            SdkEvent::ChecksumMismatch(event) => self.notify_checksum_mismatch(event),
+            SdkEvent::State(state) => self.notify_connection_state_change(state),
+            SdkEvent::Transition(transition) => self.notify_transition(transition),
+            SdkEvent::Reconnecting(event) => self.notify_reconnect(event),
+            SdkEvent::SystemStatus(status) => self.notify_system_status(status),
+            SdkEvent::Instrument(_) | SdkEvent::Candle(_) | SdkEvent::Gap(_) | SdkEvent::Resync(_) | SdkEvent::Heartbeat(_) | SdkEvent::Lagged(_) => {}
         }
-        rx
     }
     
     /// Send event to all stream subscribers
-    fn send_to_streams(&self, event: SdkEvent) {
-        if let Ok(mut streams) = self.event_streams.lock() {
-            // Remove closed streams and send to active ones
-            streams.retain(|tx| tx.send(event.clone()).is_ok());
+    fn send_to_streams(&self, event: &SdkEvent) {
+        if let Ok(mut streams) = self.event_streams.streams.lock() {
+            // Remove dropped streams and send to active ones
+            streams.retain(|stream| !stream.closed.load(Ordering::Relaxed));
+            for stream in streams.iter() {
+                stream.push(event);
+            }
         }
     }
     
@@ -161,7 +749,7 @@ impl EventDispatcher {
         let entry = CallbackEntry { id, callback };
         
         let mut subscribers = self.subscribers.lock().unwrap();
-        subscribers.entry(data_type.clone()).or_insert_with(Vec::new).push(entry);
+        subscribers.entry(data_type.clone()).or_default().push(entry);
         
         tracing::debug!("Registered callback {} for data type {:?}", id, data_type);
         id
@@ -266,19 +854,19 @@ impl EventDispatcher {
     
     /// Dispatch ticker data to registered callbacks and streams
     pub fn dispatch_ticker(&self, data: TickerData) {
-        // Send to event streams
-        self.send_to_streams(SdkEvent::Ticker(data.clone()));
-        
-        // Send to callbacks
+        self.publish(SdkEvent::Ticker(data));
+    }
+    
+    fn notify_ticker(&self, data: TickerData) {
         if let Ok(subscribers) = self.subscribers.lock() {
             if let Some(callbacks) = subscribers.get(&DataType::Ticker) {
                 tracing::debug!("Dispatching ticker data to {} callbacks", callbacks.len());
                 
                 for (index, entry) in callbacks.iter().enumerate() {
                     // Handle callback errors gracefully
-                    if let Err(_) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    if std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
                         entry.callback.on_ticker(data.clone());
-                    })) {
+                    })).is_err() {
                         let error_msg = format!("Callback {} (index {}) panicked while processing ticker data", entry.id, index);
                         tracing::error!("{}", error_msg);
                         
@@ -291,19 +879,21 @@ impl EventDispatcher {
     }
     
     /// Dispatch order book data to registered callbacks and streams
+    ///
+    /// While delivery is backed up, updates for the same symbol are merged.
     pub fn dispatch_orderbook(&self, data: OrderBookUpdate) {
-        // Send to event streams
-        self.send_to_streams(SdkEvent::OrderBook(data.clone()));
-        
-        // Send to callbacks
+        self.publish(SdkEvent::OrderBook(data));
+    }
+    
+    fn notify_orderbook(&self, data: OrderBookUpdate) {
         if let Ok(subscribers) = self.subscribers.lock() {
             if let Some(callbacks) = subscribers.get(&DataType::OrderBook) {
                 tracing::debug!("Dispatching orderbook data to {} callbacks", callbacks.len());
                 
                 for (index, entry) in callbacks.iter().enumerate() {
-                    if let Err(_) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    if std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
                         entry.callback.on_orderbook(data.clone());
-                    })) {
+                    })).is_err() {
                         let error_msg = format!("Callback {} (index {}) panicked while processing orderbook data", entry.id, index);
                         tracing::error!("{}", error_msg);
                         
@@ -317,18 +907,18 @@ impl EventDispatcher {
     
     /// Dispatch trade data to registered callbacks and streams
     pub fn dispatch_trade(&self, data: TradeData) {
-        // Send to event streams
-        self.send_to_streams(SdkEvent::Trade(data.clone()));
-        
-        // Send to callbacks
+        self.publish(SdkEvent::Trade(data));
+    }
+    
+    fn notify_trade(&self, data: TradeData) {
         if let Ok(subscribers) = self.subscribers.lock() {
             if let Some(callbacks) = subscribers.get(&DataType::Trade) {
                 tracing::debug!("Dispatching trade data to {} callbacks", callbacks.len());
                 
                 for (index, entry) in callbacks.iter().enumerate() {
-                    if let Err(_) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    if std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
                         entry.callback.on_trade(data.clone());
-                    })) {
+                    })).is_err() {
                         let error_msg = format!("Callback {} (index {}) panicked while processing trade data", entry.id, index);
                         tracing::error!("{}", error_msg);
                         
@@ -342,18 +932,18 @@ impl EventDispatcher {
     
     /// Dispatch OHLC data to registered callbacks and streams
     pub fn dispatch_ohlc(&self, data: OHLCData) {
-        // Send to event streams
-        self.send_to_streams(SdkEvent::Ohlc(data.clone()));
-        
-        // Send to callbacks
+        self.publish(SdkEvent::Ohlc(data));
+    }
+    
+    fn notify_ohlc(&self, data: OHLCData) {
         if let Ok(subscribers) = self.subscribers.lock() {
             if let Some(callbacks) = subscribers.get(&DataType::OHLC) {
                 tracing::debug!("Dispatching OHLC data to {} callbacks", callbacks.len());
                 
                 for (index, entry) in callbacks.iter().enumerate() {
-                    if let Err(_) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    if std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
                         entry.callback.on_ohlc(data.clone());
-                    })) {
+                    })).is_err() {
                         let error_msg = format!("Callback {} (index {}) panicked while processing OHLC data", entry.id, index);
                         tracing::error!("{}", error_msg);
                         
@@ -365,12 +955,37 @@ impl EventDispatcher {
         }
     }
     
+    /// Dispatch spread data to registered callbacks and streams
+    pub fn dispatch_spread(&self, data: SpreadData) {
+        self.publish(SdkEvent::Spread(data));
+    }
+    
+    fn notify_spread(&self, data: SpreadData) {
+        if let Ok(subscribers) = self.subscribers.lock() {
+            if let Some(callbacks) = subscribers.get(&DataType::Spread) {
+                tracing::debug!("Dispatching spread data to {} callbacks", callbacks.len());
+                
+                for (index, entry) in callbacks.iter().enumerate() {
+                    if std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        entry.callback.on_spread(data.clone());
+                    })).is_err() {
+                        let error_msg = format!("Callback {} (index {}) panicked while processing spread data", entry.id, index);
+                        tracing::error!("{}", error_msg);
+                        
+                        // Notify error callbacks about the callback failure
+                        self.notify_error_callbacks(SdkError::Network(error_msg));
+                    }
+                }
+            }
+        }
+    }
+    
     /// Dispatch error to all registered callbacks and streams
     pub fn dispatch_error(&self, error: SdkError) {
-        // Send to event streams
-        self.send_to_streams(SdkEvent::Error(error.clone()));
-        
-        // Send to callbacks
+        self.publish(SdkEvent::Error(error));
+    }
+    
+    fn notify_error(&self, error: SdkError) {
         if let Ok(subscribers) = self.subscribers.lock() {
             let total_callbacks: usize = subscribers.values().map(|v| v.len()).sum();
             tracing::debug!("Dispatching error to {} callbacks", total_callbacks);
@@ -387,19 +1002,63 @@ impl EventDispatcher {
         }
     }
     
+    /// Dispatch instrument reference data to event streams
+    pub fn dispatch_instrument(&self, data: InstrumentData) {
+        self.publish(SdkEvent::Instrument(data));
+    }
+    
+    /// Dispatch a locally built candle update or close to event streams
+    pub fn dispatch_candle(&self, event: CandleEvent) {
+        self.publish(SdkEvent::Candle(event));
+    }
+    
+    /// Dispatch a book checksum mismatch to streams and error callbacks
+    pub fn dispatch_checksum_mismatch(&self, event: ChecksumMismatch) {
+        self.publish(SdkEvent::ChecksumMismatch(event));
+    }
+    
+    fn notify_checksum_mismatch(&self, event: ChecksumMismatch) {
+        let error = SdkError::Parse(ParseError::ChecksumMismatch {
+            symbol: event.symbol,
+            expected: event.expected,
+            calculated: event.calculated,
+        });
+        if let Ok(subscribers) = self.subscribers.lock() {
+            if let Some(callbacks) = subscribers.get(&DataType::OrderBook) {
+                for entry in callbacks {
+                    std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        entry.callback.on_error(error.clone());
+                    })).unwrap_or_else(|_| {
+                        tracing::error!("Callback {} panicked while processing checksum mismatch", entry.id);
+                    });
+                }
+            }
+        }
+    }
+    
+    /// Dispatch a sequence gap to event streams
+    pub fn dispatch_gap(&self, event: GapEvent) {
+        self.publish(SdkEvent::Gap(event));
+    }
+    
+    /// Dispatch a sequence resync to event streams
+    pub fn dispatch_resync(&self, event: ResyncEvent) {
+        self.publish(SdkEvent::Resync(event));
+    }
+    
     /// Dispatch connection state change to registered listeners and streams
     pub fn dispatch_connection_state_change(&self, state: ConnectionState) {
-        // Send to event streams
-        self.send_to_streams(SdkEvent::State(state.clone()));
-        
-        // Send to callbacks
+        self.publish(SdkEvent::State(state));
+    }
+    
+    fn notify_connection_state_change(&self, state: ConnectionState) {
         if let Ok(listeners) = self.connection_listeners.lock() {
             tracing::debug!("Dispatching connection state change to {} listeners", listeners.len());
             
             for entry in listeners.iter() {
-                if let Err(_) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                if std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
                     entry.callback.on_connection_state_change(state.clone());
-                })) {
+                })).is_err() {
                     let error_msg = format!("Connection listener {} panicked while processing state change", entry.id);
                     tracing::error!("{}", error_msg);
                     
@@ -409,6 +1068,62 @@ impl EventDispatcher {
             }
         }
     }
+    
+    /// Dispatch a lifecycle state transition to connection listeners and streams
+    pub fn dispatch_transition(&self, transition: StateTransition) {
+        self.publish(SdkEvent::Transition(transition));
+    }
+    
+    fn notify_transition(&self, transition: StateTransition) {
+        if let Ok(listeners) = self.connection_listeners.lock() {
+            for entry in listeners.iter() {
+                std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    entry.callback.on_state_transition(transition.clone());
+                })).unwrap_or_else(|_| {
+                    tracing::error!("Connection listener {} panicked while processing state transition", entry.id);
+                });
+            }
+        }
+    }
+    
+    /// Dispatch a reconnection attempt to connection listeners and streams
+    pub fn dispatch_reconnect(&self, event: ReconnectEvent) {
+        self.publish(SdkEvent::Reconnecting(event));
+    }
+    
+    fn notify_reconnect(&self, event: ReconnectEvent) {
+        if let Ok(listeners) = self.connection_listeners.lock() {
+            for entry in listeners.iter() {
+                std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    entry.callback.on_reconnect(event.clone());
+                })).unwrap_or_else(|_| {
+                    tracing::error!("Connection listener {} panicked while processing reconnect", entry.id);
+                });
+            }
+        }
+    }
+    
+    /// Dispatch an exchange status announcement to connection listeners and streams
+    pub fn dispatch_system_status(&self, status: SystemStatus) {
+        self.publish(SdkEvent::SystemStatus(status));
+    }
+    
+    fn notify_system_status(&self, status: SystemStatus) {
+        if let Ok(listeners) = self.connection_listeners.lock() {
+            for entry in listeners.iter() {
+                std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    entry.callback.on_system_status(status.clone());
+                })).unwrap_or_else(|_| {
+                    tracing::error!("Connection listener {} panicked while processing system status", entry.id);
+                });
+            }
+        }
+    }
+    
+    /// Dispatch a Kraken heartbeat to event streams
+    pub fn dispatch_heartbeat(&self, heartbeat: Heartbeat) {
+        self.publish(SdkEvent::Heartbeat(heartbeat));
+    }
 }
 
 impl Default for EventDispatcher {
@@ -425,6 +1140,8 @@ impl Clone for EventDispatcher {
             error_callbacks: Arc::clone(&self.error_callbacks),
             next_id: Arc::clone(&self.next_id),
             event_streams: Arc::clone(&self.event_streams),
+            stream_buffer: self.stream_buffer,
+            queue: Arc::clone(&self.queue),
         }
     }
 }
\ No newline at end of file
@@ -3,11 +3,10 @@
 //! Provides a unified interface for connecting to multiple cryptocurrency exchanges.
 //! Currently implements Kraken with stubs for Binance, Coinbase, etc.
 
-use crate::data::{TickerData, TradeData, OrderBookUpdate, OHLCData};
+use crate::data::{TickerData, TradeData, OrderBookUpdate, OHLCData, KRAKEN_WS_V1_URL};
 use crate::error::SdkError;
+use crate::symbols::{SymbolFormat, SymbolRegistry};
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
-use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -65,6 +64,7 @@ pub type NativeSymbol = String;
 /// Symbol mapping between normalized and exchange-specific formats
 pub trait SymbolMapper: Send + Sync {
     fn to_native(&self, symbol: &Symbol) -> NativeSymbol;
+    #[allow(clippy::wrong_self_convention)]
     fn from_native(&self, native: &NativeSymbol) -> Symbol;
 }
 
@@ -93,7 +93,7 @@ impl Default for ExchangeConfig {
     fn default() -> Self {
         Self {
             exchange: Exchange::Kraken,
-            ws_endpoint: "wss://ws.kraken.com".to_string(),
+            ws_endpoint: KRAKEN_WS_V1_URL.to_string(),
             rest_endpoint: None,
             api_key: None,
             api_secret: None,
@@ -163,12 +163,12 @@ pub struct KrakenSymbolMapper;
 impl SymbolMapper for KrakenSymbolMapper {
     fn to_native(&self, symbol: &Symbol) -> NativeSymbol {
         // Kraken WebSocket uses "XBT/USD" format
-        symbol.replace("BTC", "XBT")
+        SymbolRegistry::new().convert(symbol, SymbolFormat::WsName)
     }
     
     fn from_native(&self, native: &NativeSymbol) -> Symbol {
         // Convert back to standard format
-        native.replace("XBT", "BTC")
+        SymbolRegistry::new().canonical(native)
     }
 }
 
@@ -189,7 +189,7 @@ impl KrakenAdapter {
     pub fn new() -> Self {
         Self::with_config(ExchangeConfig {
             exchange: Exchange::Kraken,
-            ws_endpoint: "wss://ws.kraken.com".to_string(),
+            ws_endpoint: KRAKEN_WS_V1_URL.to_string(),
             ..Default::default()
         })
     }
@@ -208,6 +208,11 @@ impl KrakenAdapter {
         }
     }
     
+    /// Configuration this adapter was created with
+    pub fn config(&self) -> &ExchangeConfig {
+        &self.config
+    }
+    
     fn set_status(&mut self, status: ExchangeStatus) {
         self.status = status;
         if let Some(cb) = &self.status_callback {
@@ -350,7 +355,6 @@ pub struct BinanceAdapter {
     orderbook_callback: Option<OrderBookCallback>,
     error_callback: Option<ErrorCallback>,
     status_callback: Option<StatusCallback>,
-    symbol_mapper: BinanceSymbolMapper,
 }
 
 impl BinanceAdapter {
@@ -372,9 +376,13 @@ impl BinanceAdapter {
             orderbook_callback: None,
             error_callback: None,
             status_callback: None,
-            symbol_mapper: BinanceSymbolMapper,
         }
     }
+    
+    /// Configuration this adapter was created with
+    pub fn config(&self) -> &ExchangeConfig {
+        &self.config
+    }
 }
 
 impl Default for BinanceAdapter {
@@ -416,15 +424,15 @@ impl ExchangeAdapter for BinanceAdapter {
         Ok(())
     }
     
-    async fn subscribe_ticker(&mut self, symbol: &Symbol) -> Result<(), SdkError> {
+    async fn subscribe_ticker(&mut self, _symbol: &Symbol) -> Result<(), SdkError> {
         Err(SdkError::NotImplemented("Binance ticker subscription".to_string()))
     }
     
-    async fn subscribe_trades(&mut self, symbol: &Symbol) -> Result<(), SdkError> {
+    async fn subscribe_trades(&mut self, _symbol: &Symbol) -> Result<(), SdkError> {
         Err(SdkError::NotImplemented("Binance trades subscription".to_string()))
     }
     
-    async fn subscribe_orderbook(&mut self, symbol: &Symbol, depth: u32) -> Result<(), SdkError> {
+    async fn subscribe_orderbook(&mut self, _symbol: &Symbol, _depth: u32) -> Result<(), SdkError> {
         Err(SdkError::NotImplemented("Binance orderbook subscription".to_string()))
     }
     
@@ -620,6 +628,8 @@ mod tests {
         let mapper = KrakenSymbolMapper;
         assert_eq!(mapper.to_native(&"BTC/USD".to_string()), "XBT/USD");
         assert_eq!(mapper.from_native(&"XBT/USD".to_string()), "BTC/USD");
+        assert_eq!(mapper.to_native(&"DOGE/USD".to_string()), "XDG/USD");
+        assert_eq!(mapper.from_native(&"XXBTZUSD".to_string()), "BTC/USD");
     }
     
     #[test]
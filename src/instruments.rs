@@ -0,0 +1,295 @@
+//! Instrument metadata and order validation
+//!
+//! Kraken rejects orders whose price is not a multiple of the pair's tick
+//! size (`EOrder:Invalid price`), whose volume has too many decimals, or
+//! which fall below `ordermin`/`costmin`. [`InstrumentRegistry`] holds those
+//! rules per pair, loaded from the REST `AssetPairs` endpoint or the v2
+//! `instrument` channel, and checks or rounds an [`OrderRequest`] before it
+//! is sent.
+
+use crate::data::InstrumentData;
+use crate::error::SdkError;
+use crate::rest_client::AssetPair;
+use crate::trading::{OrderRequest, OrderSide, OrderType};
+use rust_decimal::{Decimal, RoundingStrategy};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+/// What to do with an order whose price or volume is off the pair's grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PrecisionPolicy {
+    /// Fail with [`SdkError::InvalidOrder`]
+    #[default]
+    Reject,
+    /// Round volume down to the lot size and prices to the tick size, never
+    /// to a worse price (buys round down, sells round up)
+    Round,
+}
+
+/// Trading rules for every known pair
+///
+/// Clones share the same instruments. Pairs can be looked up by WebSocket
+/// name ("XBT/USD") and, when loaded from `AssetPairs`, by Kraken's pair
+/// name ("XXBTZUSD") and alternate name ("XBTUSD").
+#[derive(Clone, Default)]
+pub struct InstrumentRegistry {
+    instruments: Arc<RwLock<HashMap<String, Arc<InstrumentData>>>>,
+    /// Other names a pair is known by, mapped to its `symbol`
+    aliases: Arc<RwLock<HashMap<String, String>>>,
+}
+
+impl InstrumentRegistry {
+    /// Empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace the rules for a pair
+    pub fn insert(&self, instrument: InstrumentData) {
+        let mut instruments = self.instruments.write().unwrap();
+        instruments.insert(instrument.symbol.clone(), Arc::new(instrument));
+    }
+
+    /// Add a pair returned by the REST `AssetPairs` endpoint
+    pub fn insert_asset_pair(&self, pair: &AssetPair) {
+        let instrument = InstrumentData::from(pair);
+        {
+            let mut aliases = self.aliases.write().unwrap();
+            for alias in [&pair.pair, &pair.altname] {
+                if *alias != instrument.symbol {
+                    aliases.insert(alias.clone(), instrument.symbol.clone());
+                }
+            }
+        }
+        self.insert(instrument);
+    }
+
+    /// Add every pair returned by the REST `AssetPairs` endpoint
+    pub fn load_asset_pairs<'a>(&self, pairs: impl IntoIterator<Item = &'a AssetPair>) {
+        for pair in pairs {
+            self.insert_asset_pair(pair);
+        }
+    }
+
+    /// Rules for a pair, by any of its names
+    pub fn get(&self, pair: &str) -> Option<Arc<InstrumentData>> {
+        let instruments = self.instruments.read().unwrap();
+        if let Some(instrument) = instruments.get(pair) {
+            return Some(Arc::clone(instrument));
+        }
+        let aliases = self.aliases.read().unwrap();
+        aliases.get(pair).and_then(|symbol| instruments.get(symbol)).cloned()
+    }
+
+    /// Number of known pairs
+    pub fn len(&self) -> usize {
+        self.instruments.read().unwrap().len()
+    }
+
+    /// Whether no pairs are known yet
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Check `order` against its pair's rules
+    ///
+    /// Passes for pairs the registry doesn't know; Kraken has the final word.
+    pub fn validate(&self, order: &OrderRequest) -> Result<(), SdkError> {
+        match self.get(&order.pair) {
+            Some(instrument) => check_order(&instrument, order),
+            None => Ok(()),
+        }
+    }
+
+    /// Round `order` onto its pair's grid, then validate it
+    ///
+    /// Orders that are still invalid after rounding, e.g. below `ordermin`,
+    /// are rejected.
+    pub fn round(&self, mut order: OrderRequest) -> Result<OrderRequest, SdkError> {
+        let Some(instrument) = self.get(&order.pair) else {
+            return Ok(order);
+        };
+
+        order.volume = round_volume(&instrument, order.volume);
+        if has_limit_price(order.order_type) {
+            order.price = order.price.map(|price| round_price(&instrument, price, order.side));
+        }
+        if has_secondary_price(order.order_type) {
+            order.price2 = order.price2.map(|price| round_price(&instrument, price, order.side));
+        }
+
+        check_order(&instrument, &order)?;
+        Ok(order)
+    }
+
+    /// Validate or round `order` according to `policy`
+    pub fn prepare(&self, order: OrderRequest, policy: PrecisionPolicy) -> Result<OrderRequest, SdkError> {
+        match policy {
+            PrecisionPolicy::Reject => self.validate(&order).map(|()| order),
+            PrecisionPolicy::Round => self.round(order),
+        }
+    }
+}
+
+impl fmt::Debug for InstrumentRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InstrumentRegistry")
+            .field("instruments", &self.len())
+            .finish()
+    }
+}
+
+impl From<&AssetPair> for InstrumentData {
+    fn from(pair: &AssetPair) -> Self {
+        let step = |decimals: u32| Decimal::new(1, decimals);
+        InstrumentData {
+            symbol: pair.wsname.clone().unwrap_or_else(|| pair.altname.clone()),
+            base: pair.base.clone(),
+            quote: pair.quote.clone(),
+            status: pair.status.clone().unwrap_or_else(|| "online".to_string()),
+            price_precision: pair.pair_decimals,
+            price_increment: pair.tick_size.unwrap_or_else(|| step(pair.pair_decimals)),
+            qty_precision: pair.lot_decimals,
+            qty_increment: step(pair.lot_decimals),
+            qty_min: pair.order_min.unwrap_or_default(),
+            cost_min: pair.cost_min.unwrap_or_default(),
+        }
+    }
+}
+
+/// Order types whose `price` is a price on the book
+fn has_limit_price(order_type: OrderType) -> bool {
+    !matches!(order_type, OrderType::Market | OrderType::SettlePosition)
+}
+
+/// Order types whose `price2` is a limit price
+fn has_secondary_price(order_type: OrderType) -> bool {
+    matches!(order_type, OrderType::StopLossLimit | OrderType::TakeProfitLimit)
+}
+
+fn is_multiple(value: Decimal, step: Decimal) -> bool {
+    step.is_zero() || (value % step).is_zero()
+}
+
+fn round_volume(instrument: &InstrumentData, volume: Decimal) -> Decimal {
+    round_to_step(volume, instrument.qty_increment, RoundingStrategy::ToZero)
+}
+
+fn round_price(instrument: &InstrumentData, price: Decimal, side: OrderSide) -> Decimal {
+    let strategy = match side {
+        OrderSide::Buy => RoundingStrategy::ToNegativeInfinity,
+        OrderSide::Sell => RoundingStrategy::ToPositiveInfinity,
+    };
+    round_to_step(price, instrument.price_increment, strategy)
+}
+
+fn round_to_step(value: Decimal, step: Decimal, strategy: RoundingStrategy) -> Decimal {
+    if step.is_zero() {
+        return value;
+    }
+    ((value / step).round_dp_with_strategy(0, strategy) * step).normalize()
+}
+
+fn check_order(instrument: &InstrumentData, order: &OrderRequest) -> Result<(), SdkError> {
+    let invalid = |reason: String| Err(SdkError::InvalidOrder {
+        pair: order.pair.clone(),
+        reason,
+    });
+
+    if !is_multiple(order.volume, instrument.qty_increment) {
+        return invalid(format!("volume {} is not a multiple of {}", order.volume, instrument.qty_increment));
+    }
+    if order.volume < instrument.qty_min {
+        return invalid(format!("volume {} is below the minimum of {}", order.volume, instrument.qty_min));
+    }
+
+    let prices = [
+        order.price.filter(|_| has_limit_price(order.order_type)),
+        order.price2.filter(|_| has_secondary_price(order.order_type)),
+    ];
+    for price in prices.into_iter().flatten() {
+        if !is_multiple(price, instrument.price_increment) {
+            return invalid(format!("price {} is not a multiple of tick size {}", price, instrument.price_increment));
+        }
+    }
+
+    if let Some(price) = order.price.filter(|_| order.order_type == OrderType::Limit) {
+        let cost = price * order.volume;
+        if cost < instrument.cost_min {
+            return invalid(format!("cost {} is below the minimum of {}", cost, instrument.cost_min));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn xbt_usd() -> AssetPair {
+        AssetPair {
+            pair: "XXBTZUSD".to_string(),
+            altname: "XBTUSD".to_string(),
+            wsname: Some("XBT/USD".to_string()),
+            base: "XXBT".to_string(),
+            quote: "ZUSD".to_string(),
+            pair_decimals: 1,
+            lot_decimals: 8,
+            order_min: Some(dec!(0.0001)),
+            cost_min: Some(dec!(0.5)),
+            tick_size: Some(dec!(0.1)),
+            status: Some("online".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_lookup_by_any_name() {
+        let registry = InstrumentRegistry::new();
+        registry.insert_asset_pair(&xbt_usd());
+
+        for name in ["XBT/USD", "XBTUSD", "XXBTZUSD"] {
+            assert_eq!(registry.get(name).unwrap().symbol, "XBT/USD");
+        }
+        assert_eq!(registry.get("XBT/USD").unwrap().qty_increment, dec!(0.00000001));
+        assert!(registry.get("ETH/USD").is_none());
+    }
+
+    #[test]
+    fn test_rejects_off_grid_orders() {
+        let registry = InstrumentRegistry::new();
+        registry.insert_asset_pair(&xbt_usd());
+
+        assert!(registry.validate(&OrderRequest::limit_buy("XBT/USD", dec!(0.001), dec!(50000.1))).is_ok());
+        for order in [
+            OrderRequest::limit_buy("XBT/USD", dec!(0.001), dec!(50000.05)),
+            OrderRequest::limit_buy("XBT/USD", dec!(0.000000001), dec!(50000)),
+            OrderRequest::limit_buy("XBT/USD", dec!(0.00001), dec!(50000)),
+            OrderRequest::limit_buy("XBT/USD", dec!(0.0001), dec!(1)),
+        ] {
+            assert!(matches!(registry.validate(&order), Err(SdkError::InvalidOrder { .. })), "{:?}", order);
+        }
+
+        // Market orders have no price to check; unknown pairs pass
+        assert!(registry.validate(&OrderRequest::market_sell("XBT/USD", dec!(0.001))).is_ok());
+        assert!(registry.validate(&OrderRequest::limit_buy("ETH/USD", dec!(0.001), dec!(0.001))).is_ok());
+    }
+
+    #[test]
+    fn test_rounds_toward_a_better_price() {
+        let registry = InstrumentRegistry::new();
+        registry.insert_asset_pair(&xbt_usd());
+
+        let buy = registry.round(OrderRequest::limit_buy("XBT/USD", dec!(0.123456789), dec!(50000.19))).unwrap();
+        assert_eq!((buy.volume, buy.price), (dec!(0.12345678), Some(dec!(50000.1))));
+
+        let sell = registry.prepare(OrderRequest::limit_sell("XBT/USD", dec!(0.01), dec!(50000.11)), PrecisionPolicy::Round).unwrap();
+        assert_eq!(sell.price, Some(dec!(50000.2)));
+
+        // Rounding can't lift an order above the minimum
+        assert!(registry.round(OrderRequest::limit_buy("XBT/USD", dec!(0.00001), dec!(50000))).is_err());
+        assert!(registry.prepare(OrderRequest::limit_sell("XBT/USD", dec!(0.01), dec!(50000.11)), PrecisionPolicy::Reject).is_err());
+    }
+}
@@ -3,7 +3,8 @@
 //! Production-grade latency measurement for trading infrastructure.
 //! Tracks exchange-to-client latency with percentile calculations.
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
 use std::sync::Mutex;
 use std::time::{Duration, Instant};
 use chrono::{DateTime, Utc};
@@ -91,6 +92,8 @@ pub struct LatencyConfig {
     pub histogram_buckets: usize,
     /// Window for samples_per_second calculation
     pub rate_window_secs: u64,
+    /// Maximum samples kept per channel and per symbol breakdown
+    pub breakdown_samples: usize,
 }
 
 impl Default for LatencyConfig {
@@ -100,6 +103,7 @@ impl Default for LatencyConfig {
             histogram_bucket_us: 1000, // 1ms buckets
             histogram_buckets: 100,    // Up to 100ms
             rate_window_secs: 10,
+            breakdown_samples: 1000,
         }
     }
 }
@@ -118,6 +122,52 @@ pub struct LatencyAlert {
     pub timestamp: DateTime<Utc>,
 }
 
+/// Alert thresholds together with the callback that receives alerts
+#[derive(Clone)]
+pub struct LatencyAlertConfig {
+    /// Exchange timestamp -> receive (microseconds)
+    pub network_threshold_us: i64,
+    /// Receive -> dispatch (microseconds)
+    pub processing_threshold_us: i64,
+    /// Exchange timestamp -> dispatch (microseconds)
+    pub total_threshold_us: i64,
+    pub callback: LatencyAlertCallback,
+}
+
+impl LatencyAlertConfig {
+    /// Alert through `callback` using the default thresholds
+    /// (100ms network, 10ms processing, 150ms total)
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: Fn(LatencyAlert) + Send + Sync + 'static
+    {
+        Self {
+            network_threshold_us: 100_000,
+            processing_threshold_us: 10_000,
+            total_threshold_us: 150_000,
+            callback: std::sync::Arc::new(callback),
+        }
+    }
+    
+    /// Set alert thresholds (in microseconds)
+    pub fn with_thresholds(mut self, network_us: i64, processing_us: i64, total_us: i64) -> Self {
+        self.network_threshold_us = network_us;
+        self.processing_threshold_us = processing_us;
+        self.total_threshold_us = total_us;
+        self
+    }
+}
+
+impl fmt::Debug for LatencyAlertConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LatencyAlertConfig")
+            .field("network_threshold_us", &self.network_threshold_us)
+            .field("processing_threshold_us", &self.processing_threshold_us)
+            .field("total_threshold_us", &self.total_threshold_us)
+            .finish_non_exhaustive()
+    }
+}
+
 /// Types of latency alerts
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum LatencyAlertType {
@@ -127,19 +177,55 @@ pub enum LatencyAlertType {
     LatencySpike { previous_us: i64 },
 }
 
-/// Production-grade latency tracker
-pub struct LatencyTracker {
-    config: LatencyConfig,
+/// Rolling samples for one scope: everything, a channel or a symbol
+#[derive(Debug, Default)]
+struct SampleWindow {
     /// Network latency samples (microseconds)
-    network_samples: Mutex<VecDeque<i64>>,
+    network: VecDeque<i64>,
     /// Processing latency samples (microseconds)
-    processing_samples: Mutex<VecDeque<i64>>,
+    processing: VecDeque<i64>,
     /// Total latency samples (microseconds)
-    total_samples: Mutex<VecDeque<i64>>,
+    total: VecDeque<i64>,
     /// Recent measurements for rate calculation
-    recent_timestamps: Mutex<VecDeque<Instant>>,
+    recent_timestamps: VecDeque<Instant>,
     /// Last measurement
-    last_measurement: Mutex<Option<LatencyMeasurement>>,
+    last_measurement: Option<LatencyMeasurement>,
+}
+
+impl SampleWindow {
+    fn add(&mut self, measurement: &LatencyMeasurement, has_exchange_time: bool, max: usize) {
+        fn push(samples: &mut VecDeque<i64>, value: i64, max: usize) {
+            samples.push_back(value);
+            while samples.len() > max { samples.pop_front(); }
+        }
+        
+        // Without an exchange timestamp only receive -> dispatch is known
+        if has_exchange_time {
+            push(&mut self.network, measurement.network_latency_us, max);
+            push(&mut self.total, measurement.total_latency_us, max);
+        }
+        push(&mut self.processing, measurement.processing_latency_us, max);
+        
+        self.recent_timestamps.push_back(Instant::now());
+        self.last_measurement = Some(measurement.clone());
+    }
+    
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Production-grade latency tracker
+///
+/// Keeps overall samples plus a breakdown per channel and per symbol.
+pub struct LatencyTracker {
+    config: LatencyConfig,
+    /// Samples across all channels and symbols
+    overall: Mutex<SampleWindow>,
+    /// Samples per channel
+    channels: Mutex<HashMap<String, SampleWindow>>,
+    /// Samples per symbol
+    symbols: Mutex<HashMap<String, SampleWindow>>,
     /// Alert callback
     alert_callback: Mutex<Option<LatencyAlertCallback>>,
     /// Alert thresholds (microseconds)
@@ -160,11 +246,9 @@ impl LatencyTracker {
     pub fn with_config(config: LatencyConfig) -> Self {
         Self {
             config,
-            network_samples: Mutex::new(VecDeque::new()),
-            processing_samples: Mutex::new(VecDeque::new()),
-            total_samples: Mutex::new(VecDeque::new()),
-            recent_timestamps: Mutex::new(VecDeque::new()),
-            last_measurement: Mutex::new(None),
+            overall: Mutex::new(SampleWindow::default()),
+            channels: Mutex::new(HashMap::new()),
+            symbols: Mutex::new(HashMap::new()),
             alert_callback: Mutex::new(None),
             network_threshold_us: Mutex::new(100_000),    // 100ms default
             processing_threshold_us: Mutex::new(10_000),  // 10ms default
@@ -190,6 +274,13 @@ impl LatencyTracker {
         self
     }
     
+    /// Use the thresholds and callback from an alert configuration
+    pub fn set_alerts(&self, alerts: &LatencyAlertConfig) -> &Self {
+        self.set_thresholds(alerts.network_threshold_us, alerts.processing_threshold_us, alerts.total_threshold_us);
+        *self.alert_callback.lock().unwrap() = Some(alerts.callback.clone());
+        self
+    }
+    
     /// Record a latency measurement
     pub fn record(
         &self,
@@ -198,7 +289,6 @@ impl LatencyTracker {
         symbol: &str,
     ) -> LatencyMeasurement {
         let receive_timestamp = Utc::now();
-        let process_start = Instant::now();
         
         // Calculate latencies
         let network_latency = receive_timestamp
@@ -227,16 +317,10 @@ impl LatencyTracker {
         };
         
         // Store samples
-        self.add_sample(network_latency, processing_latency, total_latency);
-        
-        // Update last measurement
-        *self.last_measurement.lock().unwrap() = Some(measurement.clone());
-        
-        // Record timestamp for rate calculation
-        self.recent_timestamps.lock().unwrap().push_back(Instant::now());
+        self.add_sample(&measurement, true);
         
         // Check for alerts
-        self.check_alerts(&measurement);
+        self.check_alerts(&measurement, true);
         
         measurement
     }
@@ -273,31 +357,71 @@ impl LatencyTracker {
             symbol: symbol.to_string(),
         };
         
-        self.add_sample(network_latency, processing_latency, total_latency);
-        *self.last_measurement.lock().unwrap() = Some(measurement.clone());
-        self.recent_timestamps.lock().unwrap().push_back(Instant::now());
-        self.check_alerts(&measurement);
+        self.add_sample(&measurement, true);
+        self.check_alerts(&measurement, true);
         
         measurement
     }
     
-    fn add_sample(&self, network: i64, processing: i64, total: i64) {
-        let max = self.config.max_samples;
+    /// Record a dispatched message: exchange timestamp (when the message
+    /// carries one) -> receive -> dispatch
+    ///
+    /// Messages without an exchange timestamp only contribute processing
+    /// (receive -> dispatch) samples and alerts.
+    pub fn record_dispatch(
+        &self,
+        exchange_timestamp: Option<DateTime<Utc>>,
+        receive_timestamp: DateTime<Utc>,
+        dispatch_timestamp: DateTime<Utc>,
+        channel: &str,
+        symbol: &str,
+    ) -> LatencyMeasurement {
+        let has_exchange_time = exchange_timestamp.is_some();
+        let exchange_timestamp = exchange_timestamp.unwrap_or(receive_timestamp);
         
-        let mut net = self.network_samples.lock().unwrap();
-        net.push_back(network);
-        while net.len() > max { net.pop_front(); }
+        let network_latency = receive_timestamp
+            .signed_duration_since(exchange_timestamp)
+            .num_microseconds()
+            .unwrap_or(0);
         
-        let mut proc = self.processing_samples.lock().unwrap();
-        proc.push_back(processing);
-        while proc.len() > max { proc.pop_front(); }
+        let processing_latency = dispatch_timestamp
+            .signed_duration_since(receive_timestamp)
+            .num_microseconds()
+            .unwrap_or(0);
         
-        let mut tot = self.total_samples.lock().unwrap();
-        tot.push_back(total);
-        while tot.len() > max { tot.pop_front(); }
+        let measurement = LatencyMeasurement {
+            exchange_timestamp,
+            receive_timestamp,
+            process_timestamp: dispatch_timestamp,
+            network_latency_us: network_latency,
+            processing_latency_us: processing_latency,
+            total_latency_us: network_latency + processing_latency,
+            channel: channel.to_string(),
+            symbol: symbol.to_string(),
+        };
+        
+        self.add_sample(&measurement, has_exchange_time);
+        self.check_alerts(&measurement, has_exchange_time);
+        
+        measurement
+    }
+    
+    fn add_sample(&self, measurement: &LatencyMeasurement, has_exchange_time: bool) {
+        let max = self.config.max_samples;
+        let breakdown = self.config.breakdown_samples;
+        
+        self.overall.lock().unwrap().add(measurement, has_exchange_time, max);
+        self.channels.lock().unwrap()
+            .entry(measurement.channel.clone())
+            .or_default()
+            .add(measurement, has_exchange_time, breakdown);
+        self.symbols.lock().unwrap()
+            .entry(measurement.symbol.clone())
+            .or_default()
+            .add(measurement, has_exchange_time, breakdown);
     }
     
-    fn check_alerts(&self, measurement: &LatencyMeasurement) {
+    fn check_alerts(&self, measurement: &LatencyMeasurement, has_exchange_time: bool) {
         let callback = self.alert_callback.lock().unwrap();
         if callback.is_none() { return; }
         let cb = callback.as_ref().unwrap().clone();
@@ -307,7 +431,7 @@ impl LatencyTracker {
         let proc_threshold = *self.processing_threshold_us.lock().unwrap();
         let total_threshold = *self.total_threshold_us.lock().unwrap();
         
-        if measurement.network_latency_us > net_threshold {
+        if has_exchange_time && measurement.network_latency_us > net_threshold {
             cb(LatencyAlert {
                 alert_type: LatencyAlertType::HighNetworkLatency,
                 channel: measurement.channel.clone(),
@@ -329,7 +453,7 @@ impl LatencyTracker {
             });
         }
         
-        if measurement.total_latency_us > total_threshold {
+        if has_exchange_time && measurement.total_latency_us > total_threshold {
             cb(LatencyAlert {
                 alert_type: LatencyAlertType::HighTotalLatency,
                 channel: measurement.channel.clone(),
@@ -343,18 +467,48 @@ impl LatencyTracker {
     
     /// Get comprehensive latency statistics
     pub fn stats(&self) -> LatencyStats {
-        let network = self.calculate_percentiles(&self.network_samples.lock().unwrap());
-        let processing = self.calculate_percentiles(&self.processing_samples.lock().unwrap());
-        let total = self.calculate_percentiles(&self.total_samples.lock().unwrap());
+        self.window_stats(&mut self.overall.lock().unwrap())
+    }
+    
+    /// Latency statistics for one channel (`ticker`, `book`, `trade`, ...)
+    pub fn channel_stats(&self, channel: &str) -> Option<LatencyStats> {
+        self.channels.lock().unwrap().get_mut(channel).map(|window| self.window_stats(window))
+    }
+    
+    /// Latency statistics for one symbol
+    pub fn symbol_stats(&self, symbol: &str) -> Option<LatencyStats> {
+        self.symbols.lock().unwrap().get_mut(symbol).map(|window| self.window_stats(window))
+    }
+    
+    /// Latency statistics for every channel seen so far
+    pub fn stats_by_channel(&self) -> HashMap<String, LatencyStats> {
+        self.channels.lock().unwrap()
+            .iter_mut()
+            .map(|(channel, window)| (channel.clone(), self.window_stats(window)))
+            .collect()
+    }
+    
+    /// Latency statistics for every symbol seen so far
+    pub fn stats_by_symbol(&self) -> HashMap<String, LatencyStats> {
+        self.symbols.lock().unwrap()
+            .iter_mut()
+            .map(|(symbol, window)| (symbol.clone(), self.window_stats(window)))
+            .collect()
+    }
+    
+    fn window_stats(&self, window: &mut SampleWindow) -> LatencyStats {
+        let network = self.calculate_percentiles(&window.network);
+        let processing = self.calculate_percentiles(&window.processing);
+        let total = self.calculate_percentiles(&window.total);
         
-        let sample_count = self.total_samples.lock().unwrap().len() as u64;
+        let sample_count = window.processing.len() as u64;
         
         // Calculate samples per second
-        let mut recent = self.recent_timestamps.lock().unwrap();
+        let recent = &mut window.recent_timestamps;
         let now = Instant::now();
-        let window = Duration::from_secs(self.config.rate_window_secs);
+        let rate_window = Duration::from_secs(self.config.rate_window_secs);
         while let Some(ts) = recent.front() {
-            if now.duration_since(*ts) > window {
+            if now.duration_since(*ts) > rate_window {
                 recent.pop_front();
             } else {
                 break;
@@ -363,7 +517,7 @@ impl LatencyTracker {
         let samples_per_second = recent.len() as f64 / self.config.rate_window_secs as f64;
         
         // Build histogram
-        let histogram = self.build_histogram();
+        let histogram = self.build_histogram(&window.total);
         
         LatencyStats {
             network,
@@ -371,7 +525,7 @@ impl LatencyTracker {
             total,
             sample_count,
             samples_per_second,
-            last_measurement: self.last_measurement.lock().unwrap().clone(),
+            last_measurement: window.last_measurement.clone(),
             histogram: Some(histogram),
         }
     }
@@ -408,8 +562,7 @@ impl LatencyTracker {
         }
     }
     
-    fn build_histogram(&self) -> LatencyHistogram {
-        let samples = self.total_samples.lock().unwrap();
+    fn build_histogram(&self, samples: &VecDeque<i64>) -> LatencyHistogram {
         let bucket_width = self.config.histogram_bucket_us;
         let num_buckets = self.config.histogram_buckets;
         
@@ -442,16 +595,14 @@ impl LatencyTracker {
     
     /// Get last measurement
     pub fn last(&self) -> Option<LatencyMeasurement> {
-        self.last_measurement.lock().unwrap().clone()
+        self.overall.lock().unwrap().last_measurement.clone()
     }
     
     /// Reset all samples
     pub fn reset(&self) {
-        self.network_samples.lock().unwrap().clear();
-        self.processing_samples.lock().unwrap().clear();
-        self.total_samples.lock().unwrap().clear();
-        self.recent_timestamps.lock().unwrap().clear();
-        *self.last_measurement.lock().unwrap() = None;
+        self.overall.lock().unwrap().clear();
+        self.channels.lock().unwrap().clear();
+        self.symbols.lock().unwrap().clear();
     }
     
     /// Get uptime
@@ -525,6 +676,33 @@ mod tests {
         assert!(!histogram.buckets.is_empty());
     }
     
+    #[test]
+    fn test_breakdown_by_channel_and_symbol() {
+        let tracker = LatencyTracker::new();
+        let received = Utc::now();
+        let dispatched = received + ChronoDuration::microseconds(250);
+        
+        tracker.record_dispatch(Some(received - ChronoDuration::milliseconds(20)), received, dispatched, "trade", "XBT/USD");
+        tracker.record_dispatch(None, received, dispatched, "ticker", "XBT/USD");
+        tracker.record_dispatch(None, received, dispatched, "ticker", "ETH/USD");
+        
+        assert_eq!(tracker.stats().sample_count, 3);
+        
+        let trade = tracker.channel_stats("trade").unwrap();
+        assert_eq!(trade.network.min, 20_000.0);
+        assert_eq!(trade.total.min, 20_250.0);
+        
+        // No exchange timestamp: only receive -> dispatch is recorded
+        let ticker = tracker.channel_stats("ticker").unwrap();
+        assert_eq!(ticker.sample_count, 2);
+        assert_eq!(ticker.processing.p50, 250.0);
+        assert_eq!(ticker.network.max, 0.0);
+        
+        assert_eq!(tracker.symbol_stats("XBT/USD").unwrap().sample_count, 2);
+        assert_eq!(tracker.stats_by_symbol().len(), 2);
+        assert!(tracker.channel_stats("book").is_none());
+    }
+    
     #[test]
     fn test_format_latency() {
         assert_eq!(format_latency(500.0), "500µs");
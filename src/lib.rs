@@ -100,6 +100,12 @@ pub mod prelude {
     pub use crate::data::ClientConfig;
     /// Reconnection configuration
     pub use crate::data::ReconnectConfig;
+    /// WebSocket protocol version (v1 or v2)
+    pub use crate::data::ProtocolVersion;
+    /// How out-of-sync order books are restored
+    pub use crate::data::BookRecovery;
+    /// Trade and candle backfill after reconnects
+    pub use crate::backfill::BackfillConfig;
     
     // ── Channels ────────────────────────────────────────────────────────────
     /// Channel subscription builder
@@ -110,8 +116,18 @@ pub mod prelude {
     pub use crate::events::SdkEvent as Event;
     /// Event stream receiver
     pub use crate::events::EventReceiver;
+    /// Independent, bounded event stream (implements `futures::Stream`)
+    pub use crate::events::EventStream;
+    /// Stream filter by symbol, data type and event kind
+    pub use crate::events::{EventFilter, EventKind};
     /// Callback trait (legacy)
     pub use crate::events::EventCallback;
+    /// Book checksum mismatch (book is being resynced)
+    pub use crate::orderbook::ChecksumMismatch;
+    /// Reconnection attempt (attempt count and backoff delay)
+    pub use crate::data::ReconnectEvent;
+    /// Exchange status (`online`, `maintenance`, `cancel_only`, ...) and heartbeats
+    pub use crate::system_status::{ExchangeStatus, Heartbeat, SystemStatus};
     
     // ── Connection State ────────────────────────────────────────────────────
     /// Connection state machine states
@@ -128,6 +144,10 @@ pub mod prelude {
     pub use crate::data::OrderBookUpdate;
     /// OHLC candle
     pub use crate::data::OHLCData;
+    /// Best bid/ask spread update
+    pub use crate::data::SpreadData;
+    /// Instrument reference data (v2)
+    pub use crate::data::InstrumentData;
     /// Trade side (Buy/Sell)
     pub use crate::data::TradeSide;
     /// Price level in order book
@@ -138,10 +158,14 @@ pub mod prelude {
     // ── Errors ──────────────────────────────────────────────────────────────
     /// SDK error type
     pub use crate::error::SdkError;
+    /// Why a subscribe/unsubscribe request failed
+    pub use crate::error::SubscriptionError;
     
     // ── Backpressure (optional) ─────────────────────────────────────────────
     /// Backpressure configuration
     pub use crate::backpressure::{BackpressureConfig, DropPolicy};
+    /// Backpressure statistics for outbound events
+    pub use crate::backpressure::BackpressureStats;
 }
 
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
@@ -202,8 +226,18 @@ pub mod trading_api {
     // REST client
     pub use crate::rest_client::{
         KrakenRestClient, TradesHistoryOptions, ClosedOrdersOptions,
+        KrakenPublicClient, AssetInfo, AssetPair, HistoryPage,
     };
     
+    // Exchange status gating for orders
+    pub use crate::system_status::{ExchangeStatus, SystemStatus, SystemStatusMonitor};
+    
+    // Price and volume validation against pair rules
+    pub use crate::instruments::{InstrumentRegistry, PrecisionPolicy};
+    
+    // Pair names in any of Kraken's forms
+    pub use crate::symbols::{SymbolFormat, SymbolNormalizer, SymbolRegistry};
+    
     // Trading types
     pub use crate::trading::{
         OrderSide, OrderType, TimeInForce, OrderFlags,
@@ -250,10 +284,26 @@ pub mod extended {
     pub use crate::prelude::*;
     
     // Sequencing & gap detection
-    pub use crate::sequencing::{SequenceManager, SequenceConfig, GapEvent};
+    pub use crate::sequencing::{
+        SequenceManager, SequenceConfig, SequenceStats,
+        GapEvent, ResyncEvent, ResyncReason,
+    };
     
     // Latency tracking
-    pub use crate::latency::{LatencyTracker, LatencyStats, LatencyPercentiles};
+    pub use crate::latency::{
+        LatencyTracker, LatencyStats, LatencyPercentiles,
+        LatencyAlert, LatencyAlertConfig, LatencyAlertType, LatencyConfig,
+    };
+    
+    // Exchange status tracking
+    pub use crate::system_status::SystemStatusMonitor;
+    
+    // Connection pooling
+    pub use crate::pool::{KrakenWsPool, PoolConfig, PoolConnectionStats};
+    
+    // Session recording
+    pub use crate::recorder::{RecordedFrame, RecorderConfig, SessionRecorder};
+    pub use crate::replay::{Pacing, ReplayControl, ReplayEngine};
     
     // Retry policies
     pub use crate::retry::{RetryPolicy, CircuitBreaker, CircuitState};
@@ -344,6 +394,12 @@ pub mod visualization {
         MarketHealthTracker, MarketStatus, StaleDetectionConfig,
     };
     
+    // Candles built from trades
+    pub use crate::candles::{
+        CandleBuilder, CandleConfig, BarSpec,
+        Candle, CandleEvent,
+    };
+    
     // Data types needed for visualization
     pub use crate::data::{PriceLevel, TradeData, TradeSide};
     
@@ -384,6 +440,7 @@ pub mod visualization {
 pub mod internal {
     pub use crate::connection;
     pub use crate::parser;
+    pub use crate::wire;
     pub use crate::subscription;
     pub use crate::orderbook;
     pub use crate::middleware;
@@ -392,13 +449,17 @@ pub mod internal {
 }
 
 // Module declarations (internal)
+pub mod backfill;  // Trade and candle gap backfill after reconnects
 pub mod backpressure;
+pub mod candles;  // Local candle aggregation from trades
 pub mod client;
+pub mod clock;  // System or replay-driven time source
 pub mod connection;
 pub mod data;
 pub mod error;
 pub mod events;
 pub mod exchange;
+pub mod instruments;  // Pair trading rules and order validation
 pub mod latency;
 pub mod middleware;
 pub mod orderbook;
@@ -407,12 +468,18 @@ pub mod whale_detection;  // Statistical whale order detection
 pub mod liquidity_heatmap;  // Liquidity persistence tracking
 pub mod spoofing_detection;  // Spoofing pattern detection
 pub mod parser;
+pub mod pool;  // Connection pool sharding subscriptions across sockets
+pub mod recorder;  // Raw frame recording for debugging and replay
+pub mod replay;  // Deterministic replay of recorded sessions
 pub mod retry;
 pub mod sdk;
 pub mod sequencing;
 pub mod state;  // Connection state machine
 pub mod subscription;
+pub mod symbols;  // Symbol normalization across Kraken naming schemes
+pub mod system_status;  // Exchange status and heartbeats
 pub mod telemetry;
+pub mod wire;  // Zero-copy decoding of Kraken frames
 
 // Private/authenticated API modules
 pub mod auth;           // API key authentication & request signing
@@ -445,6 +512,9 @@ pub use events::*;
 #[doc(hidden)]
 pub use orderbook::*;
 
+#[doc(hidden)]
+pub use pool::{KrakenWsPool, PoolConfig, PoolConnectionStats};
+
 // Legacy SDK API
 #[doc(hidden)]
 pub use sdk::{KrakenSDK, KrakenSDKBuilder};
@@ -461,6 +531,7 @@ pub use sequencing::{
 pub use backpressure::{
     BackpressureManager, BackpressureConfig, BackpressureResult, BackpressureStats,
     DropPolicy, DropEvent, DropReason, CoalesceEvent, RateLimitEvent, BufferedMessage,
+    Coalesce,
 };
 
 // Legacy latency exports
@@ -468,7 +539,7 @@ pub use backpressure::{
 pub use latency::{
     LatencyTracker, LatencyConfig, LatencyMeasurement, LatencyStats,
     LatencyPercentiles, LatencyHistogram, HistogramBucket,
-    LatencyAlert, LatencyAlertType, format_latency,
+    LatencyAlert, LatencyAlertConfig, LatencyAlertType, format_latency,
 };
 
 // Legacy exchange exports
@@ -505,8 +576,6 @@ pub use telemetry::{
 // UTILITIES
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 
-use tracing_subscriber;
-
 /// Initialize logging for the SDK
 pub fn init_logging() {
     tracing_subscriber::fmt::init();
@@ -28,6 +28,7 @@
 //! }
 //! ```
 
+use crate::clock::Clock;
 use crate::orderbook::OrderBook;
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
@@ -136,6 +137,8 @@ struct SymbolTracker {
 /// heat scores for visualization. Persistent liquidity = reliable support/resistance.
 pub struct LiquidityHeatmap {
     config: HeatmapConfig,
+    /// Measures how long levels persist
+    clock: Clock,
     /// Tracking data by symbol
     trackers: Mutex<HashMap<String, SymbolTracker>>,
 }
@@ -150,20 +153,27 @@ impl LiquidityHeatmap {
     pub fn with_config(config: HeatmapConfig) -> Self {
         Self {
             config,
+            clock: Clock::system(),
             trackers: Mutex::new(HashMap::new()),
         }
     }
     
+    /// Measure persistence by `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+    
     /// Update heatmap with new order book data
     ///
     /// Call this on every order book update to track persistence.
     pub fn update(&self, book: &OrderBook) {
         let mut trackers = self.trackers.lock().unwrap();
-        let now = Utc::now();
+        let now = self.clock.now();
         
         let tracker = trackers
             .entry(book.symbol.clone())
-            .or_insert_with(SymbolTracker::default);
+            .or_default();
         
         // Calculate time delta
         let delta_secs = tracker.last_update
@@ -251,7 +261,7 @@ impl LiquidityHeatmap {
     pub fn snapshot(&self, symbol: &str) -> Option<HeatmapSnapshot> {
         let trackers = self.trackers.lock().unwrap();
         let tracker = trackers.get(symbol)?;
-        let now = Utc::now();
+        let now = self.clock.now();
         
         let mut bids: Vec<HeatLevel> = tracker.bids.iter()
             .map(|(price_str, t)| self.create_heat_level(price_str, t, now))
@@ -262,8 +272,8 @@ impl LiquidityHeatmap {
             .collect();
         
         // Sort bids descending, asks ascending
-        bids.sort_by(|a, b| b.price.cmp(&a.price));
-        asks.sort_by(|a, b| a.price.cmp(&b.price));
+        bids.sort_by_key(|b| std::cmp::Reverse(b.price));
+        asks.sort_by_key(|a| a.price);
         
         let bid_heat_avg = if bids.is_empty() {
             0.0
@@ -309,7 +319,7 @@ impl LiquidityHeatmap {
         };
         
         let mut all: Vec<HeatLevel> = snapshot.bids.into_iter()
-            .chain(snapshot.asks.into_iter())
+            .chain(snapshot.asks)
             .collect();
         
         all.sort_by(|a, b| b.heat_score.partial_cmp(&a.heat_score).unwrap_or(std::cmp::Ordering::Equal));
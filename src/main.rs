@@ -1,4 +1,4 @@
-use kraken_ws_sdk::{init_logging, KrakenWsClient};
+use kraken_ws_sdk::init_logging;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -113,6 +113,11 @@ impl LoggingMiddleware {
     pub fn info() -> Self {
         Self::new(tracing::Level::INFO)
     }
+
+    /// Level this middleware was created with
+    pub fn level(&self) -> tracing::Level {
+        self.level
+    }
 }
 
 #[async_trait]
@@ -361,6 +366,7 @@ impl MiddlewareChain {
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn add<M: Middleware + 'static>(mut self, middleware: M) -> Self {
         self.middlewares.push(Arc::new(middleware));
         self
@@ -30,14 +30,46 @@ use crate::{
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
-use std::sync::{Arc, Mutex};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Number of levels per side covered by Kraken's book checksum
+pub const CHECKSUM_DEPTH: usize = 10;
+
+/// Book depth Kraken uses when a subscription does not specify one
+pub const DEFAULT_BOOK_DEPTH: u32 = 10;
 
 /// Order book state manager
+///
+/// Each symbol's book has its own lock, so updates to one pair never wait on
+/// readers or writers of another. Readers get an immutable
+/// `Arc<OrderBook>` snapshot: taking one only clones the `Arc`, and the
+/// next update copies the book instead of mutating it while a snapshot is
+/// still held.
 #[derive(Debug)]
 pub struct OrderBookManager {
     /// Current order book state by symbol
-    order_books: Arc<Mutex<std::collections::HashMap<String, OrderBook>>>,
+    order_books: Arc<RwLock<HashMap<String, BookSlot>>>,
+    /// Subscribed depth and pair precision by symbol
+    book_settings: Arc<RwLock<HashMap<String, BookSettings>>>,
+    /// Symbols that failed checksum validation and need a fresh snapshot
+    pending_resyncs: Arc<Mutex<Vec<String>>>,
+    /// Deltas held back while a REST snapshot is fetched, by symbol
+    recovery_buffers: Arc<Mutex<HashMap<String, Vec<OrderBookUpdate>>>>,
+}
+
+/// Latest published book for one symbol
+type BookSlot = Arc<RwLock<Arc<OrderBook>>>;
+
+/// Per-symbol settings needed to mirror Kraken's book exactly
+#[derive(Debug, Clone, Copy, Default)]
+struct BookSettings {
+    /// Subscribed depth; levels beyond it are dropped after each update
+    depth: Option<usize>,
+    /// Decimal places used when formatting prices for the checksum
+    price_precision: Option<u32>,
+    /// Decimal places used when formatting quantities for the checksum
+    qty_precision: Option<u32>,
 }
 
 /// Order book state for a single symbol
@@ -48,6 +80,28 @@ pub struct OrderBook {
     pub asks: BTreeMap<Decimal, PriceLevel>,
     pub last_update: chrono::DateTime<chrono::Utc>,
     pub checksum: Option<u32>,
+    pub status: BookStatus,
+}
+
+/// Synchronization status of a local order book
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BookStatus {
+    /// Book matches the exchange (or has not been checked yet)
+    #[default]
+    Synced,
+    /// Checksum mismatch detected; deltas are ignored until a new snapshot arrives
+    Resyncing,
+}
+
+/// Raised when the local book disagrees with Kraken's checksum
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChecksumMismatch {
+    pub symbol: String,
+    /// Checksum sent by Kraken
+    pub expected: u32,
+    /// Checksum of the local book
+    pub calculated: u32,
+    pub timestamp: DateTime<Utc>,
 }
 
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
@@ -198,18 +252,95 @@ pub struct FilteredBook {
 impl OrderBookManager {
     pub fn new() -> Self {
         Self {
-            order_books: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            order_books: Arc::new(RwLock::new(HashMap::new())),
+            book_settings: Arc::new(RwLock::new(HashMap::new())),
+            pending_resyncs: Arc::new(Mutex::new(Vec::new())),
+            recovery_buffers: Arc::new(Mutex::new(HashMap::new())),
         }
     }
     
+    /// Set the subscribed depth for a symbol
+    ///
+    /// Kraken does not send deletes for levels that fall outside the
+    /// subscribed depth, so the local book is truncated to match.
+    pub fn set_depth(&self, symbol: &str, depth: usize) {
+        let mut settings = self.book_settings.write().unwrap();
+        settings.entry(symbol.to_string()).or_default().depth = Some(depth);
+    }
+    
+    /// Set the pair precision used to format levels for the checksum
+    ///
+    /// Required for v2 books, whose prices arrive as JSON numbers. v1 levels
+    /// keep the precision of the strings Kraken sends.
+    pub fn set_precision(&self, symbol: &str, price_precision: u32, qty_precision: u32) {
+        let mut settings = self.book_settings.write().unwrap();
+        let entry = settings.entry(symbol.to_string()).or_default();
+        entry.price_precision = Some(price_precision);
+        entry.qty_precision = Some(qty_precision);
+    }
+    
+    /// Subscribed depth for a symbol, if set
+    pub fn depth(&self, symbol: &str) -> Option<usize> {
+        self.settings(symbol).depth
+    }
+    
+    /// Check whether pair precision is known for a symbol
+    pub fn has_precision(&self, symbol: &str) -> bool {
+        let settings = self.book_settings.read().unwrap();
+        settings.get(symbol)
+            .map(|s| s.price_precision.is_some() && s.qty_precision.is_some())
+            .unwrap_or(false)
+    }
+    
+    /// Replace the book for a symbol with a snapshot
+    ///
+    /// The new book is built off to the side and published in one step, so
+    /// readers never see it half-applied. Clears any resync state for the
+    /// symbol.
+    pub fn apply_snapshot(&self, update: OrderBookUpdate) -> Result<Arc<OrderBook>, ParseError> {
+        self.pending_resyncs.lock().unwrap().retain(|s| s != &update.symbol);
+        self.recovery_buffers.lock().unwrap().remove(&update.symbol);
+        
+        let mut order_book = OrderBook::new(&update.symbol);
+        let result = self.merge_update(&mut order_book, update);
+        let order_book = Arc::new(order_book);
+        *self.slot(&order_book.symbol).write().unwrap() = Arc::clone(&order_book);
+        result.map(|()| order_book)
+    }
+    
     /// Apply order book update and maintain state
-    pub fn apply_update(&self, update: OrderBookUpdate) -> Result<OrderBook, ParseError> {
-        let mut order_books = self.order_books.lock().unwrap();
+    ///
+    /// When the update carries a checksum it is verified against the top
+    /// [`CHECKSUM_DEPTH`] levels. On mismatch the book is marked
+    /// [`BookStatus::Resyncing`], queued for resync, and
+    /// [`ParseError::ChecksumMismatch`] is returned. Deltas for a resyncing
+    /// book are ignored until [`apply_snapshot`](Self::apply_snapshot), or
+    /// buffered while a recovery is in progress (see
+    /// [`begin_recovery`](Self::begin_recovery)).
+    ///
+    /// Only this symbol's lock is held while the update is applied.
+    pub fn apply_update(&self, update: OrderBookUpdate) -> Result<Arc<OrderBook>, ParseError> {
+        let slot = self.slot(&update.symbol);
+        let mut current = slot.write().unwrap();
         
-        // Get or create order book for this symbol
-        let order_book = order_books
-            .entry(update.symbol.clone())
-            .or_insert_with(|| OrderBook::new(&update.symbol));
+        if current.status == BookStatus::Resyncing {
+            if let Some(buffer) = self.recovery_buffers.lock().unwrap().get_mut(&update.symbol) {
+                tracing::debug!("Buffering update for {} while recovering from a REST snapshot", update.symbol);
+                buffer.push(update);
+            } else {
+                tracing::debug!("Ignoring update for {} while awaiting resync snapshot", update.symbol);
+            }
+            return Ok(Arc::clone(&current));
+        }
+        
+        // Copies the book only if a reader still holds the previous snapshot
+        self.merge_update(Arc::make_mut(&mut current), update)?;
+        Ok(Arc::clone(&current))
+    }
+    
+    /// Merge levels into a book, then validate it and verify the checksum
+    fn merge_update(&self, order_book: &mut OrderBook, update: OrderBookUpdate) -> Result<(), ParseError> {
+        let settings = self.settings(&update.symbol);
         
         // Apply bid updates
         for bid in &update.bids {
@@ -233,6 +364,16 @@ impl OrderBookManager {
             }
         }
         
+        // Drop levels that fell outside the subscribed depth
+        if let Some(depth) = settings.depth {
+            while order_book.bids.len() > depth {
+                order_book.bids.pop_first();
+            }
+            while order_book.asks.len() > depth {
+                order_book.asks.pop_last();
+            }
+        }
+        
         // Update metadata
         order_book.last_update = update.timestamp;
         order_book.checksum = update.checksum;
@@ -240,19 +381,176 @@ impl OrderBookManager {
         // Validate order book integrity
         self.validate_order_book(order_book)?;
         
-        Ok(order_book.clone())
+        if let Some(expected) = update.checksum {
+            let calculated = book_checksum(order_book, &settings);
+            if calculated != expected {
+                tracing::warn!(
+                    "Checksum mismatch for {}: expected {}, calculated {}; resyncing",
+                    update.symbol, expected, calculated
+                );
+                order_book.status = BookStatus::Resyncing;
+                
+                let mut pending = self.pending_resyncs.lock().unwrap();
+                if !pending.contains(&update.symbol) {
+                    pending.push(update.symbol.clone());
+                }
+                
+                return Err(ParseError::ChecksumMismatch {
+                    symbol: update.symbol,
+                    expected,
+                    calculated,
+                });
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Mark a book as out of sync and queue it for a fresh snapshot
+    ///
+    /// Used when updates were lost, e.g. after a sequence gap.
+    pub fn request_resync(&self, symbol: &str) {
+        if let Some(slot) = self.existing_slot(symbol) {
+            let mut current = slot.write().unwrap();
+            if current.status != BookStatus::Resyncing {
+                Arc::make_mut(&mut current).status = BookStatus::Resyncing;
+            }
+        }
+        
+        let mut pending = self.pending_resyncs.lock().unwrap();
+        if !pending.iter().any(|s| s == symbol) {
+            pending.push(symbol.to_string());
+        }
+    }
+    
+    /// Start recovering a book from an out-of-band (REST) snapshot
+    ///
+    /// The book is marked [`BookStatus::Resyncing`] and deltas received from
+    /// now on are buffered instead of dropped, so they can be replayed on top
+    /// of the snapshot by [`apply_recovery_snapshot`](Self::apply_recovery_snapshot).
+    /// Call this before requesting the snapshot.
+    pub fn begin_recovery(&self, symbol: &str) {
+        let slot = self.slot(symbol);
+        let mut current = slot.write().unwrap();
+        if current.status != BookStatus::Resyncing {
+            Arc::make_mut(&mut current).status = BookStatus::Resyncing;
+        }
+        self.recovery_buffers.lock().unwrap().entry(symbol.to_string()).or_default();
+    }
+    
+    /// Install a REST snapshot and replay the deltas buffered since
+    /// [`begin_recovery`](Self::begin_recovery)
+    ///
+    /// Buffered levels older than the snapshot's newest level are already
+    /// reflected in it and are skipped. Only the last replayed delta's
+    /// checksum is verified, as earlier ones describe books older than the
+    /// snapshot.
+    ///
+    /// If a WebSocket snapshot arrived (or the book was cleared) in the
+    /// meantime, the recovery is stale and the current book is returned
+    /// unchanged. On error the book stays [`BookStatus::Resyncing`] but is
+    /// not queued for resync; the caller decides how to recover.
+    pub fn apply_recovery_snapshot(&self, snapshot: OrderBookUpdate) -> Result<Arc<OrderBook>, ParseError> {
+        let symbol = snapshot.symbol.clone();
+        let slot = self.slot(&symbol);
+        let mut current = slot.write().unwrap();
+        
+        let Some(buffered) = self.recovery_buffers.lock().unwrap().remove(&symbol) else {
+            tracing::debug!("Discarding stale REST snapshot for {}", symbol);
+            return Ok(Arc::clone(&current));
+        };
+        
+        // Levels carry the time Kraken last changed them
+        let snapshot_time = snapshot.bids.iter().chain(&snapshot.asks).map(|level| level.timestamp).max();
+        let mut deltas: Vec<OrderBookUpdate> = buffered.into_iter()
+            .filter_map(|mut delta| {
+                if let Some(snapshot_time) = snapshot_time {
+                    delta.bids.retain(|level| level.timestamp >= snapshot_time);
+                    delta.asks.retain(|level| level.timestamp >= snapshot_time);
+                }
+                (!delta.bids.is_empty() || !delta.asks.is_empty()).then_some(delta)
+            })
+            .collect();
+        let last = deltas.len().saturating_sub(1);
+        for delta in &mut deltas[..last] {
+            delta.checksum = None;
+        }
+        
+        let mut order_book = OrderBook::new(&symbol);
+        let mut result = self.merge_update(&mut order_book, OrderBookUpdate { checksum: None, ..snapshot });
+        for delta in deltas {
+            if result.is_err() {
+                break;
+            }
+            result = self.merge_update(&mut order_book, delta);
+        }
+        
+        if let Err(e) = result {
+            tracing::warn!("Recovery from REST snapshot failed for {}: {}", symbol, e);
+            self.pending_resyncs.lock().unwrap().retain(|s| s != &symbol);
+            if current.status != BookStatus::Resyncing {
+                Arc::make_mut(&mut current).status = BookStatus::Resyncing;
+            }
+            return Err(e);
+        }
+        
+        tracing::info!("Recovered book for {} from REST snapshot", symbol);
+        *current = Arc::new(order_book);
+        Ok(Arc::clone(&current))
+    }
+    
+    /// Stop buffering deltas for a recovery that will not complete
+    ///
+    /// The book stays [`BookStatus::Resyncing`] until the next snapshot.
+    pub fn abandon_recovery(&self, symbol: &str) {
+        self.recovery_buffers.lock().unwrap().remove(symbol);
     }
     
-    /// Get current order book state for a symbol
-    pub fn get_order_book(&self, symbol: &str) -> Option<OrderBook> {
-        let order_books = self.order_books.lock().unwrap();
-        order_books.get(symbol).cloned()
+    /// Check whether deltas are being buffered for a REST recovery
+    pub fn is_recovering(&self, symbol: &str) -> bool {
+        self.recovery_buffers.lock().unwrap().contains_key(symbol)
+    }
+    
+    /// Take the symbols queued for resync since the last call
+    ///
+    /// The client resubscribes to each returned book to get a fresh snapshot.
+    pub fn take_resync_requests(&self) -> Vec<String> {
+        std::mem::take(&mut *self.pending_resyncs.lock().unwrap())
+    }
+    
+    fn settings(&self, symbol: &str) -> BookSettings {
+        let settings = self.book_settings.read().unwrap();
+        settings.get(symbol).copied().unwrap_or_default()
+    }
+    
+    /// Slot for a symbol's book, if it is tracked
+    fn existing_slot(&self, symbol: &str) -> Option<BookSlot> {
+        self.order_books.read().unwrap().get(symbol).cloned()
+    }
+    
+    /// Slot for a symbol's book, creating an empty book if needed
+    ///
+    /// The map-wide write lock is only taken the first time a symbol is seen.
+    fn slot(&self, symbol: &str) -> BookSlot {
+        if let Some(slot) = self.existing_slot(symbol) {
+            return slot;
+        }
+        let mut order_books = self.order_books.write().unwrap();
+        Arc::clone(order_books.entry(symbol.to_string())
+            .or_insert_with(|| Arc::new(RwLock::new(Arc::new(OrderBook::new(symbol))))))
+    }
+    
+    /// Get a snapshot of the current order book for a symbol
+    ///
+    /// Cheap to take and never changes; later updates are applied to a
+    /// copy. Call again for fresh state.
+    pub fn get_order_book(&self, symbol: &str) -> Option<Arc<OrderBook>> {
+        self.existing_slot(symbol).map(|slot| Arc::clone(&slot.read().unwrap()))
     }
     
     /// Get best bid and ask prices
     pub fn get_best_bid_ask(&self, symbol: &str) -> Option<(Option<Decimal>, Option<Decimal>)> {
-        let order_books = self.order_books.lock().unwrap();
-        if let Some(order_book) = order_books.get(symbol) {
+        if let Some(order_book) = self.get_order_book(symbol) {
             let best_bid = order_book.bids.keys().next_back().copied();
             let best_ask = order_book.asks.keys().next().copied();
             Some((best_bid, best_ask))
@@ -263,8 +561,7 @@ impl OrderBookManager {
     
     /// Get order book depth (top N levels)
     pub fn get_depth(&self, symbol: &str, depth: usize) -> Option<(Vec<PriceLevel>, Vec<PriceLevel>)> {
-        let order_books = self.order_books.lock().unwrap();
-        if let Some(order_book) = order_books.get(symbol) {
+        if let Some(order_book) = self.get_order_book(symbol) {
             let bids: Vec<PriceLevel> = order_book.bids
                 .values()
                 .rev()
@@ -284,27 +581,10 @@ impl OrderBookManager {
         }
     }
     
-    /// Calculate order book checksum for integrity verification
+    /// Calculate Kraken's CRC32 checksum of the current book for a symbol
     pub fn calculate_checksum(&self, symbol: &str) -> Option<u32> {
-        let order_books = self.order_books.lock().unwrap();
-        if let Some(order_book) = order_books.get(symbol) {
-            // Simple checksum calculation (in production, use Kraken's specific algorithm)
-            let mut checksum_data = String::new();
-            
-            // Add top 10 bids and asks to checksum
-            for (price, level) in order_book.bids.iter().rev().take(10) {
-                checksum_data.push_str(&format!("{}:{}", price, level.volume));
-            }
-            
-            for (price, level) in order_book.asks.iter().take(10) {
-                checksum_data.push_str(&format!("{}:{}", price, level.volume));
-            }
-            
-            // Calculate simple hash (in production, use CRC32 or similar)
-            Some(checksum_data.len() as u32)
-        } else {
-            None
-        }
+        let settings = self.settings(symbol);
+        self.get_order_book(symbol).map(|order_book| book_checksum(&order_book, &settings))
     }
     
     /// Validate order book integrity
@@ -351,13 +631,21 @@ impl OrderBookManager {
     
     /// Clear order book for a symbol
     pub fn clear_order_book(&self, symbol: &str) {
-        let mut order_books = self.order_books.lock().unwrap();
+        let mut order_books = self.order_books.write().unwrap();
         order_books.remove(symbol);
+        self.recovery_buffers.lock().unwrap().remove(symbol);
+    }
+    
+    /// Check whether a symbol's book is waiting for a resync snapshot
+    pub fn is_resyncing(&self, symbol: &str) -> bool {
+        self.get_order_book(symbol)
+            .map(|book| book.status == BookStatus::Resyncing)
+            .unwrap_or(false)
     }
     
     /// Get all tracked symbols
     pub fn get_symbols(&self) -> Vec<String> {
-        let order_books = self.order_books.lock().unwrap();
+        let order_books = self.order_books.read().unwrap();
         order_books.keys().cloned().collect()
     }
 }
@@ -370,6 +658,7 @@ impl OrderBook {
             asks: BTreeMap::new(),
             last_update: chrono::Utc::now(),
             checksum: None,
+            status: BookStatus::Synced,
         }
     }
     
@@ -457,9 +746,9 @@ impl OrderBook {
             
             // Sort: bids descending, asks ascending
             if is_bid {
-                result.sort_by(|a, b| b.price.cmp(&a.price));
+                result.sort_by_key(|b| std::cmp::Reverse(b.price));
             } else {
-                result.sort_by(|a, b| a.price.cmp(&b.price));
+                result.sort_by_key(|a| a.price);
             }
             
             result
@@ -807,6 +1096,68 @@ impl Clone for OrderBookManager {
     fn clone(&self) -> Self {
         Self {
             order_books: Arc::clone(&self.order_books),
+            book_settings: Arc::clone(&self.book_settings),
+            pending_resyncs: Arc::clone(&self.pending_resyncs),
+            recovery_buffers: Arc::clone(&self.recovery_buffers),
+        }
+    }
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// CHECKSUM
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// Kraken book checksum: CRC32 over the top 10 asks (best first) followed by
+/// the top 10 bids (best first), each level written as price then quantity
+/// with the decimal point and leading zeros removed.
+fn book_checksum(order_book: &OrderBook, settings: &BookSettings) -> u32 {
+    let mut payload = String::new();
+    
+    for level in order_book.asks.values().take(CHECKSUM_DEPTH) {
+        payload.push_str(&checksum_field(level.price, settings.price_precision));
+        payload.push_str(&checksum_field(level.volume, settings.qty_precision));
+    }
+    
+    for level in order_book.bids.values().rev().take(CHECKSUM_DEPTH) {
+        payload.push_str(&checksum_field(level.price, settings.price_precision));
+        payload.push_str(&checksum_field(level.volume, settings.qty_precision));
+    }
+    
+    crc32(payload.as_bytes())
+}
+
+/// Format a value for the checksum, e.g. `0.05005` -> `5005`
+fn checksum_field(value: Decimal, precision: Option<u32>) -> String {
+    let mut value = value;
+    if let Some(precision) = precision {
+        value.rescale(precision);
+    }
+    
+    let digits: String = value.to_string().chars().filter(|c| *c != '.').collect();
+    digits.trim_start_matches('0').to_string()
+}
+
+/// Lookup table for the IEEE 802.3 CRC32 polynomial (reflected)
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            bit += 1;
         }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc = CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8);
     }
+    !crc
 }
\ No newline at end of file
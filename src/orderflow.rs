@@ -37,7 +37,8 @@
 //! }
 //! ```
 
-use crate::data::{PriceLevel, TradeData, TradeSide};
+use crate::clock::Clock;
+use crate::data::{TradeData, TradeSide};
 use crate::orderbook::OrderBook;
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
@@ -128,14 +129,17 @@ pub enum FlowSide {
 /// Snapshot of a price level for comparison
 #[derive(Debug, Clone)]
 struct LevelSnapshot {
-    price: Decimal,
     volume: Decimal,
-    timestamp: DateTime<Utc>,
 }
 
+/// Callback for flow events
+pub type FlowCallback = Arc<dyn Fn(FlowEvent) + Send + Sync>;
+
 /// Production-grade order flow tracker
 pub struct OrderFlowTracker {
     config: OrderFlowConfig,
+    /// Stamps flow events
+    clock: Clock,
     /// Previous snapshots by symbol
     previous_books: Mutex<HashMap<String, BookSnapshot>>,
     /// Event history
@@ -143,7 +147,7 @@ pub struct OrderFlowTracker {
     /// Event sequence counter
     sequence: Mutex<u64>,
     /// Callbacks for flow events
-    callbacks: Mutex<Vec<Arc<dyn Fn(FlowEvent) + Send + Sync>>>,
+    callbacks: Mutex<Vec<FlowCallback>>,
 }
 
 /// Snapshot of an order book for comparison
@@ -154,7 +158,6 @@ struct BookSnapshot {
     asks: BTreeMap<Decimal, LevelSnapshot>,
     best_bid: Option<Decimal>,
     best_ask: Option<Decimal>,
-    timestamp: DateTime<Utc>,
 }
 
 impl OrderFlowTracker {
@@ -167,6 +170,7 @@ impl OrderFlowTracker {
     pub fn with_config(config: OrderFlowConfig) -> Self {
         Self {
             config,
+            clock: Clock::system(),
             previous_books: Mutex::new(HashMap::new()),
             event_history: Mutex::new(VecDeque::new()),
             sequence: Mutex::new(0),
@@ -174,6 +178,12 @@ impl OrderFlowTracker {
         }
     }
     
+    /// Stamp events with `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+    
     /// Register callback for flow events
     pub fn on_event<F>(&self, callback: F)
     where
@@ -217,18 +227,14 @@ impl OrderFlowTracker {
         // Snapshot top N bid levels
         for (price, level) in book.bids.iter().rev().take(self.config.track_depth) {
             bids.insert(*price, LevelSnapshot {
-                price: *price,
                 volume: level.volume,
-                timestamp: level.timestamp,
             });
         }
         
         // Snapshot top N ask levels
         for (price, level) in book.asks.iter().take(self.config.track_depth) {
             asks.insert(*price, LevelSnapshot {
-                price: *price,
                 volume: level.volume,
-                timestamp: level.timestamp,
             });
         }
         
@@ -238,7 +244,6 @@ impl OrderFlowTracker {
             asks,
             best_bid: book.bids.keys().next_back().copied(),
             best_ask: book.asks.keys().next().copied(),
-            timestamp: book.last_update,
         }
     }
     
@@ -416,7 +421,7 @@ impl OrderFlowTracker {
             event_type,
             current_volume,
             previous_volume,
-            timestamp: Utc::now(),
+            timestamp: self.clock.now(),
             sequence: *seq,
         }
     }
@@ -554,6 +559,8 @@ pub struct LevelTradeStats {
 /// Tracks recent trades aligned with price levels for overlay visualization
 pub struct TradesByPriceLevel {
     config: TradeOverlayConfig,
+    /// Ages trades out of the aggregation window
+    clock: Clock,
     /// Trades by symbol -> price -> trades
     trades: Mutex<HashMap<String, BTreeMap<Decimal, VecDeque<LevelTrade>>>>,
 }
@@ -568,10 +575,17 @@ impl TradesByPriceLevel {
     pub fn with_config(config: TradeOverlayConfig) -> Self {
         Self {
             config,
+            clock: Clock::system(),
             trades: Mutex::new(HashMap::new()),
         }
     }
     
+    /// Age trades by `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+    
     /// Add a trade
     pub fn add_trade(&self, trade: &TradeData) {
         let mut trades = self.trades.lock().unwrap();
@@ -617,7 +631,7 @@ impl TradesByPriceLevel {
     /// Get trades at a specific price level
     pub fn get_trades_at_price(&self, symbol: &str, price: Decimal) -> Vec<LevelTrade> {
         let trades = self.trades.lock().unwrap();
-        let now = Utc::now();
+        let now = self.clock.now();
         
         trades
             .get(symbol)
@@ -638,7 +652,7 @@ impl TradesByPriceLevel {
     /// Get aggregated stats at a price level
     pub fn get_stats_at_price(&self, symbol: &str, price: Decimal) -> Option<LevelTradeStats> {
         let trades = self.trades.lock().unwrap();
-        let cutoff = Utc::now() - chrono::Duration::seconds(self.config.aggregation_window_secs as i64);
+        let cutoff = self.clock.now() - chrono::Duration::seconds(self.config.aggregation_window_secs as i64);
         
         trades.get(symbol).and_then(|t| t.get(&price)).map(|level_trades| {
             let recent: Vec<_> = level_trades
@@ -665,7 +679,7 @@ impl TradesByPriceLevel {
                 Decimal::ZERO
             };
             
-            let now = Utc::now();
+            let now = self.clock.now();
             LevelTradeStats {
                 price,
                 total_volume,
@@ -687,7 +701,7 @@ impl TradesByPriceLevel {
     /// Get all active price levels with trades for a symbol
     pub fn get_active_levels(&self, symbol: &str) -> Vec<Decimal> {
         let trades = self.trades.lock().unwrap();
-        let cutoff = Utc::now() - chrono::Duration::seconds(self.config.aggregation_window_secs as i64);
+        let cutoff = self.clock.now() - chrono::Duration::seconds(self.config.aggregation_window_secs as i64);
         
         trades
             .get(symbol)
@@ -764,6 +778,8 @@ impl Default for StaleDetectionConfig {
 /// Tracks market health and detects stale/halted conditions
 pub struct MarketHealthTracker {
     config: StaleDetectionConfig,
+    /// Measures time since each symbol's last update
+    clock: Clock,
     /// Last update time by symbol
     last_updates: Mutex<HashMap<String, DateTime<Utc>>>,
     /// Current status by symbol
@@ -778,15 +794,22 @@ impl MarketHealthTracker {
     pub fn with_config(config: StaleDetectionConfig) -> Self {
         Self {
             config,
+            clock: Clock::system(),
             last_updates: Mutex::new(HashMap::new()),
             status: Mutex::new(HashMap::new()),
         }
     }
     
+    /// Measure staleness by `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+    
     /// Record an update for a symbol
     pub fn record_update(&self, symbol: &str) {
         let mut last_updates = self.last_updates.lock().unwrap();
-        last_updates.insert(symbol.to_string(), Utc::now());
+        last_updates.insert(symbol.to_string(), self.clock.now());
         
         let mut status = self.status.lock().unwrap();
         status.insert(symbol.to_string(), MarketStatus::Active);
@@ -799,7 +822,7 @@ impl MarketHealthTracker {
         match last_updates.get(symbol) {
             None => MarketStatus::Unknown,
             Some(last_update) => {
-                let elapsed = (Utc::now() - *last_update).num_seconds() as u64;
+                let elapsed = (self.clock.now() - *last_update).num_seconds() as u64;
                 
                 if elapsed >= self.config.halt_threshold_secs {
                     MarketStatus::Halted
@@ -817,7 +840,7 @@ impl MarketHealthTracker {
         let last_updates = self.last_updates.lock().unwrap();
         last_updates
             .get(symbol)
-            .map(|t| (Utc::now() - *t).num_milliseconds().max(0) as u64)
+            .map(|t| (self.clock.now() - *t).num_milliseconds().max(0) as u64)
     }
     
     /// Update all statuses (call periodically)
@@ -826,7 +849,7 @@ impl MarketHealthTracker {
         let mut status = self.status.lock().unwrap();
         
         for (symbol, last_update) in last_updates.iter() {
-            let elapsed = (Utc::now() - *last_update).num_seconds() as u64;
+            let elapsed = (self.clock.now() - *last_update).num_seconds() as u64;
             
             let new_status = if elapsed >= self.config.halt_threshold_secs {
                 MarketStatus::Halted
@@ -889,8 +912,10 @@ mod tests {
     
     #[test]
     fn test_flow_tracker_detects_large_order() {
-        let mut config = OrderFlowConfig::default();
-        config.large_order_threshold = dec("5.0");
+        let config = OrderFlowConfig {
+            large_order_threshold: dec("5.0"),
+            ..Default::default()
+        };
         let tracker = OrderFlowTracker::with_config(config);
         
         let mut book = OrderBook::new("BTC/USD");
@@ -921,6 +946,7 @@ mod tests {
             side: TradeSide::Buy,
             timestamp: Utc::now(),
             trade_id: "trade1".to_string(),
+            backfilled: false,
         };
         
         tracker.add_trade(&trade);
@@ -949,9 +975,11 @@ mod tests {
     fn test_large_order_detection_with_small_delta() {
         // Test that large order detection fires even when delta < min_size_change
         // This was a bug: threshold crossing was gated by min_size_change
-        let mut config = OrderFlowConfig::default();
-        config.large_order_threshold = dec("10.0");
-        config.min_size_change = dec("2.0");  // Large min_size_change
+        let config = OrderFlowConfig {
+            large_order_threshold: dec("10.0"),
+            min_size_change: dec("2.0"),  // Large min_size_change
+            ..Default::default()
+        };
         let tracker = OrderFlowTracker::with_config(config);
         
         let mut book = OrderBook::new("BTC/USD");
@@ -979,9 +1007,11 @@ mod tests {
     #[test]
     fn test_large_order_detection_without_size_tracking() {
         // Test that large order detection works even when track_size_changes is false
-        let mut config = OrderFlowConfig::default();
-        config.large_order_threshold = dec("10.0");
-        config.track_size_changes = false;  // Disable size change tracking
+        let config = OrderFlowConfig {
+            large_order_threshold: dec("10.0"),
+            track_size_changes: false,  // Disable size change tracking
+            ..Default::default()
+        };
         let tracker = OrderFlowTracker::with_config(config);
         
         let mut book = OrderBook::new("BTC/USD");
@@ -1,15 +1,26 @@
 //! Message parsing and data conversion
 
 use crate::{
+    backfill::BackfillTracker,
+    candles::CandleBuilder,
+    clock::Clock,
+    connection::ConnectionManager,
     data::*,
     error::{ParseError, ProcessingError},
     events::EventDispatcher,
+    instruments::InstrumentRegistry,
+    latency::LatencyTracker,
+    orderbook::{ChecksumMismatch, OrderBookManager},
+    sequencing::SequenceManager,
+    subscription::SubscriptionManager,
+    symbols::SymbolNormalizer,
+    system_status::{ExchangeStatus, Heartbeat, SystemStatus, SystemStatusMonitor},
+    wire::{self, V1Frame, V2Frame},
 };
-use std::sync::Arc;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
 use chrono::{DateTime, Utc};
-use rust_decimal::Decimal;
 use serde_json::Value;
-use std::str::FromStr;
 
 /// Trait for parsing WebSocket messages
 pub trait DataParser: Send + Sync {
@@ -17,16 +28,167 @@ pub trait DataParser: Send + Sync {
     fn parse_orderbook(&self, data: &str) -> Result<OrderBookUpdate, ParseError>;
     fn parse_trade(&self, data: &str) -> Result<TradeData, ParseError>;
     fn parse_ohlc(&self, data: &str) -> Result<OHLCData, ParseError>;
+    
+    /// Parse a v1 `spread` frame
+    fn parse_spread(&self, _data: &str) -> Result<SpreadData, ParseError> {
+        Err(ParseError::MalformedMessage("spread messages are not supported by this parser".to_string()))
+    }
+    
+    /// Parse a Kraken WebSocket v1 data frame that was routed to `channel_name`
+    ///
+    /// The default implementation defers to the string parsers above.
+    fn parse_v1_frame(&self, frame: &[Value], channel_name: &str) -> Result<V1Message, ParseError> {
+        let data = Value::Array(frame.to_vec()).to_string();
+        match v1_channel_kind(channel_name) {
+            "ticker" => self.parse_ticker(&data).map(V1Message::Ticker),
+            "book" => self.parse_orderbook(&data).map(|update| V1Message::Book {
+                snapshot: is_v1_book_snapshot(frame),
+                update,
+            }),
+            "trade" => self.parse_trade(&data).map(|trade| V1Message::Trade(vec![trade])),
+            "ohlc" => self.parse_ohlc(&data).map(V1Message::Ohlc),
+            "spread" => self.parse_spread(&data).map(V1Message::Spread),
+            other => Err(ParseError::MalformedMessage(format!("Unsupported v1 channel: {}", other))),
+        }
+    }
+    
+    /// Parse a v1 data frame split by [`V1Frame::parse`]
+    ///
+    /// The default implementation builds the JSON values and defers to
+    /// [`parse_v1_frame`](DataParser::parse_v1_frame).
+    fn parse_v1_raw(&self, frame: &V1Frame<'_>, channel_name: &str) -> Result<V1Message, ParseError> {
+        self.parse_v1_frame(&frame.to_values()?, channel_name)
+    }
+    
+    /// Parse a Kraken WebSocket v2 frame
+    fn parse_v2_message(&self, _data: &str) -> Result<V2Message, ParseError> {
+        Err(ParseError::MalformedMessage("v2 messages are not supported by this parser".to_string()))
+    }
+    
+    /// Parse an already decoded Kraken WebSocket v2 frame
+    fn parse_v2_value(&self, json: &Value) -> Result<V2Message, ParseError> {
+        self.parse_v2_message(&json.to_string())
+    }
+    
+    /// Parse a v2 market data frame read by [`V2Frame::parse`]
+    ///
+    /// The default implementation decodes the whole frame and defers to
+    /// [`parse_v2_value`](DataParser::parse_v2_value).
+    fn parse_v2_raw(&self, frame: &V2Frame<'_>) -> Result<V2Parsed, ParseError> {
+        parse_v2_tree(self, frame)
+    }
+}
+
+/// Decoded Kraken WebSocket v1 data frame
+#[derive(Debug, Clone, PartialEq)]
+pub enum V1Message {
+    Ticker(TickerData),
+    /// Book data; `snapshot` is true for `as`/`bs` frames that replace the whole book
+    Book {
+        snapshot: bool,
+        update: OrderBookUpdate,
+    },
+    Trade(Vec<TradeData>),
+    Ohlc(OHLCData),
+    Spread(SpreadData),
+}
+
+/// Channel type of a v1 channel name, e.g. "book" for "book-10"
+pub fn v1_channel_kind(channel_name: &str) -> &str {
+    channel_name.split('-').next().unwrap_or(channel_name)
+}
+
+/// Whether a v1 book frame carries a snapshot (`as`/`bs`) rather than an update
+fn is_v1_book_snapshot(frame: &[Value]) -> bool {
+    frame.iter()
+        .filter_map(|v| v.as_object())
+        .any(|payload| payload.contains_key("as") || payload.contains_key("bs"))
+}
+
+/// Exchange timestamp of the `index`th entry of a v2 frame's `data`, if any
+fn v2_exchange_time(json: &Value, index: usize) -> Option<DateTime<Utc>> {
+    let timestamp = json.get("data")?.get(index)?.get("timestamp")?.as_str()?;
+    DateTime::parse_from_rfc3339(timestamp).ok().map(|t| t.with_timezone(&Utc))
+}
+
+/// Decode a v2 frame through a `Value` tree
+fn parse_v2_tree<P: DataParser + ?Sized>(parser: &P, frame: &V2Frame<'_>) -> Result<V2Parsed, ParseError> {
+    let json: Value = serde_json::from_str(frame.text)
+        .map_err(|e| ParseError::InvalidJson(e.to_string()))?;
+    Ok(V2Parsed {
+        message: parser.parse_v2_value(&json)?,
+        exchange_times: v2_exchange_times(&json),
+    })
+}
+
+/// Exchange timestamps of every entry in a v2 frame's `data`
+fn v2_exchange_times(json: &Value) -> Vec<Option<DateTime<Utc>>> {
+    let entries = json.get("data").and_then(|d| d.as_array()).map_or(0, |d| d.len());
+    (0..entries).map(|index| v2_exchange_time(json, index)).collect()
+}
+
+/// Decode a `systemStatus` event (v1) or `status` update entry (v2)
+///
+/// The two versions name the same fields differently.
+fn system_status(status: &Value, status_field: &str, connection_id_field: &str) -> SystemStatus {
+    let raw_status = status.get(status_field).and_then(|v| v.as_str()).unwrap_or("unknown");
+    SystemStatus {
+        status: ExchangeStatus::from_kraken(raw_status),
+        raw_status: raw_status.to_string(),
+        version: status.get("version").and_then(|v| v.as_str()).map(|v| v.to_string()),
+        connection_id: status.get(connection_id_field).and_then(|v| v.as_u64()),
+    }
+}
+
+/// Decoded Kraken WebSocket v2 frame
+#[derive(Debug, Clone, PartialEq)]
+pub enum V2Message {
+    Ticker(Vec<TickerData>),
+    /// Book data; `snapshot` is true when the levels replace the whole book
+    Book {
+        snapshot: bool,
+        updates: Vec<OrderBookUpdate>,
+    },
+    Trade(Vec<TradeData>),
+    Ohlc(Vec<OHLCData>),
+    Instrument(Vec<InstrumentData>),
+    Heartbeat,
+    Status(SystemStatus),
+    /// Response to a `method` request (subscribe, unsubscribe, ping, ...)
+    MethodResponse {
+        method: String,
+        req_id: Option<u64>,
+        success: bool,
+        error: Option<String>,
+    },
+}
+
+/// Decoded v2 frame with the exchange timestamp of each `data` entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct V2Parsed {
+    pub message: V2Message,
+    /// `timestamp` of each entry, where Kraken sent one
+    pub exchange_times: Vec<Option<DateTime<Utc>>>,
 }
 
 /// Kraken-specific data parser
+///
+/// Market data is decoded on the zero-copy path in [`wire`]; frames it
+/// can't handle are parsed through a `serde_json::Value` tree instead.
 pub struct KrakenDataParser {
-    // Internal parsing state and configuration
+    /// Stamps frames that carry no exchange time
+    clock: Clock,
 }
 
 impl KrakenDataParser {
     pub fn new() -> Self {
-        Self {}
+        Self { clock: Clock::system() }
+    }
+    
+    /// Stamp frames by `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
     }
     
     /// Parse JSON value safely
@@ -42,42 +204,11 @@ impl KrakenDataParser {
             .ok_or_else(|| ParseError::MissingField(format!("Missing or invalid field: {}", field)))
     }
     
-    /// Extract decimal field from JSON object
-    fn extract_decimal(&self, obj: &Value, field: &str) -> Result<Decimal, ParseError> {
-        let str_val = obj.get(field)
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| ParseError::MissingField(format!("Missing field: {}", field)))?;
-        
-        Decimal::from_str(str_val)
-            .map_err(|e| ParseError::InvalidDataType(format!("Invalid decimal for {}: {}", field, e)))
-    }
-    
-    /// Extract timestamp from JSON object
-    fn extract_timestamp(&self, obj: &Value, field: &str) -> Result<DateTime<Utc>, ParseError> {
-        let timestamp_str = self.extract_string(obj, field)?;
-        
-        // Try parsing as Unix timestamp first
-        if let Ok(timestamp_f64) = timestamp_str.parse::<f64>() {
-            let timestamp_secs = timestamp_f64 as i64;
-            let timestamp_nanos = ((timestamp_f64 - timestamp_secs as f64) * 1_000_000_000.0) as u32;
-            
-            DateTime::from_timestamp(timestamp_secs, timestamp_nanos)
-                .ok_or_else(|| ParseError::InvalidDataType(format!("Invalid timestamp: {}", timestamp_str)))
-        } else {
-            // Try parsing as ISO 8601 string
-            DateTime::parse_from_rfc3339(&timestamp_str)
-                .map(|dt| dt.with_timezone(&Utc))
-                .map_err(|e| ParseError::InvalidDataType(format!("Invalid timestamp format: {}", e)))
-        }
-    }
-    
-    /// Parse trade side from string
-    fn parse_trade_side(&self, side_str: &str) -> Result<TradeSide, ParseError> {
-        match side_str.to_lowercase().as_str() {
-            "b" | "buy" => Ok(TradeSide::Buy),
-            "s" | "sell" => Ok(TradeSide::Sell),
-            _ => Err(ParseError::InvalidDataType(format!("Invalid trade side: {}", side_str))),
-        }
+    /// Extract the `data` array of a v2 frame
+    fn v2_data<'a>(&self, json: &'a Value) -> Result<&'a Vec<Value>, ParseError> {
+        json.get("data")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| ParseError::MissingField("data".to_string()))
     }
     
     /// Handle malformed data gracefully
@@ -114,6 +245,55 @@ impl KrakenDataParser {
         }
     }
     
+    /// Decode a v1 `channel` frame with its [`wire`] decoder
+    fn decode_v1<T>(
+        &self,
+        data: &str,
+        channel: &str,
+        decode: impl FnOnce(&V1Frame<'_>) -> Result<T, ParseError>,
+    ) -> Result<T, ParseError> {
+        let frame = V1Frame::parse(data).map_err(|e| self.handle_malformed_data(e, data))?;
+        if v1_channel_kind(frame.channel_name) != channel {
+            let error = ParseError::MalformedMessage(format!("Invalid {} message format", channel));
+            return Err(self.handle_malformed_data(error, data));
+        }
+        decode(&frame).map_err(|e| self.handle_malformed_data(e, data))
+    }
+    
+    /// Decode a v2 data frame with its [`wire`] decoder
+    ///
+    /// Returns `None` for channels that have none (status, heartbeat, ...).
+    fn decode_v2(&self, frame: &V2Frame<'_>) -> Option<Result<V2Parsed, ParseError>> {
+        fn split<T>(entries: wire::Entries<T>) -> (Vec<T>, Vec<Option<DateTime<Utc>>>) {
+            entries.into_iter().unzip()
+        }
+        
+        if frame.event.is_some() || frame.method.is_some() {
+            return None;
+        }
+        let decoded = match frame.channel? {
+            "ticker" => wire::v2_tickers(frame, self.clock.now()).map(|entries| {
+                let (tickers, times) = split(entries);
+                (V2Message::Ticker(tickers), times)
+            }),
+            "book" => wire::v2_books(frame, self.clock.now()).map(|entries| {
+                let (updates, times) = split(entries);
+                (V2Message::Book { snapshot: frame.kind == Some("snapshot"), updates }, times)
+            }),
+            "trade" => wire::v2_trades(frame).map(|entries| {
+                let (trades, times) = split(entries);
+                (V2Message::Trade(trades), times)
+            }),
+            "ohlc" => wire::v2_ohlc(frame).map(|entries| {
+                let (candles, times) = split(entries);
+                (V2Message::Ohlc(candles), times)
+            }),
+            "instrument" => wire::v2_instruments(frame).map(|instruments| (V2Message::Instrument(instruments), Vec::new())),
+            _ => return None,
+        };
+        Some(decoded.map(|(message, exchange_times)| V2Parsed { message, exchange_times }))
+    }
+    
     /// Clean up common JSON formatting issues
     fn clean_json_data(&self, data: &str) -> String {
         data.trim()
@@ -125,245 +305,90 @@ impl KrakenDataParser {
 
 impl DataParser for KrakenDataParser {
     fn parse_ticker(&self, data: &str) -> Result<TickerData, ParseError> {
-        let json = self.parse_json(data).map_err(|e| self.handle_malformed_data(e, data))?;
-        
-        // Kraken ticker format: [channelID, data, channelName, pair]
-        if let Some(array) = json.as_array() {
-            if array.len() >= 4 {
-                let ticker_data = &array[1];
-                let symbol = array[3].as_str()
-                    .ok_or_else(|| ParseError::MissingField("symbol".to_string()))?
-                    .to_string();
-                
-                if let Some(ticker_obj) = ticker_data.as_object() {
-                    // Kraken ticker format has arrays for bid/ask/close/volume
-                    // b = bid [price, wholeLotVolume, lotVolume]
-                    // a = ask [price, wholeLotVolume, lotVolume]  
-                    // c = close [price, lotVolume]
-                    // v = volume [today, last24h]
-                    
-                    let bid = if let Some(bid_array) = ticker_obj.get("b").and_then(|v| v.as_array()) {
-                        if let Some(price_str) = bid_array.get(0).and_then(|v| v.as_str()) {
-                            Decimal::from_str(price_str).unwrap_or_default()
-                        } else { Decimal::ZERO }
-                    } else { Decimal::ZERO };
-                    
-                    let ask = if let Some(ask_array) = ticker_obj.get("a").and_then(|v| v.as_array()) {
-                        if let Some(price_str) = ask_array.get(0).and_then(|v| v.as_str()) {
-                            Decimal::from_str(price_str).unwrap_or_default()
-                        } else { Decimal::ZERO }
-                    } else { Decimal::ZERO };
-                    
-                    let last_price = if let Some(close_array) = ticker_obj.get("c").and_then(|v| v.as_array()) {
-                        if let Some(price_str) = close_array.get(0).and_then(|v| v.as_str()) {
-                            Decimal::from_str(price_str).unwrap_or_default()
-                        } else { Decimal::ZERO }
-                    } else { Decimal::ZERO };
-                    
-                    let volume = if let Some(vol_array) = ticker_obj.get("v").and_then(|v| v.as_array()) {
-                        if let Some(vol_str) = vol_array.get(0).and_then(|v| v.as_str()) {
-                            Decimal::from_str(vol_str).unwrap_or_default()
-                        } else { Decimal::ZERO }
-                    } else { Decimal::ZERO };
-                    
-                    // Convert Kraken symbol format to display format
-                    let display_symbol = match symbol.as_str() {
-                        "XBT/USD" => "BTC/USD".to_string(),
-                        "ETH/USD" => "ETH/USD".to_string(),
-                        "ADA/USD" => "ADA/USD".to_string(),
-                        _ => symbol.clone(),
-                    };
-                    
-                    return Ok(TickerData {
-                        symbol: display_symbol,
-                        bid,
-                        ask,
-                        last_price,
-                        volume,
-                        timestamp: Utc::now(),
-                    });
-                }
-            }
-        }
-        
-        Err(self.handle_malformed_data(
-            ParseError::MalformedMessage("Invalid ticker message format".to_string()),
-            data
-        ))
+        self.decode_v1(data, "ticker", |frame| wire::v1_ticker(frame, self.clock.now()))
     }
     
     fn parse_orderbook(&self, data: &str) -> Result<OrderBookUpdate, ParseError> {
-        let json = self.parse_json(data).map_err(|e| self.handle_malformed_data(e, data))?;
-        
-        // Kraken orderbook format: [channelID, data, channelName, pair]
-        if let Some(array) = json.as_array() {
-            if array.len() >= 4 {
-                let orderbook_data = &array[1];
-                let symbol = array[3].as_str()
-                    .ok_or_else(|| ParseError::MissingField("symbol".to_string()))?
-                    .to_string();
-                
-                if let Some(ob_obj) = orderbook_data.as_object() {
-                    let mut bids = Vec::new();
-                    let mut asks = Vec::new();
-                    
-                    // Parse bids
-                    if let Some(bids_array) = ob_obj.get("b").and_then(|v| v.as_array()) {
-                        for bid in bids_array {
-                            if let Some(bid_array) = bid.as_array() {
-                                if bid_array.len() >= 3 {
-                                    let price = Decimal::from_str(bid_array[0].as_str().unwrap_or("0"))
-                                        .unwrap_or_default();
-                                    let volume = Decimal::from_str(bid_array[1].as_str().unwrap_or("0"))
-                                        .unwrap_or_default();
-                                    let timestamp = DateTime::from_timestamp(
-                                        bid_array[2].as_str().unwrap_or("0").parse::<f64>().unwrap_or(0.0) as i64,
-                                        0
-                                    ).unwrap_or_else(Utc::now);
-                                    
-                                    bids.push(PriceLevel { price, volume, timestamp });
-                                }
-                            }
-                        }
-                    }
-                    
-                    // Parse asks
-                    if let Some(asks_array) = ob_obj.get("a").and_then(|v| v.as_array()) {
-                        for ask in asks_array {
-                            if let Some(ask_array) = ask.as_array() {
-                                if ask_array.len() >= 3 {
-                                    let price = Decimal::from_str(ask_array[0].as_str().unwrap_or("0"))
-                                        .unwrap_or_default();
-                                    let volume = Decimal::from_str(ask_array[1].as_str().unwrap_or("0"))
-                                        .unwrap_or_default();
-                                    let timestamp = DateTime::from_timestamp(
-                                        ask_array[2].as_str().unwrap_or("0").parse::<f64>().unwrap_or(0.0) as i64,
-                                        0
-                                    ).unwrap_or_else(Utc::now);
-                                    
-                                    asks.push(PriceLevel { price, volume, timestamp });
-                                }
-                            }
-                        }
-                    }
-                    
-                    return Ok(OrderBookUpdate {
-                        symbol,
-                        bids,
-                        asks,
-                        timestamp: Utc::now(),
-                        checksum: None, // Kraken provides checksums in some cases
-                    });
-                }
-            }
-        }
-        
-        Err(self.handle_malformed_data(
-            ParseError::MalformedMessage("Invalid orderbook message format".to_string()),
-            data
-        ))
+        self.decode_v1(data, "book", |frame| wire::v1_book(frame, self.clock.now()))
+            .map(|(_, update)| update)
     }
     
     fn parse_trade(&self, data: &str) -> Result<TradeData, ParseError> {
-        let json = self.parse_json(data).map_err(|e| self.handle_malformed_data(e, data))?;
-        
-        // Kraken trade format: [channelID, [[price, volume, time, side, orderType, misc]], channelName, pair]
-        if let Some(array) = json.as_array() {
-            if array.len() >= 4 {
-                let trades_data = &array[1];
-                let symbol = array[3].as_str()
-                    .ok_or_else(|| ParseError::MissingField("symbol".to_string()))?
-                    .to_string();
-                
-                if let Some(trades_array) = trades_data.as_array() {
-                    if let Some(trade_array) = trades_array.first().and_then(|t| t.as_array()) {
-                        if trade_array.len() >= 4 {
-                            let price = Decimal::from_str(trade_array[0].as_str().unwrap_or("0"))
-                                .unwrap_or_default();
-                            let volume = Decimal::from_str(trade_array[1].as_str().unwrap_or("0"))
-                                .unwrap_or_default();
-                            let timestamp = DateTime::from_timestamp(
-                                trade_array[2].as_str().unwrap_or("0").parse::<f64>().unwrap_or(0.0) as i64,
-                                0
-                            ).unwrap_or_else(Utc::now);
-                            let side = self.parse_trade_side(trade_array[3].as_str().unwrap_or("b"))?;
-                            let trade_id = uuid::Uuid::new_v4().to_string();
-                            
-                            // Convert Kraken symbol format to display format
-                            let display_symbol = match symbol.as_str() {
-                                "XBT/USD" => "BTC/USD".to_string(),
-                                "ETH/USD" => "ETH/USD".to_string(),
-                                "ADA/USD" => "ADA/USD".to_string(),
-                                _ => symbol.clone(),
-                            };
-                            
-                            return Ok(TradeData {
-                                symbol: display_symbol,
-                                price,
-                                volume,
-                                side,
-                                timestamp,
-                                trade_id,
-                            });
-                        }
-                    }
-                }
-            }
-        }
-        
-        Err(self.handle_malformed_data(
-            ParseError::MalformedMessage("Invalid trade message format".to_string()),
-            data
-        ))
+        self.decode_v1(data, "trade", |frame| wire::v1_trades(frame, self.clock.now()))
+            .map(|mut trades| trades.swap_remove(0))
     }
     
     fn parse_ohlc(&self, data: &str) -> Result<OHLCData, ParseError> {
+        self.decode_v1(data, "ohlc", |frame| wire::v1_ohlc(frame, self.clock.now()))
+    }
+    
+    fn parse_spread(&self, data: &str) -> Result<SpreadData, ParseError> {
+        self.decode_v1(data, "spread", |frame| wire::v1_spread(frame, self.clock.now()))
+    }
+    
+    fn parse_v1_frame(&self, frame: &[Value], channel_name: &str) -> Result<V1Message, ParseError> {
+        let text = Value::Array(frame.to_vec()).to_string();
+        self.parse_v1_raw(&V1Frame::parse(&text)?, channel_name)
+    }
+    
+    fn parse_v1_raw(&self, frame: &V1Frame<'_>, channel_name: &str) -> Result<V1Message, ParseError> {
+        let received = self.clock.now();
+        match v1_channel_kind(channel_name) {
+            "ticker" => wire::v1_ticker(frame, received).map(V1Message::Ticker),
+            "book" => wire::v1_book(frame, received).map(|(snapshot, update)| V1Message::Book { snapshot, update }),
+            "trade" => wire::v1_trades(frame, received).map(V1Message::Trade),
+            "ohlc" => wire::v1_ohlc(frame, received).map(V1Message::Ohlc),
+            "spread" => wire::v1_spread(frame, received).map(V1Message::Spread),
+            other => Err(ParseError::MalformedMessage(format!("Unsupported v1 channel: {}", other))),
+        }
+    }
+    
+    fn parse_v2_message(&self, data: &str) -> Result<V2Message, ParseError> {
+        if let Some(decoded) = V2Frame::parse(data).ok().and_then(|frame| self.decode_v2(&frame)) {
+            return decoded
+                .map(|parsed| parsed.message)
+                .map_err(|e| self.handle_malformed_data(e, data));
+        }
         let json = self.parse_json(data).map_err(|e| self.handle_malformed_data(e, data))?;
+        self.parse_v2_value(&json)
+    }
+    
+    fn parse_v2_value(&self, json: &Value) -> Result<V2Message, ParseError> {
+        if !json.is_object() {
+            return Err(ParseError::MalformedMessage("v2 messages must be JSON objects".to_string()));
+        }
         
-        // Kraken OHLC format: [channelID, data, channelName, pair]
-        if let Some(array) = json.as_array() {
-            if array.len() >= 4 {
-                let ohlc_data = &array[1];
-                let symbol = array[3].as_str()
-                    .ok_or_else(|| ParseError::MissingField("symbol".to_string()))?
-                    .to_string();
-                
-                if let Some(ohlc_array) = ohlc_data.as_array() {
-                    if ohlc_array.len() >= 8 {
-                        let timestamp = DateTime::from_timestamp(
-                            ohlc_array[0].as_str().unwrap_or("0").parse::<f64>().unwrap_or(0.0) as i64,
-                            0
-                        ).unwrap_or_else(Utc::now);
-                        let open = Decimal::from_str(ohlc_array[1].as_str().unwrap_or("0"))
-                            .unwrap_or_default();
-                        let high = Decimal::from_str(ohlc_array[2].as_str().unwrap_or("0"))
-                            .unwrap_or_default();
-                        let low = Decimal::from_str(ohlc_array[3].as_str().unwrap_or("0"))
-                            .unwrap_or_default();
-                        let close = Decimal::from_str(ohlc_array[4].as_str().unwrap_or("0"))
-                            .unwrap_or_default();
-                        let volume = Decimal::from_str(ohlc_array[6].as_str().unwrap_or("0"))
-                            .unwrap_or_default();
-                        
-                        return Ok(OHLCData {
-                            symbol,
-                            open,
-                            high,
-                            low,
-                            close,
-                            volume,
-                            timestamp,
-                            interval: "1m".to_string(), // Default interval
-                        });
-                    }
-                }
-            }
+        // Responses to subscribe/unsubscribe/ping requests
+        if let Some(method) = json.get("method").and_then(|v| v.as_str()) {
+            return Ok(V2Message::MethodResponse {
+                method: method.to_string(),
+                req_id: json.get("req_id").and_then(|v| v.as_u64()),
+                success: json.get("success").and_then(|v| v.as_bool()).unwrap_or(false),
+                error: json.get("error").and_then(|v| v.as_str()).map(|e| e.to_string()),
+            });
+        }
+        
+        // Data channels are decoded by `wire`, same as frames off the socket
+        let text = json.to_string();
+        if let Some(decoded) = V2Frame::parse(&text).ok().and_then(|frame| self.decode_v2(&frame)) {
+            return decoded.map(|parsed| parsed.message);
         }
         
-        Err(self.handle_malformed_data(
-            ParseError::MalformedMessage("Invalid OHLC message format".to_string()),
-            data
-        ))
+        let channel = self.extract_string(json, "channel")?;
+        match channel.as_str() {
+            "heartbeat" => Ok(V2Message::Heartbeat),
+            "status" => {
+                let status = self.v2_data(json)?
+                    .first()
+                    .ok_or_else(|| ParseError::MissingField("data[0]".to_string()))?;
+                Ok(V2Message::Status(system_status(status, "system", "connection_id")))
+            }
+            other => Err(ParseError::MalformedMessage(format!("Unsupported v2 channel: {}", other))),
+        }
+    }
+    
+    fn parse_v2_raw(&self, frame: &V2Frame<'_>) -> Result<V2Parsed, ParseError> {
+        self.decode_v2(frame).unwrap_or_else(|| parse_v2_tree(self, frame))
     }
 }
 
@@ -378,11 +403,131 @@ impl Default for KrakenDataParser {
 pub struct MessageHandler {
     parser: Arc<dyn DataParser>,
     dispatcher: Arc<EventDispatcher>,
+    orderbook_manager: Option<OrderBookManager>,
+    subscription_manager: Option<SubscriptionManager>,
+    sequence_manager: Option<Arc<SequenceManager>>,
+    latency_tracker: Option<Arc<LatencyTracker>>,
+    status_monitor: Option<SystemStatusMonitor>,
+    instruments: Option<InstrumentRegistry>,
+    symbols: Option<SymbolNormalizer>,
+    backfill: Option<BackfillTracker>,
+    connection_manager: Option<ConnectionManager>,
+    candles: Option<Arc<CandleBuilder>>,
+    /// v2 books whose checksums went unverified for lack of pair precision
+    unverified_books: Arc<Mutex<HashSet<String>>>,
+    /// Stamps received frames, dispatch latency and checksum mismatch events
+    clock: Clock,
 }
 
 impl MessageHandler {
     pub fn new(parser: Arc<dyn DataParser>, dispatcher: Arc<EventDispatcher>) -> Self {
-        Self { parser, dispatcher }
+        Self {
+            parser,
+            dispatcher,
+            orderbook_manager: None,
+            subscription_manager: None,
+            sequence_manager: None,
+            latency_tracker: None,
+            status_monitor: None,
+            instruments: None,
+            symbols: None,
+            backfill: None,
+            connection_manager: None,
+            candles: None,
+            unverified_books: Arc::new(Mutex::new(HashSet::new())),
+            clock: Clock::system(),
+        }
+    }
+    
+    /// Maintain local order books from book snapshots and updates
+    pub fn with_orderbook_manager(mut self, orderbook_manager: OrderBookManager) -> Self {
+        self.orderbook_manager = Some(orderbook_manager);
+        self
+    }
+    
+    /// Track subscription status frames and route v1 data by `channelID`
+    ///
+    /// Rejected (un)subscriptions are dispatched as errors.
+    pub fn with_subscription_manager(mut self, subscription_manager: SubscriptionManager) -> Self {
+        self.subscription_manager = Some(subscription_manager);
+        self
+    }
+    
+    /// Validate sequence numbers on v2 book frames that carry one
+    ///
+    /// Each symbol is tracked as channel `book:<symbol>`. When the manager
+    /// triggers a resync, the local book is queued for a fresh snapshot.
+    /// Gap and resync events are reported through the manager's callbacks.
+    pub fn with_sequence_manager(mut self, sequence_manager: Arc<SequenceManager>) -> Self {
+        self.sequence_manager = Some(sequence_manager);
+        self
+    }
+    
+    /// Record latency for every dispatched market data message
+    ///
+    /// Receive -> dispatch is always measured; exchange -> receive only for
+    /// messages carrying an exchange timestamp (trades, spreads, v1 book
+    /// levels and v2 `timestamp` fields). Samples are keyed by channel
+    /// (`ticker`, `spread`, `book`, `trade`, `ohlc`, `instrument`) and
+    /// symbol.
+    pub fn with_latency_tracker(mut self, latency_tracker: Arc<LatencyTracker>) -> Self {
+        self.latency_tracker = Some(latency_tracker);
+        self
+    }
+    
+    /// Keep the latest exchange status from `systemStatus` / `status` messages
+    pub fn with_status_monitor(mut self, status_monitor: SystemStatusMonitor) -> Self {
+        self.status_monitor = Some(status_monitor);
+        self
+    }
+    
+    /// Record pair rules from v2 `instrument` messages
+    pub fn with_instrument_registry(mut self, instruments: InstrumentRegistry) -> Self {
+        self.instruments = Some(instruments);
+        self
+    }
+    
+    /// Rewrite pair names on dispatched market data events
+    ///
+    /// Local books, sequence tracking and latency samples stay keyed by the
+    /// name Kraken sent.
+    pub fn with_symbol_normalizer(mut self, symbols: SymbolNormalizer) -> Self {
+        self.symbols = Some(symbols);
+        self
+    }
+    
+    /// Track the last trade and candle per pair so gaps can be backfilled
+    ///
+    /// Live trades already dispatched, e.g. repeated in a v2 trade snapshot
+    /// after a reconnect, are dropped.
+    pub fn with_backfill_tracker(mut self, tracker: BackfillTracker) -> Self {
+        self.backfill = Some(tracker);
+        self
+    }
+    
+    /// Record answers to application-level pings on the connection
+    pub fn with_connection_manager(mut self, connection_manager: ConnectionManager) -> Self {
+        self.connection_manager = Some(connection_manager);
+        self
+    }
+    
+    /// Build candles from dispatched trades, reconciled against `ohlc`
+    ///
+    /// Candle events are dispatched after the trade or candle that caused
+    /// them. Time bars close by the handler's clock as frames arrive.
+    pub fn with_candle_builder(mut self, candles: Arc<CandleBuilder>) -> Self {
+        self.candles = Some(candles);
+        self
+    }
+    
+    /// Stamp receive and dispatch times, and events the handler raises
+    /// itself, by `clock`
+    ///
+    /// Market data timestamps come from the parser, see
+    /// [`KrakenDataParser::with_clock`].
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
     }
     
     /// Handle incoming WebSocket message
@@ -396,7 +541,9 @@ impl MessageHandler {
         tracing::debug!("Processing message: {}", message);
         
         // Try to determine message type and route accordingly
-        if let Err(e) = self.route_message(message).await {
+        let routed = self.route_message(message).await;
+        self.close_candles();
+        if let Err(e) = routed {
             tracing::warn!("Failed to route message: {} - Message: {}", e, message);
             return Err(e);
         }
@@ -404,91 +551,429 @@ impl MessageHandler {
         Ok(())
     }
     
-    /// Route message to appropriate parser based on content
+    /// Route message to exactly one handler
+    ///
+    /// Market data frames are decoded once, on the zero-copy path; control
+    /// events, method responses and private frames are parsed into a
+    /// `Value` tree. v1 data frames are routed by the `channelID` recorded
+    /// from `subscriptionStatus`, falling back to the frame's own channel
+    /// name when the ID is unknown.
     async fn route_message(&self, message: &str) -> Result<(), ProcessingError> {
-        // Check for subscription status messages first
-        if message.contains("subscriptionStatus") {
-            tracing::debug!("Received subscription status message");
-            return Ok(());
+        let received = self.clock.now();
+        match message.trim_start().as_bytes().first() {
+            Some(b'[') => {
+                if let Ok(frame) = V1Frame::parse(message) {
+                    self.route_v1_data(&frame, received);
+                    return Ok(());
+                }
+            }
+            Some(b'{') => {
+                if let Ok(frame) = V2Frame::parse(message) {
+                    if frame.is_market_data() {
+                        self.route_v2_data(&frame, received);
+                        return Ok(());
+                    }
+                }
+            }
+            _ => {}
         }
         
-        // Check for system messages
-        if message.contains("systemStatus") {
-            tracing::debug!("Received system status message");
-            return Ok(());
+        let json: Value = match serde_json::from_str(message) {
+            Ok(json) => json,
+            Err(e) => {
+                // Graceful degradation: skip the frame, keep the connection
+                self.handle_malformed_message(message, &ProcessingError::ProcessingFailed(e.to_string()));
+                return Ok(());
+            }
+        };
+        
+        match &json {
+            Value::Array(frame) => self.route_v1_frame(frame, received),
+            Value::Object(object) => match object.get("event").and_then(|v| v.as_str()) {
+                Some(event) => self.route_v1_event(event, &json, received),
+                // v2 frames are objects without the v1 `event` field
+                None => self.route_v2_message(&json, received),
+            },
+            _ => tracing::debug!("Ignoring unexpected message: {}", message),
         }
         
-        // Check for heartbeat messages
-        if message.contains("heartbeat") {
-            tracing::debug!("Received heartbeat message");
-            return Ok(());
+        Ok(())
+    }
+    
+    /// Handle a v1 control event (`subscriptionStatus`, `heartbeat`, ...)
+    fn route_v1_event(&self, event: &str, json: &Value, received: DateTime<Utc>) {
+        match event {
+            "subscriptionStatus" => {
+                tracing::debug!("Received subscription status message");
+                self.track_subscription_status(json);
+            }
+            "systemStatus" => {
+                tracing::debug!("Received system status message");
+                self.handle_system_status(system_status(json, "status", "connectionID"));
+            }
+            "heartbeat" => {
+                tracing::debug!("Received heartbeat message");
+                self.dispatcher.dispatch_heartbeat(Heartbeat { received_at: received });
+            }
+            "pong" => {
+                tracing::debug!("Received pong message");
+                self.record_pong();
+            }
+            other => tracing::debug!("Received {} event", other),
+        }
+    }
+    
+    fn record_pong(&self) {
+        if let Some(connection_manager) = &self.connection_manager {
+            connection_manager.update_pong();
+        }
+    }
+    
+    /// Record and dispatch an exchange status announcement
+    fn handle_system_status(&self, status: SystemStatus) {
+        if let Some(monitor) = &self.status_monitor {
+            monitor.update(status.clone());
+        }
+        self.dispatcher.dispatch_system_status(status);
+    }
+    
+    /// Keep the subscription manager in sync with confirmations
+    fn track_subscription_status(&self, json: &Value) {
+        if let Some(manager) = &self.subscription_manager {
+            if let Err(e) = manager.handle_status(json) {
+                tracing::warn!("Subscription request rejected: {}", e);
+                self.dispatcher.dispatch_error(e.into());
+            }
         }
+    }
+    
+    /// Route a v1 data frame split on the zero-copy path
+    fn route_v1_data(&self, frame: &V1Frame<'_>, received: DateTime<Utc>) {
+        let route = frame.channel_id
+            .and_then(|channel_id| self.subscription_manager.as_ref()?.route(channel_id));
+        let channel_name = route.as_ref().map_or(frame.channel_name, |route| route.channel_name.as_str());
         
-        // Try to parse as market data
-        if let Err(e) = self.try_parse_market_data(message).await {
+        match self.parser.parse_v1_raw(frame, channel_name) {
+            Ok(message) => self.dispatch_v1(message, received),
             // If parsing fails, log but don't fail completely (graceful degradation)
-            tracing::debug!("Could not parse as market data: {} - Message: {}", e, message);
+            Err(e) => tracing::debug!("Could not parse {} frame: {}", channel_name, e),
         }
+    }
+    
+    /// Route a v1 data frame: [channelID, data, (data,) channelName, pair]
+    fn route_v1_frame(&self, frame: &[Value], received: DateTime<Utc>) {
+        let route = frame.first()
+            .and_then(|v| v.as_u64())
+            .and_then(|channel_id| self.subscription_manager.as_ref()?.route(channel_id));
+        let channel_name = match &route {
+            Some(route) => route.channel_name.as_str(),
+            None => match frame.len().checked_sub(2).and_then(|i| frame[i].as_str()) {
+                Some(name) => name,
+                None => {
+                    tracing::debug!("Ignoring v1 frame without a channel name");
+                    return;
+                }
+            },
+        };
         
-        Ok(())
+        match self.parser.parse_v1_frame(frame, channel_name) {
+            Ok(message) => self.dispatch_v1(message, received),
+            Err(e) => tracing::debug!("Could not parse {} frame: {}", channel_name, e),
+        }
     }
     
-    /// Try to parse message as different market data types
-    async fn try_parse_market_data(&self, message: &str) -> Result<(), ProcessingError> {
-        // Try ticker data
-        if let Ok(ticker_data) = self.parser.parse_ticker(message) {
-            tracing::debug!("Parsed ticker data: {}", ticker_data.symbol);
-            self.dispatcher.dispatch_ticker(ticker_data);
-            return Ok(());
+    /// Dispatch a decoded v1 data frame
+    fn dispatch_v1(&self, message: V1Message, received: DateTime<Utc>) {
+        match message {
+            V1Message::Ticker(mut ticker) => {
+                tracing::debug!("Parsed ticker data: {}", ticker.symbol);
+                let symbol = ticker.symbol.clone();
+                self.normalize_symbol(&mut ticker.symbol);
+                self.dispatcher.dispatch_ticker(ticker);
+                self.record_latency("ticker", &symbol, None, received);
+            }
+            V1Message::Book { snapshot, mut update } => {
+                tracing::debug!("Parsed orderbook data: {}", update.symbol);
+                // v1 levels carry the time Kraken last changed them
+                let exchange_time = update.bids.iter().chain(&update.asks).map(|level| level.timestamp).max();
+                let symbol = update.symbol.clone();
+                self.apply_book_update(update.clone(), snapshot);
+                self.normalize_symbol(&mut update.symbol);
+                self.dispatcher.dispatch_orderbook(update);
+                self.record_latency("book", &symbol, exchange_time, received);
+            }
+            V1Message::Trade(trades) => {
+                for mut trade in trades {
+                    if !self.observe_trade(&trade) {
+                        continue;
+                    }
+                    let (symbol, exchange_time) = (trade.symbol.clone(), trade.timestamp);
+                    self.normalize_symbol(&mut trade.symbol);
+                    self.dispatch_trade(trade);
+                    self.record_latency("trade", &symbol, Some(exchange_time), received);
+                }
+            }
+            V1Message::Ohlc(mut candle) => {
+                tracing::debug!("Parsed OHLC data: {}", candle.symbol);
+                self.observe_candle(&candle);
+                let symbol = candle.symbol.clone();
+                self.normalize_symbol(&mut candle.symbol);
+                self.dispatch_ohlc(candle);
+                self.record_latency("ohlc", &symbol, None, received);
+            }
+            V1Message::Spread(mut spread) => {
+                let (symbol, exchange_time) = (spread.symbol.clone(), spread.timestamp);
+                self.normalize_symbol(&mut spread.symbol);
+                self.dispatcher.dispatch_spread(spread);
+                self.record_latency("spread", &symbol, Some(exchange_time), received);
+            }
         }
-        
-        // Try order book data
-        if let Ok(orderbook_data) = self.parser.parse_orderbook(message) {
-            tracing::debug!("Parsed orderbook data: {}", orderbook_data.symbol);
-            self.dispatcher.dispatch_orderbook(orderbook_data);
-            return Ok(());
+    }
+    
+    /// Route a v2 market data frame read on the zero-copy path
+    fn route_v2_data(&self, frame: &V2Frame<'_>, received: DateTime<Utc>) {
+        match self.parser.parse_v2_raw(frame) {
+            Ok(parsed) => self.dispatch_v2(parsed, frame.sequence, received),
+            // Graceful degradation, same as v1 market data
+            Err(e) => self.handle_malformed_message(frame.text, &ProcessingError::ProcessingFailed(e.to_string())),
+        }
+    }
+    
+    /// Route a v2 frame to the dispatcher (and local books, if attached)
+    fn route_v2_message(&self, json: &Value, received: DateTime<Utc>) {
+        match json.get("method").and_then(|v| v.as_str()) {
+            Some("pong") => {
+                tracing::debug!("Received pong message");
+                self.record_pong();
+            }
+            Some(_) => self.track_subscription_status(json),
+            None => {}
         }
         
-        // Try trade data
-        if let Ok(trade_data) = self.parser.parse_trade(message) {
-            tracing::debug!("Parsed trade data: {}", trade_data.symbol);
-            self.dispatcher.dispatch_trade(trade_data);
-            return Ok(());
+        let message = match self.parser.parse_v2_value(json) {
+            Ok(message) => message,
+            Err(e) => {
+                self.handle_malformed_message(&json.to_string(), &ProcessingError::ProcessingFailed(e.to_string()));
+                return;
+            }
+        };
+        let parsed = V2Parsed { message, exchange_times: v2_exchange_times(json) };
+        self.dispatch_v2(parsed, json.get("sequence").and_then(|v| v.as_u64()), received);
+    }
+    
+    /// Dispatch a decoded v2 frame, validating `sequence` on book frames
+    fn dispatch_v2(&self, parsed: V2Parsed, sequence: Option<u64>, received: DateTime<Utc>) {
+        let exchange_time = |index: usize| parsed.exchange_times.get(index).copied().flatten();
+        match parsed.message {
+            V2Message::Ticker(tickers) => {
+                for (index, mut ticker) in tickers.into_iter().enumerate() {
+                    let symbol = ticker.symbol.clone();
+                    self.normalize_symbol(&mut ticker.symbol);
+                    self.dispatcher.dispatch_ticker(ticker);
+                    self.record_latency("ticker", &symbol, exchange_time(index), received);
+                }
+            }
+            V2Message::Book { snapshot, updates } => {
+                for (index, mut update) in updates.into_iter().enumerate() {
+                    if let Some(sequence) = sequence {
+                        self.check_book_sequence(&update.symbol, sequence, snapshot);
+                    }
+                    if let Some(manager) = &self.orderbook_manager {
+                        let mut book_update = update.clone();
+                        if !self.seed_precision(manager, &update.symbol) {
+                            book_update.checksum = None;
+                        }
+                        self.apply_book_update(book_update, snapshot);
+                    }
+                    let symbol = update.symbol.clone();
+                    self.normalize_symbol(&mut update.symbol);
+                    self.dispatcher.dispatch_orderbook(update);
+                    self.record_latency("book", &symbol, exchange_time(index), received);
+                }
+            }
+            V2Message::Trade(trades) => {
+                for (index, mut trade) in trades.into_iter().enumerate() {
+                    if !self.observe_trade(&trade) {
+                        continue;
+                    }
+                    let symbol = trade.symbol.clone();
+                    self.normalize_symbol(&mut trade.symbol);
+                    self.dispatch_trade(trade);
+                    self.record_latency("trade", &symbol, exchange_time(index), received);
+                }
+            }
+            V2Message::Ohlc(candles) => {
+                for (index, mut candle) in candles.into_iter().enumerate() {
+                    self.observe_candle(&candle);
+                    let symbol = candle.symbol.clone();
+                    self.normalize_symbol(&mut candle.symbol);
+                    self.dispatch_ohlc(candle);
+                    self.record_latency("ohlc", &symbol, exchange_time(index), received);
+                }
+            }
+            V2Message::Instrument(instruments) => {
+                for mut instrument in instruments {
+                    // Pair precision is needed to verify v2 book checksums
+                    if let Some(manager) = &self.orderbook_manager {
+                        manager.set_precision(&instrument.symbol, instrument.price_precision, instrument.qty_precision);
+                    }
+                    if let Some(instruments) = &self.instruments {
+                        instruments.insert(instrument.clone());
+                    }
+                    let symbol = instrument.symbol.clone();
+                    self.normalize_symbol(&mut instrument.symbol);
+                    self.dispatcher.dispatch_instrument(instrument);
+                    self.record_latency("instrument", &symbol, None, received);
+                }
+            }
+            V2Message::Heartbeat => {
+                tracing::debug!("Received heartbeat message");
+                self.dispatcher.dispatch_heartbeat(Heartbeat { received_at: received });
+            }
+            V2Message::Status(status) => {
+                tracing::debug!("Received system status message: {}", status.raw_status);
+                self.handle_system_status(status);
+            }
+            V2Message::MethodResponse { method, req_id, success, error } => {
+                if success {
+                    tracing::debug!("{} request {:?} succeeded", method, req_id);
+                } else {
+                    tracing::warn!("{} request {:?} failed: {}", method, req_id, error.unwrap_or_default());
+                }
+            }
+        }
+    }
+    
+    /// Whether v2 checksums can be verified for a book
+    ///
+    /// They can only be reproduced with the pair precision, from the
+    /// `instrument` channel or, failing that, the instrument registry.
+    /// A book left unverified is reported once.
+    fn seed_precision(&self, manager: &OrderBookManager, symbol: &str) -> bool {
+        if manager.has_precision(symbol) {
+            return true;
+        }
+        if let Some(instrument) = self.instruments.as_ref().and_then(|instruments| instruments.get(symbol)) {
+            manager.set_precision(symbol, instrument.price_precision, instrument.qty_precision);
+            return true;
+        }
+        if self.unverified_books.lock().unwrap().insert(symbol.to_string()) {
+            tracing::warn!("Skipping checksum validation for {}: pair precision unknown, subscribe to `instrument` first", symbol);
         }
+        false
+    }
+    
+    /// Record exchange -> receive -> dispatch latency for a dispatched message
+    fn record_latency(&self, channel: &str, symbol: &str, exchange_time: Option<DateTime<Utc>>, received: DateTime<Utc>) {
+        if let Some(tracker) = &self.latency_tracker {
+            tracker.record_dispatch(exchange_time, received, self.clock.now(), channel, symbol);
+        }
+    }
+    
+    /// Validate a book sequence number, queueing the book for resync on a gap
+    fn check_book_sequence(&self, symbol: &str, sequence: u64, snapshot: bool) {
+        let Some(sequences) = &self.sequence_manager else {
+            return;
+        };
         
-        // Try OHLC data
-        if let Ok(ohlc_data) = self.parser.parse_ohlc(message) {
-            tracing::debug!("Parsed OHLC data: {}", ohlc_data.symbol);
-            self.dispatcher.dispatch_ohlc(ohlc_data);
-            return Ok(());
+        let channel = format!("book:{}", symbol);
+        // A snapshot starts a new sequence baseline
+        if snapshot {
+            sequences.reset(&channel);
         }
         
-        // If none of the parsers worked, return an error
-        Err(ProcessingError::ProcessingFailed("Unknown message format".to_string()))
+        let result = sequences.validate(&channel, sequence, "");
+        if result.resync_triggered {
+            sequences.reset(&channel);
+            if let Some(manager) = &self.orderbook_manager {
+                manager.request_resync(symbol);
+            }
+        }
     }
     
-    /// Register a new parser
-    pub fn register_parser(&mut self, parser: Arc<dyn DataParser>) {
-        self.parser = parser;
+    /// Record a live trade for backfill; false if it was already dispatched
+    fn observe_trade(&self, trade: &TradeData) -> bool {
+        match &self.backfill {
+            Some(tracker) => tracker.observe_trade(trade),
+            None => true,
+        }
     }
     
-    /// Validate message integrity
-    fn validate_message(&self, message: &str) -> Result<(), ProcessingError> {
-        // Basic JSON validation
-        if let Err(e) = serde_json::from_str::<serde_json::Value>(message) {
-            return Err(ProcessingError::ProcessingFailed(
-                format!("Invalid JSON format: {}", e)
-            ));
+    /// Record a live candle for backfill
+    fn observe_candle(&self, candle: &OHLCData) {
+        if let Some(tracker) = &self.backfill {
+            tracker.observe_candle(candle);
         }
-        
-        // Check for minimum message length
-        if message.len() < 10 {
-            return Err(ProcessingError::ProcessingFailed(
-                "Message too short to be valid".to_string()
-            ));
+    }
+    
+    /// Dispatch a trade, then the candle events it caused
+    fn dispatch_trade(&self, trade: TradeData) {
+        let events = self.candles.as_ref().map(|candles| candles.add_trade(&trade));
+        self.dispatcher.dispatch_trade(trade);
+        for event in events.into_iter().flatten() {
+            self.dispatcher.dispatch_candle(event);
         }
+    }
+    
+    /// Dispatch an exchange candle, then the local candle events it caused
+    fn dispatch_ohlc(&self, candle: OHLCData) {
+        let events = self.candles.as_ref().map(|candles| candles.reconcile(&candle));
+        self.dispatcher.dispatch_ohlc(candle);
+        for event in events.into_iter().flatten() {
+            self.dispatcher.dispatch_candle(event);
+        }
+    }
+    
+    /// Close time bars whose bucket ended by the handler's clock
+    fn close_candles(&self) {
+        if let Some(candles) = &self.candles {
+            for event in candles.tick(self.clock.now()) {
+                self.dispatcher.dispatch_candle(event);
+            }
+        }
+    }
+    
+    /// Rewrite a pair name for dispatch, if a normalizer is attached
+    fn normalize_symbol(&self, symbol: &mut String) {
+        if let Some(symbols) = &self.symbols {
+            symbols.apply(symbol);
+        }
+    }
+    
+    /// Apply a book snapshot or update to the attached order book manager
+    ///
+    /// Checksum mismatches are dispatched as [`SdkEvent::ChecksumMismatch`];
+    /// the book stays in resync until the next snapshot.
+    ///
+    /// [`SdkEvent::ChecksumMismatch`]: crate::events::SdkEvent::ChecksumMismatch
+    pub fn apply_book_update(&self, update: OrderBookUpdate, snapshot: bool) {
+        let Some(manager) = &self.orderbook_manager else {
+            return;
+        };
         
-        Ok(())
+        let result = if snapshot {
+            manager.apply_snapshot(update)
+        } else {
+            manager.apply_update(update)
+        };
+        
+        match result {
+            Ok(_) => {}
+            Err(ParseError::ChecksumMismatch { symbol, expected, calculated }) => {
+                self.dispatcher.dispatch_checksum_mismatch(ChecksumMismatch {
+                    symbol,
+                    expected,
+                    calculated,
+                    timestamp: self.clock.now(),
+                });
+            }
+            Err(e) => tracing::warn!("Failed to update order book: {}", e),
+        }
+    }
+    
+    /// Register a new parser
+    pub fn register_parser(&mut self, parser: Arc<dyn DataParser>) {
+        self.parser = parser;
     }
     
     /// Handle malformed messages gracefully
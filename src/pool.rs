@@ -0,0 +1,606 @@
+//! Connection pool that shards subscriptions across several sockets
+//!
+//! One connection can't keep up with (or survive throttling on) hundreds of
+//! deep books. [`KrakenWsPool`] spreads channels over as many
+//! [`KrakenWsClient`]s as the per-connection limits in [`PoolConfig`]
+//! require. Every connection publishes into the same dispatcher, so the pool
+//! presents a single event stream.
+//!
+//! Each connection reconnects on its own. When one gives up, the pool moves
+//! its subscriptions to connections with room, opening a replacement if none
+//! has any.
+
+use crate::{
+    client::KrakenWsClient,
+    data::{Channel, ClientConfig, DataType},
+    error::{SdkError, SubscriptionError},
+    events::{EventCallback, EventDispatcher, EventFilter, EventStream},
+    instruments::InstrumentRegistry,
+    latency::{LatencyStats, LatencyTracker},
+    state,
+    subscription::SubscriptionAck,
+    system_status::{ExchangeStatus, SystemStatusMonitor},
+};
+use futures_util::future::try_join_all;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+/// Per-connection limits and health checking for [`KrakenWsPool`]
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    /// Upper bound on open connections
+    pub max_connections: usize,
+    /// Subscriptions (one channel for one symbol) per connection
+    pub max_subscriptions_per_connection: usize,
+    /// Book subscriptions per connection; deep books dominate the load
+    pub max_books_per_connection: usize,
+    /// How often connections are checked for having given up reconnecting
+    pub health_check_interval: Duration,
+}
+
+impl PoolConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn with_max_connections(mut self, max_connections: usize) -> Self {
+        self.max_connections = max_connections;
+        self
+    }
+    
+    pub fn with_max_subscriptions_per_connection(mut self, max_subscriptions: usize) -> Self {
+        self.max_subscriptions_per_connection = max_subscriptions;
+        self
+    }
+    
+    pub fn with_max_books_per_connection(mut self, max_books: usize) -> Self {
+        self.max_books_per_connection = max_books;
+        self
+    }
+    
+    pub fn with_health_check_interval(mut self, interval: Duration) -> Self {
+        self.health_check_interval = interval;
+        self
+    }
+    
+    /// Validate pool limits
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_connections == 0 {
+            return Err("Max connections must be greater than 0".to_string());
+        }
+        
+        if self.max_subscriptions_per_connection == 0 {
+            return Err("Max subscriptions per connection must be greater than 0".to_string());
+        }
+        
+        if self.max_books_per_connection == 0 {
+            return Err("Max books per connection must be greater than 0".to_string());
+        }
+        
+        if self.health_check_interval.is_zero() {
+            return Err("Health check interval must be greater than 0".to_string());
+        }
+        
+        Ok(())
+    }
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 10,
+            max_subscriptions_per_connection: 50,
+            max_books_per_connection: 20,
+            health_check_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Snapshot of one pooled connection
+#[derive(Debug, Clone)]
+pub struct PoolConnectionStats {
+    /// Pool-assigned id, stable for the connection's lifetime
+    pub id: usize,
+    /// Channels assigned to this connection
+    pub channels: Vec<Channel>,
+    /// Book channels among them
+    pub books: usize,
+    /// Lifecycle state of the connection
+    pub state: state::ConnectionState,
+    pub connected: bool,
+}
+
+/// Several Kraken WebSocket connections behind one subscription API and
+/// one merged event stream
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let mut pool = KrakenWsPool::new(
+///     ClientConfig::default(),
+///     PoolConfig::new().with_max_books_per_connection(20),
+/// );
+/// let mut events = pool.events();
+/// pool.connect().await?;
+/// pool.subscribe(pairs.iter().map(|p| Channel::new("book").with_symbol(p).with_depth(100)).collect()).await?;
+/// ```
+pub struct KrakenWsPool {
+    inner: Arc<PoolInner>,
+    supervisor: Option<JoinHandle<()>>,
+}
+
+/// State shared with the health check task
+struct PoolInner {
+    config: ClientConfig,
+    pool_config: PoolConfig,
+    event_dispatcher: Arc<EventDispatcher>,
+    latency_tracker: Arc<LatencyTracker>,
+    status_monitor: SystemStatusMonitor,
+    instruments: InstrumentRegistry,
+    connections: Mutex<Connections>,
+    /// Whether new connections should be opened right away
+    connected: AtomicBool,
+}
+
+#[derive(Default)]
+struct Connections {
+    members: Vec<PooledConnection>,
+    next_id: usize,
+}
+
+struct PooledConnection {
+    id: usize,
+    client: KrakenWsClient,
+    channels: Vec<Channel>,
+}
+
+impl KrakenWsPool {
+    /// Create a pool; every connection uses `config`
+    pub fn new(config: ClientConfig, pool_config: PoolConfig) -> Self {
+        if let Err(e) = pool_config.validate() {
+            tracing::error!("Invalid pool configuration: {}", e);
+        }
+        
+        let event_dispatcher = Arc::new(
+            EventDispatcher::new()
+                .with_backpressure(config.backpressure_config.clone())
+                .with_stream_buffer(config.buffer_size),
+        );
+        let latency_tracker = Arc::new(LatencyTracker::with_config(config.latency_config.clone()));
+        if let Some(alerts) = &config.latency_alerts {
+            latency_tracker.set_alerts(alerts);
+        }
+        
+        Self {
+            inner: Arc::new(PoolInner {
+                config,
+                pool_config,
+                event_dispatcher,
+                latency_tracker,
+                status_monitor: SystemStatusMonitor::new(),
+                instruments: InstrumentRegistry::new(),
+                connections: Mutex::new(Connections::default()),
+                connected: AtomicBool::new(false),
+            }),
+            supervisor: None,
+        }
+    }
+    
+    /// Connect every pooled connection and start health checks
+    ///
+    /// Connections are opened as subscriptions need them; before the first
+    /// `subscribe` there is nothing to connect.
+    pub async fn connect(&mut self) -> Result<(), SdkError> {
+        self.inner.connected.store(true, Ordering::SeqCst);
+        
+        let mut result = Ok(());
+        {
+            let mut connections = self.inner.connections.lock().await;
+            for member in connections.members.iter_mut().filter(|m| !m.client.is_running()) {
+                if let Err(e) = member.client.connect().await {
+                    tracing::error!("Pooled connection {} failed to connect: {}", member.id, e);
+                    result = Err(e);
+                }
+            }
+        }
+        
+        if self.supervisor.as_ref().map_or(true, |task| task.is_finished()) {
+            let inner = Arc::clone(&self.inner);
+            self.supervisor = Some(tokio::spawn(async move { inner.supervise().await }));
+        }
+        
+        result
+    }
+    
+    /// Subscribe, spreading channels over connections with room
+    ///
+    /// Channels already in the pool go to the connection that has them.
+    /// Fails with [`SubscriptionError::PoolExhausted`] (subscribing nothing)
+    /// when the limits leave no room. While connected, resolves once Kraken
+    /// answers every connection, like [`KrakenWsClient::subscribe`].
+    pub async fn subscribe(&mut self, channels: Vec<Channel>) -> Result<(), SdkError> {
+        let acks = {
+            let mut connections = self.inner.connections.lock().await;
+            self.inner.assign(&mut connections, channels).await?
+        };
+        
+        let result = try_join_all(acks.into_iter().map(|ack| ack.wait(self.inner.config.subscription_timeout))).await;
+        
+        // Channels Kraken rejected no longer count against their connection
+        let mut connections = self.inner.connections.lock().await;
+        connections.sync();
+        connections.close_idle().await;
+        result?;
+        Ok(())
+    }
+    
+    /// Unsubscribe from channels, closing connections left without any
+    pub async fn unsubscribe(&mut self, channels: Vec<Channel>) -> Result<(), SdkError> {
+        let mut acks = Vec::new();
+        {
+            let mut connections = self.inner.connections.lock().await;
+            for channel in &channels {
+                if connections.owner(channel).is_none() {
+                    return Err(SubscriptionError::NotSubscribed(describe(channel)).into());
+                }
+            }
+            
+            for member in connections.members.iter_mut() {
+                let (leaving, staying): (Vec<_>, Vec<_>) = member.channels.drain(..)
+                    .partition(|c| channels.iter().any(|channel| same_subscription(c, channel)));
+                member.channels = staying;
+                // An idle connection is simply closed below
+                if leaving.is_empty() || member.channels.is_empty() {
+                    continue;
+                }
+                if let Some(ack) = member.client.request_unsubscription(&leaving).await? {
+                    acks.push(ack);
+                }
+            }
+            connections.close_idle().await;
+        }
+        
+        try_join_all(acks.into_iter().map(|ack| ack.wait(self.inner.config.subscription_timeout))).await?;
+        Ok(())
+    }
+    
+    /// Disconnect every connection and stop health checks
+    pub async fn disconnect(&mut self) -> Result<(), SdkError> {
+        self.inner.connected.store(false, Ordering::SeqCst);
+        if let Some(task) = self.supervisor.take() {
+            task.abort();
+        }
+        
+        let mut connections = self.inner.connections.lock().await;
+        for member in connections.members.iter_mut() {
+            member.client.disconnect().await?;
+        }
+        Ok(())
+    }
+    
+    /// Move subscriptions off connections that gave up reconnecting
+    ///
+    /// Runs on every health check; call it to rebalance right away.
+    pub async fn rebalance(&self) {
+        self.inner.rebalance().await;
+    }
+    
+    /// Register a callback for market data events from every connection
+    pub fn register_callback(&self, data_type: DataType, callback: Arc<dyn EventCallback>) -> u64 {
+        self.inner.event_dispatcher.register_callback(data_type, callback)
+    }
+    
+    /// Register a callback for connection state changes of every connection
+    pub fn register_connection_listener(&self, callback: Arc<dyn EventCallback>) -> u64 {
+        self.inner.event_dispatcher.register_connection_listener(callback)
+    }
+    
+    /// Merged event stream of every connection
+    pub fn events(&self) -> crate::events::EventReceiver {
+        self.inner.event_dispatcher.create_event_stream()
+    }
+    
+    /// Merged event stream that only receives events passing `filter`
+    pub fn events_with_filter(&self, filter: EventFilter) -> EventStream {
+        self.inner.event_dispatcher.subscribe(filter)
+    }
+    
+    /// Filtered event stream with its own buffer capacity
+    pub fn events_with_buffer(&self, filter: EventFilter, capacity: usize) -> EventStream {
+        self.inner.event_dispatcher.subscribe_with_buffer(filter, capacity)
+    }
+    
+    /// Latest exchange status reported on any connection
+    pub fn exchange_status(&self) -> Option<ExchangeStatus> {
+        self.inner.status_monitor.status()
+    }
+    
+    /// Shared handle to the exchange status
+    pub fn status_monitor(&self) -> SystemStatusMonitor {
+        self.inner.status_monitor.clone()
+    }
+    
+    /// Shared handle to pair rules received on any connection
+    pub fn instrument_registry(&self) -> InstrumentRegistry {
+        self.inner.instruments.clone()
+    }
+    
+    /// Latency statistics across every connection
+    pub fn latency_stats(&self) -> LatencyStats {
+        self.inner.latency_tracker.stats()
+    }
+    
+    /// Latency statistics for one channel across every connection
+    pub fn channel_latency_stats(&self, channel: &str) -> Option<LatencyStats> {
+        self.inner.latency_tracker.channel_stats(channel)
+    }
+    
+    /// Latency statistics for one symbol
+    pub fn symbol_latency_stats(&self, symbol: &str) -> Option<LatencyStats> {
+        self.inner.latency_tracker.symbol_stats(symbol)
+    }
+    
+    /// Number of open (or queued) connections
+    pub async fn connection_count(&self) -> usize {
+        self.inner.connections.lock().await.members.len()
+    }
+    
+    /// Assignment and state of every connection
+    pub async fn connection_stats(&self) -> Vec<PoolConnectionStats> {
+        let connections = self.inner.connections.lock().await;
+        connections.members.iter()
+            .map(|member| PoolConnectionStats {
+                id: member.id,
+                channels: member.channels.clone(),
+                books: member.books(),
+                state: member.client.state(),
+                connected: member.client.is_connected(),
+            })
+            .collect()
+    }
+    
+    /// Id of the connection carrying `channel`
+    pub async fn connection_for(&self, channel: &Channel) -> Option<usize> {
+        let connections = self.inner.connections.lock().await;
+        connections.owner(channel).map(|index| connections.members[index].id)
+    }
+    
+    /// Local order book for a symbol, from the connection carrying its book
+    pub async fn get_order_book(&self, symbol: &str) -> Option<Arc<crate::orderbook::OrderBook>> {
+        let connections = self.inner.connections.lock().await;
+        connections.members.iter()
+            .filter(|member| member.channels.iter().any(|c| c.name == "book" && c.symbol.as_deref() == Some(symbol)))
+            .find_map(|member| member.client.get_order_book(symbol))
+    }
+}
+
+impl Drop for KrakenWsPool {
+    fn drop(&mut self) {
+        if let Some(task) = self.supervisor.take() {
+            task.abort();
+        }
+    }
+}
+
+impl std::fmt::Debug for KrakenWsPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KrakenWsPool")
+            .field("pool_config", &self.inner.pool_config)
+            .field("connected", &self.inner.connected.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+impl PoolInner {
+    /// Health check loop
+    async fn supervise(&self) {
+        let mut interval = tokio::time::interval(self.pool_config.health_check_interval);
+        loop {
+            interval.tick().await;
+            self.rebalance().await;
+        }
+    }
+    
+    async fn rebalance(&self) {
+        if !self.connected.load(Ordering::SeqCst) {
+            return;
+        }
+        
+        let mut connections = self.connections.lock().await;
+        let (dead, alive): (Vec<_>, Vec<_>) = connections.members.drain(..)
+            .partition(|member| !member.client.is_running());
+        connections.members = alive;
+        if dead.is_empty() {
+            return;
+        }
+        
+        let orphaned: Vec<Channel> = dead.into_iter()
+            .flat_map(|member| {
+                tracing::warn!(
+                    "Pooled connection {} is down; moving its {} subscriptions",
+                    member.id, member.channels.len()
+                );
+                member.channels
+            })
+            .collect();
+        if orphaned.is_empty() {
+            return;
+        }
+        
+        // Confirmations arrive as events; nobody waits on them here
+        if let Err(e) = self.assign(&mut connections, orphaned).await {
+            tracing::error!("Failed to move subscriptions: {}", e);
+            self.event_dispatcher.dispatch_error(e);
+        }
+    }
+    
+    /// Place channels on connections and send (or queue) their subscriptions
+    async fn assign(&self, connections: &mut Connections, channels: Vec<Channel>) -> Result<Vec<SubscriptionAck>, SdkError> {
+        if channels.is_empty() {
+            return Err(SubscriptionError::InvalidChannel("No channels provided".to_string()).into());
+        }
+        
+        // Plan every placement first so a full pool subscribes nothing
+        let mut planned: Vec<(usize, Channel)> = Vec::with_capacity(channels.len());
+        let mut added: Vec<(usize, Channel)> = Vec::new();
+        for channel in channels {
+            let index = match connections.owner(&channel) {
+                Some(index) => index,
+                None => {
+                    let index = match connections.place(&channel, &self.pool_config) {
+                        Some(index) => index,
+                        None if connections.members.len() < self.pool_config.max_connections => self.open(connections),
+                        None => {
+                            let in_use = connections.members.len();
+                            connections.release(&added);
+                            connections.close_idle().await;
+                            return Err(SubscriptionError::PoolExhausted {
+                                channel: describe(&channel),
+                                connections: in_use,
+                            }.into());
+                        }
+                    };
+                    connections.members[index].channels.push(channel.clone());
+                    added.push((connections.members[index].id, channel.clone()));
+                    index
+                }
+            };
+            planned.push((connections.members[index].id, channel));
+        }
+        
+        let connect = self.connected.load(Ordering::SeqCst);
+        let mut acks = Vec::new();
+        let mut failure = None;
+        for member in connections.members.iter_mut() {
+            let batch: Vec<Channel> = planned.iter()
+                .filter(|(id, _)| *id == member.id)
+                .map(|(_, channel)| channel.clone())
+                .collect();
+            if batch.is_empty() {
+                continue;
+            }
+            
+            // A connection that fails to open keeps the channels queued and
+            // is replaced by the next health check
+            if connect && !member.client.is_running() {
+                if let Err(e) = member.client.connect().await {
+                    tracing::error!("Pooled connection {} failed to connect: {}", member.id, e);
+                    failure.get_or_insert(e);
+                }
+            }
+            match member.client.request_subscription(&batch).await {
+                Ok(Some(ack)) => acks.push(ack),
+                Ok(None) => {}
+                Err(e) => {
+                    failure.get_or_insert(e);
+                }
+            }
+        }
+        
+        // Channels a connection refused don't count against it
+        connections.sync();
+        connections.close_idle().await;
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(acks),
+        }
+    }
+    
+    /// Add an empty connection and return its index
+    fn open(&self, connections: &mut Connections) -> usize {
+        let id = connections.next_id;
+        connections.next_id += 1;
+        tracing::info!("Opening pooled connection {}", id);
+        
+        let client = KrakenWsClient::with_shared(
+            self.config.clone(),
+            Arc::clone(&self.event_dispatcher),
+            Arc::clone(&self.latency_tracker),
+            self.status_monitor.clone(),
+            self.instruments.clone(),
+        );
+        connections.members.push(PooledConnection {
+            id,
+            client,
+            channels: Vec::new(),
+        });
+        connections.members.len() - 1
+    }
+}
+
+impl Connections {
+    /// Index of the connection that has `channel`
+    fn owner(&self, channel: &Channel) -> Option<usize> {
+        self.members.iter().position(|member| member.position(channel).is_some())
+    }
+    
+    /// Least loaded connection with room for `channel`
+    fn place(&self, channel: &Channel, limits: &PoolConfig) -> Option<usize> {
+        self.members.iter()
+            .enumerate()
+            .filter(|(_, member)| member.has_room(channel, limits))
+            .min_by_key(|(_, member)| member.channels.len())
+            .map(|(index, _)| index)
+    }
+    
+    /// Undo placements of a failed assignment
+    fn release(&mut self, added: &[(usize, Channel)]) {
+        for member in self.members.iter_mut() {
+            member.channels.retain(|c| {
+                !added.iter().any(|(id, channel)| *id == member.id && same_subscription(c, channel))
+            });
+        }
+    }
+    
+    /// Forget channels a connection no longer has (e.g. rejected by Kraken)
+    fn sync(&mut self) {
+        for member in self.members.iter_mut() {
+            let known = member.client.subscribed_channels();
+            member.channels.retain(|c| known.iter().any(|k| same_subscription(c, k)));
+        }
+    }
+    
+    /// Close and drop connections without subscriptions
+    async fn close_idle(&mut self) {
+        let (idle, busy): (Vec<_>, Vec<_>) = self.members.drain(..)
+            .partition(|member| member.channels.is_empty());
+        self.members = busy;
+        
+        for mut member in idle {
+            tracing::info!("Closing idle pooled connection {}", member.id);
+            if let Err(e) = member.client.cleanup().await {
+                tracing::warn!("Failed to close pooled connection {}: {}", member.id, e);
+            }
+        }
+    }
+}
+
+impl PooledConnection {
+    fn position(&self, channel: &Channel) -> Option<usize> {
+        self.channels.iter().position(|c| same_subscription(c, channel))
+    }
+    
+    fn books(&self) -> usize {
+        self.channels.iter().filter(|c| c.name == "book").count()
+    }
+    
+    fn has_room(&self, channel: &Channel, limits: &PoolConfig) -> bool {
+        self.channels.len() < limits.max_subscriptions_per_connection
+            && (channel.name != "book" || self.books() < limits.max_books_per_connection)
+    }
+}
+
+/// Same channel, symbol and interval (depth is a property of the subscription)
+fn same_subscription(a: &Channel, b: &Channel) -> bool {
+    a.name == b.name && a.symbol == b.symbol && a.interval == b.interval
+}
+
+fn describe(channel: &Channel) -> String {
+    match &channel.symbol {
+        Some(symbol) => format!("{} {}", channel.name, symbol),
+        None => channel.name.clone(),
+    }
+}
@@ -4,13 +4,19 @@
 //! - Own trades (execution reports)
 //! - Open orders (order status updates)
 //! - Balances (real-time balance changes)
+//!
+//! `ownTrades` and `openOrders` frames carry a `sequence` number. A gap
+//! resubscribes the channel for a fresh snapshot and is reported as
+//! [`PrivateEvent::Gap`] and [`PrivateEvent::Resync`].
 
 use crate::error::SdkError;
-use crate::trading::{Execution, Order, OrderSide, OrderStatus, OrderType, Balances, AssetBalance};
+use crate::recorder::{RecordingSink, SessionRecorder};
+use crate::sequencing::{GapEvent, ResyncEvent, SequenceConfig, SequenceManager, SequenceStats};
+use crate::symbols::SymbolNormalizer;
+use crate::trading::{Execution, Order, OrderSide, OrderStatus, OrderType};
 use chrono::{DateTime, TimeZone, Utc};
 use futures_util::{SinkExt, StreamExt};
 use rust_decimal::Decimal;
-use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -28,6 +34,10 @@ pub enum PrivateEvent {
     OrderUpdate(OrderUpdate),
     /// Balance change
     BalanceUpdate(BalanceUpdate),
+    /// Sequence gap on a private channel
+    Gap(GapEvent),
+    /// Private channel resubscribed to recover from a gap
+    Resync(ResyncEvent),
     /// Connection state change
     Connected,
     Disconnected,
@@ -82,6 +92,14 @@ pub struct PrivateWsConfig {
     pub auto_reconnect: bool,
     /// Max reconnect attempts
     pub max_reconnect_attempts: u32,
+    /// Authenticated WebSocket endpoint
+    pub endpoint: String,
+    /// Sequence validation for `ownTrades` and `openOrders`
+    pub sequence_config: SequenceConfig,
+    /// Rewrites execution pair names; `None` keeps Kraken's names
+    pub symbols: Option<SymbolNormalizer>,
+    /// Writes every frame sent and received to disk, tokens redacted
+    pub recorder: Option<SessionRecorder>,
 }
 
 impl PrivateWsConfig {
@@ -91,6 +109,10 @@ impl PrivateWsConfig {
             channels: vec![PrivateChannel::OwnTrades, PrivateChannel::OpenOrders],
             auto_reconnect: true,
             max_reconnect_attempts: 10,
+            endpoint: KRAKEN_WS_AUTH_URL.to_string(),
+            sequence_config: SequenceConfig::strict(),
+            symbols: None,
+            recorder: None,
         }
     }
 
@@ -98,6 +120,26 @@ impl PrivateWsConfig {
         self.channels = channels;
         self
     }
+
+    pub fn with_endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = endpoint.to_string();
+        self
+    }
+
+    pub fn with_sequence_config(mut self, sequence_config: SequenceConfig) -> Self {
+        self.sequence_config = sequence_config;
+        self
+    }
+
+    pub fn with_symbols(mut self, symbols: SymbolNormalizer) -> Self {
+        self.symbols = Some(symbols);
+        self
+    }
+
+    pub fn with_recorder(mut self, recorder: SessionRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
 }
 
 /// Private WebSocket client for authenticated feeds
@@ -109,6 +151,7 @@ pub struct PrivateWsClient {
     // Track current state
     open_orders: Arc<RwLock<HashMap<String, Order>>>,
     recent_executions: Arc<RwLock<Vec<Execution>>>,
+    sequence_manager: Arc<SequenceManager>,
 }
 
 impl PrivateWsClient {
@@ -116,6 +159,18 @@ impl PrivateWsClient {
     pub fn new(config: PrivateWsConfig) -> Self {
         let (event_tx, _) = broadcast::channel(1024);
         
+        let sequence_manager = Arc::new(SequenceManager::with_config(config.sequence_config.clone()));
+        {
+            let tx = event_tx.clone();
+            sequence_manager.on_gap(move |gap| {
+                let _ = tx.send(PrivateEvent::Gap(gap));
+            });
+            let tx = event_tx.clone();
+            sequence_manager.on_resync(move |resync| {
+                let _ = tx.send(PrivateEvent::Resync(resync));
+            });
+        }
+        
         Self {
             config,
             event_tx,
@@ -123,6 +178,7 @@ impl PrivateWsClient {
             is_connected: Arc::new(RwLock::new(false)),
             open_orders: Arc::new(RwLock::new(HashMap::new())),
             recent_executions: Arc::new(RwLock::new(Vec::new())),
+            sequence_manager,
         }
     }
 
@@ -146,6 +202,11 @@ impl PrivateWsClient {
         self.recent_executions.read().await.clone()
     }
 
+    /// Sequence validation statistics for `ownTrades` and `openOrders`
+    pub fn sequence_stats(&self) -> SequenceStats {
+        self.sequence_manager.stats()
+    }
+
     /// Connect and start receiving events
     pub async fn connect(&mut self) -> Result<(), SdkError> {
         let (shutdown_tx, mut shutdown_rx) = mpsc::channel::<()>(1);
@@ -156,6 +217,7 @@ impl PrivateWsClient {
         let is_connected = self.is_connected.clone();
         let open_orders = self.open_orders.clone();
         let recent_executions = self.recent_executions.clone();
+        let sequence_manager = self.sequence_manager.clone();
 
         tokio::spawn(async move {
             let mut reconnect_attempts = 0;
@@ -167,6 +229,7 @@ impl PrivateWsClient {
                     &is_connected,
                     &open_orders,
                     &recent_executions,
+                    &sequence_manager,
                     &mut shutdown_rx,
                 ).await {
                     Ok(()) => {
@@ -224,27 +287,30 @@ async fn connect_and_run(
     is_connected: &Arc<RwLock<bool>>,
     open_orders: &Arc<RwLock<HashMap<String, Order>>>,
     recent_executions: &Arc<RwLock<Vec<Execution>>>,
+    sequence_manager: &SequenceManager,
     shutdown_rx: &mut mpsc::Receiver<()>,
 ) -> Result<(), SdkError> {
     tracing::info!("Connecting to Kraken private WebSocket...");
 
-    let (ws_stream, _) = connect_async(KRAKEN_WS_AUTH_URL)
+    let (ws_stream, _) = connect_async(config.endpoint.as_str())
         .await
         .map_err(|e| SdkError::Connection(crate::error::ConnectionError::EstablishmentFailed(e.to_string())))?;
 
-    let (mut write, mut read) = ws_stream.split();
+    let recorder = config.recorder.as_ref().map(SessionRecorder::connection);
+    let (write, read) = ws_stream.split();
+    let mut write = RecordingSink::new(write, recorder.clone());
+    let mut read = read.inspect(move |message| {
+        if let (Some(recorder), Ok(message)) = (&recorder, message) {
+            recorder.record_inbound(message);
+        }
+    });
+
+    // Sequences restart with each subscription
+    sequence_manager.reset_all();
 
     // Subscribe to channels
     for channel in &config.channels {
-        let subscribe_msg = serde_json::json!({
-            "event": "subscribe",
-            "subscription": {
-                "name": channel.as_str(),
-                "token": config.token
-            }
-        });
-
-        write.send(Message::Text(subscribe_msg.to_string()))
+        write.send(subscription_message("subscribe", channel.as_str(), &config.token))
             .await
             .map_err(|e| SdkError::Network(e.to_string()))?;
     }
@@ -262,8 +328,18 @@ async fn connect_and_run(
             msg = read.next() => {
                 match msg {
                     Some(Ok(Message::Text(text))) => {
-                        if let Err(e) = handle_message(&text, event_tx, open_orders, recent_executions).await {
-                            tracing::warn!("Error handling message: {}", e);
+                        match handle_message(&text, event_tx, open_orders, recent_executions, sequence_manager, config.symbols.as_ref()).await {
+                            Ok(Some(channel)) => {
+                                // Resubscribe for a fresh snapshot after a sequence gap
+                                tracing::info!("Resubscribing to {} after sequence gap", channel);
+                                for action in ["unsubscribe", "subscribe"] {
+                                    write.send(subscription_message(action, &channel, &config.token))
+                                        .await
+                                        .map_err(|e| SdkError::Network(e.to_string()))?;
+                                }
+                            }
+                            Ok(None) => {}
+                            Err(e) => tracing::warn!("Error handling message: {}", e),
                         }
                     }
                     Some(Ok(Message::Ping(data))) => {
@@ -290,12 +366,27 @@ async fn connect_and_run(
     Ok(())
 }
 
+/// Build a private channel (un)subscribe request
+fn subscription_message(action: &str, channel: &str, token: &str) -> Message {
+    let message = serde_json::json!({
+        "event": action,
+        "subscription": {
+            "name": channel,
+            "token": token
+        }
+    });
+    Message::Text(message.to_string())
+}
+
+/// Handle one frame; returns the channel to resubscribe after a sequence gap
 async fn handle_message(
     text: &str,
     event_tx: &broadcast::Sender<PrivateEvent>,
     open_orders: &Arc<RwLock<HashMap<String, Order>>>,
     recent_executions: &Arc<RwLock<Vec<Execution>>>,
-) -> Result<(), SdkError> {
+    sequence_manager: &SequenceManager,
+    symbols: Option<&SymbolNormalizer>,
+) -> Result<Option<String>, SdkError> {
     let json: Value = serde_json::from_str(text)
         .map_err(|e| SdkError::Parse(crate::error::ParseError::InvalidJson(e.to_string())))?;
 
@@ -304,26 +395,42 @@ async fn handle_message(
         match event {
             "systemStatus" | "subscriptionStatus" | "heartbeat" => {
                 tracing::debug!("System message: {}", event);
-                return Ok(());
+                return Ok(None);
             }
             "error" => {
                 let error_msg = json["errorMessage"].as_str().unwrap_or("Unknown error");
                 tracing::error!("WebSocket error: {}", error_msg);
                 let _ = event_tx.send(PrivateEvent::Error(error_msg.to_string()));
-                return Ok(());
+                return Ok(None);
             }
             _ => {}
         }
     }
 
-    // Handle data messages (arrays)
+    // Handle data messages: [data, channelName, {"sequence": N}]
     if let Some(arr) = json.as_array() {
         if arr.len() >= 2 {
-            let channel_name = arr.last().and_then(|v| v.as_str()).unwrap_or("");
+            let channel_name = arr[1..].iter().find_map(|v| v.as_str()).unwrap_or("");
+            let sequence = arr[1..].iter()
+                .find_map(|v| v.get("sequence"))
+                .and_then(|v| v.as_u64());
+            
+            if let Some(sequence) = sequence {
+                let result = sequence_manager.validate(channel_name, sequence, text);
+                if result.resync_triggered {
+                    // The resubscription snapshot supersedes this frame
+                    sequence_manager.reset(channel_name);
+                    return Ok(Some(channel_name.to_string()));
+                }
+                if !result.in_order && result.gap_size == 0 {
+                    tracing::debug!("Skipping duplicate {} frame {}", channel_name, sequence);
+                    return Ok(None);
+                }
+            }
             
             match channel_name {
                 "ownTrades" => {
-                    handle_own_trades(&arr[0], event_tx, recent_executions).await?;
+                    handle_own_trades(&arr[0], event_tx, recent_executions, symbols).await?;
                 }
                 "openOrders" => {
                     handle_open_orders(&arr[0], event_tx, open_orders).await?;
@@ -335,19 +442,20 @@ async fn handle_message(
         }
     }
 
-    Ok(())
+    Ok(None)
 }
 
 async fn handle_own_trades(
     data: &Value,
     event_tx: &broadcast::Sender<PrivateEvent>,
     recent_executions: &Arc<RwLock<Vec<Execution>>>,
+    symbols: Option<&SymbolNormalizer>,
 ) -> Result<(), SdkError> {
     if let Some(trades) = data.as_array() {
         for trade_obj in trades {
             if let Some(trade_map) = trade_obj.as_object() {
                 for (trade_id, trade_data) in trade_map {
-                    let execution = Execution {
+                    let mut execution = Execution {
                         trade_id: trade_id.clone(),
                         order_txid: trade_data["ordertxid"].as_str().unwrap_or("").to_string(),
                         pair: trade_data["pair"].as_str().unwrap_or("").to_string(),
@@ -360,6 +468,9 @@ async fn handle_own_trades(
                         fee_currency: "USD".to_string(),
                         time: parse_timestamp_str(trade_data["time"].as_str()),
                     };
+                    if let Some(symbols) = symbols {
+                        symbols.apply(&mut execution.pair);
+                    }
 
                     // Store execution
                     {
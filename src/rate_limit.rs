@@ -14,8 +14,9 @@ use tokio::time::sleep;
 use crate::error::SdkError;
 
 /// Kraken account tier for rate limiting
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum AccountTier {
+    #[default]
     Starter,
     Intermediate,
     Pro,
@@ -40,12 +41,6 @@ impl AccountTier {
     }
 }
 
-impl Default for AccountTier {
-    fn default() -> Self {
-        AccountTier::Starter // Conservative default
-    }
-}
-
 /// API endpoint categories with different costs
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EndpointCost {
@@ -156,15 +151,16 @@ impl RateLimiter {
         const MAX_ORDERS_PER_SECOND: u32 = 1;
         
         loop {
-            let mut last_reset = self.last_order_reset.lock().unwrap();
-            let elapsed = last_reset.elapsed();
-            
-            if elapsed >= Duration::from_secs(1) {
-                // Reset counter every second
-                self.order_counter.store(0, Ordering::Relaxed);
-                *last_reset = Instant::now();
+            {
+                let mut last_reset = self.last_order_reset.lock().unwrap();
+                let elapsed = last_reset.elapsed();
+                
+                if elapsed >= Duration::from_secs(1) {
+                    // Reset counter every second
+                    self.order_counter.store(0, Ordering::Relaxed);
+                    *last_reset = Instant::now();
+                }
             }
-            drop(last_reset);
 
             let current = self.order_counter.load(Ordering::Relaxed);
             if current < MAX_ORDERS_PER_SECOND {
@@ -180,9 +176,10 @@ impl RateLimiter {
             }
 
             // Wait until next second
-            let last_reset = self.last_order_reset.lock().unwrap();
-            let wait_time = Duration::from_secs(1).saturating_sub(last_reset.elapsed());
-            drop(last_reset);
+            let wait_time = {
+                let last_reset = self.last_order_reset.lock().unwrap();
+                Duration::from_secs(1).saturating_sub(last_reset.elapsed())
+            };
             
             if wait_time > Duration::ZERO {
                 sleep(wait_time).await;
@@ -0,0 +1,849 @@
+//! Raw WebSocket session recording
+//!
+//! [`SessionRecorder`] writes every frame a client sends or receives to an
+//! append-only JSON Lines file, one [`RecordedFrame`] per line, stamped with
+//! the local time it was received (or sent), the connection it belongs to
+//! and its direction. Recordings are the input for debugging feed issues
+//! after the fact and for replaying sessions offline.
+//!
+//! Frames are written by a background thread, so recording never blocks the
+//! connection on disk I/O. Written frames are flushed at least once a
+//! second, so a crash loses roughly the last second of the session;
+//! [`SessionRecorder::flush`] writes everything out on demand.
+//!
+//! Files rotate once they reach [`RecorderConfig::max_file_bytes`]: the
+//! active file is renamed with a timestamp suffix (plus a counter when that
+//! name is taken) and the oldest rotated files beyond
+//! [`RecorderConfig::max_files`] are deleted. With compression enabled each
+//! file is a gzip stream, and its size only counts what the encoder has
+//! written out, so a file may end up slightly over the limit.
+//!
+//! Authentication tokens never reach the disk: the value of every `"token"`
+//! field is replaced with [`REDACTED`] before a frame is written.
+
+use crate::error::SdkError;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures_util::Sink;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Replaces token values in recorded frames
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Size at which the active file is rotated unless configured otherwise
+pub const DEFAULT_MAX_FILE_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Rotated files kept unless configured otherwise
+pub const DEFAULT_MAX_FILES: usize = 10;
+
+/// Timestamp suffix of rotated files, e.g. `session.20240101T120000.000.jsonl`
+const ROTATION_FORMAT: &str = "%Y%m%dT%H%M%S%.3f";
+const ROTATION_SUFFIX_LEN: usize = 19;
+
+/// Longest a written frame waits in a buffer before it is flushed
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Whether a frame was received or sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Inbound,
+    Outbound,
+}
+
+/// WebSocket frame type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrameKind {
+    Text,
+    Binary,
+    Ping,
+    Pong,
+    Close,
+}
+
+/// One line of a recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    /// Local time the frame was received or sent
+    pub timestamp: DateTime<Utc>,
+    /// Connection the frame belongs to; every (re)connect gets a new id
+    pub connection_id: u64,
+    pub direction: Direction,
+    pub kind: FrameKind,
+    /// Text frames and close reasons as-is (tokens redacted); binary, ping
+    /// and pong payloads base64-encoded
+    pub data: String,
+    /// Status code of close frames
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub close_code: Option<u16>,
+}
+
+impl RecordedFrame {
+    /// Capture a frame, redacting tokens; raw frames are not recorded
+    pub fn capture(connection_id: u64, direction: Direction, message: &Message) -> Option<Self> {
+        let (kind, data, close_code) = match message {
+            Message::Text(text) => (FrameKind::Text, redact_tokens(text).into_owned(), None),
+            Message::Binary(data) => (FrameKind::Binary, BASE64.encode(data), None),
+            Message::Ping(data) => (FrameKind::Ping, BASE64.encode(data), None),
+            Message::Pong(data) => (FrameKind::Pong, BASE64.encode(data), None),
+            Message::Close(frame) => match frame {
+                Some(frame) => (FrameKind::Close, frame.reason.to_string(), Some(frame.code.into())),
+                None => (FrameKind::Close, String::new(), None),
+            },
+            Message::Frame(_) => return None,
+        };
+        Some(Self {
+            timestamp: Utc::now(),
+            connection_id,
+            direction,
+            kind,
+            data,
+            close_code,
+        })
+    }
+
+    /// Rebuild the WebSocket message (with tokens still redacted)
+    pub fn to_message(&self) -> Result<Message, SdkError> {
+        let decode = |data: &str| {
+            BASE64.decode(data)
+                .map_err(|e| SdkError::Configuration(format!("Invalid recorded payload: {}", e)))
+        };
+        Ok(match self.kind {
+            FrameKind::Text => Message::Text(self.data.clone()),
+            FrameKind::Binary => Message::Binary(decode(&self.data)?),
+            FrameKind::Ping => Message::Ping(decode(&self.data)?),
+            FrameKind::Pong => Message::Pong(decode(&self.data)?),
+            FrameKind::Close => Message::Close(self.close_code.map(|code| CloseFrame {
+                code: CloseCode::from(code),
+                reason: Cow::Owned(self.data.clone()),
+            })),
+        })
+    }
+}
+
+/// Where and how a session is recorded
+#[derive(Debug, Clone)]
+pub struct RecorderConfig {
+    /// Active recording file; rotated files are written next to it
+    pub path: PathBuf,
+    /// Rotate once the active file reaches this size (`None` never rotates)
+    pub max_file_bytes: Option<u64>,
+    /// Rotated files kept; older ones are deleted (`None` keeps all)
+    pub max_files: Option<usize>,
+    /// Write gzip streams instead of plain JSON Lines; an existing file is
+    /// rotated on open rather than appended to
+    pub compress: bool,
+}
+
+impl RecorderConfig {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_file_bytes: Some(DEFAULT_MAX_FILE_BYTES),
+            max_files: Some(DEFAULT_MAX_FILES),
+            compress: false,
+        }
+    }
+
+    pub fn with_max_file_bytes(mut self, max_file_bytes: u64) -> Self {
+        self.max_file_bytes = Some(max_file_bytes);
+        self
+    }
+
+    pub fn with_max_files(mut self, max_files: usize) -> Self {
+        self.max_files = Some(max_files);
+        self
+    }
+
+    pub fn with_compression(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+}
+
+/// Records frames from any number of connections into one file set
+///
+/// Clones share the file and its writer thread, which finishes the file
+/// once the last clone is dropped. Write failures are logged and never
+/// interrupt the connection being recorded.
+#[derive(Clone)]
+pub struct SessionRecorder {
+    shared: Arc<RecorderShared>,
+}
+
+struct RecorderShared {
+    path: PathBuf,
+    commands: Mutex<Sender<Command>>,
+    next_connection: AtomicU64,
+}
+
+/// Work for the writer thread
+enum Command {
+    Write(RecordedFrame),
+    /// Finish the active file and reopen it, then report back
+    Flush(SyncSender<io::Result<()>>),
+}
+
+/// Owned by the writer thread
+struct RecorderState {
+    config: RecorderConfig,
+    writer: Option<FrameWriter>,
+    /// Set after a failed write so the failure is logged once
+    failing: bool,
+}
+
+enum FrameWriter {
+    Plain(Counted<BufWriter<File>>),
+    Gzip(GzEncoder<Counted<File>>),
+}
+
+/// Counts the bytes written through it on top of the file's existing size
+struct Counted<W> {
+    inner: W,
+    bytes: u64,
+}
+
+impl<W: Write> Write for Counted<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.bytes += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl FrameWriter {
+    fn open(config: &RecorderConfig) -> io::Result<Self> {
+        let mut file = OpenOptions::new().create(true).read(true).append(true).open(&config.path)?;
+        let mut size = file.metadata()?.len();
+        let writer = if config.compress {
+            // Appending to a finished file starts a new gzip member
+            let file = Counted { inner: file, bytes: size };
+            FrameWriter::Gzip(GzEncoder::new(file, Compression::default()))
+        } else {
+            // Terminate a line cut short by a crash so it stays on its own
+            if size > 0 {
+                let mut last = [0u8];
+                file.seek(SeekFrom::End(-1))?;
+                file.read_exact(&mut last)?;
+                if last[0] != b'\n' {
+                    file.write_all(b"\n")?;
+                    size += 1;
+                }
+            }
+            FrameWriter::Plain(Counted { inner: BufWriter::new(file), bytes: size })
+        };
+        Ok(writer)
+    }
+
+    /// Bytes in the file once everything written so far is flushed
+    fn size(&self) -> u64 {
+        match self {
+            FrameWriter::Plain(file) => file.bytes,
+            FrameWriter::Gzip(encoder) => encoder.get_ref().bytes,
+        }
+    }
+
+    fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+        match self {
+            FrameWriter::Plain(file) => file.write_all(line),
+            FrameWriter::Gzip(encoder) => encoder.write_all(line),
+        }
+    }
+
+    /// Push buffered frames to the file; a flushed gzip stream can be read
+    /// up to this point
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            FrameWriter::Plain(file) => file.flush(),
+            FrameWriter::Gzip(encoder) => encoder.flush(),
+        }
+    }
+
+    fn finish(self) -> io::Result<()> {
+        match self {
+            FrameWriter::Plain(mut file) => file.flush(),
+            FrameWriter::Gzip(encoder) => encoder.finish().and_then(|mut file| file.flush()),
+        }
+    }
+}
+
+impl SessionRecorder {
+    /// Open (or append to) the recording file
+    pub fn open(config: RecorderConfig) -> Result<Self, SdkError> {
+        if let Some(parent) = config.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(|e| recording_error(&config.path, e))?;
+        }
+        // A gzip file left by an earlier run may end in an unfinished stream,
+        // which would hide anything appended after it
+        let unfinished = config.compress && fs::metadata(&config.path).is_ok_and(|m| m.len() > 0);
+        if unfinished {
+            move_aside(&config).map_err(|e| recording_error(&config.path, e))?;
+        }
+        let writer = FrameWriter::open(&config).map_err(|e| recording_error(&config.path, e))?;
+        let path = config.path.clone();
+        let state = RecorderState {
+            config,
+            writer: Some(writer),
+            failing: false,
+        };
+        let (commands, queue) = mpsc::channel();
+        thread::Builder::new()
+            .name("session-recorder".to_string())
+            .spawn(move || state.run(queue))
+            .map_err(|e| recording_error(&path, e))?;
+        Ok(Self {
+            shared: Arc::new(RecorderShared {
+                path,
+                commands: Mutex::new(commands),
+                next_connection: AtomicU64::new(1),
+            }),
+        })
+    }
+
+    /// Recorder for a new connection, with its own connection id
+    pub fn connection(&self) -> ConnectionRecorder {
+        ConnectionRecorder {
+            id: self.shared.next_connection.fetch_add(1, Ordering::Relaxed),
+            recorder: self.clone(),
+        }
+    }
+
+    /// Record one frame
+    ///
+    /// The frame is stamped now and queued for the writer thread.
+    pub fn record(&self, connection_id: u64, direction: Direction, message: &Message) {
+        if let Some(frame) = RecordedFrame::capture(connection_id, direction, message) {
+            self.send(Command::Write(frame));
+        }
+    }
+
+    /// Active recording file
+    pub fn path(&self) -> PathBuf {
+        self.shared.path.clone()
+    }
+
+    /// Write out every frame recorded so far, finishing the active file
+    /// (completing its gzip stream) and reopening it
+    ///
+    /// Blocks until the writer thread is done.
+    pub fn flush(&self) -> Result<(), SdkError> {
+        let (reply, done) = mpsc::sync_channel(1);
+        self.send(Command::Flush(reply));
+        match done.recv() {
+            Ok(result) => result.map_err(|e| recording_error(&self.shared.path, e)),
+            Err(_) => Err(SdkError::Configuration(format!(
+                "Recording {}: writer thread stopped", self.shared.path.display()
+            ))),
+        }
+    }
+
+    fn send(&self, command: Command) {
+        // Only fails if the writer thread panicked; flush() reports that
+        let _ = self.shared.commands.lock().unwrap().send(command);
+    }
+}
+
+impl RecorderState {
+    /// Write queued frames until every recorder handle is dropped
+    fn run(mut self, queue: Receiver<Command>) {
+        let mut flush_due: Option<Instant> = None;
+        loop {
+            let command = match flush_due {
+                Some(due) => queue.recv_timeout(due.saturating_duration_since(Instant::now())),
+                None => queue.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match command {
+                Ok(Command::Write(frame)) => {
+                    self.write(&frame);
+                    let due = *flush_due.get_or_insert_with(|| Instant::now() + FLUSH_INTERVAL);
+                    if Instant::now() >= due {
+                        self.flush_writer();
+                        flush_due = None;
+                    }
+                }
+                Ok(Command::Flush(reply)) => {
+                    let _ = reply.send(self.rotate(false));
+                    flush_due = None;
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    self.flush_writer();
+                    flush_due = None;
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        if let Some(writer) = self.writer.take() {
+            if let Err(e) = writer.finish() {
+                tracing::warn!("Failed to finish recording {}: {}", self.config.path.display(), e);
+            }
+        }
+    }
+
+    fn write(&mut self, frame: &RecordedFrame) {
+        let mut line = match serde_json::to_vec(frame) {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::warn!("Failed to serialize recorded frame: {}", e);
+                return;
+            }
+        };
+        line.push(b'\n');
+
+        let result = self.write_line(&line);
+        self.report(result);
+    }
+
+    fn flush_writer(&mut self) {
+        if let Some(writer) = &mut self.writer {
+            let result = writer.flush();
+            self.report(result);
+        }
+    }
+
+    /// Log the first of a run of failures
+    fn report(&mut self, result: io::Result<()>) {
+        match result {
+            Ok(()) => self.failing = false,
+            Err(e) => {
+                if !self.failing {
+                    tracing::warn!("Failed to record frame to {}: {}", self.config.path.display(), e);
+                }
+                self.failing = true;
+            }
+        }
+    }
+
+    fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+        let size = self.writer.as_ref().map_or(0, FrameWriter::size);
+        let full = self.config.max_file_bytes
+            .is_some_and(|max| size > 0 && size + line.len() as u64 > max);
+        if full || self.writer.is_none() {
+            self.rotate(full)?;
+        }
+        let writer = self.writer.as_mut().expect("writer opened above");
+        writer.write_line(line)
+    }
+
+    /// Reopen the active file, moving it aside first when `full`
+    fn rotate(&mut self, full: bool) -> io::Result<()> {
+        if let Some(writer) = self.writer.take() {
+            writer.finish()?;
+        }
+        if full {
+            move_aside(&self.config)?;
+        }
+        self.writer = Some(FrameWriter::open(&self.config)?);
+        Ok(())
+    }
+}
+
+impl fmt::Debug for SessionRecorder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionRecorder")
+            .field("path", &self.shared.path)
+            .finish()
+    }
+}
+
+/// Records the frames of one connection
+#[derive(Debug, Clone)]
+pub struct ConnectionRecorder {
+    id: u64,
+    recorder: SessionRecorder,
+}
+
+impl ConnectionRecorder {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn record_inbound(&self, message: &Message) {
+        self.recorder.record(self.id, Direction::Inbound, message);
+    }
+
+    pub fn record_outbound(&self, message: &Message) {
+        self.recorder.record(self.id, Direction::Outbound, message);
+    }
+}
+
+/// Sink that records every frame sent through it
+pub(crate) struct RecordingSink<S> {
+    inner: S,
+    recorder: Option<ConnectionRecorder>,
+}
+
+impl<S> RecordingSink<S> {
+    pub fn new(inner: S, recorder: Option<ConnectionRecorder>) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+impl<S: Sink<Message> + Unpin> Sink<Message> for RecordingSink<S> {
+    type Error = S::Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner).poll_ready(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: Message) -> Result<(), Self::Error> {
+        if let Some(recorder) = &self.recorder {
+            recorder.record_outbound(&item);
+        }
+        Pin::new(&mut self.inner).start_send(item)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner).poll_close(cx)
+    }
+}
+
+/// Read one recording file, plain or gzip
+///
+/// A truncated final line (from a crash mid-write) is skipped.
+pub fn read_recording(path: impl AsRef<Path>) -> Result<Vec<RecordedFrame>, SdkError> {
+    let path = path.as_ref();
+    let mut file = BufReader::new(File::open(path).map_err(|e| recording_error(path, e))?);
+    let compressed = file.fill_buf().map_err(|e| recording_error(path, e))?.starts_with(&[0x1f, 0x8b]);
+    let reader: Box<dyn Read> = if compressed {
+        Box::new(MultiGzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+
+    let mut lines = BufReader::new(reader).lines().peekable();
+    let mut frames = Vec::new();
+    let mut number = 0;
+    while let Some(line) = lines.next() {
+        number += 1;
+        let line = match line {
+            Ok(line) => line,
+            // A gzip stream still being written (or cut short) ends in an
+            // I/O error after its last flushed frame
+            Err(e) if compressed => {
+                tracing::warn!("Recording {} ends early: {}", path.display(), e);
+                break;
+            }
+            Err(e) => return Err(recording_error(path, e)),
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(frame) => frames.push(frame),
+            Err(e) if lines.peek().is_none() => tracing::warn!("Skipping truncated last line of {}: {}", path.display(), e),
+            Err(e) => {
+                return Err(SdkError::Configuration(format!(
+                    "Invalid frame on line {} of {}: {}", number, path.display(), e
+                )))
+            }
+        }
+    }
+    Ok(frames)
+}
+
+/// Every file of a recording, oldest first: rotated files, then the active one
+pub fn recording_segments(path: impl AsRef<Path>) -> Result<Vec<PathBuf>, SdkError> {
+    let path = path.as_ref();
+    let mut segments = rotated_segments(path).map_err(|e| recording_error(path, e))?;
+    if path.exists() {
+        segments.push(path.to_path_buf());
+    }
+    Ok(segments)
+}
+
+/// Read every file of a recording in order
+pub fn read_session(path: impl AsRef<Path>) -> Result<Vec<RecordedFrame>, SdkError> {
+    let mut frames = Vec::new();
+    for segment in recording_segments(path)? {
+        frames.extend(read_recording(segment)?);
+    }
+    Ok(frames)
+}
+
+/// Replace the value of every `"token"` string field with [`REDACTED`]
+pub fn redact_tokens(text: &str) -> Cow<'_, str> {
+    const KEY: &str = "\"token\"";
+    if !text.contains(KEY) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut redacted = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(found) = rest.find(KEY) {
+        let after_key = found + KEY.len();
+        redacted.push_str(&rest[..after_key]);
+        rest = &rest[after_key..];
+
+        let value = rest.trim_start();
+        let Some(value) = value.strip_prefix(':').map(str::trim_start) else { continue };
+        let Some(body) = value.strip_prefix('"') else { continue };
+        let Some(end) = string_end(body) else { continue };
+
+        redacted.push_str(&rest[..rest.len() - body.len()]);
+        redacted.push_str(REDACTED);
+        rest = &body[end..];
+    }
+    redacted.push_str(rest);
+    Cow::Owned(redacted)
+}
+
+/// Offset of the closing quote of a JSON string body
+fn string_end(body: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in body.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Rotate the active file and delete the oldest rotated files over the limit
+fn move_aside(config: &RecorderConfig) -> io::Result<()> {
+    let rotated = rotated_path(&config.path, Utc::now())?;
+    if let Err(e) = fs::rename(&config.path, &rotated) {
+        let _ = fs::remove_file(&rotated);
+        return Err(e);
+    }
+    if let Some(max_files) = config.max_files {
+        let segments = rotated_segments(&config.path)?;
+        let excess = segments.len().saturating_sub(max_files);
+        for old in &segments[..excess] {
+            fs::remove_file(old)?;
+        }
+    }
+    Ok(())
+}
+
+/// Claim a free rotated name: `dir/session.jsonl` becomes
+/// `dir/session.<timestamp>.jsonl`, or `dir/session.<timestamp>-<n>.jsonl`
+/// when that is taken
+///
+/// The name is claimed by creating an empty file, so rotations within the
+/// same millisecond, even from another recorder, never overwrite each other.
+fn rotated_path(path: &Path, at: DateTime<Utc>) -> io::Result<PathBuf> {
+    let (stem, extension) = split_name(path);
+    let base = format!("{}.{}", stem, at.format(ROTATION_FORMAT));
+    let mut n = 0;
+    loop {
+        let candidate = match n {
+            0 => path.with_file_name(format!("{}{}", base, extension)),
+            n => path.with_file_name(format!("{}-{}{}", base, n, extension)),
+        };
+        match OpenOptions::new().write(true).create_new(true).open(&candidate) {
+            Ok(_) => return Ok(candidate),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => n += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Rotated files of a recording, oldest first
+fn rotated_segments(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let (stem, extension) = split_name(path);
+    let prefix = format!("{}.", stem);
+    let dir = match path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        Some(dir) => dir.to_path_buf(),
+        None => PathBuf::from("."),
+    };
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    // Rotations within the same millisecond carry a counter
+    let mut segments: Vec<((String, u64), PathBuf)> = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Some(middle) = name.strip_prefix(&prefix).and_then(|n| n.strip_suffix(extension.as_str())) else {
+            continue;
+        };
+        let Some(suffix) = middle.get(..ROTATION_SUFFIX_LEN) else { continue };
+        if NaiveDateTime::parse_from_str(suffix, ROTATION_FORMAT).is_err() {
+            continue;
+        }
+        let counter = match &middle[ROTATION_SUFFIX_LEN..] {
+            "" => 0,
+            rest => match rest.strip_prefix('-').and_then(|n| n.parse().ok()) {
+                Some(n) => n,
+                None => continue,
+            },
+        };
+        segments.push(((suffix.to_string(), counter), entry.path()));
+    }
+    segments.sort();
+    Ok(segments.into_iter().map(|(_, path)| path).collect())
+}
+
+/// File stem and extension (with its dot, possibly empty)
+fn split_name(path: &Path) -> (String, String) {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let extension = path.extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    (stem, extension)
+}
+
+fn recording_error(path: &Path, e: io::Error) -> SdkError {
+    SdkError::Configuration(format!("Recording {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("kraken-recorder-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_round_trip_with_connection_ids() {
+        for compress in [false, true] {
+            let dir = temp_dir(if compress { "gzip" } else { "plain" });
+            let path = dir.join("session.jsonl");
+            let recorder = SessionRecorder::open(RecorderConfig::new(&path).with_compression(compress)).unwrap();
+
+            let first = recorder.connection();
+            let second = recorder.connection();
+            first.record_outbound(&Message::Text(r#"{"event":"ping"}"#.to_string()));
+            first.record_inbound(&Message::Ping(vec![1, 2, 3]));
+            second.record_inbound(&Message::Close(Some(CloseFrame {
+                code: CloseCode::Away,
+                reason: Cow::Borrowed("bye"),
+            })));
+            recorder.flush().unwrap();
+
+            let frames = read_recording(&path).unwrap();
+            assert_eq!(frames.len(), 3);
+            assert_eq!((frames[0].connection_id, frames[0].direction), (first.id(), Direction::Outbound));
+            assert_eq!(frames[1].to_message().unwrap(), Message::Ping(vec![1, 2, 3]));
+            assert_eq!(frames[2].connection_id, second.id());
+            assert_ne!(first.id(), second.id());
+            assert_eq!(frames[2].close_code, Some(1001));
+
+            // Reopening appends to plain files and rotates gzip files, whose
+            // stream was never finished here
+            let recorder = SessionRecorder::open(RecorderConfig::new(&path).with_compression(compress)).unwrap();
+            recorder.record(9, Direction::Inbound, &Message::Text("{}".to_string()));
+            recorder.flush().unwrap();
+            recorder.record(9, Direction::Inbound, &Message::Text("[]".to_string()));
+            recorder.flush().unwrap();
+            assert_eq!(recording_segments(&path).unwrap().len(), if compress { 2 } else { 1 });
+            let frames = read_session(&path).unwrap();
+            assert_eq!(frames.len(), 5);
+            assert_eq!(frames[4].data, "[]");
+
+            fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_redacts_tokens() {
+        let subscribe = r#"{"event":"subscribe","subscription":{"name":"ownTrades","token": "abc\"def"}}"#;
+        assert_eq!(
+            redact_tokens(subscribe),
+            r#"{"event":"subscribe","subscription":{"name":"ownTrades","token": "[REDACTED]"}}"#
+        );
+        let v2 = r#"{"method":"subscribe","params":{"channel":"executions","token":"xyz","snapshot":true}}"#;
+        assert_eq!(
+            redact_tokens(v2),
+            r#"{"method":"subscribe","params":{"channel":"executions","token":"[REDACTED]","snapshot":true}}"#
+        );
+        assert!(matches!(redact_tokens(r#"["token"]"#), Cow::Owned(_)));
+        assert_eq!(redact_tokens(r#"["token"]"#), r#"["token"]"#);
+        assert!(matches!(redact_tokens(r#"{"event":"heartbeat"}"#), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_rotates_and_prunes() {
+        let dir = temp_dir("rotate");
+        let path = dir.join("feed.jsonl");
+        let config = RecorderConfig::new(&path).with_max_file_bytes(300).with_max_files(2);
+        let recorder = SessionRecorder::open(config).unwrap();
+        let connection = recorder.connection();
+
+        for i in 0..20 {
+            connection.record_inbound(&Message::Text(format!(r#"{{"n":{}}}"#, i)));
+        }
+        recorder.flush().unwrap();
+
+        let segments = recording_segments(&path).unwrap();
+        assert_eq!(segments.len(), 3, "{:?}", segments);
+        assert_eq!(segments.last(), Some(&path));
+        for segment in &segments {
+            assert!(fs::metadata(segment).unwrap().len() <= 300);
+        }
+
+        // The newest frames survive, in order
+        let frames = read_session(&path).unwrap();
+        let last: Vec<String> = frames.iter().map(|f| f.data.clone()).collect();
+        assert_eq!(last.last().map(String::as_str), Some(r#"{"n":19}"#));
+        assert!(frames.windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    #[test]
+    fn test_rotations_in_the_same_millisecond_keep_their_own_files() {
+        let dir = temp_dir("same-millisecond");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("feed.jsonl");
+        let at = Utc::now();
+
+        let first = rotated_path(&path, at).unwrap();
+        let second = rotated_path(&path, at).unwrap();
+        assert_ne!(first, second);
+        assert!(first.exists() && second.exists());
+        assert_eq!(recording_segments(&path).unwrap(), vec![first, second]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_recording_is_written_when_the_recorder_is_dropped() {
+        let dir = temp_dir("drop");
+        let path = dir.join("session.jsonl");
+        let recorder = SessionRecorder::open(RecorderConfig::new(&path).with_compression(true)).unwrap();
+        recorder.record(1, Direction::Inbound, &Message::Text("{}".to_string()));
+        drop(recorder);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while read_recording(&path).map(|frames| frames.len()).unwrap_or(0) < 1 {
+            assert!(Instant::now() < deadline, "frame never written");
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,522 @@
+//! Deterministic replay of recorded sessions
+//!
+//! [`ReplayEngine`] feeds the inbound frames of a [`recording`] through the
+//! same [`MessageHandler`], [`OrderBookManager`] and [`EventDispatcher`]
+//! pipeline the live client uses. Before each frame its [`Clock`] is set
+//! to the frame's recorded receive time, so anything stamped or aged by
+//! that clock (the parser, and analytics built with `with_clock`) sees the
+//! same times on every replay, whatever the pacing:
+//!
+//! ```rust,ignore
+//! let mut replay = ReplayEngine::open("session.jsonl")?.with_pacing(Pacing::Accelerated(10.0));
+//! let flow = OrderFlowTracker::new().with_clock(replay.clock());
+//! // Register an EventCallback that passes each updated book to `flow`
+//! replay.dispatcher().register_callback(DataType::OrderBook, feed);
+//! replay.run().await;
+//! ```
+//!
+//! Playback can be paused, resumed and re-paced from another task through a
+//! [`ReplayControl`]; [`ReplayEngine::step`] and [`ReplayEngine::seek`]
+//! move through the recording frame by frame or to a point in time.
+//!
+//! Outbound frames are not sent anywhere. Book subscriptions among them set
+//! the local book depth, as subscribing does on the live client, so
+//! checksums verify the same way. A new connection id clears the books and
+//! sequences, as a reconnect does.
+//!
+//! [`recording`]: crate::recorder
+
+use crate::backpressure::BackpressureConfig;
+use crate::clock::Clock;
+use crate::error::SdkError;
+use crate::events::EventDispatcher;
+use crate::orderbook::{OrderBookManager, DEFAULT_BOOK_DEPTH};
+use crate::parser::{KrakenDataParser, MessageHandler};
+use crate::recorder::{read_session, Direction, FrameKind, RecordedFrame};
+use crate::sequencing::{SequenceConfig, SequenceManager};
+use crate::subscription::SubscriptionManager;
+use crate::symbols::SymbolNormalizer;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+/// How fast [`ReplayEngine::run`] feeds frames
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pacing {
+    /// Keep the recorded gaps between frames
+    RealTime,
+    /// Shrink the recorded gaps by this factor
+    Accelerated(f64),
+    /// No waiting between frames
+    AsFastAsPossible,
+}
+
+impl Pacing {
+    /// Playback speed relative to the recording; `None` means no waiting
+    fn speed(self) -> Option<f64> {
+        match self {
+            Pacing::RealTime => Some(1.0),
+            Pacing::Accelerated(speed) if speed.is_finite() && speed > 0.0 => Some(speed),
+            Pacing::Accelerated(_) | Pacing::AsFastAsPossible => None,
+        }
+    }
+}
+
+/// Pauses, resumes and re-paces a running replay
+///
+/// Clones control the same replay. A change takes effect before the next
+/// frame is fed.
+#[derive(Debug, Clone)]
+pub struct ReplayControl {
+    shared: Arc<ControlState>,
+}
+
+#[derive(Debug)]
+struct ControlState {
+    state: Mutex<Playback>,
+    changed: Notify,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Playback {
+    paused: bool,
+    pacing: Pacing,
+}
+
+impl ReplayControl {
+    fn new(pacing: Pacing) -> Self {
+        Self {
+            shared: Arc::new(ControlState {
+                state: Mutex::new(Playback { paused: false, pacing }),
+                changed: Notify::new(),
+            }),
+        }
+    }
+
+    pub fn pause(&self) {
+        self.update(|playback| playback.paused = true);
+    }
+
+    pub fn resume(&self) {
+        self.update(|playback| playback.paused = false);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.shared.state.lock().unwrap().paused
+    }
+
+    pub fn set_pacing(&self, pacing: Pacing) {
+        self.update(|playback| playback.pacing = pacing);
+    }
+
+    pub fn pacing(&self) -> Pacing {
+        self.shared.state.lock().unwrap().pacing
+    }
+
+    fn update(&self, apply: impl FnOnce(&mut Playback)) {
+        apply(&mut self.shared.state.lock().unwrap());
+        self.shared.changed.notify_waiters();
+    }
+
+    /// Wait until playback is not paused; returns whether it had to wait
+    async fn wait_while_paused(&self) -> bool {
+        let mut waited = false;
+        loop {
+            let changed = self.shared.changed.notified();
+            if !self.is_paused() {
+                return waited;
+            }
+            waited = true;
+            changed.await;
+        }
+    }
+}
+
+/// Replays recorded frames through the client's message pipeline
+pub struct ReplayEngine {
+    frames: Vec<RecordedFrame>,
+    /// Index of the next frame to feed
+    position: usize,
+    /// Connection of the last frame fed
+    connection: Option<u64>,
+    clock: Clock,
+    control: ReplayControl,
+    dispatcher: Arc<EventDispatcher>,
+    orderbook_manager: OrderBookManager,
+    subscription_manager: SubscriptionManager,
+    sequence_manager: Arc<SequenceManager>,
+    handler: MessageHandler,
+}
+
+impl ReplayEngine {
+    /// Replay `frames` in the order they were recorded
+    pub fn new(frames: Vec<RecordedFrame>) -> Self {
+        let start = frames.first().map(|frame| frame.timestamp).unwrap_or_default();
+        let clock = Clock::manual(start);
+        // Wall-clock rate limits would drop events depending on the pacing
+        let dispatcher = Arc::new(EventDispatcher::new().with_backpressure(BackpressureConfig {
+            max_messages_per_second: 0,
+            ..Default::default()
+        }));
+        let sequence_manager = Arc::new(SequenceManager::with_config(SequenceConfig::strict()));
+        {
+            let gaps = Arc::clone(&dispatcher);
+            sequence_manager.on_gap(move |gap| gaps.dispatch_gap(gap));
+            let resyncs = Arc::clone(&dispatcher);
+            sequence_manager.on_resync(move |resync| resyncs.dispatch_resync(resync));
+        }
+        let orderbook_manager = OrderBookManager::new();
+        let subscription_manager = SubscriptionManager::new();
+        let handler = Self::handler(&clock, &dispatcher, &orderbook_manager, &subscription_manager, &sequence_manager, None);
+
+        Self {
+            frames,
+            position: 0,
+            connection: None,
+            clock,
+            control: ReplayControl::new(Pacing::AsFastAsPossible),
+            dispatcher,
+            orderbook_manager,
+            subscription_manager,
+            sequence_manager,
+            handler,
+        }
+    }
+
+    /// Replay every file of a recording, rotated files first
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SdkError> {
+        Ok(Self::new(read_session(path)?))
+    }
+
+    pub fn with_pacing(self, pacing: Pacing) -> Self {
+        self.control.set_pacing(pacing);
+        self
+    }
+
+    /// Rewrite pair names on dispatched events, as
+    /// [`ClientConfig::symbols`](crate::data::ClientConfig::symbols) does
+    pub fn with_symbols(mut self, symbols: SymbolNormalizer) -> Self {
+        self.handler = Self::handler(
+            &self.clock,
+            &self.dispatcher,
+            &self.orderbook_manager,
+            &self.subscription_manager,
+            &self.sequence_manager,
+            Some(symbols),
+        );
+        self
+    }
+
+    fn handler(
+        clock: &Clock,
+        dispatcher: &Arc<EventDispatcher>,
+        orderbook_manager: &OrderBookManager,
+        subscription_manager: &SubscriptionManager,
+        sequence_manager: &Arc<SequenceManager>,
+        symbols: Option<SymbolNormalizer>,
+    ) -> MessageHandler {
+        let parser = Arc::new(KrakenDataParser::new().with_clock(clock.clone()));
+        let handler = MessageHandler::new(parser, Arc::clone(dispatcher))
+            .with_orderbook_manager(orderbook_manager.clone())
+            .with_subscription_manager(subscription_manager.clone())
+            .with_sequence_manager(Arc::clone(sequence_manager))
+            .with_clock(clock.clone());
+        match symbols {
+            Some(symbols) => handler.with_symbol_normalizer(symbols),
+            None => handler,
+        }
+    }
+
+    /// Set to each frame's receive time before it is fed
+    pub fn clock(&self) -> Clock {
+        self.clock.clone()
+    }
+
+    pub fn control(&self) -> ReplayControl {
+        self.control.clone()
+    }
+
+    /// Events of the replayed session
+    ///
+    /// Callbacks run as each frame is fed. Streams drop events once their
+    /// buffer is full, so a slow stream consumer sees a different subset
+    /// depending on the pacing.
+    pub fn dispatcher(&self) -> Arc<EventDispatcher> {
+        Arc::clone(&self.dispatcher)
+    }
+
+    pub fn orderbook_manager(&self) -> OrderBookManager {
+        self.orderbook_manager.clone()
+    }
+
+    /// Number of recorded frames, inbound and outbound
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Index of the next frame to feed
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.frames.len()
+    }
+
+    /// Receive time of the next frame
+    pub fn next_timestamp(&self) -> Option<DateTime<Utc>> {
+        self.frames.get(self.position).map(|frame| frame.timestamp)
+    }
+
+    /// Feed the next frame now, paused or not
+    pub async fn step(&mut self) -> Option<&RecordedFrame> {
+        let index = self.position;
+        let frame = self.frames.get(index)?.clone();
+        self.position += 1;
+        self.feed(&frame).await;
+        self.frames.get(index)
+    }
+
+    /// Feed frames, paced and honouring pauses, until the recording ends
+    ///
+    /// Returns the number of frames fed.
+    pub async fn run(&mut self) -> usize {
+        let control = self.control.clone();
+        let start = self.position;
+        // Wall time and recorded time of the frame pacing is measured from
+        let mut anchor: Option<(Instant, DateTime<Utc>)> = None;
+
+        while let Some(recorded) = self.next_timestamp() {
+            if control.wait_while_paused().await {
+                anchor = None;
+            }
+
+            if let Some(speed) = control.pacing().speed() {
+                match anchor {
+                    Some((wall, from)) => {
+                        let gap = (recorded - from).to_std().unwrap_or_default();
+                        let changed = control.shared.changed.notified();
+                        tokio::select! {
+                            _ = tokio::time::sleep_until(wall + gap.div_f64(speed)) => {}
+                            // Pausing or re-pacing restarts the measurement
+                            _ = changed => {
+                                anchor = None;
+                                continue;
+                            }
+                        }
+                    }
+                    None => anchor = Some((Instant::now(), recorded)),
+                }
+            } else {
+                anchor = None;
+            }
+
+            self.step().await;
+        }
+        self.position - start
+    }
+
+    /// Move to the first frame received at or after `to`
+    ///
+    /// Frames on the way are fed as fast as possible, so books and
+    /// analytics reach the state they had at `to`. Seeking backwards
+    /// rewinds first; see [`rewind`](Self::rewind). Returns the number of
+    /// frames fed.
+    pub async fn seek(&mut self, to: DateTime<Utc>) -> usize {
+        let behind = self.position > 0 && self.frames[self.position - 1].timestamp >= to;
+        if behind {
+            self.rewind();
+        }
+        let start = self.position;
+        while self.next_timestamp().is_some_and(|timestamp| timestamp < to) {
+            self.step().await;
+        }
+        self.position - start
+    }
+
+    /// Start over from the first frame
+    ///
+    /// Books, sequences and channel routes are cleared. Analytics fed from
+    /// the dispatcher keep their state and need resetting by the caller.
+    pub fn rewind(&mut self) {
+        self.position = 0;
+        self.connection = None;
+        self.reset_connection_state();
+        if let Some(first) = self.frames.first() {
+            self.clock.set(first.timestamp);
+        }
+    }
+
+    async fn feed(&mut self, frame: &RecordedFrame) {
+        if self.connection != Some(frame.connection_id) {
+            if self.connection.is_some() {
+                self.reset_connection_state();
+            }
+            self.connection = Some(frame.connection_id);
+        }
+        self.clock.set(frame.timestamp);
+
+        if frame.kind != FrameKind::Text {
+            return;
+        }
+        match frame.direction {
+            Direction::Outbound => self.apply_subscription(&frame.data),
+            Direction::Inbound => {
+                if let Err(e) = self.handler.handle_message(&frame.data).await {
+                    tracing::warn!("Failed to process replayed frame, continuing: {}", e);
+                }
+                // The live session answered resyncs; the recording holds the outcome
+                self.orderbook_manager.take_resync_requests();
+            }
+        }
+    }
+
+    /// Set book depth from a recorded v1 or v2 book subscription
+    fn apply_subscription(&self, text: &str) {
+        let Ok(request) = serde_json::from_str::<Value>(text) else {
+            return;
+        };
+        let (subscription, pairs) = if request["event"] == "subscribe" {
+            (&request["subscription"], &request["pair"])
+        } else if request["method"] == "subscribe" {
+            (&request["params"], &request["params"]["symbol"])
+        } else {
+            return;
+        };
+        let name = subscription.get("name").or_else(|| subscription.get("channel"));
+        if name.and_then(Value::as_str) != Some("book") {
+            return;
+        }
+
+        let depth = subscription["depth"].as_u64().unwrap_or(DEFAULT_BOOK_DEPTH as u64) as usize;
+        // Books are keyed by the pair name on the wire
+        for pair in pairs.as_array().into_iter().flatten().filter_map(Value::as_str) {
+            self.orderbook_manager.set_depth(pair, depth);
+        }
+    }
+
+    /// What a reconnect resets on the live client
+    fn reset_connection_state(&self) {
+        for symbol in self.orderbook_manager.get_symbols() {
+            self.orderbook_manager.clear_order_book(&symbol);
+        }
+        self.orderbook_manager.take_resync_requests();
+        self.sequence_manager.reset_all();
+        self.subscription_manager.clear_routes();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventCallback;
+    use crate::data::*;
+    use chrono::Duration;
+
+    fn frame(offset_ms: i64, connection_id: u64, direction: Direction, data: &str) -> RecordedFrame {
+        RecordedFrame {
+            timestamp: DateTime::from_timestamp(1_700_000_000, 0).unwrap() + Duration::milliseconds(offset_ms),
+            connection_id,
+            direction,
+            kind: FrameKind::Text,
+            data: data.to_string(),
+            close_code: None,
+        }
+    }
+
+    fn session() -> Vec<RecordedFrame> {
+        vec![
+            frame(0, 1, Direction::Outbound, r#"{"event":"subscribe","pair":["XBT/USD"],"subscription":{"name":"book","depth":25}}"#),
+            frame(10, 1, Direction::Inbound, r#"[1,{"as":[["5541.30000","2.50700000","1534614248.123678"]],"bs":[["5541.20000","1.52900000","1534614248.765567"]]},"book-25","XBT/USD"]"#),
+            frame(20, 1, Direction::Inbound, r#"[0,{"a":["5541.3","1","1.0"],"b":["5541.2","2","2.0"],"c":["5541.25","0.1"],"v":["100.0","200.0"],"p":["5541.2","5541.3"],"t":[10,20],"l":["5500.0","5500.0"],"h":["5600.0","5600.0"],"o":["5540.0","5540.0"]},"ticker","XBT/USD"]"#),
+            frame(40, 1, Direction::Inbound, r#"[1,{"b":[["5541.10000","3.00000000","1534614249.000000"]]},"book-25","XBT/USD"]"#),
+        ]
+    }
+
+    #[derive(Default)]
+    struct Tickers(Mutex<Vec<TickerData>>);
+
+    impl EventCallback for Tickers {
+        fn on_ticker(&self, data: TickerData) {
+            self.0.lock().unwrap().push(data);
+        }
+        fn on_orderbook(&self, _data: OrderBookUpdate) {}
+        fn on_trade(&self, _data: TradeData) {}
+        fn on_ohlc(&self, _data: OHLCData) {}
+        fn on_error(&self, _error: SdkError) {}
+        fn on_connection_state_change(&self, _state: ConnectionState) {}
+    }
+
+    #[tokio::test]
+    async fn test_replay_stamps_frames_with_recorded_time() {
+        let frames = session();
+        let mut replay = ReplayEngine::new(frames.clone());
+        let tickers = Arc::new(Tickers::default());
+        replay.dispatcher().register_callback(DataType::Ticker, tickers.clone());
+
+        assert_eq!(replay.run().await, 4);
+        assert!(replay.is_finished());
+        let tickers = tickers.0.lock().unwrap();
+        assert_eq!(tickers.len(), 1);
+        assert_eq!(tickers[0].timestamp, frames[2].timestamp);
+
+        let book = replay.orderbook_manager().get_order_book("XBT/USD").unwrap();
+        assert_eq!(book.bids.len(), 2);
+        assert_eq!(book.last_update, frames[3].timestamp);
+    }
+
+    #[tokio::test]
+    async fn test_seek_step_and_new_connection() {
+        let mut frames = session();
+        frames.push(frame(50, 2, Direction::Inbound, r#"{"event":"heartbeat"}"#));
+        let mut replay = ReplayEngine::new(frames.clone());
+
+        assert_eq!(replay.seek(frames[3].timestamp).await, 3);
+        assert_eq!(replay.next_timestamp(), Some(frames[3].timestamp));
+        assert_eq!(replay.clock().now(), frames[2].timestamp);
+        let book = replay.orderbook_manager().get_order_book("XBT/USD").unwrap();
+        assert_eq!(book.bids.len(), 1);
+
+        assert_eq!(replay.step().await.map(|f| f.connection_id), Some(1));
+        assert_eq!(replay.orderbook_manager().get_order_book("XBT/USD").unwrap().bids.len(), 2);
+
+        // A reconnect starts from empty books
+        replay.step().await;
+        assert!(replay.orderbook_manager().get_order_book("XBT/USD").is_none());
+
+        // Seeking backwards replays from the start
+        assert_eq!(replay.seek(frames[2].timestamp).await, 2);
+        assert_eq!(replay.orderbook_manager().get_order_book("XBT/USD").unwrap().bids.len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_pacing_and_pause() {
+        let mut replay = ReplayEngine::new(session()).with_pacing(Pacing::RealTime);
+        let control = replay.control();
+        let started = Instant::now();
+        replay.run().await;
+        assert_eq!(started.elapsed(), std::time::Duration::from_millis(40));
+
+        replay.rewind();
+        control.set_pacing(Pacing::Accelerated(4.0));
+        control.pause();
+        let resume = tokio::spawn({
+            let control = control.clone();
+            async move {
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                control.resume();
+            }
+        });
+        let started = Instant::now();
+        assert_eq!(replay.run().await, 4);
+        assert_eq!(started.elapsed(), std::time::Duration::from_millis(110));
+        resume.await.unwrap();
+    }
+}
@@ -3,12 +3,19 @@
 //! Provides authenticated access to Kraken's REST API for:
 //! - Account data (balances, positions, trade history)
 //! - Order management (place, cancel, edit orders)
-//! - Market data queries
+//!
+//! [`KrakenPublicClient`] covers the public market data endpoints without
+//! credentials, for bootstrapping books and backfilling history.
 
 use crate::auth::Credentials;
-use crate::error::SdkError;
+use crate::data::{OHLCData, OrderBookUpdate, PriceLevel, SpreadData, TickerData, TradeData, TradeSide};
+use crate::error::{ParseError, SdkError};
+use crate::instruments::{InstrumentRegistry, PrecisionPolicy};
 use crate::rate_limit::{AccountTier, EndpointCost, RateLimiter};
+use crate::symbols::{SymbolFormat, SymbolNormalizer, SymbolRegistry};
+use crate::system_status::{ExchangeStatus, SystemStatus, SystemStatusMonitor};
 use crate::trading::*;
+use chrono::{DateTime, TimeZone, Utc};
 use rust_decimal::Decimal;
 use serde::de::DeserializeOwned;
 use serde_json::Value;
@@ -23,6 +30,9 @@ pub struct KrakenRestClient {
     credentials: Credentials,
     rate_limiter: Arc<RateLimiter>,
     http_client: reqwest::Client,
+    status_monitor: Option<SystemStatusMonitor>,
+    instruments: Option<(InstrumentRegistry, PrecisionPolicy)>,
+    symbols: Option<SymbolNormalizer>,
 }
 
 impl KrakenRestClient {
@@ -37,6 +47,9 @@ impl KrakenRestClient {
             credentials,
             rate_limiter: Arc::new(RateLimiter::new(tier)),
             http_client: reqwest::Client::new(),
+            status_monitor: None,
+            instruments: None,
+            symbols: None,
         }
     }
 
@@ -51,6 +64,52 @@ impl KrakenRestClient {
         &self.rate_limiter
     }
 
+    /// Public market data client sharing this client's rate limiter
+    pub fn public(&self) -> KrakenPublicClient {
+        KrakenPublicClient {
+            rate_limiter: Arc::clone(&self.rate_limiter),
+            http_client: self.http_client.clone(),
+            symbols: self.symbols.clone(),
+        }
+    }
+
+    /// Refuse orders the trading engine would reject in its current mode
+    ///
+    /// With a monitor fed by a WebSocket connection (see
+    /// `KrakenWsClient::status_monitor`), orders fail locally with
+    /// [`SdkError::OrderRejected`] during maintenance and `cancel_only`, and
+    /// non-post-only orders fail during `post_only`. Cancels are never blocked.
+    pub fn with_status_monitor(mut self, status_monitor: SystemStatusMonitor) -> Self {
+        self.status_monitor = Some(status_monitor);
+        self
+    }
+
+    /// Check orders against pair rules before sending them
+    ///
+    /// Orders with a price off the tick size, a volume off the lot size, or
+    /// below `ordermin`/`costmin` fail locally with
+    /// [`SdkError::InvalidOrder`], or are rounded first with
+    /// [`PrecisionPolicy::Round`]. Pairs missing from the registry are sent
+    /// as they are.
+    pub fn with_instruments(mut self, registry: InstrumentRegistry, policy: PrecisionPolicy) -> Self {
+        self.instruments = Some((registry, policy));
+        self
+    }
+
+    /// Rewrite pair names on returned orders, fills and positions
+    ///
+    /// Order pairs may then be given in any form; they are sent to Kraken as
+    /// REST altnames.
+    pub fn with_symbols(mut self, symbols: SymbolNormalizer) -> Self {
+        self.symbols = Some(symbols);
+        self
+    }
+
+    /// Exchange status known to the attached monitor
+    pub fn exchange_status(&self) -> Option<ExchangeStatus> {
+        self.status_monitor.as_ref().and_then(|monitor| monitor.status())
+    }
+
     // ========== Account Endpoints ==========
 
     /// Get account balances
@@ -120,13 +179,14 @@ impl KrakenRestClient {
         
         let mut executions = Vec::new();
         for (trade_id, trade_data) in trades {
-            if let Ok(exec) = parse_execution(trade_id, trade_data) {
+            if let Ok(mut exec) = parse_execution(trade_id, trade_data) {
+                self.normalize_pair(&mut exec.pair);
                 executions.push(exec);
             }
         }
         
         // Sort by time descending
-        executions.sort_by(|a, b| b.time.cmp(&a.time));
+        executions.sort_by_key(|e| std::cmp::Reverse(e.time));
         
         Ok(executions)
     }
@@ -140,7 +200,8 @@ impl KrakenRestClient {
         
         let mut result = Vec::new();
         for (txid, order_data) in orders {
-            if let Ok(order) = parse_order(txid, order_data) {
+            if let Ok(mut order) = parse_order(txid, order_data) {
+                self.normalize_pair(&mut order.pair);
                 result.push(order);
             }
         }
@@ -169,7 +230,8 @@ impl KrakenRestClient {
         
         let mut result = Vec::new();
         for (txid, order_data) in orders {
-            if let Ok(order) = parse_order(txid, order_data) {
+            if let Ok(mut order) = parse_order(txid, order_data) {
+                self.normalize_pair(&mut order.pair);
                 result.push(order);
             }
         }
@@ -186,7 +248,8 @@ impl KrakenRestClient {
         
         let mut result = Vec::new();
         for (pos_id, pos_data) in positions {
-            if let Ok(position) = parse_position(pos_id, pos_data) {
+            if let Ok(mut position) = parse_position(pos_id, pos_data) {
+                self.normalize_pair(&mut position.pair);
                 result.push(position);
             }
         }
@@ -198,6 +261,17 @@ impl KrakenRestClient {
 
     /// Place a new order
     pub async fn add_order(&self, request: OrderRequest) -> Result<OrderResponse, SdkError> {
+        if let Some(monitor) = &self.status_monitor {
+            monitor.check_order(&request)?;
+        }
+        let mut request = match &self.instruments {
+            Some((registry, policy)) => registry.prepare(request, *policy)?,
+            None => request,
+        };
+        if let Some(symbols) = &self.symbols {
+            request.pair = symbols.registry.convert(&request.pair, SymbolFormat::AltName);
+        }
+        
         let params = request.to_params();
         let params_ref: Vec<(String, String)> = params.into_iter().collect();
         
@@ -236,6 +310,10 @@ impl KrakenRestClient {
 
     /// Edit an existing order
     pub async fn edit_order(&self, request: EditOrderRequest) -> Result<OrderResponse, SdkError> {
+        if let Some(status) = self.exchange_status() {
+            status.check_edit()?;
+        }
+        
         let mut params = vec![("txid".to_string(), request.txid)];
         
         if let Some(volume) = request.volume {
@@ -264,6 +342,13 @@ impl KrakenRestClient {
 
     // ========== Internal Methods ==========
 
+    /// Rewrite a pair name from a response, if a normalizer is attached
+    fn normalize_pair(&self, pair: &mut String) {
+        if let Some(symbols) = &self.symbols {
+            symbols.apply(pair);
+        }
+    }
+
     /// Make an authenticated private API request
     async fn private_request<T: DeserializeOwned>(
         &self,
@@ -302,37 +387,251 @@ impl KrakenRestClient {
             .await
             .map_err(|e| SdkError::Network(e.to_string()))?;
 
-        let status = response.status();
-        let body = response.text().await
-            .map_err(|e| SdkError::Network(e.to_string()))?;
+        read_response(endpoint, response).await
+    }
+}
 
-        tracing::debug!("Kraken API response [{}]: {}", endpoint, &body[..body.len().min(200)]);
+/// Read a Kraken REST response and extract its `result`
+async fn read_response<T: DeserializeOwned>(endpoint: &str, response: reqwest::Response) -> Result<T, SdkError> {
+    let status = response.status();
+    let body = response.text().await
+        .map_err(|e| SdkError::Network(e.to_string()))?;
 
-        // Parse response
-        let json: Value = serde_json::from_str(&body)
-            .map_err(|e| SdkError::Parse(crate::error::ParseError::InvalidJson(e.to_string())))?;
+    tracing::debug!("Kraken API response [{}]: {}", endpoint, &body[..body.len().min(200)]);
 
-        // Check for errors
-        if let Some(errors) = json["error"].as_array() {
-            if !errors.is_empty() {
-                let error_msgs: Vec<String> = errors
-                    .iter()
-                    .filter_map(|e| e.as_str().map(|s| s.to_string()))
-                    .collect();
-                return Err(SdkError::Network(error_msgs.join(", ")));
-            }
+    // Parse response
+    let json: Value = serde_json::from_str(&body)
+        .map_err(|e| SdkError::Parse(crate::error::ParseError::InvalidJson(e.to_string())))?;
+
+    // Check for errors
+    if let Some(errors) = json["error"].as_array() {
+        if !errors.is_empty() {
+            let error_msgs: Vec<String> = errors
+                .iter()
+                .filter_map(|e| e.as_str().map(|s| s.to_string()))
+                .collect();
+            return Err(SdkError::Network(error_msgs.join(", ")));
+        }
+    }
+
+    if !status.is_success() {
+        return Err(SdkError::Network(format!("HTTP {}: {}", status, body)));
+    }
+
+    // Extract result
+    let result = json.get("result")
+        .ok_or_else(|| SdkError::Parse(crate::error::ParseError::MissingField("result".to_string())))?;
+
+    serde_json::from_value(result.clone())
+        .map_err(|e| SdkError::Parse(crate::error::ParseError::InvalidDataType(e.to_string())))
+}
+
+/// Credential-free client for Kraken's public REST market data
+///
+/// Requests are charged to a [`RateLimiter`] as standard queries. Use
+/// [`KrakenRestClient::public`] to share one limiter with private calls.
+#[derive(Clone)]
+pub struct KrakenPublicClient {
+    rate_limiter: Arc<RateLimiter>,
+    http_client: reqwest::Client,
+    symbols: Option<SymbolNormalizer>,
+}
+
+impl KrakenPublicClient {
+    /// Create a public client with its own rate limiter
+    pub fn new() -> Self {
+        Self::with_rate_limiter(Arc::new(RateLimiter::new(AccountTier::default())))
+    }
+
+    /// Create a public client charging requests to an existing rate limiter
+    pub fn with_rate_limiter(rate_limiter: Arc<RateLimiter>) -> Self {
+        Self {
+            rate_limiter,
+            http_client: reqwest::Client::new(),
+            symbols: None,
+        }
+    }
+
+    /// Rewrite pair names on returned market data
+    ///
+    /// Pairs may then be given in any form; they are sent to Kraken as REST
+    /// altnames.
+    pub fn with_symbols(mut self, symbols: SymbolNormalizer) -> Self {
+        self.symbols = Some(symbols);
+        self
+    }
+
+    /// Get the rate limiter for monitoring
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &self.rate_limiter
+    }
+
+    /// Get Kraken's server time
+    pub async fn get_server_time(&self) -> Result<DateTime<Utc>, SdkError> {
+        let response: Value = self.public_request("Time", &[]).await?;
+        response["unixtime"].as_i64()
+            .and_then(|seconds| Utc.timestamp_opt(seconds, 0).single())
+            .ok_or_else(|| SdkError::Parse(ParseError::MissingField("unixtime".to_string())))
+    }
+
+    /// Get the trading engine status
+    ///
+    /// The result can be fed to [`SystemStatusMonitor::update`] before a
+    /// WebSocket connection reports one.
+    pub async fn get_system_status(&self) -> Result<SystemStatus, SdkError> {
+        let response: Value = self.public_request("SystemStatus", &[]).await?;
+        let raw_status = response["status"].as_str()
+            .ok_or_else(|| SdkError::Parse(ParseError::MissingField("status".to_string())))?;
+        Ok(SystemStatus {
+            status: ExchangeStatus::from_kraken(raw_status),
+            raw_status: raw_status.to_string(),
+            version: None,
+            connection_id: None,
+        })
+    }
+
+    /// Get asset info by Kraken asset name; all assets if `assets` is empty
+    pub async fn get_assets(&self, assets: &[&str]) -> Result<HashMap<String, AssetInfo>, SdkError> {
+        let params = list_param("asset", assets);
+        let response: HashMap<String, Value> = self.public_request("Assets", &params).await?;
+        Ok(response.into_iter()
+            .map(|(asset, data)| (asset.clone(), parse_asset(asset, &data)))
+            .collect())
+    }
+
+    /// Get tradable pairs by Kraken pair name; all pairs if `pairs` is empty
+    pub async fn get_asset_pairs(&self, pairs: &[&str]) -> Result<HashMap<String, AssetPair>, SdkError> {
+        let params = list_param("pair", pairs);
+        let response: HashMap<String, Value> = self.public_request("AssetPairs", &params).await?;
+        Ok(response.into_iter()
+            .map(|(pair, data)| (pair.clone(), parse_asset_pair(pair, &data)))
+            .collect())
+    }
+
+    /// Load every tradable pair into `registry`, returning how many
+    pub async fn load_instruments(&self, registry: &InstrumentRegistry) -> Result<usize, SdkError> {
+        let pairs = self.get_asset_pairs(&[]).await?;
+        registry.load_asset_pairs(pairs.values());
+        Ok(pairs.len())
+    }
+
+    /// Load every pair's names into `registry`, returning how many
+    pub async fn load_symbols(&self, registry: &SymbolRegistry) -> Result<usize, SdkError> {
+        let pairs = self.get_asset_pairs(&[]).await?;
+        registry.load_asset_pairs(pairs.values());
+        Ok(pairs.len())
+    }
+
+    /// Get tickers for one or more pairs
+    ///
+    /// Each ticker's `symbol` is the pair name Kraken keys the result by,
+    /// unless a normalizer is attached.
+    pub async fn get_ticker(&self, pairs: &[&str]) -> Result<Vec<TickerData>, SdkError> {
+        let pairs: Vec<String> = pairs.iter().map(|pair| self.request_pair(pair)).collect();
+        let params = list_param("pair", &pairs.iter().map(String::as_str).collect::<Vec<_>>());
+        let response: HashMap<String, Value> = self.public_request("Ticker", &params).await?;
+        Ok(response.iter()
+            .map(|(pair, data)| parse_ticker(&self.output_pair(pair), data))
+            .collect())
+    }
+
+    /// Get OHLC candles for a pair, oldest first
+    ///
+    /// `interval` is in minutes. Pass the returned `last` as `since` to
+    /// poll for newer candles.
+    pub async fn get_ohlc(&self, pair: &str, interval: u32, since: Option<u64>) -> Result<HistoryPage<OHLCData>, SdkError> {
+        let mut params = vec![
+            ("pair".to_string(), self.request_pair(pair)),
+            ("interval".to_string(), interval.to_string()),
+        ];
+        params.extend(since.map(|since| ("since".to_string(), since.to_string())));
+        let response: Value = self.public_request("OHLC", &params).await?;
+        history_page(&response, |symbol, entry| parse_ohlc(&self.output_pair(symbol), interval, entry))
+    }
+
+    /// Get the order book for a pair, up to `count` levels per side
+    ///
+    /// Returned as a snapshot that can seed
+    /// [`OrderBookManager::apply_snapshot`](crate::orderbook::OrderBookManager::apply_snapshot)
+    /// or [`apply_recovery_snapshot`](crate::orderbook::OrderBookManager::apply_recovery_snapshot).
+    pub async fn get_order_book(&self, pair: &str, count: Option<u32>) -> Result<OrderBookUpdate, SdkError> {
+        let mut params = vec![("pair".to_string(), self.request_pair(pair))];
+        params.extend(count.map(|count| ("count".to_string(), count.to_string())));
+        let response: HashMap<String, Value> = self.public_request("Depth", &params).await?;
+        let (symbol, book) = response.into_iter().next()
+            .ok_or_else(|| SdkError::Parse(ParseError::MissingField(pair.to_string())))?;
+        Ok(parse_depth(self.output_pair(&symbol), &book))
+    }
+
+    /// Get recent trades for a pair, oldest first
+    ///
+    /// Pass the returned `last` as `since` to page forward.
+    pub async fn get_recent_trades(&self, pair: &str, since: Option<u64>) -> Result<HistoryPage<TradeData>, SdkError> {
+        let mut params = vec![("pair".to_string(), self.request_pair(pair))];
+        params.extend(since.map(|since| ("since".to_string(), since.to_string())));
+        let response: Value = self.public_request("Trades", &params).await?;
+        history_page(&response, |symbol, entry| parse_trade(&self.output_pair(symbol), entry))
+    }
+
+    /// Get recent best bid/ask spreads for a pair, oldest first
+    ///
+    /// Kraken does not include volumes here, so `bid_volume` and
+    /// `ask_volume` are zero.
+    pub async fn get_recent_spreads(&self, pair: &str, since: Option<u64>) -> Result<HistoryPage<SpreadData>, SdkError> {
+        let mut params = vec![("pair".to_string(), self.request_pair(pair))];
+        params.extend(since.map(|since| ("since".to_string(), since.to_string())));
+        let response: Value = self.public_request("Spread", &params).await?;
+        history_page(&response, |symbol, entry| parse_spread(&self.output_pair(symbol), entry))
+    }
+
+    /// Pair name to send to Kraken
+    fn request_pair(&self, pair: &str) -> String {
+        match &self.symbols {
+            Some(symbols) => symbols.registry.convert(pair, SymbolFormat::AltName),
+            None => pair.to_string(),
         }
+    }
 
-        if !status.is_success() {
-            return Err(SdkError::Network(format!("HTTP {}: {}", status, body)));
+    /// Pair name to return to the caller
+    fn output_pair(&self, pair: &str) -> String {
+        match &self.symbols {
+            Some(symbols) => symbols.normalize(pair),
+            None => pair.to_string(),
         }
+    }
+
+    /// Make an unauthenticated public API request
+    async fn public_request<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        params: &[(String, String)],
+    ) -> Result<T, SdkError> {
+        self.rate_limiter.acquire(EndpointCost::Standard).await?;
+
+        let url = format!("{}/{}/public/{}", KRAKEN_API_URL, API_VERSION, endpoint);
+        let response = self.http_client
+            .get(&url)
+            .query(params)
+            .send()
+            .await
+            .map_err(|e| SdkError::Network(e.to_string()))?;
 
-        // Extract result
-        let result = json.get("result")
-            .ok_or_else(|| SdkError::Parse(crate::error::ParseError::MissingField("result".to_string())))?;
+        read_response(endpoint, response).await
+    }
+}
+
+impl Default for KrakenPublicClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        serde_json::from_value(result.clone())
-            .map_err(|e| SdkError::Parse(crate::error::ParseError::InvalidDataType(e.to_string())))
+impl std::fmt::Debug for KrakenPublicClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KrakenPublicClient")
+            .field("rate_limiter", &self.rate_limiter.stats())
+            .field("symbols", &self.symbols)
+            .finish()
     }
 }
 
@@ -354,8 +653,208 @@ pub struct ClosedOrdersOptions {
     pub offset: Option<u32>,
 }
 
+// ========== Market Data Types ==========
+
+/// Asset returned by the `Assets` endpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetInfo {
+    /// Kraken asset name, e.g. "XXBT"
+    pub asset: String,
+    /// Alternate name, e.g. "XBT"
+    pub altname: String,
+    /// Decimal places used for record keeping
+    pub decimals: u32,
+    /// Decimal places used for display
+    pub display_decimals: u32,
+    pub status: Option<String>,
+}
+
+/// Tradable pair returned by the `AssetPairs` endpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetPair {
+    /// Kraken pair name, e.g. "XXBTZUSD"
+    pub pair: String,
+    /// Alternate name, e.g. "XBTUSD"
+    pub altname: String,
+    /// WebSocket name, e.g. "XBT/USD"
+    pub wsname: Option<String>,
+    pub base: String,
+    pub quote: String,
+    /// Price precision; pass to `OrderBookManager::set_precision`
+    pub pair_decimals: u32,
+    /// Volume precision; pass to `OrderBookManager::set_precision`
+    pub lot_decimals: u32,
+    pub order_min: Option<Decimal>,
+    pub cost_min: Option<Decimal>,
+    pub tick_size: Option<Decimal>,
+    pub status: Option<String>,
+}
+
+/// Page of historical market data
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryPage<T> {
+    pub entries: Vec<T>,
+    /// Cursor to pass as `since` for the next page
+    pub last: u64,
+}
+
 // ========== Parsing Helpers ==========
 
+/// Comma-separated list parameter, omitted when empty
+fn list_param(name: &str, values: &[&str]) -> Vec<(String, String)> {
+    if values.is_empty() {
+        Vec::new()
+    } else {
+        vec![(name.to_string(), values.join(","))]
+    }
+}
+
+/// Split a `{"<pair>": [...], "last": n}` result and parse each entry
+fn history_page<T>(
+    response: &Value,
+    parse: impl Fn(&str, &Value) -> Option<T>,
+) -> Result<HistoryPage<T>, SdkError> {
+    let object = response.as_object()
+        .ok_or_else(|| SdkError::Parse(ParseError::InvalidDataType("Expected object".to_string())))?;
+
+    // Trades send `last` as a string of nanoseconds, the others as a number
+    let last = match &object.get("last") {
+        Some(Value::String(last)) => last.parse().ok(),
+        Some(last) => last.as_u64(),
+        None => None,
+    }.ok_or_else(|| SdkError::Parse(ParseError::MissingField("last".to_string())))?;
+
+    let (symbol, entries) = object.iter()
+        .find(|(key, _)| key.as_str() != "last")
+        .ok_or_else(|| SdkError::Parse(ParseError::MissingField("pair".to_string())))?;
+    let entries = entries.as_array()
+        .ok_or_else(|| SdkError::Parse(ParseError::InvalidDataType("Expected array".to_string())))?;
+
+    Ok(HistoryPage {
+        entries: entries.iter().filter_map(|entry| parse(symbol, entry)).collect(),
+        last,
+    })
+}
+
+fn parse_asset(asset: String, data: &Value) -> AssetInfo {
+    AssetInfo {
+        altname: data["altname"].as_str().unwrap_or(&asset).to_string(),
+        asset,
+        decimals: data["decimals"].as_u64().unwrap_or(0) as u32,
+        display_decimals: data["display_decimals"].as_u64().unwrap_or(0) as u32,
+        status: data["status"].as_str().map(|s| s.to_string()),
+    }
+}
+
+fn parse_asset_pair(pair: String, data: &Value) -> AssetPair {
+    let optional_decimal = |key: &str| data[key].as_str().and_then(|s| s.parse().ok());
+    AssetPair {
+        altname: data["altname"].as_str().unwrap_or(&pair).to_string(),
+        pair,
+        wsname: data["wsname"].as_str().map(|s| s.to_string()),
+        base: data["base"].as_str().unwrap_or("").to_string(),
+        quote: data["quote"].as_str().unwrap_or("").to_string(),
+        pair_decimals: data["pair_decimals"].as_u64().unwrap_or(0) as u32,
+        lot_decimals: data["lot_decimals"].as_u64().unwrap_or(0) as u32,
+        order_min: optional_decimal("ordermin"),
+        cost_min: optional_decimal("costmin"),
+        tick_size: optional_decimal("tick_size"),
+        status: data["status"].as_str().map(|s| s.to_string()),
+    }
+}
+
+/// Ticker with `[today, last24h]` arrays, except `o` which is today's open
+fn parse_ticker(pair: &str, data: &Value) -> TickerData {
+    let field = |key: &str, index: usize| data[key][index].as_str().and_then(|s| s.parse().ok());
+    TickerData {
+        symbol: pair.to_string(),
+        bid: field("b", 0).unwrap_or_default(),
+        ask: field("a", 0).unwrap_or_default(),
+        last_price: field("c", 0).unwrap_or_default(),
+        volume: field("v", 0).unwrap_or_default(),
+        timestamp: Utc::now(),
+        bid_qty: field("b", 2).unwrap_or_default(),
+        ask_qty: field("a", 2).unwrap_or_default(),
+        last_qty: field("c", 1),
+        volume_24h: field("v", 1).unwrap_or_default(),
+        vwap_today: field("p", 0),
+        vwap_24h: field("p", 1).unwrap_or_default(),
+        trades_today: data["t"][0].as_u64(),
+        trades_24h: data["t"][1].as_u64(),
+        low_today: field("l", 0),
+        low_24h: field("l", 1).unwrap_or_default(),
+        high_today: field("h", 0),
+        high_24h: field("h", 1).unwrap_or_default(),
+        open_today: data["o"].as_str().and_then(|s| s.parse().ok()),
+        open_24h: Decimal::ZERO,
+    }
+}
+
+/// OHLC entry: [time, open, high, low, close, vwap, volume, count]
+fn parse_ohlc(symbol: &str, interval: u32, entry: &Value) -> Option<OHLCData> {
+    let field = |index: usize| entry[index].as_str().and_then(|s| s.parse().ok());
+    Some(OHLCData {
+        symbol: symbol.to_string(),
+        open: field(1)?,
+        high: field(2)?,
+        low: field(3)?,
+        close: field(4)?,
+        volume: field(6)?,
+        timestamp: Utc.timestamp_opt(entry[0].as_i64()?, 0).single()?,
+        interval: interval.to_string(),
+        backfilled: false,
+    })
+}
+
+/// Depth result: {"asks": [[price, volume, timestamp]], "bids": [...]}
+fn parse_depth(symbol: String, book: &Value) -> OrderBookUpdate {
+    let levels = |side: &str| book[side].as_array()
+        .map(|levels| levels.iter()
+            .filter_map(|level| Some(PriceLevel {
+                price: level[0].as_str()?.parse().ok()?,
+                volume: level[1].as_str()?.parse().ok()?,
+                timestamp: parse_timestamp(level[2].as_f64()),
+            }))
+            .collect())
+        .unwrap_or_default();
+    OrderBookUpdate {
+        bids: levels("bids"),
+        asks: levels("asks"),
+        symbol,
+        timestamp: Utc::now(),
+        checksum: None,
+    }
+}
+
+/// Trade entry: [price, volume, time, side, orderType, misc, tradeId]
+fn parse_trade(symbol: &str, entry: &Value) -> Option<TradeData> {
+    Some(TradeData {
+        symbol: symbol.to_string(),
+        price: entry[0].as_str()?.parse().ok()?,
+        volume: entry[1].as_str()?.parse().ok()?,
+        side: match entry[3].as_str()? {
+            "b" => TradeSide::Buy,
+            "s" => TradeSide::Sell,
+            _ => return None,
+        },
+        timestamp: parse_timestamp(entry[2].as_f64()),
+        trade_id: entry[6].as_u64().map(|id| id.to_string()).unwrap_or_default(),
+        backfilled: false,
+    })
+}
+
+/// Spread entry: [time, bid, ask]
+fn parse_spread(symbol: &str, entry: &Value) -> Option<SpreadData> {
+    Some(SpreadData {
+        symbol: symbol.to_string(),
+        bid: entry[1].as_str()?.parse().ok()?,
+        ask: entry[2].as_str()?.parse().ok()?,
+        bid_volume: Decimal::ZERO,
+        ask_volume: Decimal::ZERO,
+        timestamp: Utc.timestamp_opt(entry[0].as_i64()?, 0).single()?,
+    })
+}
+
 fn parse_execution(trade_id: &str, data: &Value) -> Result<Execution, SdkError> {
     Ok(Execution {
         trade_id: trade_id.to_string(),
@@ -411,7 +910,6 @@ fn parse_decimal(s: Option<&str>) -> Decimal {
 }
 
 fn parse_timestamp(ts: Option<f64>) -> chrono::DateTime<chrono::Utc> {
-    use chrono::TimeZone;
     ts.map(|t| chrono::Utc.timestamp_opt(t as i64, ((t.fract()) * 1_000_000_000.0) as u32).unwrap())
         .unwrap_or_else(chrono::Utc::now)
 }
@@ -444,6 +942,94 @@ impl std::fmt::Debug for KrakenRestClient {
         f.debug_struct("KrakenRestClient")
             .field("credentials", &self.credentials)
             .field("rate_limiter", &self.rate_limiter.stats())
+            .field("exchange_status", &self.exchange_status())
+            .field("instruments", &self.instruments)
+            .field("symbols", &self.symbols)
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_ticker() {
+        let data = json!({
+            "a": ["30300.10000", "1", "1.000"],
+            "b": ["30300.00000", "1", "1.000"],
+            "c": ["30303.20000", "0.00067643"],
+            "v": ["4083.67001100", "4412.73601799"],
+            "p": ["30706.77771", "30689.13205"],
+            "t": [34619, 38907],
+            "l": ["29868.30000", "29868.30000"],
+            "h": ["31631.00000", "31631.00000"],
+            "o": "30502.80000"
+        });
+        let ticker = parse_ticker("XXBTZUSD", &data);
+        assert_eq!(ticker.symbol, "XXBTZUSD");
+        assert_eq!((ticker.bid, ticker.ask), (dec!(30300.00000), dec!(30300.10000)));
+        assert_eq!(ticker.last_qty, Some(dec!(0.00067643)));
+        assert_eq!(ticker.volume_24h, dec!(4412.73601799));
+        assert_eq!((ticker.trades_today, ticker.trades_24h), (Some(34619), Some(38907)));
+        assert_eq!(ticker.open_today, Some(dec!(30502.80000)));
+    }
+
+    #[test]
+    fn test_history_pages() {
+        let trades = json!({
+            "XXBTZUSD": [["30243.40000", "0.34507674", 1688669448.2148, "s", "l", "", 61044952]],
+            "last": "1688671969564286520"
+        });
+        let page = history_page(&trades, parse_trade).unwrap();
+        assert_eq!(page.last, 1688671969564286520);
+        assert_eq!(page.entries[0].side, TradeSide::Sell);
+        assert_eq!(page.entries[0].trade_id, "61044952");
+        assert_eq!(page.entries[0].timestamp.timestamp(), 1688669448);
+
+        let candles = json!({
+            "XXBTZUSD": [[1688671200, "30306.1", "30306.2", "30305.7", "30305.7", "30306.1", "3.39243896", 23]],
+            "last": 1688672160
+        });
+        let page = history_page(&candles, |symbol, entry| parse_ohlc(symbol, 15, entry)).unwrap();
+        assert_eq!(page.last, 1688672160);
+        assert_eq!((page.entries[0].close, page.entries[0].volume), (dec!(30305.7), dec!(3.39243896)));
+        assert_eq!(page.entries[0].interval, "15");
+
+        let spreads = json!({"XXBTZUSD": [[1688671834, "30292.10000", "30297.50000"]], "last": 1688672106});
+        let page = history_page(&spreads, parse_spread).unwrap();
+        assert_eq!(page.entries[0].spread(), dec!(5.40000));
+
+        assert!(history_page(&json!({"XXBTZUSD": []}), parse_spread).is_err());
+    }
+
+    #[test]
+    fn test_parse_depth_and_pairs() {
+        let book = json!({
+            "asks": [["30384.10000", "2.059", 1688671659]],
+            "bids": [["30297.00000", "0.115", 1688671656], ["30296.70000", "0.002", 1688671674]]
+        });
+        let update = parse_depth("XXBTZUSD".to_string(), &book);
+        assert_eq!((update.bids.len(), update.asks.len()), (2, 1));
+        assert_eq!(update.bids[0].price, dec!(30297.00000));
+        assert_eq!(update.checksum, None);
+
+        let pair = parse_asset_pair("XXBTZUSD".to_string(), &json!({
+            "altname": "XBTUSD", "wsname": "XBT/USD", "base": "XXBT", "quote": "ZUSD",
+            "pair_decimals": 1, "lot_decimals": 8, "ordermin": "0.0001", "tick_size": "0.1", "status": "online"
+        }));
+        assert_eq!(pair.wsname.as_deref(), Some("XBT/USD"));
+        assert_eq!((pair.pair_decimals, pair.lot_decimals), (1, 8));
+        assert_eq!(pair.order_min, Some(dec!(0.0001)));
+        assert_eq!(pair.cost_min, None);
+    }
+
+    #[test]
+    fn test_public_client_shares_rate_limiter() {
+        let client = KrakenRestClient::new(Credentials::new("key", "c2VjcmV0").unwrap());
+        let public = client.public();
+        assert!(std::ptr::eq(public.rate_limiter(), client.rate_limiter()));
+    }
+}
@@ -1,12 +1,25 @@
 //! Kraken WebSocket SDK - Clean Public API
 //! 
 //! Minimal but powerful API surface for real-time cryptocurrency data.
+//!
+//! `connect()` opens the socket and returns; a [`KrakenWsClient`] then
+//! subscribes to every registered pair and a background task invokes the
+//! registered closures for its events. Lost connections are re-established
+//! by the client with exponential backoff when `auto_reconnect` is enabled,
+//! and subscriptions are replayed on the new socket.
 
 use crate::{
-    data::{TickerData, TradeData, OrderBookUpdate, OHLCData, ConnectionState},
+    client::{ClientHandle, KrakenWsClient},
+    data::{
+        Channel, ClientConfig, ConnectionState, OHLCData, OrderBookUpdate, ProtocolVersion,
+        ReconnectConfig, TickerData, TradeData,
+    },
     error::SdkError,
+    events::SdkEvent,
+    symbols::{SymbolNormalizer, SymbolRegistry},
 };
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinHandle;
 
 /// Callback for ticker data updates
 pub type TickerCallback = Arc<dyn Fn(TickerData) + Send + Sync>;
@@ -29,16 +42,20 @@ pub type ErrorCallback = Arc<dyn Fn(SdkError) + Send + Sync>;
 /// Builder for creating KrakenSDK instances
 pub struct KrakenSDKBuilder {
     endpoint: String,
+    protocol: ProtocolVersion,
     auto_reconnect: bool,
     max_reconnect_attempts: u32,
+    symbols: Option<SymbolNormalizer>,
 }
 
 impl KrakenSDKBuilder {
     pub fn new() -> Self {
         Self {
-            endpoint: "wss://ws.kraken.com".to_string(),
+            endpoint: ProtocolVersion::V1.default_endpoint().to_string(),
+            protocol: ProtocolVersion::V1,
             auto_reconnect: true,
             max_reconnect_attempts: 10,
+            symbols: None,
         }
     }
     
@@ -47,6 +64,15 @@ impl KrakenSDKBuilder {
         self
     }
     
+    /// Select the WebSocket protocol version (switches a default endpoint too)
+    pub fn protocol(mut self, protocol: ProtocolVersion) -> Self {
+        if self.endpoint == self.protocol.default_endpoint() {
+            self.endpoint = protocol.default_endpoint().to_string();
+        }
+        self.protocol = protocol;
+        self
+    }
+    
     pub fn auto_reconnect(mut self, enabled: bool) -> Self {
         self.auto_reconnect = enabled;
         self
@@ -57,8 +83,14 @@ impl KrakenSDKBuilder {
         self
     }
     
+    /// Deliver pair names in `symbols`' format (see [`ClientConfig::symbols`])
+    pub fn symbols(mut self, symbols: SymbolNormalizer) -> Self {
+        self.symbols = Some(symbols);
+        self
+    }
+    
     pub fn build(self) -> KrakenSDK {
-        KrakenSDK::new(self.endpoint, self.auto_reconnect, self.max_reconnect_attempts)
+        KrakenSDK::with_protocol(self.endpoint, self.protocol, self.auto_reconnect, self.max_reconnect_attempts, self.symbols)
     }
 }
 
@@ -71,35 +103,76 @@ impl Default for KrakenSDKBuilder {
 /// Main SDK interface - minimal but powerful
 pub struct KrakenSDK {
     endpoint: String,
-    auto_reconnect: bool,
-    max_reconnect_attempts: u32,
-    ticker_callbacks: std::sync::Mutex<Vec<(String, TickerCallback)>>,
-    orderbook_callbacks: std::sync::Mutex<Vec<(String, u32, OrderBookCallback)>>,
-    trade_callbacks: std::sync::Mutex<Vec<(String, TradeCallback)>>,
-    reconnect_callback: std::sync::Mutex<Option<ReconnectCallback>>,
-    error_callback: std::sync::Mutex<Option<ErrorCallback>>,
-    connection_state: std::sync::Mutex<ConnectionState>,
+    protocol: ProtocolVersion,
+    callbacks: Callbacks,
+    client: tokio::sync::Mutex<KrakenWsClient>,
+    /// Client access for the synchronous API, refreshed on (dis)connect
+    handle: Mutex<ClientHandle>,
+    /// Delivers the client's events to the registered callbacks
+    delivery: Mutex<Option<JoinHandle<()>>>,
+}
+
+/// Callbacks registered on the SDK, shared with the delivery task
+#[derive(Clone)]
+struct Callbacks {
+    /// Resolves subscribed and delivered pair names to one form
+    symbols: SymbolNormalizer,
+    ticker: Arc<Mutex<Vec<(String, TickerCallback)>>>,
+    orderbook: Arc<Mutex<Vec<(String, u32, OrderBookCallback)>>>,
+    trade: Arc<Mutex<Vec<(String, TradeCallback)>>>,
+    reconnect: Arc<Mutex<Option<ReconnectCallback>>>,
+    error: Arc<Mutex<Option<ErrorCallback>>>,
 }
 
 impl KrakenSDK {
     /// Create new SDK instance
     pub fn new(endpoint: String, auto_reconnect: bool, max_reconnect_attempts: u32) -> Self {
+        Self::with_protocol(endpoint, ProtocolVersion::V1, auto_reconnect, max_reconnect_attempts, None)
+    }
+    
+    fn with_protocol(
+        endpoint: String,
+        protocol: ProtocolVersion,
+        auto_reconnect: bool,
+        max_reconnect_attempts: u32,
+        symbols: Option<SymbolNormalizer>,
+    ) -> Self {
+        // Callbacks match pairs by the same registry the client names them by
+        let registry = symbols.as_ref()
+            .map(|symbols| symbols.registry.clone())
+            .unwrap_or_default();
+        let config = ClientConfig {
+            endpoint: endpoint.clone(),
+            protocol,
+            auto_reconnect,
+            reconnect_config: ReconnectConfig {
+                max_attempts: max_reconnect_attempts,
+                ..Default::default()
+            },
+            symbols,
+            ..Default::default()
+        };
+        let client = KrakenWsClient::new(config);
+        let handle = client.handle();
+        
         Self {
             endpoint,
-            auto_reconnect,
-            max_reconnect_attempts,
-            ticker_callbacks: std::sync::Mutex::new(Vec::new()),
-            orderbook_callbacks: std::sync::Mutex::new(Vec::new()),
-            trade_callbacks: std::sync::Mutex::new(Vec::new()),
-            reconnect_callback: std::sync::Mutex::new(None),
-            error_callback: std::sync::Mutex::new(None),
-            connection_state: std::sync::Mutex::new(ConnectionState::Disconnected),
+            protocol,
+            callbacks: Callbacks::new(registry),
+            client: tokio::sync::Mutex::new(client),
+            handle: Mutex::new(handle),
+            delivery: Mutex::new(None),
         }
     }
     
-    /// Create SDK with default settings
-    pub fn default() -> Self {
-        KrakenSDKBuilder::new().build()
+    /// WebSocket endpoint this SDK connects to
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+    
+    /// WebSocket protocol version
+    pub fn protocol(&self) -> ProtocolVersion {
+        self.protocol
     }
     
     /// Subscribe to ticker updates for a trading pair
@@ -115,8 +188,11 @@ impl KrakenSDK {
     where
         F: Fn(TickerData) + Send + Sync + 'static
     {
-        let mut callbacks = self.ticker_callbacks.lock().unwrap();
+        let mut callbacks = self.callbacks.ticker.lock().unwrap();
         callbacks.push((pair.to_string(), Arc::new(callback)));
+        drop(callbacks);
+        
+        self.send_subscription(Channel::new("ticker").with_symbol(pair));
         self
     }
     
@@ -133,8 +209,11 @@ impl KrakenSDK {
     where
         F: Fn(OrderBookUpdate) + Send + Sync + 'static
     {
-        let mut callbacks = self.orderbook_callbacks.lock().unwrap();
+        let mut callbacks = self.callbacks.orderbook.lock().unwrap();
         callbacks.push((pair.to_string(), depth, Arc::new(callback)));
+        drop(callbacks);
+        
+        self.send_subscription(Channel::new("book").with_symbol(pair).with_depth(depth));
         self
     }
     
@@ -151,25 +230,40 @@ impl KrakenSDK {
     where
         F: Fn(TradeData) + Send + Sync + 'static
     {
-        let mut callbacks = self.trade_callbacks.lock().unwrap();
+        let mut callbacks = self.callbacks.trade.lock().unwrap();
         callbacks.push((pair.to_string(), Arc::new(callback)));
+        drop(callbacks);
+        
+        self.send_subscription(Channel::new("trade").with_symbol(pair));
         self
     }
     
     /// Unsubscribe from a trading pair
     pub fn unsubscribe(&self, pair: &str) -> &Self {
+        let channels: Vec<Channel> = self.callbacks.channels()
+            .into_iter()
+            .filter(|c| c.symbol.as_deref() == Some(pair))
+            .collect();
+        
         {
-            let mut callbacks = self.ticker_callbacks.lock().unwrap();
+            let mut callbacks = self.callbacks.ticker.lock().unwrap();
             callbacks.retain(|(p, _)| p != pair);
         }
         {
-            let mut callbacks = self.orderbook_callbacks.lock().unwrap();
+            let mut callbacks = self.callbacks.orderbook.lock().unwrap();
             callbacks.retain(|(p, _, _)| p != pair);
         }
         {
-            let mut callbacks = self.trade_callbacks.lock().unwrap();
+            let mut callbacks = self.callbacks.trade.lock().unwrap();
             callbacks.retain(|(p, _)| p != pair);
         }
+        
+        let handle = self.handle.lock().unwrap().clone();
+        for channel in channels {
+            if let Err(e) = handle.unsubscribe(std::slice::from_ref(&channel)) {
+                tracing::debug!("Not unsubscribing {} {}: {}", channel.name, pair, e);
+            }
+        }
         self
     }
     
@@ -186,7 +280,7 @@ impl KrakenSDK {
     where
         F: Fn(u32) + Send + Sync + 'static
     {
-        let mut callback = self.reconnect_callback.lock().unwrap();
+        let mut callback = self.callbacks.reconnect.lock().unwrap();
         *callback = Some(Arc::new(handler));
         self
     }
@@ -196,53 +290,73 @@ impl KrakenSDK {
     where
         F: Fn(SdkError) + Send + Sync + 'static
     {
-        let mut callback = self.error_callback.lock().unwrap();
+        let mut callback = self.callbacks.error.lock().unwrap();
         *callback = Some(Arc::new(handler));
         self
     }
     
     /// Connect to Kraken WebSocket API
+    ///
+    /// Returns once the socket is open; data is delivered to the registered
+    /// callbacks from a background task until [`disconnect`](Self::disconnect).
+    /// Connecting again after reconnection gave up starts a new connection.
     pub async fn connect(&self) -> Result<(), SdkError> {
-        *self.connection_state.lock().unwrap() = ConnectionState::Connecting;
+        let mut client = self.client.lock().await;
+        if client.is_running() {
+            return Ok(());
+        }
         
-        // Connection logic delegated to internal implementation
-        // This would use the existing connection manager
+        {
+            let mut delivery = self.delivery.lock().unwrap();
+            if delivery.as_ref().map_or(true, |task| task.is_finished()) {
+                let mut events = client.events();
+                let callbacks = self.callbacks.clone();
+                *delivery = Some(tokio::spawn(async move {
+                    while let Some(event) = events.recv().await {
+                        callbacks.dispatch(event);
+                    }
+                }));
+            }
+        }
         
-        *self.connection_state.lock().unwrap() = ConnectionState::Connected;
-        Ok(())
+        let result = client.connect().await;
+        *self.handle.lock().unwrap() = client.handle();
+        result
     }
     
     /// Disconnect from Kraken WebSocket API
     pub async fn disconnect(&self) -> Result<(), SdkError> {
-        *self.connection_state.lock().unwrap() = ConnectionState::Disconnected;
-        Ok(())
+        let mut client = self.client.lock().await;
+        let result = client.disconnect().await;
+        *self.handle.lock().unwrap() = client.handle();
+        result
     }
     
     /// Check if connected
     pub fn is_connected(&self) -> bool {
-        matches!(*self.connection_state.lock().unwrap(), ConnectionState::Connected)
+        matches!(self.state(), ConnectionState::Connected)
     }
     
     /// Get current connection state
     pub fn state(&self) -> ConnectionState {
-        self.connection_state.lock().unwrap().clone()
+        self.handle.lock().unwrap().connection_state()
     }
     
     /// Get subscribed pairs
     pub fn subscribed_pairs(&self) -> Vec<String> {
         let mut pairs = Vec::new();
         
-        for (pair, _) in self.ticker_callbacks.lock().unwrap().iter() {
+        for (pair, _) in self.callbacks.ticker.lock().unwrap().iter() {
             if !pairs.contains(pair) {
                 pairs.push(pair.clone());
             }
         }
-        for (pair, _, _) in self.orderbook_callbacks.lock().unwrap().iter() {
+        for (pair, _, _) in self.callbacks.orderbook.lock().unwrap().iter() {
             if !pairs.contains(pair) {
                 pairs.push(pair.clone());
             }
         }
-        for (pair, _) in self.trade_callbacks.lock().unwrap().iter() {
+        for (pair, _) in self.callbacks.trade.lock().unwrap().iter() {
             if !pairs.contains(pair) {
                 pairs.push(pair.clone());
             }
@@ -251,34 +365,126 @@ impl KrakenSDK {
         pairs
     }
     
-    // Internal: dispatch ticker data to callbacks
-    pub(crate) fn dispatch_ticker(&self, data: TickerData) {
-        let callbacks = self.ticker_callbacks.lock().unwrap();
-        for (pair, callback) in callbacks.iter() {
-            if pair == &data.symbol || pair == "*" {
-                callback(data.clone());
+    /// Subscribe on the live connection, or queue until `connect`
+    fn send_subscription(&self, channel: Channel) {
+        if channel.symbol.as_deref() == Some("*") {
+            return;
+        }
+        
+        let handle = self.handle.lock().unwrap().clone();
+        if let Err(e) = handle.subscribe(&[channel]) {
+            self.callbacks.error(e);
+        }
+    }
+}
+
+impl Drop for KrakenSDK {
+    fn drop(&mut self) {
+        if let Ok(delivery) = self.delivery.get_mut() {
+            if let Some(task) = delivery.take() {
+                task.abort();
             }
         }
     }
+}
+
+impl Callbacks {
+    fn new(registry: SymbolRegistry) -> Self {
+        Self {
+            symbols: SymbolNormalizer::canonical(registry),
+            ticker: Arc::default(),
+            orderbook: Arc::default(),
+            trade: Arc::default(),
+            reconnect: Arc::default(),
+            error: Arc::default(),
+        }
+    }
     
-    // Internal: dispatch orderbook data to callbacks
-    pub(crate) fn dispatch_orderbook(&self, data: OrderBookUpdate) {
-        let callbacks = self.orderbook_callbacks.lock().unwrap();
-        for (pair, _, callback) in callbacks.iter() {
-            if pair == &data.symbol || pair == "*" {
-                callback(data.clone());
+    /// Channels to subscribe for the registered callbacks
+    fn channels(&self) -> Vec<Channel> {
+        let mut channels = Vec::new();
+        
+        for (pair, _) in self.ticker.lock().unwrap().iter() {
+            channels.push(Channel::new("ticker").with_symbol(pair));
+        }
+        for (pair, depth, _) in self.orderbook.lock().unwrap().iter() {
+            channels.push(Channel::new("book").with_symbol(pair).with_depth(*depth));
+        }
+        for (pair, _) in self.trade.lock().unwrap().iter() {
+            channels.push(Channel::new("trade").with_symbol(pair));
+        }
+        
+        // Wildcard callbacks listen to every pair but cannot be subscribed
+        channels.retain(|c| c.symbol.as_deref() != Some("*"));
+        let mut unique: Vec<Channel> = Vec::with_capacity(channels.len());
+        for channel in channels {
+            if !unique.contains(&channel) {
+                unique.push(channel);
             }
         }
+        unique
     }
     
-    // Internal: dispatch trade data to callbacks
-    pub(crate) fn dispatch_trade(&self, data: TradeData) {
-        let callbacks = self.trade_callbacks.lock().unwrap();
-        for (pair, callback) in callbacks.iter() {
-            if pair == &data.symbol || pair == "*" {
-                callback(data.clone());
+    /// Deliver an event from the dispatcher to the matching callbacks
+    fn dispatch(&self, event: SdkEvent) {
+        match event {
+            SdkEvent::Ticker(data) => {
+                let callbacks: Vec<_> = self.ticker.lock().unwrap().iter()
+                    .filter(|(pair, _)| self.pair_matches(pair, &data.symbol))
+                    .map(|(_, callback)| Arc::clone(callback))
+                    .collect();
+                for callback in callbacks {
+                    callback(data.clone());
+                }
+            }
+            SdkEvent::OrderBook(data) => {
+                let callbacks: Vec<_> = self.orderbook.lock().unwrap().iter()
+                    .filter(|(pair, _, _)| self.pair_matches(pair, &data.symbol))
+                    .map(|(_, _, callback)| Arc::clone(callback))
+                    .collect();
+                for callback in callbacks {
+                    callback(data.clone());
+                }
             }
+            SdkEvent::Trade(data) => {
+                let callbacks: Vec<_> = self.trade.lock().unwrap().iter()
+                    .filter(|(pair, _)| self.pair_matches(pair, &data.symbol))
+                    .map(|(_, callback)| Arc::clone(callback))
+                    .collect();
+                for callback in callbacks {
+                    callback(data.clone());
+                }
+            }
+            SdkEvent::Reconnecting(event) => self.reconnecting(event.attempt),
+            SdkEvent::Error(error) => self.error(error),
+            _ => {}
+        }
+    }
+    
+    fn error(&self, error: SdkError) {
+        let callback = self.error.lock().unwrap().clone();
+        match callback {
+            Some(callback) => callback(error),
+            None => tracing::warn!("SDK error: {}", error),
         }
     }
+    
+    fn reconnecting(&self, attempt: u32) {
+        let callback = self.reconnect.lock().unwrap().clone();
+        if let Some(callback) = callback {
+            callback(attempt);
+        }
+    }
+    
+    /// Compare pair names given in any form, e.g. "BTC/USD" and "XBT/USD"
+    fn pair_matches(&self, subscribed: &str, symbol: &str) -> bool {
+        subscribed == "*" || self.symbols.normalize(subscribed) == self.symbols.normalize(symbol)
+    }
 }
 
+impl Default for KrakenSDK {
+    /// Create SDK with default settings
+    fn default() -> Self {
+        KrakenSDKBuilder::new().build()
+    }
+}
\ No newline at end of file
@@ -32,8 +32,6 @@ pub struct SequenceState {
 #[derive(Debug, Clone)]
 struct PendingMessage {
     sequence: u64,
-    data: String,
-    received_at: DateTime<Utc>,
 }
 
 impl Default for SequenceState {
@@ -115,6 +113,19 @@ pub struct SequenceConfig {
     pub auto_resync: bool,
 }
 
+impl SequenceConfig {
+    /// Resync on any gap
+    ///
+    /// Live feeds can't replay missed messages, so waiting for them to
+    /// arrive out of order only delays the resync.
+    pub fn strict() -> Self {
+        Self {
+            max_gap_size: 0,
+            ..Self::default()
+        }
+    }
+}
+
 impl Default for SequenceConfig {
     fn default() -> Self {
         Self {
@@ -175,8 +186,9 @@ impl SequenceManager {
     /// Validate and process a message with sequence number
     /// 
     /// Returns SequenceResult indicating whether message is in order
-    /// and current state.
-    pub fn validate(&self, channel: &str, sequence: u64, data: &str) -> SequenceResult {
+    /// and current state. Out-of-order messages are tracked by sequence
+    /// only; their payload isn't kept.
+    pub fn validate(&self, channel: &str, sequence: u64, _data: &str) -> SequenceResult {
         let mut channels = self.channels.lock().unwrap();
         let state = channels.entry(channel.to_string()).or_default();
         
@@ -233,11 +245,7 @@ impl SequenceManager {
             }
             
             // Store as pending
-            state.pending_messages.insert(sequence, PendingMessage {
-                sequence,
-                data: data.to_string(),
-                received_at: Utc::now(),
-            });
+            state.pending_messages.insert(sequence, PendingMessage { sequence });
             
             // Check if too many pending
             if state.pending_messages.len() > self.config.max_pending_messages && self.config.auto_resync {
@@ -25,6 +25,7 @@
 //! }
 //! ```
 
+use crate::clock::Clock;
 use crate::orderflow::{FlowEvent, FlowEventType, FlowSide};
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
@@ -110,11 +111,9 @@ impl From<FlowSide> for SpoofSide {
 #[derive(Debug, Clone)]
 struct PendingAppearance {
     symbol: String,
-    price: Decimal,
     volume: Decimal,
     side: SpoofSide,
     appeared_at: DateTime<Utc>,
-    sequence: u64,
 }
 
 /// Key for tracking pending appearances
@@ -142,6 +141,8 @@ struct TradeTracker {
 /// orders that vanish suspiciously fast without trading.
 pub struct SpoofingDetector {
     config: SpoofingConfig,
+    /// Stamps trades and expires pending appearances
+    clock: Clock,
     /// Pending large order appearances
     pending: Mutex<HashMap<AppearanceKey, PendingAppearance>>,
     /// Trade tracking per level
@@ -160,12 +161,19 @@ impl SpoofingDetector {
     pub fn with_config(config: SpoofingConfig) -> Self {
         Self {
             config,
+            clock: Clock::system(),
             pending: Mutex::new(HashMap::new()),
             trades: Mutex::new(HashMap::new()),
             alerts: Mutex::new(Vec::new()),
         }
     }
     
+    /// Expire appearances and stamp trades by `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+    
     /// Process a flow event and potentially return a spoofing alert
     ///
     /// Call this for every FlowEvent from OrderFlowTracker.
@@ -207,11 +215,9 @@ impl SpoofingDetector {
         
         let appearance = PendingAppearance {
             symbol: event.symbol.clone(),
-            price: event.price,
             volume: event.current_volume,
             side: event.side.into(),
             appeared_at: event.timestamp,
-            sequence: event.sequence,
         };
         
         let mut pending = self.pending.lock().unwrap();
@@ -307,13 +313,13 @@ impl SpoofingDetector {
         
         let mut trades = self.trades.lock().unwrap();
         let tracker = trades.entry(key).or_default();
-        tracker.last_trade_at = Some(Utc::now());
+        tracker.last_trade_at = Some(self.clock.now());
         tracker.trade_count += 1;
     }
     
     /// Clean up expired pending appearances
     fn cleanup_expired(&self) {
-        let now = Utc::now();
+        let now = self.clock.now();
         let expiry_ms = self.config.pending_expiry_ms as i64;
         
         let mut pending = self.pending.lock().unwrap();
@@ -26,12 +26,13 @@ use std::time::{Duration, Instant};
 /// 
 /// Each state represents a distinct phase in the connection lifecycle.
 /// Transitions are deterministic - each state has defined exit conditions.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub enum ConnectionState {
     /// Initial state - not connected
     /// 
     /// Transitions:
     /// - `connect()` → CONNECTING
+    #[default]
     Disconnected,
     
     /// Attempting to establish WebSocket connection
@@ -78,6 +79,7 @@ pub enum ConnectionState {
     /// 
     /// Transitions:
     /// - retry → CONNECTING
+    /// - connect() → CONNECTING (retry count reset)
     /// - max retries → CLOSED
     /// - close() → CLOSED
     Degraded {
@@ -99,12 +101,6 @@ pub enum ConnectionState {
     },
 }
 
-impl Default for ConnectionState {
-    fn default() -> Self {
-        ConnectionState::Disconnected
-    }
-}
-
 impl fmt::Display for ConnectionState {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -318,7 +314,8 @@ impl StateMachine {
     /// User initiates connection
     pub fn connect(&mut self) -> Result<StateTransition, StateError> {
         match &self.state {
-            ConnectionState::Disconnected | ConnectionState::Closed { .. } => {
+            ConnectionState::Disconnected | ConnectionState::Closed { .. } | ConnectionState::Degraded { .. } => {
+                self.current_retry_count = 0;
                 Ok(self.transition(ConnectionState::Connecting, TransitionTrigger::UserConnect))
             }
             _ => Err(StateError::InvalidTransition {
@@ -475,6 +472,27 @@ impl StateMachine {
         }
     }
     
+    /// No data or heartbeat arrived within the liveness window
+    pub fn heartbeat_timeout(&mut self) -> Result<StateTransition, StateError> {
+        match &self.state {
+            ConnectionState::Subscribed | ConnectionState::Resyncing | 
+            ConnectionState::Subscribing | ConnectionState::Authenticating => {
+                Ok(self.transition(
+                    ConnectionState::Degraded {
+                        reason: DegradedReason::HeartbeatTimeout,
+                        retry_count: 0,
+                        since: Instant::now(),
+                    },
+                    TransitionTrigger::HeartbeatTimeout,
+                ))
+            }
+            _ => Err(StateError::InvalidTransition {
+                from: self.state.clone(),
+                action: "heartbeat_timeout".to_string(),
+            }),
+        }
+    }
+    
     /// Attempt retry from degraded state
     pub fn retry(&mut self) -> Result<StateTransition, StateError> {
         match &self.state {
@@ -609,6 +627,30 @@ mod tests {
         assert!(matches!(*sm.state(), ConnectionState::Subscribed));
     }
     
+    #[test]
+    fn test_heartbeat_timeout_and_user_reconnect() {
+        let mut sm = StateMachine::new(StateMachineConfig::default());
+        
+        sm.connect().unwrap();
+        sm.connection_established().unwrap();
+        sm.subscriptions_confirmed().unwrap();
+        
+        sm.heartbeat_timeout().unwrap();
+        assert!(matches!(
+            *sm.state(),
+            ConnectionState::Degraded { reason: DegradedReason::HeartbeatTimeout, .. }
+        ));
+        assert_eq!(sm.history().last().unwrap().trigger, TransitionTrigger::HeartbeatTimeout);
+        
+        // A user connect from DEGRADED starts over with a fresh retry budget
+        sm.retry().unwrap();
+        sm.connection_failed("still failing".to_string()).unwrap();
+        assert_eq!(sm.retry_count(), 1);
+        sm.connect().unwrap();
+        assert_eq!(sm.retry_count(), 0);
+        assert!(matches!(*sm.state(), ConnectionState::Connecting));
+    }
+    
     #[test]
     fn test_invalid_transitions() {
         let mut sm = StateMachine::new(StateMachineConfig::default());
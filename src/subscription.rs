@@ -1,31 +1,90 @@
 //! Subscription management for WebSocket channels
 
 use crate::{
-    data::Channel,
+    data::{Channel, ProtocolVersion},
     error::SubscriptionError,
 };
 use serde_json::{json, Value};
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
 use tokio_tungstenite::tungstenite::Message;
 
+/// Routing entry for a confirmed v1 subscription
+///
+/// Kraken v1 data frames start with the `channelID` assigned in the
+/// `subscriptionStatus` confirmation, which identifies the channel and pair
+/// without inspecting the payload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelRoute {
+    pub channel_id: u64,
+    /// Channel name as sent on data frames, e.g. "book-10" or "ohlc-5"
+    pub channel_name: String,
+    pub channel: Channel,
+}
+
 /// Subscription manager for handling channel subscriptions
 #[derive(Debug)]
 pub struct SubscriptionManager {
-    active_subscriptions: Arc<Mutex<HashSet<String>>>,
+    active_subscriptions: Arc<Mutex<HashMap<String, Channel>>>,
     pending_subscriptions: Arc<Mutex<HashMap<String, Channel>>>,
+    routes: Arc<Mutex<HashMap<u64, ChannelRoute>>>,
+    requests: Arc<Mutex<HashMap<u64, PendingRequest>>>,
+    protocol: ProtocolVersion,
+    next_req_id: Arc<AtomicU64>,
 }
 
 impl SubscriptionManager {
     pub fn new() -> Self {
+        Self::with_protocol(ProtocolVersion::V1)
+    }
+    
+    /// Create a subscription manager speaking the given protocol version
+    pub fn with_protocol(protocol: ProtocolVersion) -> Self {
         Self {
-            active_subscriptions: Arc::new(Mutex::new(HashSet::new())),
+            active_subscriptions: Arc::new(Mutex::new(HashMap::new())),
             pending_subscriptions: Arc::new(Mutex::new(HashMap::new())),
+            routes: Arc::new(Mutex::new(HashMap::new())),
+            requests: Arc::new(Mutex::new(HashMap::new())),
+            protocol,
+            next_req_id: Arc::new(AtomicU64::new(1)),
         }
     }
     
+    /// Protocol version used for generated messages
+    pub fn protocol(&self) -> ProtocolVersion {
+        self.protocol
+    }
+    
     /// Create subscription message for a channel
+    ///
+    /// Kraken accepts a single channel type per request, so only the first
+    /// request group is returned. Use [`create_subscription_messages`] to
+    /// subscribe to mixed channels.
+    ///
+    /// [`create_subscription_messages`]: SubscriptionManager::create_subscription_messages
     pub fn create_subscription_message(&self, channels: &[Channel]) -> Result<Message, SubscriptionError> {
+        self.create_subscription_messages(channels)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| SubscriptionError::InvalidChannel("No valid pairs found".to_string()))
+    }
+    
+    /// Create one subscription message per channel type/interval/depth group
+    pub fn create_subscription_messages(&self, channels: &[Channel]) -> Result<Vec<Message>, SubscriptionError> {
+        self.prepare_subscription(channels).map(|(messages, _)| messages)
+    }
+    
+    /// Create subscription messages and a handle that resolves once Kraken
+    /// has confirmed or rejected every one of them
+    pub fn create_subscription_request(&self, channels: &[Channel]) -> Result<(Vec<Message>, SubscriptionAck), SubscriptionError> {
+        let (messages, req_ids) = self.prepare_subscription(channels)?;
+        Ok((messages, self.acknowledge(req_ids)))
+    }
+    
+    fn prepare_subscription(&self, channels: &[Channel]) -> Result<(Vec<Message>, Vec<u64>), SubscriptionError> {
         if channels.is_empty() {
             return Err(SubscriptionError::InvalidChannel("No channels provided".to_string()));
         }
@@ -35,53 +94,47 @@ impl SubscriptionManager {
             self.validate_channel(channel)?;
         }
         
-        // Group channels by subscription type
-        let mut ticker_pairs = Vec::new();
-        let mut trade_pairs = Vec::new();
-        
-        for channel in channels {
-            if let Some(symbol) = &channel.symbol {
-                match channel.name.as_str() {
-                    "ticker" => ticker_pairs.push(symbol.clone()),
-                    "trade" => trade_pairs.push(symbol.clone()),
-                    _ => {}
-                }
-            }
-        }
-        
-        // Create subscription message for ticker data (most common case)
-        let (pairs, subscription_name) = if !ticker_pairs.is_empty() {
-            (ticker_pairs, "ticker")
-        } else if !trade_pairs.is_empty() {
-            (trade_pairs, "trade")
-        } else {
+        let groups = self.group_channels(channels);
+        if groups.is_empty() {
             return Err(SubscriptionError::InvalidChannel("No valid pairs found".to_string()));
-        };
-        
-        let message = json!({
-            "event": "subscribe",
-            "pair": pairs,
-            "subscription": {
-                "name": subscription_name
-            }
-        });
+        }
         
-        let message_str = serde_json::to_string(&message)
-            .map_err(|e| SubscriptionError::SubscriptionFailed(format!("Failed to serialize subscription message: {}", e)))?;
+        let mut messages = Vec::with_capacity(groups.len());
+        let mut req_ids = Vec::with_capacity(groups.len());
+        for group in &groups {
+            let (req_id, message) = self.build_request("subscribe", group);
+            let message_str = serde_json::to_string(&message)
+                .map_err(|e| SubscriptionError::SubscriptionFailed(format!("Failed to serialize subscription message: {}", e)))?;
+            tracing::info!("Created subscription message: {}", message_str);
+            messages.push(Message::Text(message_str));
+            req_ids.push(req_id);
+        }
         
         // Add to pending subscriptions
-        for channel in channels {
-            let subscription_key = self.generate_subscription_key(channel);
+        {
             let mut pending = self.pending_subscriptions.lock().unwrap();
-            pending.insert(subscription_key, channel.clone());
+            for channel in channels {
+                let subscription_key = self.generate_subscription_key(channel);
+                pending.insert(subscription_key, channel.clone());
+            }
         }
         
-        tracing::info!("Created subscription message: {}", message_str);
-        Ok(Message::Text(message_str))
+        Ok((messages, req_ids))
     }
     
     /// Create unsubscription message for a channel
     pub fn create_unsubscription_message(&self, channels: &[Channel]) -> Result<Message, SubscriptionError> {
+        self.prepare_unsubscription(channels).map(|(message, _)| message)
+    }
+    
+    /// Create an unsubscription message and a handle that resolves once
+    /// Kraken has answered it
+    pub fn create_unsubscription_request(&self, channels: &[Channel]) -> Result<(Message, SubscriptionAck), SubscriptionError> {
+        let (message, req_id) = self.prepare_unsubscription(channels)?;
+        Ok((message, self.acknowledge(vec![req_id])))
+    }
+    
+    fn prepare_unsubscription(&self, channels: &[Channel]) -> Result<(Message, u64), SubscriptionError> {
         if channels.is_empty() {
             return Err(SubscriptionError::InvalidChannel("No channels provided".to_string()));
         }
@@ -90,72 +143,309 @@ impl SubscriptionManager {
         let subscription_key = self.generate_subscription_key(&channels[0]);
         {
             let active = self.active_subscriptions.lock().unwrap();
-            if !active.contains(&subscription_key) {
+            if !active.contains_key(&subscription_key) {
                 return Err(SubscriptionError::NotSubscribed(format!("Not subscribed to channel: {}", channels[0].name)));
             }
         }
         
-        let mut subscription_data = json!({
-            "name": channels[0].name
-        });
+        let group = SubscriptionGroup {
+            name: channels[0].name.clone(),
+            interval: channels[0].interval.clone(),
+            depth: channels[0].depth,
+            symbols: channels.iter()
+                .filter_map(|c| c.symbol.clone())
+                .collect(),
+        };
+        let (req_id, message) = self.build_request("unsubscribe", &group);
+        
+        let message_str = serde_json::to_string(&message)
+            .map_err(|e| SubscriptionError::SubscriptionFailed(format!("Failed to serialize unsubscription message: {}", e)))?;
         
-        if let Some(symbol) = &channels[0].symbol {
-            subscription_data["symbol"] = json!(symbol);
+        Ok((Message::Text(message_str), req_id))
+    }
+    
+    /// Create unsubscribe + subscribe messages that restart a stream
+    ///
+    /// Used to get a fresh book snapshot after a checksum mismatch.
+    pub fn create_resubscription_messages(&self, channels: &[Channel]) -> Result<Vec<Message>, SubscriptionError> {
+        let groups = self.group_channels(channels);
+        if groups.is_empty() {
+            return Err(SubscriptionError::InvalidChannel("No valid pairs found".to_string()));
         }
         
-        if let Some(interval) = &channels[0].interval {
-            subscription_data["interval"] = json!(interval);
+        let mut messages = Vec::with_capacity(groups.len() * 2);
+        for action in ["unsubscribe", "subscribe"] {
+            for group in &groups {
+                let (_, message) = self.build_request(action, group);
+                let message_str = serde_json::to_string(&message)
+                    .map_err(|e| SubscriptionError::SubscriptionFailed(format!("Failed to serialize {} message: {}", action, e)))?;
+                messages.push(Message::Text(message_str));
+            }
         }
         
-        let message = json!({
-            "event": "unsubscribe",
-            "pair": channels.iter()
-                .filter_map(|c| c.symbol.as_ref())
-                .collect::<Vec<_>>(),
-            "subscription": subscription_data,
-            "reqid": uuid::Uuid::new_v4().to_string()
+        Ok(messages)
+    }
+    
+    /// Group channels into requests Kraken accepts (one channel type each)
+    fn group_channels(&self, channels: &[Channel]) -> Vec<SubscriptionGroup> {
+        let mut groups: Vec<SubscriptionGroup> = Vec::new();
+        
+        for channel in channels {
+            // v2 instrument is the only channel that doesn't take symbols
+            let symbol_optional = self.protocol == ProtocolVersion::V2 && channel.name == "instrument";
+            if channel.symbol.is_none() && !symbol_optional {
+                continue;
+            }
+            
+            let group = match groups.iter_mut().find(|g| {
+                g.name == channel.name && g.interval == channel.interval && g.depth == channel.depth
+            }) {
+                Some(group) => group,
+                None => {
+                    groups.push(SubscriptionGroup {
+                        name: channel.name.clone(),
+                        interval: channel.interval.clone(),
+                        depth: channel.depth,
+                        symbols: Vec::new(),
+                    });
+                    groups.last_mut().unwrap()
+                }
+            };
+            
+            if let Some(symbol) = &channel.symbol {
+                if !group.symbols.contains(symbol) {
+                    group.symbols.push(symbol.clone());
+                }
+            }
+        }
+        
+        groups
+    }
+    
+    /// Build a (un)subscribe request in the configured protocol format
+    ///
+    /// The request is tracked by its id until Kraken has answered it.
+    fn build_request(&self, action: &str, group: &SubscriptionGroup) -> (u64, Value) {
+        let req_id = self.next_req_id();
+        self.requests.lock().unwrap().insert(req_id, PendingRequest {
+            group: group.clone(),
+            // Kraken answers once per pair
+            outstanding: group.symbols.len().max(1),
+            reply: None,
         });
         
-        let message_str = serde_json::to_string(&message)
-            .map_err(|e| SubscriptionError::SubscriptionFailed(format!("Failed to serialize unsubscription message: {}", e)))?;
+        // Kraken sends intervals and depths as numbers
+        let interval = group.interval.as_ref()
+            .map(|i| i.parse::<u32>().map(|n| json!(n)).unwrap_or_else(|_| json!(i)));
         
-        Ok(Message::Text(message_str))
+        match self.protocol {
+            ProtocolVersion::V1 => {
+                let mut subscription = json!({
+                    "name": group.name
+                });
+                if let Some(interval) = interval {
+                    subscription["interval"] = interval;
+                }
+                if let Some(depth) = group.depth {
+                    subscription["depth"] = json!(depth);
+                }
+                
+                (req_id, json!({
+                    "event": action,
+                    "pair": group.symbols,
+                    "subscription": subscription,
+                    "reqid": req_id
+                }))
+            }
+            ProtocolVersion::V2 => {
+                let mut params = json!({
+                    "channel": group.name
+                });
+                if !group.symbols.is_empty() {
+                    params["symbol"] = json!(group.symbols);
+                }
+                if let Some(interval) = interval {
+                    params["interval"] = interval;
+                }
+                if let Some(depth) = group.depth {
+                    params["depth"] = json!(depth);
+                }
+                
+                (req_id, json!({
+                    "method": action,
+                    "params": params,
+                    "req_id": req_id
+                }))
+            }
+        }
+    }
+    
+    /// Allocate the next request id
+    fn next_req_id(&self) -> u64 {
+        self.next_req_id.fetch_add(1, Ordering::Relaxed)
+    }
+    
+    /// Attach a reply channel to requests that were just built
+    fn acknowledge(&self, req_ids: Vec<u64>) -> SubscriptionAck {
+        let mut requests = self.requests.lock().unwrap();
+        let replies = req_ids.into_iter()
+            .filter_map(|req_id| {
+                let request = requests.get_mut(&req_id)?;
+                let (reply, receiver) = oneshot::channel();
+                request.reply = Some(reply);
+                Some((req_id, receiver))
+            })
+            .collect();
+        
+        SubscriptionAck {
+            replies,
+            requests: Arc::clone(&self.requests),
+        }
     }
     
     /// Handle subscription confirmation
     pub fn handle_subscription_confirmation(&self, message: &str) -> Result<(), SubscriptionError> {
-        let json: Value = serde_json::from_str(message)
-            .map_err(|e| SubscriptionError::SubscriptionFailed(format!("Invalid confirmation message: {}", e)))?;
+        let json = Self::parse_status(message)?;
+        let result = self.confirm_subscription(&json);
+        self.settle(&json, &result);
+        result
+    }
+    
+    /// Handle unsubscription confirmation
+    pub fn handle_unsubscription_confirmation(&self, message: &str) -> Result<(), SubscriptionError> {
+        let json = Self::parse_status(message)?;
+        let result = self.confirm_unsubscription(&json);
+        self.settle(&json, &result);
+        result
+    }
+    
+    /// Apply an already parsed (un)subscription status frame
+    ///
+    /// Accepts v1 `subscriptionStatus` events and v2 method responses; any
+    /// other frame is ignored. The request the frame answers (matched by
+    /// `reqid` / `req_id`) is resolved with the same result.
+    pub fn handle_status(&self, json: &Value) -> Result<(), SubscriptionError> {
+        let result = self.confirm_subscription(json)
+            .and_then(|_| self.confirm_unsubscription(json));
+        self.settle(json, &result);
+        result
+    }
+    
+    /// Number of (un)subscribe requests Kraken hasn't fully answered
+    pub fn outstanding_requests(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+    
+    /// Record Kraken's answer for the request a status frame refers to
+    ///
+    /// A request resolves with its first rejection, or successfully once
+    /// every pair in it has been confirmed.
+    fn settle(&self, json: &Value, result: &Result<(), SubscriptionError>) {
+        let is_status = json.get("event").and_then(|v| v.as_str()) == Some("subscriptionStatus")
+            || matches!(json.get("method").and_then(|v| v.as_str()), Some("subscribe" | "unsubscribe"));
+        if !is_status {
+            return;
+        }
+        let Some(req_id) = Self::status_req_id(json) else {
+            return;
+        };
+        
+        let mut requests = self.requests.lock().unwrap();
+        let Some(request) = requests.get_mut(&req_id) else {
+            return;
+        };
+        
+        request.outstanding = request.outstanding.saturating_sub(1);
+        if let Err(e) = result {
+            if let Some(reply) = request.reply.take() {
+                let _ = reply.send(Err(e.clone()));
+            }
+        }
+        if request.outstanding == 0 {
+            if let Some(reply) = request.reply.take() {
+                let _ = reply.send(Ok(()));
+            }
+            requests.remove(&req_id);
+        }
+    }
+    
+    /// Request id echoed in a v1 status event or v2 method response
+    fn status_req_id(json: &Value) -> Option<u64> {
+        json.get("reqid")
+            .or_else(|| json.get("req_id"))
+            .and_then(|v| v.as_u64())
+    }
+    
+    /// Turn Kraken's error message for a rejected request into a typed error
+    fn rejection(&self, json: &Value, message: &str) -> SubscriptionError {
+        let lowercase = message.to_lowercase();
+        
+        if lowercase.contains("pair") || lowercase.contains("symbol") {
+            let pair = json.get("pair")
+                .or_else(|| json.get("symbol"))
+                .and_then(|v| v.as_str())
+                .unwrap_or(message);
+            return SubscriptionError::UnknownPair(pair.to_string());
+        }
+        
+        if lowercase.contains("depth") {
+            // v1 echoes the subscription; v2 only the request id
+            let depth = json.get("subscription")
+                .and_then(|s| s.get("depth"))
+                .and_then(|v| v.as_u64())
+                .map(|depth| depth as u32)
+                .or_else(|| {
+                    let req_id = Self::status_req_id(json)?;
+                    self.requests.lock().unwrap().get(&req_id)?.group.depth
+                });
+            if let Some(depth) = depth {
+                return SubscriptionError::InvalidDepth(depth);
+            }
+        }
+        
+        SubscriptionError::SubscriptionFailed(message.to_string())
+    }
+    
+    fn parse_status(message: &str) -> Result<Value, SubscriptionError> {
+        serde_json::from_str(message)
+            .map_err(|e| SubscriptionError::SubscriptionFailed(format!("Invalid confirmation message: {}", e)))
+    }
+    
+    fn confirm_subscription(&self, json: &Value) -> Result<(), SubscriptionError> {
+        if json.get("method").and_then(|v| v.as_str()) == Some("subscribe") {
+            if json.get("success").and_then(|v| v.as_bool()) != Some(true) {
+                self.remove_rejected(json);
+            }
+            let channel = self.v2_result_channel(json)?;
+            self.activate(channel.clone());
+            
+            tracing::info!("Subscription confirmed for channel: {}", channel.name);
+            return Ok(());
+        }
         
         if let Some(event) = json.get("event").and_then(|v| v.as_str()) {
             if event == "subscriptionStatus" {
                 if let Some(status) = json.get("status").and_then(|v| v.as_str()) {
                     if status == "subscribed" {
                         // Move from pending to active
-                        if let Some(subscription) = json.get("subscription") {
-                            if let Some(name) = subscription.get("name").and_then(|v| v.as_str()) {
-                                let channel = Channel::new(name);
-                                let subscription_key = self.generate_subscription_key(&channel);
-                                
-                                {
-                                    let mut pending = self.pending_subscriptions.lock().unwrap();
-                                    pending.remove(&subscription_key);
-                                }
-                                
-                                {
-                                    let mut active = self.active_subscriptions.lock().unwrap();
-                                    active.insert(subscription_key);
-                                }
-                                
-                                tracing::info!("Subscription confirmed for channel: {}", name);
-                                return Ok(());
-                            }
+                        if let Some(channel) = Self::v1_status_channel(json) {
+                            let channel = self.activate(channel);
+                            self.add_route(json, channel.clone());
+                            
+                            tracing::info!("Subscription confirmed for channel: {}", channel.name);
+                            return Ok(());
                         }
                     } else if status == "error" {
+                        // A rejected request is no longer pending
+                        if let Some(channel) = Self::v1_status_channel(json) {
+                            let subscription_key = self.generate_subscription_key(&channel);
+                            self.pending_subscriptions.lock().unwrap().remove(&subscription_key);
+                        }
+                        
                         let error_msg = json.get("errorMessage")
                             .and_then(|v| v.as_str())
                             .unwrap_or("Unknown subscription error");
-                        return Err(SubscriptionError::SubscriptionFailed(error_msg.to_string()));
+                        return Err(self.rejection(json, error_msg));
                     }
                 }
             }
@@ -164,28 +454,32 @@ impl SubscriptionManager {
         Ok(())
     }
     
-    /// Handle unsubscription confirmation
-    pub fn handle_unsubscription_confirmation(&self, message: &str) -> Result<(), SubscriptionError> {
-        let json: Value = serde_json::from_str(message)
-            .map_err(|e| SubscriptionError::SubscriptionFailed(format!("Invalid confirmation message: {}", e)))?;
+    fn confirm_unsubscription(&self, json: &Value) -> Result<(), SubscriptionError> {
+        if json.get("method").and_then(|v| v.as_str()) == Some("unsubscribe") {
+            let channel = self.v2_result_channel(json)?;
+            let subscription_key = self.generate_subscription_key(&channel);
+            
+            self.active_subscriptions.lock().unwrap().remove(&subscription_key);
+            
+            tracing::info!("Unsubscription confirmed for channel: {}", channel.name);
+            return Ok(());
+        }
         
         if let Some(event) = json.get("event").and_then(|v| v.as_str()) {
             if event == "subscriptionStatus" {
                 if let Some(status) = json.get("status").and_then(|v| v.as_str()) {
                     if status == "unsubscribed" {
-                        if let Some(subscription) = json.get("subscription") {
-                            if let Some(name) = subscription.get("name").and_then(|v| v.as_str()) {
-                                let channel = Channel::new(name);
-                                let subscription_key = self.generate_subscription_key(&channel);
-                                
-                                {
-                                    let mut active = self.active_subscriptions.lock().unwrap();
-                                    active.remove(&subscription_key);
-                                }
-                                
-                                tracing::info!("Unsubscription confirmed for channel: {}", name);
-                                return Ok(());
+                        if let Some(channel) = Self::v1_status_channel(json) {
+                            let subscription_key = self.generate_subscription_key(&channel);
+                            
+                            {
+                                let mut active = self.active_subscriptions.lock().unwrap();
+                                active.remove(&subscription_key);
                             }
+                            self.remove_route(json, &subscription_key);
+                            
+                            tracing::info!("Unsubscription confirmed for channel: {}", channel.name);
+                            return Ok(());
                         }
                     }
                 }
@@ -195,23 +489,205 @@ impl SubscriptionManager {
         Ok(())
     }
     
+    /// Drop the pending entries a rejected v2 subscribe refers to
+    ///
+    /// v2 errors don't echo the channel, so the request's group supplies
+    /// it; the echoed `symbol`, when present, narrows the group to one pair.
+    fn remove_rejected(&self, json: &Value) {
+        let symbol = json.get("symbol").and_then(|v| v.as_str());
+        let group = Self::status_req_id(json)
+            .and_then(|req_id| self.requests.lock().unwrap().get(&req_id).map(|r| r.group.clone()));
+        let mut pending = self.pending_subscriptions.lock().unwrap();
+        
+        let Some(group) = group else {
+            // Unknown request: the pair can't be pending under another id
+            if let Some(symbol) = symbol {
+                pending.retain(|_, channel| channel.symbol.as_deref() != Some(symbol));
+            }
+            return;
+        };
+        for pair in group.symbols.iter().filter(|pair| symbol.map_or(true, |s| s == pair.as_str())) {
+            let mut channel = Channel::new(&group.name).with_symbol(pair);
+            if let Some(interval) = &group.interval {
+                channel = channel.with_interval(interval);
+            }
+            pending.remove(&self.generate_subscription_key(&channel));
+        }
+        if group.symbols.is_empty() {
+            pending.remove(&group.name);
+        }
+    }
+    
+    /// Record the `channelID` Kraken assigned to a confirmed v1 subscription
+    fn add_route(&self, json: &Value, channel: Channel) {
+        let Some(channel_id) = json.get("channelID").and_then(|v| v.as_u64()) else {
+            return;
+        };
+        let channel_name = json.get("channelName")
+            .and_then(|v| v.as_str())
+            .map(|name| name.to_string())
+            .unwrap_or_else(|| channel.name.clone());
+        
+        self.routes.lock().unwrap().insert(channel_id, ChannelRoute {
+            channel_id,
+            channel_name,
+            channel,
+        });
+    }
+    
+    /// Forget the route of an unsubscribed v1 channel
+    fn remove_route(&self, json: &Value, subscription_key: &str) {
+        let mut routes = self.routes.lock().unwrap();
+        match json.get("channelID").and_then(|v| v.as_u64()) {
+            Some(channel_id) => {
+                routes.remove(&channel_id);
+            }
+            None => routes.retain(|_, route| self.generate_subscription_key(&route.channel) != subscription_key),
+        }
+    }
+    
+    /// Look up the subscription a v1 data frame belongs to by its `channelID`
+    pub fn route(&self, channel_id: u64) -> Option<ChannelRoute> {
+        self.routes.lock().unwrap().get(&channel_id).cloned()
+    }
+    
+    /// Number of v1 channel IDs with a known route
+    pub fn route_count(&self) -> usize {
+        self.routes.lock().unwrap().len()
+    }
+    
+    /// Drop every channel ID; Kraken assigns new ones on each connection
+    pub fn clear_routes(&self) {
+        self.routes.lock().unwrap().clear();
+    }
+    
+    /// Move a confirmed channel from pending to active
+    ///
+    /// The pending entry is preferred because confirmations don't echo every
+    /// request option (e.g. v2 book depth).
+    fn activate(&self, confirmed: Channel) -> Channel {
+        let subscription_key = self.generate_subscription_key(&confirmed);
+        let channel = self.pending_subscriptions.lock().unwrap()
+            .remove(&subscription_key)
+            .unwrap_or(confirmed);
+        self.active_subscriptions.lock().unwrap().insert(subscription_key, channel.clone());
+        channel
+    }
+    
+    /// Extract the channel from a v1 `subscriptionStatus` event
+    fn v1_status_channel(json: &Value) -> Option<Channel> {
+        let subscription = json.get("subscription")?;
+        let name = subscription.get("name").and_then(|v| v.as_str())?;
+        
+        let mut channel = Channel::new(name);
+        if let Some(pair) = json.get("pair").and_then(|v| v.as_str()) {
+            channel = channel.with_symbol(pair);
+        }
+        if let Some(interval) = subscription.get("interval").and_then(|v| v.as_u64()) {
+            channel = channel.with_interval(&interval.to_string());
+        }
+        Some(channel)
+    }
+    
+    /// Extract the confirmed channel from a v2 method response
+    fn v2_result_channel(&self, json: &Value) -> Result<Channel, SubscriptionError> {
+        if json.get("success").and_then(|v| v.as_bool()) != Some(true) {
+            let error_msg = json.get("error")
+                .and_then(|v| v.as_str())
+                .unwrap_or("Unknown subscription error");
+            return Err(self.rejection(json, error_msg));
+        }
+        
+        let result = json.get("result")
+            .ok_or_else(|| SubscriptionError::SubscriptionFailed("Missing result in response".to_string()))?;
+        let name = result.get("channel")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| SubscriptionError::SubscriptionFailed("Missing channel in response".to_string()))?;
+        
+        let mut channel = Channel::new(name);
+        if let Some(symbol) = result.get("symbol").and_then(|v| v.as_str()) {
+            channel = channel.with_symbol(symbol);
+        }
+        if let Some(interval) = result.get("interval").and_then(|v| v.as_u64()) {
+            channel = channel.with_interval(&interval.to_string());
+        }
+        Ok(channel)
+    }
+    
     /// Check if subscribed to a channel
     pub fn is_subscribed(&self, channel: &Channel) -> bool {
         let subscription_key = self.generate_subscription_key(channel);
         let active = self.active_subscriptions.lock().unwrap();
-        active.contains(&subscription_key)
+        active.contains_key(&subscription_key)
     }
     
     /// Get list of active subscriptions
     pub fn get_active_subscriptions(&self) -> Vec<String> {
         let active = self.active_subscriptions.lock().unwrap();
-        active.iter().cloned().collect()
+        active.keys().cloned().collect()
+    }
+    
+    /// Number of subscription requests awaiting confirmation
+    pub fn pending_count(&self) -> usize {
+        self.pending_subscriptions.lock().unwrap().len()
+    }
+    
+    /// Stop waiting for channels Kraken hasn't confirmed yet, so they are
+    /// not sent again on the next connection
+    pub fn remove_pending(&self, channels: &[Channel]) {
+        let mut pending = self.pending_subscriptions.lock().unwrap();
+        for channel in channels {
+            pending.remove(&self.generate_subscription_key(channel));
+        }
+    }
+    
+    /// Get every channel that is subscribed or awaiting confirmation
+    pub fn get_subscribed_channels(&self) -> Vec<Channel> {
+        let mut channels: Vec<(String, Channel)> = Vec::new();
+        {
+            let active = self.active_subscriptions.lock().unwrap();
+            channels.extend(active.iter().map(|(k, c)| (k.clone(), c.clone())));
+        }
+        {
+            let pending = self.pending_subscriptions.lock().unwrap();
+            for (key, channel) in pending.iter() {
+                if !channels.iter().any(|(k, _)| k == key) {
+                    channels.push((key.clone(), channel.clone()));
+                }
+            }
+        }
+        
+        channels.sort_by(|a, b| a.0.cmp(&b.0));
+        channels.into_iter().map(|(_, channel)| channel).collect()
+    }
+    
+    /// Create subscribe messages that restore every subscription on a new connection
+    ///
+    /// Active subscriptions move back to pending until Kraken confirms them
+    /// again, and channel IDs from the previous connection are dropped.
+    /// Returns no messages when nothing is subscribed.
+    pub fn create_replay_messages(&self) -> Result<Vec<Message>, SubscriptionError> {
+        self.clear_routes();
+        // Answers to requests sent on the old connection will never arrive;
+        // only requests someone is still waiting on are kept (until they time out)
+        self.requests.lock().unwrap().retain(|_, request| request.reply.is_some());
+        
+        let channels = self.get_subscribed_channels();
+        if channels.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        self.active_subscriptions.lock().unwrap().clear();
+        self.create_subscription_messages(&channels)
     }
     
     /// Validate channel specification
     fn validate_channel(&self, channel: &Channel) -> Result<(), SubscriptionError> {
         // Validate channel name
-        let valid_channels = ["ticker", "ohlc", "trade", "book", "spread"];
+        let valid_channels: &[&str] = match self.protocol {
+            ProtocolVersion::V1 => &["ticker", "ohlc", "trade", "book", "spread"],
+            ProtocolVersion::V2 => &["ticker", "ohlc", "trade", "book", "instrument"],
+        };
         if !valid_channels.contains(&channel.name.as_str()) {
             return Err(SubscriptionError::InvalidChannel(
                 format!("Invalid channel name: {}. Valid channels: {:?}", channel.name, valid_channels)
@@ -230,6 +706,16 @@ impl SubscriptionManager {
             }
         }
         
+        // Validate book depth
+        if channel.name == "book" {
+            if let Some(depth) = channel.depth {
+                let valid_depths = [10, 25, 100, 500, 1000];
+                if !valid_depths.contains(&depth) {
+                    return Err(SubscriptionError::InvalidDepth(depth));
+                }
+            }
+        }
+        
         Ok(())
     }
     
@@ -257,6 +743,81 @@ impl Clone for SubscriptionManager {
         Self {
             active_subscriptions: Arc::clone(&self.active_subscriptions),
             pending_subscriptions: Arc::clone(&self.pending_subscriptions),
+            routes: Arc::clone(&self.routes),
+            requests: Arc::clone(&self.requests),
+            protocol: self.protocol,
+            next_req_id: Arc::clone(&self.next_req_id),
+        }
+    }
+}
+
+/// Channels that can share a single (un)subscribe request
+#[derive(Debug, Clone)]
+struct SubscriptionGroup {
+    name: String,
+    interval: Option<String>,
+    depth: Option<u32>,
+    symbols: Vec<String>,
+}
+
+/// Result Kraken sent for one (un)subscribe request
+type Reply = Result<(), SubscriptionError>;
+
+/// (Un)subscribe request awaiting Kraken's answer
+#[derive(Debug)]
+struct PendingRequest {
+    group: SubscriptionGroup,
+    /// Pairs not yet confirmed or rejected
+    outstanding: usize,
+    reply: Option<oneshot::Sender<Reply>>,
+}
+
+/// Handle to the outcome of (un)subscribe requests
+///
+/// Resolves once Kraken has confirmed every request (correlated by
+/// `reqid` / `req_id`), or with the first rejection. Dropping the handle
+/// stops tracking the requests; the subscriptions themselves are unaffected.
+#[derive(Debug)]
+pub struct SubscriptionAck {
+    replies: Vec<(u64, oneshot::Receiver<Reply>)>,
+    requests: Arc<Mutex<HashMap<u64, PendingRequest>>>,
+}
+
+impl SubscriptionAck {
+    /// Ids of the requests this handle waits on
+    pub fn req_ids(&self) -> Vec<u64> {
+        self.replies.iter().map(|(req_id, _)| *req_id).collect()
+    }
+    
+    /// Wait for Kraken's answer to every request
+    ///
+    /// Fails with the first rejection, e.g. [`SubscriptionError::UnknownPair`]
+    /// or [`SubscriptionError::InvalidDepth`], or with
+    /// [`SubscriptionError::Timeout`] when no answer arrives in time.
+    pub async fn wait(mut self, timeout: Duration) -> Result<(), SubscriptionError> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        
+        for (req_id, reply) in self.replies.iter_mut() {
+            match tokio::time::timeout_at(deadline, reply).await {
+                Ok(Ok(result)) => result?,
+                Ok(Err(_)) => {
+                    return Err(SubscriptionError::SubscriptionFailed(
+                        format!("Request {} was abandoned before Kraken answered", req_id)
+                    ));
+                }
+                Err(_) => return Err(SubscriptionError::Timeout { req_id: *req_id, timeout }),
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+impl Drop for SubscriptionAck {
+    fn drop(&mut self) {
+        let mut requests = self.requests.lock().unwrap();
+        for (req_id, _) in &self.replies {
+            requests.remove(req_id);
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,344 @@
+//! Kraken symbol normalization
+//!
+//! Kraken names the same pair three ways: WebSocket names (`XBT/USD`), REST
+//! altnames (`XBTUSD`) and legacy pair names (`XXBTZUSD`), and uses `XBT`
+//! and `XDG` where everyone else says BTC and DOGE. [`SymbolRegistry`]
+//! accepts any of these forms and converts between them, plus a canonical
+//! `BTC/USD` form for joining data across feeds.
+//!
+//! Names are exact once the registry is loaded from `AssetPairs`; before
+//! that, common pairs are converted using Kraken's known asset codes.
+//!
+//! ```rust,ignore
+//! let symbols = SymbolRegistry::new();
+//! public_client.load_symbols(&symbols).await?;
+//!
+//! assert_eq!(symbols.canonical("XXBTZUSD"), "BTC/USD");
+//! assert_eq!(symbols.convert("BTC/USD", SymbolFormat::AltName), "XBTUSD");
+//! ```
+
+use crate::rest_client::AssetPair;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+/// Legacy asset codes and their common names
+///
+/// Older assets carry an `X` (crypto) or `Z` (fiat) prefix in legacy names.
+const LEGACY_ASSETS: &[(&str, &str)] = &[
+    ("XXBT", "BTC"),
+    ("XXDG", "DOGE"),
+    ("XETH", "ETH"),
+    ("XETC", "ETC"),
+    ("XLTC", "LTC"),
+    ("XXRP", "XRP"),
+    ("XXLM", "XLM"),
+    ("XXMR", "XMR"),
+    ("XZEC", "ZEC"),
+    ("XREP", "REP"),
+    ("XMLN", "MLN"),
+    ("ZUSD", "USD"),
+    ("ZEUR", "EUR"),
+    ("ZGBP", "GBP"),
+    ("ZCAD", "CAD"),
+    ("ZJPY", "JPY"),
+    ("ZAUD", "AUD"),
+    ("ZCHF", "CHF"),
+];
+
+/// Quote currencies tried, longest first, when splitting an altname
+const QUOTES: &[&str] = &[
+    "USDT", "USDC", "PYUSD", "USD", "EUR", "GBP", "CAD", "JPY", "AUD", "CHF", "XBT", "ETH", "DAI", "DOT",
+];
+
+/// Form of a pair name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SymbolFormat {
+    /// Leave names as Kraken sent them
+    #[default]
+    Native,
+    /// `BTC/USD`: common asset names, joinable across feeds
+    Canonical,
+    /// `XBT/USD`: WebSocket v1 name
+    WsName,
+    /// `XBTUSD`: REST altname
+    AltName,
+    /// `XXBTZUSD`: legacy pair name, as keyed in REST results
+    Legacy,
+}
+
+/// Every name of one pair
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairNames {
+    /// e.g. "BTC/USD"
+    pub canonical: String,
+    /// e.g. "XBT/USD"
+    pub wsname: String,
+    /// e.g. "XBTUSD"
+    pub altname: String,
+    /// e.g. "XXBTZUSD"
+    pub legacy: String,
+}
+
+impl PairNames {
+    /// The name in `format`; `None` for [`SymbolFormat::Native`]
+    pub fn get(&self, format: SymbolFormat) -> Option<&str> {
+        match format {
+            SymbolFormat::Native => None,
+            SymbolFormat::Canonical => Some(&self.canonical),
+            SymbolFormat::WsName => Some(&self.wsname),
+            SymbolFormat::AltName => Some(&self.altname),
+            SymbolFormat::Legacy => Some(&self.legacy),
+        }
+    }
+
+    /// Names derived from Kraken's asset naming rules alone
+    fn derive(symbol: &str) -> Option<Self> {
+        let symbol = symbol.to_uppercase();
+        let (base, quote) = match symbol.split_once('/') {
+            Some(split) => split,
+            None => split_pair(&symbol)?,
+        };
+        let (base, quote) = (canonical_asset(base), canonical_asset(quote));
+        let (kraken_base, kraken_quote) = (kraken_asset(base), kraken_asset(quote));
+        let legacy = match (legacy_asset(base), legacy_asset(quote)) {
+            (Some(base), Some(quote)) => format!("{}{}", base, quote),
+            _ => format!("{}{}", kraken_base, kraken_quote),
+        };
+        Some(Self {
+            canonical: format!("{}/{}", base, quote),
+            wsname: format!("{}/{}", kraken_base, kraken_quote),
+            altname: format!("{}{}", kraken_base, kraken_quote),
+            legacy,
+        })
+    }
+}
+
+/// Common name of a Kraken asset code, e.g. "XXBT" or "XBT" -> "BTC"
+pub fn canonical_asset(asset: &str) -> &str {
+    if let Some((_, name)) = LEGACY_ASSETS.iter().find(|(legacy, _)| *legacy == asset) {
+        return name;
+    }
+    match asset {
+        "XBT" => "BTC",
+        "XDG" => "DOGE",
+        other => other,
+    }
+}
+
+/// Kraken's code for a common asset name, e.g. "BTC" -> "XBT"
+fn kraken_asset(asset: &str) -> &str {
+    match asset {
+        "BTC" => "XBT",
+        "DOGE" => "XDG",
+        other => other,
+    }
+}
+
+/// Legacy code of a common asset name, if it has one
+fn legacy_asset(asset: &str) -> Option<&'static str> {
+    LEGACY_ASSETS.iter().find(|(_, name)| *name == asset).map(|(legacy, _)| *legacy)
+}
+
+/// Split a name without a slash into base and quote
+fn split_pair(symbol: &str) -> Option<(&str, &str)> {
+    // Legacy names of legacy assets: "XXBTZUSD"
+    if symbol.len() == 8 && symbol.is_char_boundary(4) {
+        let (base, quote) = symbol.split_at(4);
+        if legacy_asset(canonical_asset(base)) == Some(base) && legacy_asset(canonical_asset(quote)) == Some(quote) {
+            return Some((base, quote));
+        }
+    }
+    QUOTES.iter()
+        .filter_map(|quote| symbol.strip_suffix(quote).map(|base| (base, *quote)))
+        .find(|(base, _)| !base.is_empty())
+}
+
+/// Names of every known pair, looked up by any of them
+///
+/// Clones share the same table.
+#[derive(Clone, Default)]
+pub struct SymbolRegistry {
+    pairs: Arc<RwLock<HashMap<String, Arc<PairNames>>>>,
+}
+
+impl SymbolRegistry {
+    /// Registry that only knows Kraken's asset naming rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a pair returned by the REST `AssetPairs` endpoint
+    pub fn insert_asset_pair(&self, pair: &AssetPair) {
+        let base = canonical_asset(&pair.base);
+        let quote = canonical_asset(&pair.quote);
+        let names = Arc::new(PairNames {
+            canonical: format!("{}/{}", base, quote),
+            wsname: pair.wsname.clone().unwrap_or_else(|| format!("{}/{}", kraken_asset(base), kraken_asset(quote))),
+            altname: pair.altname.clone(),
+            legacy: pair.pair.clone(),
+        });
+
+        let mut pairs = self.pairs.write().unwrap();
+        for name in [&names.canonical, &names.wsname, &names.altname, &names.legacy] {
+            pairs.insert(name.to_uppercase(), Arc::clone(&names));
+        }
+    }
+
+    /// Add every pair returned by the REST `AssetPairs` endpoint
+    pub fn load_asset_pairs<'a>(&self, pairs: impl IntoIterator<Item = &'a AssetPair>) {
+        for pair in pairs {
+            self.insert_asset_pair(pair);
+        }
+    }
+
+    /// Number of pairs loaded from `AssetPairs`
+    pub fn len(&self) -> usize {
+        let pairs = self.pairs.read().unwrap();
+        pairs.values().map(|names| &names.canonical).collect::<HashSet<_>>().len()
+    }
+
+    /// Whether nothing has been loaded from `AssetPairs`
+    pub fn is_empty(&self) -> bool {
+        self.pairs.read().unwrap().is_empty()
+    }
+
+    /// Every name of a pair given in any form
+    ///
+    /// Unknown pairs are derived from Kraken's asset codes; `None` when the
+    /// name can't be split into base and quote.
+    pub fn names(&self, symbol: &str) -> Option<Arc<PairNames>> {
+        let pairs = self.pairs.read().unwrap();
+        if let Some(names) = pairs.get(&symbol.to_uppercase()) {
+            return Some(Arc::clone(names));
+        }
+        // Canonical and WS names differ only by asset codes
+        let derived = PairNames::derive(symbol)?;
+        Some(pairs.get(&derived.canonical).cloned().unwrap_or_else(|| Arc::new(derived)))
+    }
+
+    /// Convert a pair name in any form to `format`
+    ///
+    /// Names that can't be resolved are returned unchanged.
+    pub fn convert(&self, symbol: &str, format: SymbolFormat) -> String {
+        self.names(symbol)
+            .and_then(|names| names.get(format).map(str::to_string))
+            .unwrap_or_else(|| symbol.to_string())
+    }
+
+    /// Canonical `BTC/USD` form of a pair name in any form
+    pub fn canonical(&self, symbol: &str) -> String {
+        self.convert(symbol, SymbolFormat::Canonical)
+    }
+}
+
+impl fmt::Debug for SymbolRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SymbolRegistry")
+            .field("pairs", &self.len())
+            .finish()
+    }
+}
+
+/// Rewrites pair names on data leaving the SDK into one format
+///
+/// Attach to [`ClientConfig`](crate::data::ClientConfig),
+/// [`KrakenRestClient`](crate::rest_client::KrakenRestClient),
+/// [`KrakenPublicClient`](crate::rest_client::KrakenPublicClient) and
+/// [`PrivateWsConfig`](crate::private_ws::PrivateWsConfig) with the same
+/// format so tickers, positions and fills can be joined by symbol.
+#[derive(Debug, Clone)]
+pub struct SymbolNormalizer {
+    pub registry: SymbolRegistry,
+    pub format: SymbolFormat,
+}
+
+impl SymbolNormalizer {
+    pub fn new(registry: SymbolRegistry, format: SymbolFormat) -> Self {
+        Self { registry, format }
+    }
+
+    /// Normalizer to the canonical `BTC/USD` form
+    pub fn canonical(registry: SymbolRegistry) -> Self {
+        Self::new(registry, SymbolFormat::Canonical)
+    }
+
+    /// `symbol` in this normalizer's format
+    pub fn normalize(&self, symbol: &str) -> String {
+        self.registry.convert(symbol, self.format)
+    }
+
+    /// Rewrite `symbol` in place
+    pub fn apply(&self, symbol: &mut String) {
+        if self.format != SymbolFormat::Native {
+            *symbol = self.normalize(symbol);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair(pair: &str, altname: &str, wsname: &str, base: &str, quote: &str) -> AssetPair {
+        AssetPair {
+            pair: pair.to_string(),
+            altname: altname.to_string(),
+            wsname: Some(wsname.to_string()),
+            base: base.to_string(),
+            quote: quote.to_string(),
+            pair_decimals: 1,
+            lot_decimals: 8,
+            order_min: None,
+            cost_min: None,
+            tick_size: None,
+            status: None,
+        }
+    }
+
+    #[test]
+    fn test_derived_names() {
+        let symbols = SymbolRegistry::new();
+        for name in ["BTC/USD", "XBT/USD", "XBTUSD", "XXBTZUSD", "xbt/usd"] {
+            let names = symbols.names(name).unwrap();
+            assert_eq!(names.canonical, "BTC/USD", "{}", name);
+            assert_eq!(names.wsname, "XBT/USD");
+            assert_eq!(names.altname, "XBTUSD");
+            assert_eq!(names.legacy, "XXBTZUSD");
+        }
+
+        assert_eq!(symbols.canonical("XDG/EUR"), "DOGE/EUR");
+        assert_eq!(symbols.convert("DOGE/USDT", SymbolFormat::AltName), "XDGUSDT");
+        assert_eq!(symbols.convert("SOL/USD", SymbolFormat::Legacy), "SOLUSD");
+        assert_eq!(symbols.canonical("ETHXBT"), "ETH/BTC");
+        assert_eq!(symbols.canonical("nonsense"), "nonsense");
+        assert_eq!(canonical_asset("XXBT"), "BTC");
+        assert_eq!(canonical_asset("ZUSD"), "USD");
+    }
+
+    #[test]
+    fn test_asset_pairs_override_derivation() {
+        let symbols = SymbolRegistry::new();
+        symbols.load_asset_pairs(&[
+            pair("XETHZUSD", "ETHUSD", "ETH/USD", "XETH", "ZUSD"),
+            pair("XDGUSD", "XDGUSD", "XDG/USD", "XXDG", "ZUSD"),
+        ]);
+        assert_eq!(symbols.len(), 2);
+
+        // Kraken's real legacy name for DOGE/USD isn't derivable
+        assert_eq!(symbols.convert("DOGE/USD", SymbolFormat::Legacy), "XDGUSD");
+        assert_eq!(symbols.canonical("XDGUSD"), "DOGE/USD");
+        assert_eq!(symbols.convert("XETHZUSD", SymbolFormat::WsName), "ETH/USD");
+    }
+
+    #[test]
+    fn test_normalizer() {
+        let normalizer = SymbolNormalizer::canonical(SymbolRegistry::new());
+        let mut symbol = "XBT/EUR".to_string();
+        normalizer.apply(&mut symbol);
+        assert_eq!(symbol, "BTC/EUR");
+
+        let native = SymbolNormalizer::new(SymbolRegistry::new(), SymbolFormat::Native);
+        assert_eq!(native.normalize("XBT/EUR"), "XBT/EUR");
+    }
+}
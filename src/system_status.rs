@@ -0,0 +1,250 @@
+//! Exchange status and heartbeats
+//!
+//! Kraken announces the trading engine's state with `systemStatus` (v1) or
+//! the `status` channel (v2), and sends a `heartbeat` when a connection is
+//! otherwise quiet. [`SystemStatusMonitor`] keeps the latest status so it
+//! can be queried, awaited, or used to gate REST order placement.
+
+use crate::error::SdkError;
+use crate::trading::{OrderRequest, OrderType};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// Trading engine state published by Kraken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExchangeStatus {
+    /// Fully operational
+    Online,
+    /// Offline for maintenance; no orders or cancels
+    Maintenance,
+    /// Only cancels are accepted
+    CancelOnly,
+    /// Only limit orders are accepted
+    LimitOnly,
+    /// Only post-only limit orders are accepted
+    PostOnly,
+    /// A status this SDK doesn't know
+    Unknown,
+}
+
+impl ExchangeStatus {
+    /// Parse Kraken's status string (`online`, `cancel_only`, ...)
+    pub fn from_kraken(status: &str) -> Self {
+        match status {
+            "online" => ExchangeStatus::Online,
+            "maintenance" => ExchangeStatus::Maintenance,
+            "cancel_only" => ExchangeStatus::CancelOnly,
+            "limit_only" => ExchangeStatus::LimitOnly,
+            "post_only" => ExchangeStatus::PostOnly,
+            _ => ExchangeStatus::Unknown,
+        }
+    }
+
+    /// Kraken's name for this status
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExchangeStatus::Online => "online",
+            ExchangeStatus::Maintenance => "maintenance",
+            ExchangeStatus::CancelOnly => "cancel_only",
+            ExchangeStatus::LimitOnly => "limit_only",
+            ExchangeStatus::PostOnly => "post_only",
+            ExchangeStatus::Unknown => "unknown",
+        }
+    }
+
+    /// Whether the engine accepts new orders of any kind
+    pub fn accepts_orders(&self) -> bool {
+        !matches!(self, ExchangeStatus::Maintenance | ExchangeStatus::CancelOnly)
+    }
+
+    /// Check whether the engine would accept `order`
+    ///
+    /// An unknown status never blocks an order; Kraken has the final word.
+    pub fn check_order(&self, order: &OrderRequest) -> Result<(), SdkError> {
+        match self {
+            ExchangeStatus::LimitOnly if order.order_type != OrderType::Limit => {
+                Err(self.rejection("only limit orders are accepted"))
+            }
+            ExchangeStatus::PostOnly if order.order_type != OrderType::Limit || !order.flags.post_only => {
+                Err(self.rejection("only post-only limit orders are accepted"))
+            }
+            _ => self.check_edit(),
+        }
+    }
+
+    /// Check whether the engine would accept an order edit
+    ///
+    /// Edits keep the original order's type and flags, so only maintenance
+    /// and `cancel_only` block them.
+    pub fn check_edit(&self) -> Result<(), SdkError> {
+        match self {
+            ExchangeStatus::Maintenance => Err(self.rejection("the exchange is down for maintenance")),
+            ExchangeStatus::CancelOnly => Err(self.rejection("only cancels are accepted")),
+            _ => Ok(()),
+        }
+    }
+
+    fn rejection(&self, reason: &str) -> SdkError {
+        SdkError::OrderRejected {
+            status: *self,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ExchangeStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Kraken `systemStatus` event (v1) or `status` channel update (v2)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SystemStatus {
+    pub status: ExchangeStatus,
+    /// Status string as sent by Kraken
+    pub raw_status: String,
+    /// WebSocket API version, e.g. "1.9.1"
+    pub version: Option<String>,
+    /// Kraken's id for this connection
+    pub connection_id: Option<u64>,
+}
+
+/// Heartbeat Kraken sends when no other messages are due
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Heartbeat {
+    /// When the heartbeat was received
+    pub received_at: DateTime<Utc>,
+}
+
+/// Latest exchange status, shared between the WebSocket client and REST helpers
+///
+/// Clones share the same status. Use [`watch`](SystemStatusMonitor::watch) to
+/// react to changes, e.g. pausing a strategy during maintenance.
+#[derive(Clone)]
+pub struct SystemStatusMonitor {
+    sender: Arc<watch::Sender<Option<SystemStatus>>>,
+}
+
+impl SystemStatusMonitor {
+    /// Monitor that hasn't seen a status yet
+    pub fn new() -> Self {
+        let (sender, _) = watch::channel(None);
+        Self {
+            sender: Arc::new(sender),
+        }
+    }
+
+    /// Record the latest status
+    pub fn update(&self, status: SystemStatus) {
+        let previous = self.sender.send_replace(Some(status.clone()));
+        match previous {
+            Some(previous) if previous.status == status.status => {}
+            _ => tracing::info!("Exchange status: {}", status.raw_status),
+        }
+    }
+
+    /// Latest status message, if one has been received
+    pub fn current(&self) -> Option<SystemStatus> {
+        self.sender.borrow().clone()
+    }
+
+    /// Latest exchange status, if one has been received
+    pub fn status(&self) -> Option<ExchangeStatus> {
+        self.sender.borrow().as_ref().map(|s| s.status)
+    }
+
+    /// Receiver that is notified on every status update
+    pub fn watch(&self) -> watch::Receiver<Option<SystemStatus>> {
+        self.sender.subscribe()
+    }
+
+    /// Check `order` against the latest status
+    ///
+    /// Passes while no status has been received.
+    pub fn check_order(&self, order: &OrderRequest) -> Result<(), SdkError> {
+        match self.status() {
+            Some(status) => status.check_order(order),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for SystemStatusMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for SystemStatusMonitor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SystemStatusMonitor")
+            .field("status", &self.status())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn status(raw: &str) -> SystemStatus {
+        SystemStatus {
+            status: ExchangeStatus::from_kraken(raw),
+            raw_status: raw.to_string(),
+            version: None,
+            connection_id: None,
+        }
+    }
+
+    #[test]
+    fn test_order_gating_by_status() {
+        let market = OrderRequest::market_buy("XBT/USD", dec!(0.01));
+        let limit = OrderRequest::limit_buy("XBT/USD", dec!(0.01), dec!(50000));
+        let post_only = limit.clone().post_only();
+
+        assert!(ExchangeStatus::Online.check_order(&market).is_ok());
+        assert!(ExchangeStatus::Unknown.check_order(&market).is_ok());
+
+        for blocked in [ExchangeStatus::Maintenance, ExchangeStatus::CancelOnly] {
+            assert!(matches!(
+                blocked.check_order(&post_only),
+                Err(SdkError::OrderRejected { status, .. }) if status == blocked
+            ));
+            assert!(blocked.check_edit().is_err());
+        }
+
+        assert!(ExchangeStatus::LimitOnly.check_order(&market).is_err());
+        assert!(ExchangeStatus::LimitOnly.check_order(&limit).is_ok());
+
+        assert!(ExchangeStatus::PostOnly.check_order(&market).is_err());
+        assert!(ExchangeStatus::PostOnly.check_order(&limit).is_err());
+        assert!(ExchangeStatus::PostOnly.check_order(&post_only).is_ok());
+        assert!(ExchangeStatus::PostOnly.check_edit().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_monitor_tracks_latest_status() {
+        let monitor = SystemStatusMonitor::new();
+        let market = OrderRequest::market_buy("XBT/USD", dec!(0.01));
+        let mut changes = monitor.watch();
+
+        // Nothing known yet: orders pass
+        assert_eq!(monitor.status(), None);
+        assert!(monitor.check_order(&market).is_ok());
+
+        monitor.clone().update(status("cancel_only"));
+        changes.changed().await.unwrap();
+        assert_eq!(changes.borrow().as_ref().map(|s| s.status), Some(ExchangeStatus::CancelOnly));
+        assert!(monitor.check_order(&market).is_err());
+
+        monitor.update(status("online"));
+        assert_eq!(monitor.current().unwrap().raw_status, "online");
+        assert!(monitor.check_order(&market).is_ok());
+    }
+}
@@ -108,6 +108,11 @@ impl MetricsRegistry {
         }
     }
 
+    /// Configuration this registry was created with
+    pub fn config(&self) -> &TelemetryConfig {
+        &self.config
+    }
+
     /// Get or create a counter
     pub fn counter(&self, name: &str, help: &str) -> Counter {
         let mut counters = self.counters.lock().unwrap();
@@ -52,9 +52,10 @@ impl fmt::Display for OrderType {
 }
 
 /// Time in force for orders
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum TimeInForce {
     /// Good till cancelled (default)
+    #[default]
     GTC,
     /// Immediate or cancel
     IOC,
@@ -62,12 +63,6 @@ pub enum TimeInForce {
     GTD,
 }
 
-impl Default for TimeInForce {
-    fn default() -> Self {
-        TimeInForce::GTC
-    }
-}
-
 /// Order flags
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct OrderFlags {
@@ -109,6 +109,7 @@ pub struct JsBackpressureStats {
 #[derive(Clone)]
 pub struct JsConfig {
     endpoint: String,
+    protocol: crate::data::ProtocolVersion,
     auto_reconnect: bool,
     max_reconnect_attempts: u32,
     max_messages_per_second: u32,
@@ -120,7 +121,8 @@ impl JsConfig {
     #[wasm_bindgen(constructor)]
     pub fn new() -> Self {
         Self {
-            endpoint: "wss://ws.kraken.com".to_string(),
+            endpoint: crate::data::KRAKEN_WS_V1_URL.to_string(),
+            protocol: crate::data::ProtocolVersion::V1,
             auto_reconnect: true,
             max_reconnect_attempts: 10,
             max_messages_per_second: 1000,
@@ -133,6 +135,21 @@ impl JsConfig {
         self.clone()
     }
     
+    /// Use the v2 WebSocket protocol (switches a default endpoint too)
+    #[wasm_bindgen(js_name = setUseV2)]
+    pub fn set_use_v2(&mut self, enabled: bool) -> Self {
+        let protocol = if enabled {
+            crate::data::ProtocolVersion::V2
+        } else {
+            crate::data::ProtocolVersion::V1
+        };
+        if self.endpoint == self.protocol.default_endpoint() {
+            self.endpoint = protocol.default_endpoint().to_string();
+        }
+        self.protocol = protocol;
+        self.clone()
+    }
+    
     #[wasm_bindgen(js_name = setAutoReconnect)]
     pub fn set_auto_reconnect(&mut self, enabled: bool) -> Self {
         self.auto_reconnect = enabled;
@@ -24,6 +24,7 @@
 //! }
 //! ```
 
+use crate::clock::Clock;
 use crate::orderbook::OrderBook;
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
@@ -173,6 +174,8 @@ impl RollingStats {
 /// statistical outliers (significantly larger than typical orders).
 pub struct WhaleDetector {
     config: WhaleConfig,
+    /// Stamps detections
+    clock: Clock,
     /// Rolling statistics by symbol
     stats: Mutex<HashMap<String, RollingStats>>,
 }
@@ -187,10 +190,17 @@ impl WhaleDetector {
     pub fn with_config(config: WhaleConfig) -> Self {
         Self {
             config,
+            clock: Clock::system(),
             stats: Mutex::new(HashMap::new()),
         }
     }
     
+    /// Stamp detections with `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+    
     /// Analyze an order book for whale orders
     ///
     /// Returns a list of detected whales, sorted by z-score (largest first).
@@ -202,7 +212,7 @@ impl WhaleDetector {
             .entry(book.symbol.clone())
             .or_insert_with(|| RollingStats::new(self.config.window_size));
         
-        let now = Utc::now();
+        let now = self.clock.now();
         let min_size_f64 = decimal_to_f64(self.config.min_absolute_size);
         
         // Analyze bid side
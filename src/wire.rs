@@ -0,0 +1,702 @@
+//! Zero-copy decoding of Kraken frames
+//!
+//! The hot path never builds a `serde_json::Value` tree. A frame is split
+//! into slices borrowed from the received text, and prices and quantities
+//! are read from their JSON text straight into [`Decimal`] without an
+//! intermediate `String` or `f64`. [`KrakenDataParser`] decodes every
+//! market data and instrument channel with these functions and only uses
+//! a `Value` tree for what they don't cover (control events, method
+//! responses, private feeds). Frames that carry no exchange time are
+//! stamped with the receive time the parser passes in.
+//!
+//! [`KrakenDataParser`]: crate::parser::KrakenDataParser
+
+use crate::{data::*, error::ParseError};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::de::{self, Deserializer, IgnoredAny, SeqAccess, Visitor};
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+/// Kraken v1 data frame split into slices of the received text
+///
+/// `[channelID, payload, (payload,) channelName, pair]`
+#[derive(Debug, Clone)]
+pub struct V1Frame<'a> {
+    /// Kraken's `channelID`
+    pub channel_id: Option<u64>,
+    /// Undecoded payloads; book updates may split asks and bids across two
+    pub payloads: Vec<&'a RawValue>,
+    pub channel_name: &'a str,
+    pub pair: &'a str,
+}
+
+impl<'a> V1Frame<'a> {
+    /// Split a v1 data frame without decoding its payloads
+    ///
+    /// Fails for anything that isn't a public data frame, e.g. private
+    /// `[payload, "ownTrades", {"sequence": n}]` frames.
+    pub fn parse(text: &'a str) -> Result<Self, ParseError> {
+        let parts: Vec<&'a RawValue> = serde_json::from_str(text).map_err(invalid_json)?;
+        match parts.as_slice() {
+            [channel_id, payloads @ .., channel_name, pair] if !payloads.is_empty() => Ok(Self {
+                channel_id: channel_id.get().parse().ok(),
+                payloads: payloads.to_vec(),
+                channel_name: plain_str(channel_name)
+                    .ok_or_else(|| ParseError::MissingField("channelName".to_string()))?,
+                pair: plain_str(pair).ok_or_else(|| ParseError::MissingField("pair".to_string()))?,
+            }),
+            _ => Err(ParseError::MalformedMessage("Invalid v1 data frame".to_string())),
+        }
+    }
+
+    /// The whole frame as JSON values, for parsers that work on a `Value` tree
+    pub fn to_values(&self) -> Result<Vec<Value>, ParseError> {
+        let mut frame = Vec::with_capacity(self.payloads.len() + 3);
+        frame.push(self.channel_id.map_or(Value::Null, Value::from));
+        for payload in &self.payloads {
+            frame.push(serde_json::from_str(payload.get()).map_err(invalid_json)?);
+        }
+        frame.push(Value::from(self.channel_name));
+        frame.push(Value::from(self.pair));
+        Ok(frame)
+    }
+
+    /// The single payload of a ticker, trade, spread or OHLC frame
+    fn payload(&self, channel: &str) -> Result<&'a RawValue, ParseError> {
+        match self.payloads.as_slice() {
+            [payload] => Ok(payload),
+            _ => Err(ParseError::MalformedMessage(format!("Invalid {} message format", channel))),
+        }
+    }
+}
+
+/// JSON object frame with its `data` left undecoded
+///
+/// Covers v2 frames as well as v1 `event` objects, which the parser hands
+/// to the `Value`-based path.
+#[derive(Debug, Clone, Deserialize)]
+pub struct V2Frame<'a> {
+    /// The whole frame
+    #[serde(skip)]
+    pub text: &'a str,
+    /// v1 control event (`subscriptionStatus`, `heartbeat`, ...)
+    #[serde(borrow, default)]
+    pub event: Option<&'a str>,
+    /// Set on responses to `method` requests
+    #[serde(borrow, default)]
+    pub method: Option<&'a str>,
+    #[serde(borrow, default)]
+    pub channel: Option<&'a str>,
+    /// `snapshot` or `update`
+    #[serde(rename = "type", borrow, default)]
+    pub kind: Option<&'a str>,
+    #[serde(default)]
+    pub sequence: Option<u64>,
+    #[serde(borrow, default)]
+    pub data: Option<&'a RawValue>,
+}
+
+impl<'a> V2Frame<'a> {
+    /// Read the routing fields of an object frame
+    pub fn parse(text: &'a str) -> Result<Self, ParseError> {
+        let frame: V2Frame<'a> = serde_json::from_str(text).map_err(invalid_json)?;
+        Ok(Self { text, ..frame })
+    }
+
+    /// Whether `data` can be decoded by [`KrakenDataParser`] without a `Value` tree
+    ///
+    /// [`KrakenDataParser`]: crate::parser::KrakenDataParser
+    pub fn is_market_data(&self) -> bool {
+        self.event.is_none()
+            && self.method.is_none()
+            && matches!(self.channel, Some("ticker" | "book" | "trade" | "ohlc"))
+    }
+
+    /// Undecoded `data` array
+    fn data(&self) -> Result<&'a RawValue, ParseError> {
+        self.data.ok_or_else(|| ParseError::MissingField("data".to_string()))
+    }
+}
+
+/// Decimal read from its JSON text, quoted (v1) or a bare number (v2)
+struct Dec(Decimal);
+
+impl<'de> Deserialize<'de> for Dec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = <&RawValue>::deserialize(deserializer)?;
+        decimal(raw.get())
+            .map(Dec)
+            .ok_or_else(|| de::Error::custom(format_args!("invalid decimal: {}", raw.get())))
+    }
+}
+
+/// Decode a decimal from JSON text, quoted or not
+fn decimal(text: &str) -> Option<Decimal> {
+    let text = text.strip_prefix('"').and_then(|t| t.strip_suffix('"')).unwrap_or(text);
+    // v2 numbers may use exponent form
+    Decimal::from_str(text).or_else(|_| Decimal::from_scientific(text)).ok()
+}
+
+/// Contents of a JSON string that needs no unescaping
+fn plain_str(raw: &RawValue) -> Option<&str> {
+    raw.get()
+        .strip_prefix('"')?
+        .strip_suffix('"')
+        .filter(|s| !s.contains('\\'))
+}
+
+/// Decode a v1 `"seconds.micros"` timestamp without going through `f64`
+fn v1_time(text: &str) -> Option<DateTime<Utc>> {
+    let (seconds, fraction) = text.split_once('.').unwrap_or((text, ""));
+    let digits = &fraction[..fraction.len().min(9)];
+    let nanos = match digits {
+        "" => 0,
+        digits => digits.parse::<u32>().ok()? * 10u32.pow(9 - digits.len() as u32),
+    };
+    DateTime::from_timestamp(seconds.parse().ok()?, nanos)
+}
+
+/// Id for a v1 trade, which Kraken sends without one
+///
+/// Derived from the trade itself and its position in the frame, so the
+/// same frame yields the same ids when parsed again or replayed. Two frames
+/// only share an id if they carry the same trade at the same position.
+fn v1_trade_id(pair: &str, trade: &V1Trade<'_>, index: usize) -> String {
+    format!("trade:{}:{}:{}:{}:{}:{}", pair, trade.time, trade.price, trade.volume, trade.side, index)
+}
+
+/// Decode a v2 RFC 3339 timestamp
+fn v2_time(text: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text).ok().map(|t| t.with_timezone(&Utc))
+}
+
+fn trade_side(side: &str) -> Result<TradeSide, ParseError> {
+    match side {
+        "b" | "buy" => Ok(TradeSide::Buy),
+        "s" | "sell" => Ok(TradeSide::Sell),
+        _ => Err(ParseError::InvalidDataType(format!("Invalid trade side: {}", side))),
+    }
+}
+
+fn invalid_json(e: serde_json::Error) -> ParseError {
+    ParseError::InvalidJson(e.to_string())
+}
+
+/// Decode `T` from a payload slice
+fn decode<'a, T: Deserialize<'a>>(raw: &'a RawValue) -> Result<T, ParseError> {
+    serde_json::from_str(raw.get()).map_err(invalid_json)
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// V1 PAYLOADS
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+/// v1 book level: [price, volume, timestamp, ("r")]
+struct V1Level(PriceLevel);
+
+impl<'de> Deserialize<'de> for V1Level {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct LevelVisitor;
+
+        impl<'de> Visitor<'de> for LevelVisitor {
+            type Value = V1Level;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a [price, volume, timestamp] book level")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<V1Level, A::Error> {
+                let Dec(price) = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let Dec(volume) = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                let timestamp: &str = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(2, &self))?;
+                // Republished levels carry a trailing "r"
+                while seq.next_element::<IgnoredAny>()?.is_some() {}
+
+                let timestamp = v1_time(timestamp)
+                    .ok_or_else(|| de::Error::custom(format!("invalid level timestamp: {}", timestamp)))?;
+                Ok(V1Level(PriceLevel { price, volume, timestamp }))
+            }
+        }
+
+        deserializer.deserialize_seq(LevelVisitor)
+    }
+}
+
+/// v1 trade: [price, volume, time, side, orderType, misc]
+struct V1Trade<'a> {
+    price: Decimal,
+    volume: Decimal,
+    time: &'a str,
+    side: &'a str,
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for V1Trade<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TradeVisitor;
+
+        impl<'de> Visitor<'de> for TradeVisitor {
+            type Value = V1Trade<'de>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a [price, volume, time, side, ...] trade")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<V1Trade<'de>, A::Error> {
+                let Dec(price) = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let Dec(volume) = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                let time = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(2, &self))?;
+                let side = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(3, &self))?;
+                while seq.next_element::<IgnoredAny>()?.is_some() {}
+
+                Ok(V1Trade { price, volume, time, side })
+            }
+        }
+
+        deserializer.deserialize_seq(TradeVisitor)
+    }
+}
+
+/// v1 book payload; snapshots use "as"/"bs", updates "a"/"b"
+#[derive(Deserialize)]
+struct V1BookPayload<'a> {
+    #[serde(rename = "as")]
+    snapshot_asks: Option<Vec<V1Level>>,
+    #[serde(rename = "bs")]
+    snapshot_bids: Option<Vec<V1Level>>,
+    a: Option<Vec<V1Level>>,
+    b: Option<Vec<V1Level>>,
+    #[serde(borrow)]
+    c: Option<&'a str>,
+}
+
+/// v1 ticker payload: `[today, last24h]` style arrays, left undecoded
+#[derive(Deserialize)]
+struct V1TickerPayload<'a> {
+    #[serde(borrow, default)]
+    a: Vec<&'a RawValue>,
+    #[serde(borrow, default)]
+    b: Vec<&'a RawValue>,
+    #[serde(borrow, default)]
+    c: Vec<&'a RawValue>,
+    #[serde(borrow, default)]
+    v: Vec<&'a RawValue>,
+    #[serde(borrow, default)]
+    p: Vec<&'a RawValue>,
+    #[serde(borrow, default)]
+    t: Vec<&'a RawValue>,
+    #[serde(borrow, default)]
+    l: Vec<&'a RawValue>,
+    #[serde(borrow, default)]
+    h: Vec<&'a RawValue>,
+    #[serde(borrow, default)]
+    o: Vec<&'a RawValue>,
+}
+
+/// `index`th element of a raw array as a decimal
+fn decimal_at(values: &[&RawValue], index: usize) -> Option<Decimal> {
+    values.get(index).and_then(|v| decimal(v.get()))
+}
+
+/// Decode a v1 ticker frame
+pub(crate) fn v1_ticker(frame: &V1Frame<'_>, received: DateTime<Utc>) -> Result<TickerData, ParseError> {
+    let ticker: V1TickerPayload = decode(frame.payload("ticker")?)?;
+    let count = |index: usize| ticker.t.get(index).and_then(|v| v.get().parse::<u64>().ok());
+
+    Ok(TickerData {
+        symbol: frame.pair.to_string(),
+        bid: decimal_at(&ticker.b, 0).unwrap_or_default(),
+        ask: decimal_at(&ticker.a, 0).unwrap_or_default(),
+        last_price: decimal_at(&ticker.c, 0).unwrap_or_default(),
+        volume: decimal_at(&ticker.v, 0).unwrap_or_default(),
+        timestamp: received,
+        bid_qty: decimal_at(&ticker.b, 2).unwrap_or_default(),
+        ask_qty: decimal_at(&ticker.a, 2).unwrap_or_default(),
+        last_qty: decimal_at(&ticker.c, 1),
+        volume_24h: decimal_at(&ticker.v, 1).unwrap_or_default(),
+        vwap_today: decimal_at(&ticker.p, 0),
+        vwap_24h: decimal_at(&ticker.p, 1).unwrap_or_default(),
+        trades_today: count(0),
+        trades_24h: count(1),
+        low_today: decimal_at(&ticker.l, 0),
+        low_24h: decimal_at(&ticker.l, 1).unwrap_or_default(),
+        high_today: decimal_at(&ticker.h, 0),
+        high_24h: decimal_at(&ticker.h, 1).unwrap_or_default(),
+        open_today: decimal_at(&ticker.o, 0),
+        open_24h: decimal_at(&ticker.o, 1).unwrap_or_default(),
+    })
+}
+
+/// Decode a v1 spread frame: [bid, ask, timestamp, bidVolume, askVolume]
+pub(crate) fn v1_spread(frame: &V1Frame<'_>, received: DateTime<Utc>) -> Result<SpreadData, ParseError> {
+    let spread: Vec<&RawValue> = decode(frame.payload("spread")?)?;
+    if spread.len() < 5 {
+        return Err(ParseError::MalformedMessage("Invalid spread message format".to_string()));
+    }
+
+    Ok(SpreadData {
+        symbol: frame.pair.to_string(),
+        bid: decimal_at(&spread, 0).unwrap_or_default(),
+        ask: decimal_at(&spread, 1).unwrap_or_default(),
+        bid_volume: decimal_at(&spread, 3).unwrap_or_default(),
+        ask_volume: decimal_at(&spread, 4).unwrap_or_default(),
+        timestamp: plain_str(spread[2]).and_then(v1_time).unwrap_or(received),
+    })
+}
+
+/// Decode a v1 book frame, returning whether it is a snapshot
+pub(crate) fn v1_book(frame: &V1Frame<'_>, received: DateTime<Utc>) -> Result<(bool, OrderBookUpdate), ParseError> {
+    let mut snapshot = false;
+    let mut bids = Vec::new();
+    let mut asks = Vec::new();
+    let mut checksum = None;
+
+    for payload in &frame.payloads {
+        let book: V1BookPayload = decode(payload)?;
+        snapshot |= book.snapshot_asks.is_some() || book.snapshot_bids.is_some();
+        for levels in [book.snapshot_bids, book.b].into_iter().flatten() {
+            bids.extend(levels.into_iter().map(|V1Level(level)| level));
+        }
+        for levels in [book.snapshot_asks, book.a].into_iter().flatten() {
+            asks.extend(levels.into_iter().map(|V1Level(level)| level));
+        }
+        if let Some(c) = book.c {
+            checksum = c.parse::<u32>().ok();
+        }
+    }
+
+    Ok((snapshot, OrderBookUpdate {
+        symbol: frame.pair.to_string(),
+        bids,
+        asks,
+        timestamp: received,
+        checksum,
+    }))
+}
+
+/// Decode a v1 trade frame
+pub(crate) fn v1_trades(frame: &V1Frame<'_>, received: DateTime<Utc>) -> Result<Vec<TradeData>, ParseError> {
+    let trades: Vec<V1Trade> = decode(frame.payload("trade")?)?;
+    if trades.is_empty() {
+        return Err(ParseError::MalformedMessage("Invalid trade message format".to_string()));
+    }
+
+    trades.into_iter()
+        .enumerate()
+        .map(|(index, trade)| Ok(TradeData {
+            symbol: frame.pair.to_string(),
+            price: trade.price,
+            volume: trade.volume,
+            side: trade_side(trade.side)?,
+            timestamp: v1_time(trade.time).unwrap_or(received),
+            trade_id: v1_trade_id(frame.pair, &trade, index),
+            backfilled: false,
+        }))
+        .collect()
+}
+
+/// Decode a v1 OHLC frame: [time, etime, open, high, low, close, vwap, volume, count]
+pub(crate) fn v1_ohlc(frame: &V1Frame<'_>, received: DateTime<Utc>) -> Result<OHLCData, ParseError> {
+    let ohlc: Vec<&RawValue> = decode(frame.payload("OHLC")?)?;
+    if ohlc.len() < 8 {
+        return Err(ParseError::MalformedMessage("Invalid OHLC message format".to_string()));
+    }
+
+    Ok(OHLCData {
+        symbol: frame.pair.to_string(),
+        open: decimal_at(&ohlc, 2).unwrap_or_default(),
+        high: decimal_at(&ohlc, 3).unwrap_or_default(),
+        low: decimal_at(&ohlc, 4).unwrap_or_default(),
+        close: decimal_at(&ohlc, 5).unwrap_or_default(),
+        volume: decimal_at(&ohlc, 7).unwrap_or_default(),
+        timestamp: plain_str(ohlc[0]).and_then(v1_time).unwrap_or(received),
+        // Channel name carries the interval, e.g. "ohlc-5"
+        interval: frame.channel_name.strip_prefix("ohlc-").unwrap_or("1").to_string(),
+        backfilled: false,
+    })
+}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// V2 DATA ENTRIES
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+#[derive(Deserialize)]
+struct V2Ticker<'a> {
+    #[serde(borrow)]
+    symbol: &'a str,
+    bid: Dec,
+    ask: Dec,
+    last: Dec,
+    volume: Dec,
+    bid_qty: Option<Dec>,
+    ask_qty: Option<Dec>,
+    vwap: Option<Dec>,
+    low: Option<Dec>,
+    high: Option<Dec>,
+    change: Option<Dec>,
+    #[serde(borrow)]
+    timestamp: Option<&'a str>,
+}
+
+#[derive(Deserialize)]
+struct V2Level {
+    price: Dec,
+    qty: Dec,
+}
+
+#[derive(Deserialize)]
+struct V2Book<'a> {
+    #[serde(borrow)]
+    symbol: &'a str,
+    #[serde(default)]
+    bids: Vec<V2Level>,
+    #[serde(default)]
+    asks: Vec<V2Level>,
+    checksum: Option<u32>,
+    #[serde(borrow)]
+    timestamp: Option<&'a str>,
+}
+
+#[derive(Deserialize)]
+struct V2Trade<'a> {
+    #[serde(borrow)]
+    symbol: &'a str,
+    #[serde(borrow)]
+    side: &'a str,
+    price: Dec,
+    qty: Dec,
+    #[serde(borrow)]
+    trade_id: &'a RawValue,
+    #[serde(borrow)]
+    timestamp: &'a str,
+}
+
+#[derive(Deserialize)]
+struct V2Ohlc<'a> {
+    #[serde(borrow)]
+    symbol: &'a str,
+    open: Dec,
+    high: Dec,
+    low: Dec,
+    close: Dec,
+    volume: Dec,
+    interval: u64,
+    #[serde(borrow)]
+    interval_begin: &'a str,
+    #[serde(borrow)]
+    timestamp: Option<&'a str>,
+}
+
+#[derive(Deserialize)]
+struct V2Pair<'a> {
+    #[serde(borrow)]
+    symbol: &'a str,
+    #[serde(borrow)]
+    base: &'a str,
+    #[serde(borrow)]
+    quote: &'a str,
+    #[serde(borrow)]
+    status: &'a str,
+    price_precision: u32,
+    price_increment: Dec,
+    qty_precision: u32,
+    qty_increment: Dec,
+    qty_min: Dec,
+    cost_min: Option<Dec>,
+}
+
+/// v2 instrument `data`; the `assets` list is not used
+#[derive(Deserialize)]
+struct V2Instruments<'a> {
+    #[serde(borrow)]
+    pairs: Vec<V2Pair<'a>>,
+}
+
+/// Decoded v2 entries, each with its exchange timestamp
+pub(crate) type Entries<T> = Vec<(T, Option<DateTime<Utc>>)>;
+
+/// Decode the `data` of a v2 ticker frame
+pub(crate) fn v2_tickers(frame: &V2Frame<'_>, received: DateTime<Utc>) -> Result<Entries<TickerData>, ParseError> {
+    let tickers: Vec<V2Ticker> = decode(frame.data()?)?;
+    Ok(tickers.into_iter()
+        .map(|ticker| {
+            let exchange_time = ticker.timestamp.and_then(v2_time);
+            let optional = |value: Option<Dec>| value.map(|Dec(v)| v).unwrap_or_default();
+            let last_price = ticker.last.0;
+            (TickerData {
+                symbol: ticker.symbol.to_string(),
+                bid: ticker.bid.0,
+                ask: ticker.ask.0,
+                last_price,
+                volume: ticker.volume.0,
+                timestamp: exchange_time.unwrap_or(received),
+                bid_qty: optional(ticker.bid_qty),
+                ask_qty: optional(ticker.ask_qty),
+                volume_24h: ticker.volume.0,
+                vwap_24h: optional(ticker.vwap),
+                low_24h: optional(ticker.low),
+                high_24h: optional(ticker.high),
+                open_24h: last_price - optional(ticker.change),
+                ..Default::default()
+            }, exchange_time)
+        })
+        .collect())
+}
+
+/// Decode the `data` of a v2 book frame
+pub(crate) fn v2_books(frame: &V2Frame<'_>, received: DateTime<Utc>) -> Result<Entries<OrderBookUpdate>, ParseError> {
+    let books: Vec<V2Book> = decode(frame.data()?)?;
+    Ok(books.into_iter()
+        .map(|book| {
+            let exchange_time = book.timestamp.and_then(v2_time);
+            let timestamp = exchange_time.unwrap_or(received);
+            let levels = |levels: Vec<V2Level>| levels.into_iter()
+                .map(|level| PriceLevel { price: level.price.0, volume: level.qty.0, timestamp })
+                .collect();
+            (OrderBookUpdate {
+                symbol: book.symbol.to_string(),
+                bids: levels(book.bids),
+                asks: levels(book.asks),
+                timestamp,
+                checksum: book.checksum,
+            }, exchange_time)
+        })
+        .collect())
+}
+
+/// Decode the `data` of a v2 trade frame
+pub(crate) fn v2_trades(frame: &V2Frame<'_>) -> Result<Entries<TradeData>, ParseError> {
+    let trades: Vec<V2Trade> = decode(frame.data()?)?;
+    trades.into_iter()
+        .map(|trade| {
+            let timestamp = v2_time(trade.timestamp)
+                .ok_or_else(|| ParseError::InvalidDataType(format!("Invalid timestamp: {}", trade.timestamp)))?;
+            let trade_id = plain_str(trade.trade_id).unwrap_or(trade.trade_id.get());
+            Ok((TradeData {
+                symbol: trade.symbol.to_string(),
+                price: trade.price.0,
+                volume: trade.qty.0,
+                side: trade_side(trade.side)?,
+                timestamp,
+                trade_id: trade_id.to_string(),
+                backfilled: false,
+            }, Some(timestamp)))
+        })
+        .collect()
+}
+
+/// Decode the `data` of a v2 OHLC frame
+pub(crate) fn v2_ohlc(frame: &V2Frame<'_>) -> Result<Entries<OHLCData>, ParseError> {
+    let candles: Vec<V2Ohlc> = decode(frame.data()?)?;
+    candles.into_iter()
+        .map(|candle| {
+            let timestamp = v2_time(candle.interval_begin)
+                .ok_or_else(|| ParseError::InvalidDataType(format!("Invalid timestamp: {}", candle.interval_begin)))?;
+            Ok((OHLCData {
+                symbol: candle.symbol.to_string(),
+                open: candle.open.0,
+                high: candle.high.0,
+                low: candle.low.0,
+                close: candle.close.0,
+                volume: candle.volume.0,
+                timestamp,
+                interval: candle.interval.to_string(),
+                backfilled: false,
+            }, candle.timestamp.and_then(v2_time)))
+        })
+        .collect()
+}
+
+/// Decode the `data.pairs` of a v2 instrument frame
+pub(crate) fn v2_instruments(frame: &V2Frame<'_>) -> Result<Vec<InstrumentData>, ParseError> {
+    let instruments: V2Instruments = decode(frame.data()?)?;
+    Ok(instruments.pairs.into_iter()
+        .map(|pair| InstrumentData {
+            symbol: pair.symbol.to_string(),
+            base: pair.base.to_string(),
+            quote: pair.quote.to_string(),
+            status: pair.status.to_string(),
+            price_precision: pair.price_precision,
+            price_increment: pair.price_increment.0,
+            qty_precision: pair.qty_precision,
+            qty_increment: pair.qty_increment.0,
+            qty_min: pair.qty_min.0,
+            cost_min: pair.cost_min.map(|Dec(v)| v).unwrap_or_default(),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_decimals_keep_their_text() {
+        assert_eq!(decimal("\"5541.30000\"").unwrap().to_string(), "5541.30000");
+        assert_eq!(decimal("0.00001").unwrap(), dec!(0.00001));
+        assert_eq!(decimal("1e-5").unwrap(), dec!(0.00001));
+        // Beyond f64's 15-16 significant digits
+        assert_eq!(decimal("123456789.123456789").unwrap(), dec!(123456789.123456789));
+        assert!(decimal("\"abc\"").is_none());
+    }
+
+    #[test]
+    fn test_v1_time_without_floats() {
+        let time = v1_time("1534614057.321597").unwrap();
+        assert_eq!(time.timestamp(), 1534614057);
+        assert_eq!(time.timestamp_subsec_micros(), 321597);
+        assert_eq!(v1_time("1534614057").unwrap().timestamp_subsec_nanos(), 0);
+        assert!(v1_time("soon").is_none());
+    }
+
+    #[test]
+    fn test_frames_split_without_decoding() {
+        let text = r#"[336,{"a":[["5541.30000","2.50700000","1534614248.123678"]]},{"b":[],"c":"974942666"},"book-10","XBT/USD"]"#;
+        let frame = V1Frame::parse(text).unwrap();
+        assert_eq!(frame.channel_id, Some(336));
+        assert_eq!(frame.payloads.len(), 2);
+        assert_eq!((frame.channel_name, frame.pair), ("book-10", "XBT/USD"));
+        assert_eq!(frame.to_values().unwrap(), serde_json::from_str::<Vec<Value>>(text).unwrap());
+
+        // Private frames have no channelID and a trailing sequence object
+        assert!(V1Frame::parse(r#"[[{"TDLH43-DVQXD-2KHVYY":{}}],"ownTrades",{"sequence":2}]"#).is_err());
+
+        let frame = V2Frame::parse(r#"{"channel":"book","type":"update","data":[],"sequence":7}"#).unwrap();
+        assert!(frame.is_market_data());
+        assert_eq!((frame.kind, frame.sequence), (Some("update"), Some(7)));
+        assert!(!V2Frame::parse(r#"{"method":"subscribe","success":true}"#).unwrap().is_market_data());
+    }
+
+    #[test]
+    fn test_v1_trade_ids_are_stable() {
+        let text = r#"[337,[["50000.0","0.1","1534614057.321597","s","l",""],["50001.0","0.2","1534614057.321597","b","l",""]],"trade","XBT/USD"]"#;
+        let received = DateTime::from_timestamp(0, 0).unwrap();
+        let ids = || -> Vec<String> {
+            v1_trades(&V1Frame::parse(text).unwrap(), received).unwrap()
+                .into_iter()
+                .map(|trade| trade.trade_id)
+                .collect()
+        };
+        assert_eq!(ids(), vec![
+            "trade:XBT/USD:1534614057.321597:50000.0:0.1:s:0",
+            "trade:XBT/USD:1534614057.321597:50001.0:0.2:b:1",
+        ]);
+        assert_eq!(ids(), ids());
+
+        // A different first trade at the same time in another frame
+        let other = r#"[337,[["50002.0","0.1","1534614057.321597","b","l",""]],"trade","XBT/USD"]"#;
+        let other = v1_trades(&V1Frame::parse(other).unwrap(), received).unwrap();
+        assert!(!ids().contains(&other[0].trade_id));
+    }
+
+    #[test]
+    fn test_v1_level_needs_a_timestamp() {
+        let received = DateTime::from_timestamp(0, 0).unwrap();
+        let frame = V1Frame::parse(r#"[336,{"a":[["5541.30000","2.50700000","soon"]]},"book-10","XBT/USD"]"#).unwrap();
+        assert!(v1_book(&frame, received).is_err());
+    }
+}
@@ -1,13 +1,15 @@
 //! Integration tests for the Kraken WebSocket SDK
 
 use kraken_ws_sdk::{
-    init_logging, Channel, ClientConfig, DataType, EventCallback, KrakenWsClient,
+    Channel, ClientConfig, DataType, EventCallback, KrakenWsClient,
     TickerData, TradeData, OrderBookUpdate, OHLCData, ConnectionState, SdkError,
-    ReconnectConfig,
+    ReconnectConfig, KrakenSDKBuilder, SdkEvent, SubscriptionError, KrakenWsPool, PoolConfig,
 };
+use futures_util::{SinkExt, StreamExt};
 use std::sync::{Arc, atomic::{AtomicU64, Ordering}};
 use std::time::Duration;
-use tokio_test;
+use tokio::net::TcpListener;
+use tokio_tungstenite::{accept_async, tungstenite::Message};
 
 /// Test callback for integration tests
 struct TestCallback {
@@ -77,6 +79,9 @@ async fn test_client_creation() {
     
     assert!(!client.is_connected());
     assert_eq!(client.connection_state(), ConnectionState::Disconnected);
+    // Kraken sends the exchange status once connected
+    assert!(client.exchange_status().is_none());
+    assert!(client.system_status().is_none());
 }
 
 #[tokio::test]
@@ -137,6 +142,31 @@ async fn test_config_validation() {
         ..Default::default()
     };
     assert!(zero_buffer_config.validate().is_err());
+    
+    let zero_outgoing_config = ClientConfig {
+        outgoing_buffer_size: 0,
+        ..Default::default()
+    };
+    assert!(zero_outgoing_config.validate().is_err());
+    
+    // Liveness window must outlast the ping interval
+    let liveness_config = ClientConfig {
+        ping_interval: Duration::from_secs(30),
+        liveness_timeout: Duration::from_secs(10),
+        ..Default::default()
+    };
+    assert!(liveness_config.validate().is_err());
+}
+
+#[tokio::test]
+async fn test_client_refuses_invalid_config() {
+    let mut client = KrakenWsClient::new(ClientConfig {
+        endpoint: "ws://127.0.0.1:9".to_string(),
+        ping_interval: Duration::ZERO,
+        ..Default::default()
+    });
+    assert!(matches!(client.connect().await, Err(SdkError::Configuration(_))));
+    assert!(!client.is_connected());
 }
 
 #[tokio::test]
@@ -164,6 +194,13 @@ async fn test_reconnect_config_validation() {
         ..Default::default()
     };
     assert!(invalid_delay_config.validate().is_err());
+    
+    // Reconnecting is turned off with `auto_reconnect`, not zero attempts
+    let no_attempts_config = ReconnectConfig {
+        max_attempts: 0,
+        ..Default::default()
+    };
+    assert!(no_attempts_config.validate().is_err());
 }
 
 #[tokio::test]
@@ -175,6 +212,12 @@ async fn test_client_builder() {
         .api_credentials("test_key", "test_secret")
         .buffer_size(2048)
         .timeout(Duration::from_secs(45))
+        .backpressure_config(kraken_ws_sdk::backpressure::BackpressureConfig {
+            max_buffer_size: 500,
+            ..Default::default()
+        })
+        .latency_alerts(kraken_ws_sdk::latency::LatencyAlertConfig::new(|_| {}).with_thresholds(50_000, 5_000, 80_000))
+        .on_latency_alert(|alert| println!("{:?}", alert))
         .build();
     
     assert_eq!(config.endpoint, "wss://test.example.com");
@@ -182,6 +225,15 @@ async fn test_client_builder() {
     assert_eq!(config.api_secret, Some("test_secret".to_string()));
     assert_eq!(config.buffer_size, 2048);
     assert_eq!(config.timeout, Duration::from_secs(45));
+    assert_eq!(config.backpressure_config.max_buffer_size, 500);
+    
+    // Replacing the callback keeps the thresholds
+    assert_eq!(config.latency_alerts.as_ref().map(|a| a.total_threshold_us), Some(80_000));
+    
+    let client = KrakenWsClient::new(config);
+    assert_eq!(client.backpressure_stats().total_received, 0);
+    assert_eq!(client.latency_stats().sample_count, 0);
+    assert!(client.channel_latency_stats("ticker").is_none());
 }
 
 #[tokio::test]
@@ -229,6 +281,687 @@ async fn test_cleanup() {
     assert!(result.is_ok());
 }
 
+#[tokio::test]
+async fn test_client_builder_protocol() {
+    use kraken_ws_sdk::{ClientConfigBuilder, ProtocolVersion};
+    
+    let v2_config = ClientConfigBuilder::new()
+        .protocol(ProtocolVersion::V2)
+        .build();
+    assert_eq!(v2_config.protocol, ProtocolVersion::V2);
+    assert_eq!(v2_config.endpoint, "wss://ws.kraken.com/v2");
+    
+    // An explicit endpoint is kept when switching protocol
+    let custom_config = ClientConfigBuilder::new()
+        .endpoint("wss://test.example.com")
+        .protocol(ProtocolVersion::V2)
+        .build();
+    assert_eq!(custom_config.endpoint, "wss://test.example.com");
+    assert!(custom_config.validate().is_ok());
+    
+    // Kraken's endpoints must match the protocol
+    let v2_on_v1_endpoint = ClientConfigBuilder::new()
+        .protocol(ProtocolVersion::V2)
+        .endpoint("wss://ws.kraken.com")
+        .build();
+    assert!(v2_on_v1_endpoint.validate().is_err());
+    let v1_on_v2_endpoint = ClientConfigBuilder::new()
+        .endpoint("wss://ws.kraken.com/v2/")
+        .build();
+    assert!(v1_on_v2_endpoint.validate().is_err());
+    assert!(v2_config.validate().is_ok());
+}
+
+#[tokio::test]
+async fn test_v2_message_flow() {
+    use kraken_ws_sdk::{
+        events::EventDispatcher,
+        orderbook::OrderBookManager,
+        parser::{DataParser, KrakenDataParser, MessageHandler},
+    };
+    
+    let dispatcher = Arc::new(EventDispatcher::new());
+    let orderbook_manager = OrderBookManager::new();
+    let parser: Arc<dyn DataParser> = Arc::new(KrakenDataParser::new());
+    let handler = MessageHandler::new(parser, dispatcher.clone())
+        .with_orderbook_manager(orderbook_manager.clone());
+    
+    let callback = Arc::new(TestCallback::new());
+    let dyn_callback: Arc<dyn EventCallback> = callback.clone();
+    dispatcher.register_callback(DataType::Ticker, dyn_callback.clone());
+    dispatcher.register_callback(DataType::Trade, dyn_callback.clone());
+    dispatcher.register_callback(DataType::OrderBook, dyn_callback);
+    
+    let messages = [
+        r#"{"method":"subscribe","req_id":1,"success":true,"result":{"channel":"ticker","symbol":"BTC/USD"}}"#,
+        r#"{"channel":"heartbeat"}"#,
+        r#"{"channel":"ticker","type":"snapshot","data":[{"symbol":"BTC/USD","bid":50000.0,"bid_qty":1.0,"ask":50000.1,"ask_qty":2.0,"last":50000.0,"volume":100.0}]}"#,
+        r#"{"channel":"trade","type":"update","data":[{"symbol":"BTC/USD","side":"buy","price":50000.1,"qty":0.1,"ord_type":"market","trade_id":1,"timestamp":"2024-05-01T12:00:00.000000Z"}]}"#,
+        r#"{"channel":"book","type":"snapshot","data":[{"symbol":"BTC/USD","bids":[{"price":50000.0,"qty":1.0}],"asks":[{"price":50000.1,"qty":2.0}],"checksum":0}]}"#,
+    ];
+    for message in messages {
+        handler.handle_message(message).await.unwrap();
+    }
+    
+    assert_eq!(callback.get_ticker_count(), 1);
+    assert_eq!(callback.get_trade_count(), 1);
+    assert_eq!(callback.get_orderbook_count(), 1);
+    assert_eq!(callback.get_error_count(), 0);
+    assert!(orderbook_manager.get_order_book("BTC/USD").is_some());
+}
+
+const TICKER_FRAME: &str = r#"[0,{"a":["50001.0","1","1.0"],"b":["50000.0","2","2.0"],"c":["50000.5","0.1"],"v":["100.0","200.0"],"p":["50000.2","50000.3"],"t":[10,20],"l":["49999.0","49998.0"],"h":["50002.0","50003.0"],"o":["49999.5","49999.6"]},"ticker","XBT/USD"]"#;
+
+/// Accept one WebSocket client, wait for its subscription and return it
+async fn accept_subscriber(
+    listener: &TcpListener,
+) -> (tokio_tungstenite::WebSocketStream<tokio::net::TcpStream>, String) {
+    let (stream, _) = listener.accept().await.unwrap();
+    let mut ws = accept_async(stream).await.unwrap();
+    let subscription = loop {
+        match ws.next().await {
+            Some(Ok(Message::Text(text))) => break text,
+            Some(Ok(_)) => continue,
+            other => panic!("client went away before subscribing: {:?}", other),
+        }
+    };
+    (ws, subscription)
+}
+
+#[tokio::test]
+async fn test_sdk_delivers_ticker_callbacks() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("ws://{}", listener.local_addr().unwrap());
+    
+    let sdk = KrakenSDKBuilder::new()
+        .endpoint(&endpoint)
+        .auto_reconnect(false)
+        .build();
+    let tickers = Arc::new(AtomicU64::new(0));
+    let counter = tickers.clone();
+    sdk.subscribe_ticker("BTC/USD", move |ticker| {
+        assert_eq!(ticker.symbol, "XBT/USD");
+        counter.fetch_add(1, Ordering::Relaxed);
+    });
+    
+    let server = tokio::spawn(async move {
+        let (mut ws, subscription) = accept_subscriber(&listener).await;
+        assert!(subscription.contains("\"subscribe\""));
+        assert!(subscription.contains("BTC/USD"));
+        ws.send(Message::Text(TICKER_FRAME.to_string())).await.unwrap();
+        // Keep the socket open until the client disconnects
+        while let Some(Ok(_)) = ws.next().await {}
+    });
+    
+    sdk.connect().await.unwrap();
+    assert!(sdk.is_connected());
+    
+    for _ in 0..50 {
+        if tickers.load(Ordering::Relaxed) > 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(tickers.load(Ordering::Relaxed), 1);
+    
+    sdk.disconnect().await.unwrap();
+    assert!(!sdk.is_connected());
+    server.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_sdk_matches_callbacks_by_the_client_symbol_format() {
+    use kraken_ws_sdk::symbols::{SymbolFormat, SymbolNormalizer, SymbolRegistry};
+    
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("ws://{}", listener.local_addr().unwrap());
+    
+    let sdk = KrakenSDKBuilder::new()
+        .endpoint(&endpoint)
+        .auto_reconnect(false)
+        .symbols(SymbolNormalizer::new(SymbolRegistry::new(), SymbolFormat::AltName))
+        .build();
+    let tickers = Arc::new(AtomicU64::new(0));
+    let counter = tickers.clone();
+    sdk.subscribe_ticker("BTC/USD", move |ticker| {
+        assert_eq!(ticker.symbol, "XBTUSD");
+        counter.fetch_add(1, Ordering::Relaxed);
+    });
+    
+    let server = tokio::spawn(async move {
+        let (mut ws, _) = accept_subscriber(&listener).await;
+        ws.send(Message::Text(TICKER_FRAME.to_string())).await.unwrap();
+        while let Some(Ok(_)) = ws.next().await {}
+    });
+    
+    sdk.connect().await.unwrap();
+    for _ in 0..50 {
+        if tickers.load(Ordering::Relaxed) > 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(tickers.load(Ordering::Relaxed), 1);
+    
+    sdk.disconnect().await.unwrap();
+    server.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_sdk_reconnects_and_replays_subscriptions() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("ws://{}", listener.local_addr().unwrap());
+    
+    let sdk = KrakenSDKBuilder::new()
+        .endpoint(&endpoint)
+        .max_reconnect_attempts(3)
+        .build();
+    let reconnects = Arc::new(AtomicU64::new(0));
+    let counter = reconnects.clone();
+    sdk.on_reconnect(move |_attempt| {
+        counter.fetch_add(1, Ordering::Relaxed);
+    });
+    sdk.subscribe_trades("ETH/USD", |_| {});
+    
+    let server = tokio::spawn(async move {
+        // Drop the first connection right after the subscription arrives
+        let (ws, _) = accept_subscriber(&listener).await;
+        drop(ws);
+        
+        let (mut ws, replayed) = accept_subscriber(&listener).await;
+        while let Some(Ok(_)) = ws.next().await {}
+        replayed
+    });
+    
+    sdk.connect().await.unwrap();
+    
+    for _ in 0..100 {
+        if reconnects.load(Ordering::Relaxed) > 0 && sdk.is_connected() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(reconnects.load(Ordering::Relaxed), 1);
+    assert!(sdk.is_connected());
+    
+    sdk.disconnect().await.unwrap();
+    let replayed = server.await.unwrap();
+    assert!(replayed.contains("trade"));
+    assert!(replayed.contains("ETH/USD"));
+}
+
+#[tokio::test]
+async fn test_sdk_connects_again_after_giving_up() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("ws://{}", listener.local_addr().unwrap());
+    
+    let sdk = KrakenSDKBuilder::new()
+        .endpoint(&endpoint)
+        .auto_reconnect(false)
+        .build();
+    sdk.subscribe_ticker("XBT/USD", |_| {});
+    
+    let server = tokio::spawn(async move {
+        let (ws, _) = accept_subscriber(&listener).await;
+        drop(ws);
+        
+        let (mut ws, resubscribed) = accept_subscriber(&listener).await;
+        while let Some(Ok(_)) = ws.next().await {}
+        resubscribed
+    });
+    
+    sdk.connect().await.unwrap();
+    for _ in 0..100 {
+        if sdk.state() == ConnectionState::Failed {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(sdk.state(), ConnectionState::Failed);
+    
+    sdk.connect().await.unwrap();
+    assert!(sdk.is_connected());
+    sdk.disconnect().await.unwrap();
+    assert!(server.await.unwrap().contains("XBT/USD"));
+}
+
+#[tokio::test]
+async fn test_sdk_connect_failure() {
+    // Nothing listens on this port once the listener is dropped
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("ws://{}", listener.local_addr().unwrap());
+    drop(listener);
+    
+    let sdk = KrakenSDKBuilder::new().endpoint(&endpoint).build();
+    assert!(sdk.connect().await.is_err());
+    assert!(!sdk.is_connected());
+}
+
+#[tokio::test]
+async fn test_client_reconnects_and_replays_subscriptions() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let config = ClientConfig {
+        endpoint: format!("ws://{}", listener.local_addr().unwrap()),
+        reconnect_config: ReconnectConfig {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(10),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    
+    let mut client = KrakenWsClient::new(config);
+    let mut events = client.events();
+    client.subscribe(vec![Channel::new("book").with_symbol("XBT/USD")]).await.unwrap();
+    
+    let server = tokio::spawn(async move {
+        let (mut ws, first) = accept_subscriber(&listener).await;
+        let snapshot = r#"[1,{"as":[["5541.30000","2.50700000","1534614248.123678"]],"bs":[["5541.20000","1.52900000","1534614248.765567"]]},"book-10","XBT/USD"]"#;
+        ws.send(Message::Text(snapshot.to_string())).await.unwrap();
+        // Give the client time to build the book, then drop the connection
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        drop(ws);
+        
+        let (mut ws, replayed) = accept_subscriber(&listener).await;
+        while let Some(Ok(_)) = ws.next().await {}
+        (first, replayed)
+    });
+    
+    client.connect().await.unwrap();
+    
+    let mut saw_book = false;
+    let mut reconnect_attempt = None;
+    let deadline = tokio::time::sleep(Duration::from_secs(5));
+    tokio::pin!(deadline);
+    loop {
+        tokio::select! {
+            _ = &mut deadline => panic!("client did not reconnect"),
+            event = events.recv() => match event.unwrap() {
+                SdkEvent::OrderBook(_) => saw_book = true,
+                SdkEvent::Reconnecting(r) => reconnect_attempt = Some(r.attempt),
+                SdkEvent::State(ConnectionState::Connected) if reconnect_attempt.is_some() => break,
+                _ => {}
+            },
+        }
+    }
+    
+    assert!(saw_book);
+    assert_eq!(reconnect_attempt, Some(1));
+    assert!(client.is_connected());
+    // The book from the old connection is dropped until a new snapshot arrives
+    assert!(client.get_order_book("XBT/USD").is_none());
+    
+    client.disconnect().await.unwrap();
+    let (first, replayed) = server.await.unwrap();
+    assert!(first.contains("XBT/USD"));
+    assert!(replayed.contains("\"book\""));
+    assert!(replayed.contains("XBT/USD"));
+}
+
+#[tokio::test]
+async fn test_client_gives_up_after_max_reconnect_attempts() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let config = ClientConfig {
+        endpoint: format!("ws://{}", listener.local_addr().unwrap()),
+        reconnect_config: ReconnectConfig {
+            max_attempts: 2,
+            initial_delay: Duration::from_millis(10),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    
+    let mut client = KrakenWsClient::new(config);
+    let mut events = client.events();
+    
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let ws = accept_async(stream).await.unwrap();
+        // Close the socket and stop listening
+        drop(ws);
+    });
+    
+    client.connect().await.unwrap();
+    server.await.unwrap();
+    
+    let mut attempts = Vec::new();
+    loop {
+        let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("client did not give up")
+            .unwrap();
+        match event {
+            SdkEvent::Reconnecting(r) => attempts.push((r.attempt, r.max_attempts, r.delay)),
+            SdkEvent::State(ConnectionState::Failed) if attempts.len() == 2 => break,
+            _ => {}
+        }
+    }
+    
+    // The backoff slept on is the one the state machine reports
+    assert_eq!(attempts, vec![
+        (1, 2, Duration::from_millis(10)),
+        (2, 2, Duration::from_millis(20)),
+    ]);
+    assert!(!client.is_connected());
+}
+
+fn liveness_test_config(endpoint: String) -> ClientConfig {
+    ClientConfig {
+        endpoint,
+        ping_interval: Duration::from_millis(50),
+        liveness_timeout: Duration::from_millis(200),
+        reconnect_config: ReconnectConfig {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(10),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_client_answers_pings_and_stays_alive() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let mut client = KrakenWsClient::new(liveness_test_config(format!("ws://{}", listener.local_addr().unwrap())));
+    let mut events = client.events();
+    
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = accept_async(stream).await.unwrap();
+        ws.send(Message::Ping(b"probe".to_vec())).await.unwrap();
+        
+        let mut app_pings = 0;
+        let mut ws_pong = false;
+        while let Some(Ok(message)) = ws.next().await {
+            match message {
+                Message::Pong(data) => ws_pong = data == b"probe",
+                Message::Text(text) if text.contains("\"ping\"") => {
+                    app_pings += 1;
+                    let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                    let pong = serde_json::json!({"event": "pong", "reqid": request["reqid"]});
+                    ws.send(Message::Text(pong.to_string())).await.unwrap();
+                }
+                _ => {}
+            }
+        }
+        (ws_pong, app_pings)
+    });
+    
+    client.connect().await.unwrap();
+    // Several liveness windows pass; pongs keep the connection alive
+    tokio::time::sleep(Duration::from_millis(600)).await;
+    assert!(client.is_connected());
+    assert!(client.is_healthy());
+    while let Ok(event) = events.try_recv() {
+        assert!(!matches!(event, SdkEvent::Reconnecting(_)), "unexpected reconnect");
+    }
+    
+    client.disconnect().await.unwrap();
+    let (ws_pong, app_pings) = server.await.unwrap();
+    assert!(ws_pong);
+    assert!(app_pings >= 5);
+}
+
+#[tokio::test]
+async fn test_client_subscribe_waits_for_confirmation() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let config = ClientConfig {
+        endpoint: format!("ws://{}", listener.local_addr().unwrap()),
+        subscription_timeout: Duration::from_millis(300),
+        ..Default::default()
+    };
+    let mut client = KrakenWsClient::new(config);
+    
+    // Confirms XBT/USD, rejects XBT/FOO and never answers ETH/USD
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = accept_async(stream).await.unwrap();
+        while let Some(Ok(message)) = ws.next().await {
+            let Message::Text(text) = message else { continue };
+            let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+            if request["event"] != "subscribe" {
+                continue;
+            }
+            let pair = request["pair"][0].as_str().unwrap();
+            let status = match pair {
+                "XBT/USD" => serde_json::json!({
+                    "channelID": 42, "channelName": "ticker", "event": "subscriptionStatus", "pair": pair,
+                    "reqid": request["reqid"], "status": "subscribed", "subscription": {"name": "ticker"}
+                }),
+                "XBT/FOO" => serde_json::json!({
+                    "errorMessage": "Currency pair not supported XBT/FOO", "event": "subscriptionStatus", "pair": pair,
+                    "reqid": request["reqid"], "status": "error", "subscription": {"name": "ticker"}
+                }),
+                _ => continue,
+            };
+            ws.send(Message::Text(status.to_string())).await.unwrap();
+        }
+    });
+    
+    client.connect().await.unwrap();
+    
+    let ticker = Channel::new("ticker").with_symbol("XBT/USD");
+    client.subscribe(vec![ticker.clone()]).await.unwrap();
+    assert!(client.is_subscribed(&ticker));
+    
+    let result = client.subscribe(vec![Channel::new("ticker").with_symbol("XBT/FOO")]).await;
+    assert!(matches!(
+        result,
+        Err(SdkError::Subscription(SubscriptionError::UnknownPair(pair))) if pair == "XBT/FOO"
+    ));
+    
+    let result = client.subscribe(vec![Channel::new("ticker").with_symbol("ETH/USD")]).await;
+    assert!(matches!(result, Err(SdkError::Subscription(SubscriptionError::Timeout { .. }))));
+    
+    client.disconnect().await.unwrap();
+    server.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_pool_moves_subscriptions_off_dead_connection() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let config = ClientConfig {
+        endpoint: format!("ws://{}", listener.local_addr().unwrap()),
+        reconnect_config: ReconnectConfig {
+            max_attempts: 1,
+            initial_delay: Duration::from_millis(10),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let pool_config = PoolConfig::new()
+        .with_max_subscriptions_per_connection(2)
+        .with_health_check_interval(Duration::from_millis(50));
+    let mut pool = KrakenWsPool::new(config, pool_config);
+    
+    // Every connection confirms its pairs except ETH/USD. The second
+    // connection closes after subscribing and its reconnect is refused.
+    let subscribed = Arc::new(std::sync::Mutex::new(Vec::<(usize, String)>::new()));
+    let server = {
+        let subscribed = Arc::clone(&subscribed);
+        tokio::spawn(async move {
+            for index in 0.. {
+                let (stream, _) = listener.accept().await.unwrap();
+                if index == 2 {
+                    drop(stream);
+                    continue;
+                }
+                let subscribed = Arc::clone(&subscribed);
+                tokio::spawn(async move {
+                    let mut ws = accept_async(stream).await.unwrap();
+                    while let Some(Ok(message)) = ws.next().await {
+                        let Message::Text(text) = message else { continue };
+                        let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                        if request["event"] != "subscribe" {
+                            continue;
+                        }
+                        for pair in request["pair"].as_array().unwrap() {
+                            let pair = pair.as_str().unwrap();
+                            subscribed.lock().unwrap().push((index, pair.to_string()));
+                            let status = if pair == "ETH/USD" {
+                                serde_json::json!({
+                                    "errorMessage": "Currency pair not supported ETH/USD", "event": "subscriptionStatus", "pair": pair,
+                                    "reqid": request["reqid"], "status": "error", "subscription": {"name": "ticker"}
+                                })
+                            } else {
+                                serde_json::json!({
+                                    "channelID": index * 100 + 1, "channelName": "ticker", "event": "subscriptionStatus", "pair": pair,
+                                    "reqid": request["reqid"], "status": "subscribed", "subscription": {"name": "ticker"}
+                                })
+                            };
+                            ws.send(Message::Text(status.to_string())).await.unwrap();
+                        }
+                        if index == 1 {
+                            return;
+                        }
+                    }
+                });
+            }
+        })
+    };
+    
+    pool.connect().await.unwrap();
+    let ticker = |symbol: &str| Channel::new("ticker").with_symbol(symbol);
+    let result = pool.subscribe(vec![ticker("XBT/USD"), ticker("ETH/USD"), ticker("SOL/USD")]).await;
+    assert!(matches!(
+        result,
+        Err(SdkError::Subscription(SubscriptionError::UnknownPair(pair))) if pair == "ETH/USD"
+    ));
+    // The rejected pair no longer takes up room
+    assert_eq!(pool.connection_for(&ticker("ETH/USD")).await, None);
+    
+    // SOL/USD is resubscribed on another connection once its own gives up
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    loop {
+        let moved = subscribed.lock().unwrap().iter().any(|(index, pair)| pair == "SOL/USD" && *index != 1);
+        if moved {
+            break;
+        }
+        assert!(tokio::time::Instant::now() < deadline, "subscription was not moved");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let owner = pool.connection_for(&ticker("SOL/USD")).await;
+    assert!(owner.is_some());
+    assert_ne!(owner, Some(1));
+    assert!(pool.connection_for(&ticker("XBT/USD")).await.is_some());
+    
+    pool.disconnect().await.unwrap();
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_client_reconnects_silent_connection() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let mut client = KrakenWsClient::new(liveness_test_config(format!("ws://{}", listener.local_addr().unwrap())));
+    let mut events = client.events();
+    
+    let server = tokio::spawn(async move {
+        // A half-open peer: reads everything, never answers
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut silent = accept_async(stream).await.unwrap();
+        tokio::spawn(async move { while let Some(Ok(_)) = silent.next().await {} });
+        
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = accept_async(stream).await.unwrap();
+        while let Some(Ok(_)) = ws.next().await {}
+    });
+    
+    client.connect().await.unwrap();
+    
+    let mut lost = false;
+    loop {
+        let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("silent connection was not detected")
+            .unwrap();
+        match event {
+            SdkEvent::Error(SdkError::Connection(_)) => lost = true,
+            SdkEvent::Reconnecting(r) => {
+                assert_eq!(r.attempt, 1);
+                break;
+            }
+            _ => {}
+        }
+    }
+    assert!(lost);
+    
+    client.disconnect().await.unwrap();
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_client_publishes_state_transitions() {
+    use kraken_ws_sdk::state::{self, DegradedReason, TransitionTrigger};
+    use tokio_tungstenite::tungstenite::protocol::{frame::coding::CloseCode, CloseFrame};
+    
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let config = ClientConfig {
+        endpoint: format!("ws://{}", listener.local_addr().unwrap()),
+        reconnect_config: ReconnectConfig {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(10),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    
+    let mut client = KrakenWsClient::new(config);
+    let mut events = client.events();
+    client.subscribe(vec![Channel::new("ticker").with_symbol("XBT/USD")]).await.unwrap();
+    
+    let server = tokio::spawn(async move {
+        let (mut ws, _) = accept_subscriber(&listener).await;
+        let ack = r#"{"channelID":1,"channelName":"ticker","event":"subscriptionStatus","pair":"XBT/USD","status":"subscribed","subscription":{"name":"ticker"}}"#;
+        ws.send(Message::Text(ack.to_string())).await.unwrap();
+        ws.close(Some(CloseFrame { code: CloseCode::Away, reason: "maintenance".into() })).await.unwrap();
+        
+        let (mut ws, _) = accept_subscriber(&listener).await;
+        while let Some(Ok(_)) = ws.next().await {}
+    });
+    
+    client.connect().await.unwrap();
+    
+    let mut triggers = Vec::new();
+    loop {
+        let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("client did not reconnect")
+            .unwrap();
+        if let SdkEvent::Transition(transition) = event {
+            let reconnected = transition.trigger == TransitionTrigger::ConnectionEstablished
+                && triggers.contains(&TransitionTrigger::RetryAttempt(1));
+            if let state::ConnectionState::Degraded { reason, .. } = &transition.to {
+                assert_eq!(*reason, DegradedReason::ServerDisconnect);
+            }
+            triggers.push(transition.trigger);
+            if reconnected {
+                break;
+            }
+        }
+    }
+    
+    assert_eq!(triggers, vec![
+        TransitionTrigger::UserConnect,
+        TransitionTrigger::ConnectionEstablished,
+        TransitionTrigger::SubscriptionsConfirmed,
+        TransitionTrigger::ServerDisconnect(1001, "maintenance".to_string()),
+        TransitionTrigger::RetryAttempt(1),
+        TransitionTrigger::ConnectionEstablished,
+    ]);
+    let history: Vec<_> = client.state_history().into_iter().map(|t| t.trigger).collect();
+    assert_eq!(history, triggers);
+    assert_eq!(client.state(), state::ConnectionState::Subscribing);
+    // Retry budget resets once a connection is established
+    assert_eq!(client.retry_delay(), Duration::from_millis(10));
+    
+    client.disconnect().await.unwrap();
+    assert!(matches!(
+        client.state(),
+        state::ConnectionState::Closed { reason: state::ClosedReason::UserRequested }
+    ));
+    server.await.unwrap();
+}
+
 // Mock WebSocket server for testing (would be implemented with a test framework)
 // This is a placeholder for more comprehensive integration tests
 #[tokio::test]
@@ -243,7 +976,7 @@ async fn test_real_connection() {
         ..Default::default()
     };
     
-    let mut client = KrakenWsClient::new(config);
+    let client = KrakenWsClient::new(config);
     let callback: Arc<dyn EventCallback> = Arc::new(TestCallback::new());
     
     client.register_callback(DataType::Ticker, callback.clone());
@@ -251,4 +984,198 @@ async fn test_real_connection() {
     // This would fail without a mock server running
     // let result = client.connect().await;
     // assert!(result.is_ok());
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_private_feed_resubscribes_after_sequence_gap() {
+    use kraken_ws_sdk::trading_api::{PrivateChannel, PrivateEvent, PrivateWsClient, PrivateWsConfig};
+    
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("ws://{}", listener.local_addr().unwrap());
+    
+    let config = PrivateWsConfig::new("test-token".to_string())
+        .with_channels(vec![PrivateChannel::OwnTrades])
+        .with_endpoint(&endpoint);
+    let mut client = PrivateWsClient::new(config);
+    let mut events = client.subscribe();
+    
+    let server = tokio::spawn(async move {
+        let (mut ws, subscription) = accept_subscriber(&listener).await;
+        assert!(subscription.contains("ownTrades"));
+        
+        let trade = |sequence: u64, id: &str| format!(
+            r#"[[{{"{}":{{"ordertxid":"O1","pair":"XBT/USD","type":"buy","price":"50000.0","vol":"0.1","cost":"5000.0","fee":"1.0","time":"1700000000.0"}}}}],"ownTrades",{{"sequence":{}}}]"#,
+            id, sequence
+        );
+        for (sequence, id) in [(1, "T1"), (2, "T2"), (4, "T4")] {
+            ws.send(Message::Text(trade(sequence, id))).await.unwrap();
+        }
+        
+        // The gap is answered with an unsubscribe + subscribe round trip
+        let mut requests = Vec::new();
+        while requests.len() < 2 {
+            match ws.next().await {
+                Some(Ok(Message::Text(text))) => requests.push(text),
+                Some(Ok(_)) => continue,
+                other => panic!("client went away before resubscribing: {:?}", other),
+            }
+        }
+        requests
+    });
+    
+    client.connect().await.unwrap();
+    
+    let mut executions = Vec::new();
+    let mut gap = None;
+    let mut resync = None;
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while resync.is_none() {
+            match events.recv().await.unwrap() {
+                PrivateEvent::Execution(execution) => executions.push(execution.trade_id),
+                PrivateEvent::Gap(event) => gap = Some(event),
+                PrivateEvent::Resync(event) => resync = Some(event),
+                _ => {}
+            }
+        }
+    })
+    .await
+    .expect("no resync event");
+    
+    // The frame after the gap is dropped in favour of the new snapshot
+    assert_eq!(executions, vec!["T1".to_string(), "T2".to_string()]);
+    let gap = gap.expect("no gap event");
+    assert_eq!(gap.channel, "ownTrades");
+    assert_eq!(gap.expected_sequence, 3);
+    assert_eq!(gap.received_sequence, 4);
+    assert_eq!(resync.unwrap().last_good_sequence, 2);
+    
+    let requests = tokio::time::timeout(Duration::from_secs(5), server).await.unwrap().unwrap();
+    let actions: Vec<serde_json::Value> = requests.iter()
+        .map(|r| serde_json::from_str(r).unwrap())
+        .collect();
+    assert_eq!(actions[0]["event"], "unsubscribe");
+    assert_eq!(actions[1]["event"], "subscribe");
+    assert_eq!(actions[1]["subscription"]["name"], "ownTrades");
+    
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn test_client_records_session() {
+    use kraken_ws_sdk::recorder::{read_recording, Direction, FrameKind, RecorderConfig, SessionRecorder};
+    
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let dir = std::env::temp_dir().join(format!("kraken-session-{}", std::process::id()));
+    let path = dir.join("session.jsonl");
+    let recorder = SessionRecorder::open(RecorderConfig::new(&path)).unwrap();
+    let config = ClientConfig {
+        endpoint: format!("ws://{}", listener.local_addr().unwrap()),
+        recorder: Some(recorder.clone()),
+        ..Default::default()
+    };
+    let mut client = KrakenWsClient::new(config);
+    client.subscribe(vec![Channel::new("ticker").with_symbol("XBT/USD")]).await.unwrap();
+    
+    let server = tokio::spawn(async move {
+        let (mut ws, _) = accept_subscriber(&listener).await;
+        ws.send(Message::Text(TICKER_FRAME.to_string())).await.unwrap();
+        while let Some(Ok(_)) = ws.next().await {}
+    });
+    
+    client.connect().await.unwrap();
+    let mut frames = Vec::new();
+    for _ in 0..50 {
+        recorder.flush().unwrap();
+        frames = read_recording(&path).unwrap();
+        if frames.iter().any(|f| f.direction == Direction::Inbound) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    client.disconnect().await.unwrap();
+    server.await.unwrap();
+    
+    let subscribe = &frames[0];
+    assert_eq!((subscribe.direction, subscribe.kind), (Direction::Outbound, FrameKind::Text));
+    assert!(subscribe.data.contains("\"subscribe\""));
+    let ticker = frames.iter().find(|f| f.direction == Direction::Inbound).unwrap();
+    assert_eq!(ticker.data, TICKER_FRAME);
+    assert!(frames.iter().all(|f| f.connection_id == subscribe.connection_id));
+    
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_replay_analytics_are_deterministic() {
+    use kraken_ws_sdk::liquidity_heatmap::LiquidityHeatmap;
+    use kraken_ws_sdk::orderbook::OrderBookManager;
+    use kraken_ws_sdk::orderflow::OrderFlowTracker;
+    use kraken_ws_sdk::recorder::{Direction, FrameKind, RecordedFrame};
+    use kraken_ws_sdk::replay::{Pacing, ReplayEngine};
+    use kraken_ws_sdk::spoofing_detection::SpoofingDetector;
+    
+    /// Feeds every book update into the analytics under test
+    struct Analytics {
+        books: OrderBookManager,
+        flow: OrderFlowTracker,
+        spoofing: SpoofingDetector,
+        heatmap: LiquidityHeatmap,
+    }
+    
+    impl EventCallback for Analytics {
+        fn on_ticker(&self, _data: TickerData) {}
+        fn on_orderbook(&self, data: OrderBookUpdate) {
+            let book = self.books.get_order_book(&data.symbol).unwrap();
+            let events = self.flow.track_update(&book);
+            self.spoofing.process_events(&events);
+            self.heatmap.update(&book);
+        }
+        fn on_trade(&self, _data: TradeData) {}
+        fn on_ohlc(&self, _data: OHLCData) {}
+        fn on_error(&self, _error: SdkError) {}
+        fn on_connection_state_change(&self, _state: ConnectionState) {}
+    }
+    
+    let start = chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+    let frame = |offset_ms: i64, direction: Direction, data: &str| RecordedFrame {
+        timestamp: start + chrono::Duration::milliseconds(offset_ms),
+        connection_id: 1,
+        direction,
+        kind: FrameKind::Text,
+        data: data.to_string(),
+        close_code: None,
+    };
+    let frames = vec![
+        frame(0, Direction::Outbound, r#"{"event":"subscribe","pair":["XBT/USD"],"subscription":{"name":"book","depth":25}}"#),
+        frame(5, Direction::Inbound, r#"[1,{"as":[["5541.30000","2.00000000","1700000000.000000"]],"bs":[["5541.20000","1.50000000","1700000000.000000"]]},"book-25","XBT/USD"]"#),
+        // A large bid appears and is pulled 300ms later, untraded
+        frame(100, Direction::Inbound, r#"[1,{"b":[["5540.00000","40.00000000","1700000000.100000"]]},"book-25","XBT/USD"]"#),
+        frame(250, Direction::Inbound, r#"[1,{"a":[["5541.40000","12.00000000","1700000000.250000"]]},"book-25","XBT/USD"]"#),
+        frame(400, Direction::Inbound, r#"[1,{"b":[["5540.00000","0.00000000","1700000000.400000"]]},"book-25","XBT/USD"]"#),
+        frame(600, Direction::Inbound, r#"[1,{"a":[["5541.30000","0.50000000","1700000000.600000"]]},"book-25","XBT/USD"]"#),
+    ];
+    
+    let mut outputs = Vec::new();
+    for pacing in [Pacing::AsFastAsPossible, Pacing::Accelerated(20.0)] {
+        let mut replay = ReplayEngine::new(frames.clone()).with_pacing(pacing);
+        let analytics = Arc::new(Analytics {
+            books: replay.orderbook_manager(),
+            flow: OrderFlowTracker::new().with_clock(replay.clock()),
+            spoofing: SpoofingDetector::new().with_clock(replay.clock()),
+            heatmap: LiquidityHeatmap::new().with_clock(replay.clock()),
+        });
+        replay.dispatcher().register_callback(DataType::OrderBook, analytics.clone());
+        assert_eq!(replay.run().await, frames.len());
+        
+        let events = analytics.flow.get_recent_events(100);
+        let alerts = analytics.spoofing.get_recent_alerts(100);
+        assert!(!events.is_empty());
+        assert_eq!(alerts.len(), 1);
+        outputs.push((
+            serde_json::to_string(&events).unwrap(),
+            serde_json::to_string(&alerts).unwrap(),
+            serde_json::to_string(&analytics.heatmap.snapshot("XBT/USD")).unwrap(),
+        ));
+    }
+    assert_eq!(outputs[0], outputs[1]);
+}
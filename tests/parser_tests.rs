@@ -1,10 +1,23 @@
 //! Comprehensive tests for the Kraken message parser
 
 use kraken_ws_sdk::{
-    parser::{KrakenDataParser, DataParser, MessageHandler},
-    events::EventDispatcher,
-    data::*,
+    candles::{BarSpec, CandleBuilder, CandleConfig, CandleEvent},
+    clock::Clock,
+    parser::{KrakenDataParser, DataParser, MessageHandler, V1Message, V2Message},
+    wire::V1Frame,
+    events::{EventDispatcher, EventKind, SdkEvent},
+    instruments::InstrumentRegistry,
+    trading::OrderRequest,
+    data::{Channel, TradeSide},
+    error::ParseError,
+    latency::{LatencyAlertConfig, LatencyAlertType, LatencyTracker},
+    orderbook::OrderBookManager,
+    sequencing::{SequenceConfig, SequenceManager},
+    subscription::SubscriptionManager,
+    system_status::{ExchangeStatus, SystemStatus, SystemStatusMonitor},
 };
+use rust_decimal::Decimal;
+use std::str::FromStr;
 use std::sync::Arc;
 
 #[tokio::test]
@@ -29,20 +42,23 @@ async fn test_parse_kraken_ticker_message() {
         "XBT/USD"
     ]"#;
     
-    let result = parser.parse_ticker(ticker_message);
-    
-    match result {
-        Ok(ticker_data) => {
-            assert_eq!(ticker_data.symbol, "XBT/USD");
-            // Note: The actual parsing logic would need to be implemented
-            // to extract the correct fields from the Kraken format
-        }
-        Err(e) => {
-            // Expected to fail with current implementation
-            // as it's a simplified parser
-            println!("Expected parsing error: {}", e);
-        }
-    }
+    let ticker = parser.parse_ticker(ticker_message).unwrap();
+    assert_eq!(ticker.symbol, "XBT/USD");
+    assert_eq!(ticker.bid, Decimal::from_str("50000.00000").unwrap());
+    assert_eq!(ticker.bid_qty, Decimal::from(2));
+    assert_eq!(ticker.ask_qty, Decimal::from(1));
+    assert_eq!(ticker.last_qty, Some(Decimal::from_str("0.1").unwrap()));
+    assert_eq!(ticker.volume, Decimal::from(100));
+    assert_eq!(ticker.volume_24h, Decimal::from(200));
+    assert_eq!(ticker.vwap_today, Some(Decimal::from_str("50000.25").unwrap()));
+    assert_eq!(ticker.vwap_24h, Decimal::from_str("50000.3").unwrap());
+    assert_eq!((ticker.trades_today, ticker.trades_24h), (Some(10), Some(20)));
+    assert_eq!(ticker.low_24h, Decimal::from(49998));
+    assert_eq!(ticker.high_today, Some(Decimal::from(50002)));
+    assert_eq!(ticker.open_today, Some(Decimal::from_str("49999.5").unwrap()));
+    assert_eq!(ticker.open_24h, Decimal::from_str("49999.6").unwrap());
+    assert_eq!(ticker.spread(), Decimal::from(1));
+    assert_eq!(ticker.mid_price(), Decimal::from_str("50000.5").unwrap());
 }
 
 #[tokio::test]
@@ -344,4 +360,724 @@ async fn test_concurrent_message_processing() {
         let result = handle.await.unwrap();
         assert!(result.is_ok());
     }
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_parse_v2_ticker_message() {
+    let parser = KrakenDataParser::new();
+    
+    let ticker_message = r#"{
+        "channel": "ticker",
+        "type": "snapshot",
+        "data": [{
+            "symbol": "BTC/USD",
+            "bid": 50000.1,
+            "bid_qty": 0.5,
+            "ask": 50000.2,
+            "ask_qty": 1.25,
+            "last": 50000.15,
+            "volume": 1234.5678,
+            "vwap": 49900.0,
+            "low": 49000.0,
+            "high": 51000.0,
+            "change": 100.0,
+            "change_pct": 0.2
+        }]
+    }"#;
+    
+    let V2Message::Ticker(tickers) = parser.parse_v2_message(ticker_message).unwrap() else {
+        panic!("Expected ticker message");
+    };
+    assert_eq!(tickers.len(), 1);
+    assert_eq!(tickers[0].symbol, "BTC/USD");
+    assert_eq!(tickers[0].bid, Decimal::from_str("50000.1").unwrap());
+    assert_eq!(tickers[0].ask, Decimal::from_str("50000.2").unwrap());
+    assert_eq!(tickers[0].last_price, Decimal::from_str("50000.15").unwrap());
+    assert_eq!(tickers[0].volume, Decimal::from_str("1234.5678").unwrap());
+    assert_eq!(tickers[0].bid_qty, Decimal::from_str("0.5").unwrap());
+    assert_eq!(tickers[0].ask_qty, Decimal::from_str("1.25").unwrap());
+    assert_eq!(tickers[0].vwap_24h, Decimal::from(49900));
+    assert_eq!(tickers[0].low_24h, Decimal::from(49000));
+    assert_eq!(tickers[0].high_24h, Decimal::from(51000));
+    assert_eq!(tickers[0].open_24h, Decimal::from_str("49900.15").unwrap());
+    // v2 has no "today" statistics
+    assert_eq!(tickers[0].vwap_today, None);
+    assert_eq!(tickers[0].trades_24h, None);
+}
+
+#[tokio::test]
+async fn test_parse_v2_book_message() {
+    let parser = KrakenDataParser::new();
+    
+    let snapshot = r#"{
+        "channel": "book",
+        "type": "snapshot",
+        "data": [{
+            "symbol": "BTC/USD",
+            "bids": [{"price": 50000.0, "qty": 1.5}, {"price": 49999.9, "qty": 0.00000001}],
+            "asks": [{"price": 50000.1, "qty": 2.0}],
+            "checksum": 3310070434
+        }]
+    }"#;
+    
+    let V2Message::Book { snapshot: is_snapshot, updates } = parser.parse_v2_message(snapshot).unwrap() else {
+        panic!("Expected book message");
+    };
+    assert!(is_snapshot);
+    assert_eq!(updates[0].symbol, "BTC/USD");
+    assert_eq!(updates[0].bids.len(), 2);
+    assert_eq!(updates[0].bids[1].volume, Decimal::from_str("0.00000001").unwrap());
+    assert_eq!(updates[0].asks[0].price, Decimal::from_str("50000.1").unwrap());
+    assert_eq!(updates[0].checksum, Some(3310070434));
+    
+    let update = r#"{
+        "channel": "book",
+        "type": "update",
+        "data": [{
+            "symbol": "BTC/USD",
+            "bids": [{"price": 50000.0, "qty": 0.0}],
+            "asks": [],
+            "checksum": 1234,
+            "timestamp": "2024-05-01T12:00:00.123456Z"
+        }]
+    }"#;
+    
+    let V2Message::Book { snapshot: is_snapshot, updates } = parser.parse_v2_message(update).unwrap() else {
+        panic!("Expected book message");
+    };
+    assert!(!is_snapshot);
+    assert!(updates[0].bids[0].volume.is_zero());
+    assert_eq!(updates[0].timestamp.to_rfc3339(), "2024-05-01T12:00:00.123456+00:00");
+}
+
+#[tokio::test]
+async fn test_parse_v2_trade_and_ohlc_messages() {
+    let parser = KrakenDataParser::new();
+    
+    let trade_message = r#"{
+        "channel": "trade",
+        "type": "update",
+        "data": [
+            {"symbol": "ETH/USD", "side": "buy", "price": 3000.5, "qty": 0.25, "ord_type": "market", "trade_id": 100, "timestamp": "2024-05-01T12:00:00.000000Z"},
+            {"symbol": "ETH/USD", "side": "sell", "price": 3000.4, "qty": 1.0, "ord_type": "limit", "trade_id": 101, "timestamp": "2024-05-01T12:00:01.000000Z"}
+        ]
+    }"#;
+    
+    let V2Message::Trade(trades) = parser.parse_v2_message(trade_message).unwrap() else {
+        panic!("Expected trade message");
+    };
+    assert_eq!(trades.len(), 2);
+    assert_eq!(trades[0].side, TradeSide::Buy);
+    assert_eq!(trades[0].trade_id, "100");
+    assert_eq!(trades[1].side, TradeSide::Sell);
+    assert_eq!(trades[1].volume, Decimal::from(1));
+    
+    let ohlc_message = r#"{
+        "channel": "ohlc",
+        "type": "update",
+        "data": [{
+            "symbol": "BTC/USD",
+            "open": 50000.0, "high": 50100.0, "low": 49900.0, "close": 50050.0,
+            "trades": 42, "volume": 12.5, "vwap": 50010.0,
+            "interval_begin": "2024-05-01T12:00:00.000000000Z",
+            "interval": 5,
+            "timestamp": "2024-05-01T12:05:00.000000Z"
+        }]
+    }"#;
+    
+    let V2Message::Ohlc(candles) = parser.parse_v2_message(ohlc_message).unwrap() else {
+        panic!("Expected OHLC message");
+    };
+    assert_eq!(candles[0].interval, "5");
+    assert_eq!(candles[0].close, Decimal::from(50050));
+    assert_eq!(candles[0].timestamp.to_rfc3339(), "2024-05-01T12:00:00+00:00");
+}
+
+#[tokio::test]
+async fn test_parse_v2_instrument_message() {
+    let parser = KrakenDataParser::new();
+    
+    let instrument_message = r#"{
+        "channel": "instrument",
+        "type": "snapshot",
+        "data": {
+            "assets": [{"id": "BTC", "status": "enabled", "precision": 10}],
+            "pairs": [{
+                "symbol": "BTC/USD",
+                "base": "BTC",
+                "quote": "USD",
+                "status": "online",
+                "qty_precision": 8,
+                "qty_increment": 0.00000001,
+                "price_precision": 1,
+                "price_increment": 0.1,
+                "cost_precision": 5,
+                "marginable": true,
+                "has_index": true,
+                "cost_min": 0.5,
+                "qty_min": 0.0001
+            }]
+        }
+    }"#;
+    
+    let V2Message::Instrument(instruments) = parser.parse_v2_message(instrument_message).unwrap() else {
+        panic!("Expected instrument message");
+    };
+    assert_eq!(instruments[0].symbol, "BTC/USD");
+    assert_eq!(instruments[0].status, "online");
+    assert_eq!(instruments[0].price_precision, 1);
+    assert_eq!(instruments[0].price_increment, Decimal::from_str("0.1").unwrap());
+    assert_eq!(instruments[0].qty_increment, Decimal::from_str("0.00000001").unwrap());
+    assert_eq!(instruments[0].qty_min, Decimal::from_str("0.0001").unwrap());
+}
+
+#[tokio::test]
+async fn test_instrument_messages_fill_registry() {
+    let parser: Arc<dyn DataParser> = Arc::new(KrakenDataParser::new());
+    let dispatcher = Arc::new(EventDispatcher::new());
+    let registry = InstrumentRegistry::new();
+    let handler = MessageHandler::new(parser, dispatcher).with_instrument_registry(registry.clone());
+    
+    let instrument_message = r#"{"channel":"instrument","type":"snapshot","data":{"assets":[],"pairs":[
+        {"symbol":"BTC/USD","base":"BTC","quote":"USD","status":"online","qty_precision":8,
+         "qty_increment":0.00000001,"price_precision":1,"price_increment":0.1,"cost_min":0.5,"qty_min":0.0001}
+    ]}}"#;
+    handler.handle_message(instrument_message).await.unwrap();
+    
+    let instrument = registry.get("BTC/USD").unwrap();
+    assert_eq!(instrument.price_increment, Decimal::from_str("0.1").unwrap());
+    let off_tick = OrderRequest::limit_buy("BTC/USD", Decimal::from_str("0.01").unwrap(), Decimal::from_str("50000.05").unwrap());
+    assert!(registry.validate(&off_tick).is_err());
+}
+
+#[tokio::test]
+async fn test_parse_v2_control_messages() {
+    let parser = KrakenDataParser::new();
+    
+    assert_eq!(
+        parser.parse_v2_message(r#"{"channel":"heartbeat"}"#).unwrap(),
+        V2Message::Heartbeat
+    );
+    assert_eq!(
+        parser.parse_v2_message(r#"{"channel":"status","type":"update","data":[{"system":"online","api_version":"v2"}]}"#).unwrap(),
+        V2Message::Status(SystemStatus {
+            status: ExchangeStatus::Online,
+            raw_status: "online".to_string(),
+            version: None,
+            connection_id: None,
+        })
+    );
+    assert_eq!(
+        parser.parse_v2_message(r#"{"method":"subscribe","req_id":7,"success":false,"error":"Currency pair not supported"}"#).unwrap(),
+        V2Message::MethodResponse {
+            method: "subscribe".to_string(),
+            req_id: Some(7),
+            success: false,
+            error: Some("Currency pair not supported".to_string()),
+        }
+    );
+    
+    // v1 frames and unknown channels are rejected
+    assert!(parser.parse_v2_message(r#"[0, {}, "ticker", "XBT/USD"]"#).is_err());
+    assert!(parser.parse_v2_message(r#"{"channel":"unknown","data":[]}"#).is_err());
+}
+
+#[tokio::test]
+async fn test_v2_book_maintains_order_book() {
+    let parser: Arc<dyn DataParser> = Arc::new(KrakenDataParser::new());
+    let dispatcher = Arc::new(EventDispatcher::new());
+    let orderbook_manager = OrderBookManager::new();
+    let handler = MessageHandler::new(parser, dispatcher.clone())
+        .with_orderbook_manager(orderbook_manager.clone());
+    let mut events = dispatcher.create_event_stream();
+    
+    let snapshot = r#"{"channel":"book","type":"snapshot","data":[{"symbol":"BTC/USD","bids":[{"price":100.0,"qty":1.0},{"price":99.0,"qty":2.0}],"asks":[{"price":101.0,"qty":1.0}],"checksum":0}]}"#;
+    let update = r#"{"channel":"book","type":"update","data":[{"symbol":"BTC/USD","bids":[{"price":100.0,"qty":0.0}],"asks":[{"price":101.5,"qty":3.0}],"checksum":0,"timestamp":"2024-05-01T12:00:00.000000Z"}]}"#;
+    
+    handler.handle_message(snapshot).await.unwrap();
+    handler.handle_message(update).await.unwrap();
+    
+    let (best_bid, best_ask) = orderbook_manager.get_best_bid_ask("BTC/USD").unwrap();
+    assert_eq!(best_bid, Some(Decimal::from(99)));
+    assert_eq!(best_ask, Some(Decimal::from(101)));
+    
+    // A new snapshot replaces the book
+    let resnapshot = r#"{"channel":"book","type":"snapshot","data":[{"symbol":"BTC/USD","bids":[{"price":90.0,"qty":1.0}],"asks":[{"price":91.0,"qty":1.0}],"checksum":0}]}"#;
+    handler.handle_message(resnapshot).await.unwrap();
+    let book = orderbook_manager.get_order_book("BTC/USD").unwrap();
+    assert_eq!(book.bids.len(), 1);
+    assert_eq!(book.asks.len(), 1);
+    
+    let mut book_events = 0;
+    while let Ok(event) = events.try_recv() {
+        if let SdkEvent::OrderBook(_) = event {
+            book_events += 1;
+        }
+    }
+    assert_eq!(book_events, 3);
+}
+
+#[tokio::test]
+async fn test_v2_book_checksums_need_pair_precision() {
+    let parser: Arc<dyn DataParser> = Arc::new(KrakenDataParser::new());
+    let dispatcher = Arc::new(EventDispatcher::new());
+    let registry = InstrumentRegistry::new();
+    let orderbook_manager = OrderBookManager::new();
+    let handler = MessageHandler::new(parser.clone(), dispatcher.clone())
+        .with_orderbook_manager(orderbook_manager.clone())
+        .with_instrument_registry(registry.clone());
+    let mut events = dispatcher.create_event_stream();
+    
+    // Without the pair precision the checksum can't be reproduced, so the book is kept unverified
+    let snapshot = r#"{"channel":"book","type":"snapshot","data":[{"symbol":"BTC/USD","bids":[{"price":100.0,"qty":1.0}],"asks":[{"price":101.0,"qty":1.0}],"checksum":0}]}"#;
+    handler.handle_message(snapshot).await.unwrap();
+    assert!(orderbook_manager.get_order_book("BTC/USD").is_some());
+    while let Ok(event) = events.try_recv() {
+        assert!(!matches!(event, SdkEvent::ChecksumMismatch(_)));
+    }
+    
+    // Instruments seen by another connection sharing the registry seed the precision
+    let instrument_handler = MessageHandler::new(parser, Arc::new(EventDispatcher::new()))
+        .with_instrument_registry(registry.clone());
+    let instrument_message = r#"{"channel":"instrument","type":"snapshot","data":{"assets":[],"pairs":[{"symbol":"BTC/USD","base":"BTC","quote":"USD","status":"online","qty_precision":8,"qty_increment":0.00000001,"price_precision":1,"price_increment":0.1,"qty_min":0.0001,"cost_min":0.5}]}}"#;
+    instrument_handler.handle_message(instrument_message).await.unwrap();
+    
+    handler.handle_message(snapshot).await.unwrap();
+    let mut mismatches = 0;
+    while let Ok(event) = events.try_recv() {
+        if let SdkEvent::ChecksumMismatch(mismatch) = event {
+            assert_eq!(mismatch.symbol, "BTC/USD");
+            mismatches += 1;
+        }
+    }
+    assert_eq!(mismatches, 1);
+}
+
+#[tokio::test]
+async fn test_v2_instrument_reaches_event_stream() {
+    let parser: Arc<dyn DataParser> = Arc::new(KrakenDataParser::new());
+    let dispatcher = Arc::new(EventDispatcher::new());
+    let handler = MessageHandler::new(parser, dispatcher.clone());
+    let mut events = dispatcher.create_event_stream();
+    
+    let instrument_message = r#"{"channel":"instrument","type":"snapshot","data":{"assets":[],"pairs":[{"symbol":"ETH/USD","base":"ETH","quote":"USD","status":"online","qty_precision":8,"qty_increment":0.00000001,"price_precision":2,"price_increment":0.01,"qty_min":0.002,"cost_min":0.5}]}}"#;
+    handler.handle_message(instrument_message).await.unwrap();
+    
+    match events.try_recv() {
+        Ok(SdkEvent::Instrument(instrument)) => assert_eq!(instrument.symbol, "ETH/USD"),
+        other => panic!("Expected instrument event, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_parse_kraken_orderbook_snapshot_and_checksum() {
+    let parser = KrakenDataParser::new();
+    
+    let snapshot = r#"[
+        336,
+        {
+            "as": [["50001.00000", "0.50000000", "1534614248.765567"]],
+            "bs": [["50000.00000", "1.00000000", "1534614248.654321"]]
+        },
+        "book-10",
+        "XBT/USD"
+    ]"#;
+    let update = parser.parse_orderbook(snapshot).unwrap();
+    assert_eq!(update.symbol, "XBT/USD");
+    assert_eq!(update.asks.len(), 1);
+    assert_eq!(update.bids.len(), 1);
+    assert_eq!(update.checksum, None);
+    
+    // Asks and bids in separate objects, checksum on the last one
+    let split_update = r#"[
+        336,
+        {"a": [["50001.00000", "0.00000000", "1534614248.765567"]]},
+        {"b": [["50000.00000", "2.00000000", "1534614248.654321", "r"]], "c": "974947235"},
+        "book-10",
+        "XBT/USD"
+    ]"#;
+    let update = parser.parse_orderbook(split_update).unwrap();
+    assert_eq!(update.symbol, "XBT/USD");
+    assert!(update.asks[0].volume.is_zero());
+    assert_eq!(update.bids[0].volume, Decimal::from(2));
+    assert_eq!(update.checksum, Some(974947235));
+    
+    // Non-book frames are rejected
+    let ticker = r#"[0, {"a": ["1", "1", "1"]}, "ticker", "XBT/USD"]"#;
+    assert!(parser.parse_orderbook(ticker).is_err());
+}
+
+#[tokio::test]
+async fn test_checksum_mismatch_dispatches_event() {
+    let parser: Arc<dyn DataParser> = Arc::new(KrakenDataParser::new());
+    let dispatcher = Arc::new(EventDispatcher::new());
+    let orderbook_manager = OrderBookManager::new();
+    let handler = MessageHandler::new(parser, dispatcher.clone())
+        .with_orderbook_manager(orderbook_manager.clone());
+    let mut events = dispatcher.create_event_stream();
+    
+    let instrument = r#"{"channel":"instrument","type":"snapshot","data":{"assets":[],"pairs":[{"symbol":"BTC/USD","base":"BTC","quote":"USD","status":"online","qty_precision":8,"qty_increment":0.00000001,"price_precision":1,"price_increment":0.1,"qty_min":0.0001,"cost_min":0.5}]}}"#;
+    let snapshot = r#"{"channel":"book","type":"snapshot","data":[{"symbol":"BTC/USD","bids":[{"price":50000.0,"qty":1.5},{"price":49999.9,"qty":0.00000001}],"asks":[{"price":50000.1,"qty":2.0}],"checksum":314293462}]}"#;
+    let corrupt = r#"{"channel":"book","type":"update","data":[{"symbol":"BTC/USD","bids":[{"price":50000.0,"qty":1.0}],"asks":[],"checksum":1}]}"#;
+    
+    handler.handle_message(instrument).await.unwrap();
+    handler.handle_message(snapshot).await.unwrap();
+    assert!(!orderbook_manager.is_resyncing("BTC/USD"));
+    
+    handler.handle_message(corrupt).await.unwrap();
+    assert!(orderbook_manager.is_resyncing("BTC/USD"));
+    assert_eq!(orderbook_manager.take_resync_requests(), vec!["BTC/USD".to_string()]);
+    
+    let mismatch = std::iter::from_fn(|| events.try_recv().ok())
+        .find_map(|event| match event {
+            SdkEvent::ChecksumMismatch(mismatch) => Some(mismatch),
+            _ => None,
+        })
+        .expect("Expected checksum mismatch event");
+    assert_eq!(mismatch.symbol, "BTC/USD");
+    assert_eq!(mismatch.expected, 1);
+    
+    // A new snapshot restores the book
+    handler.handle_message(snapshot).await.unwrap();
+    assert!(!orderbook_manager.is_resyncing("BTC/USD"));
+}
+
+#[tokio::test]
+async fn test_only_pong_responses_record_pongs() {
+    use kraken_ws_sdk::connection::ConnectionManager;
+    use kraken_ws_sdk::data::{ConnectionConfig, ProtocolVersion, ReconnectConfig};
+    
+    let connection_manager = ConnectionManager::new(ConnectionConfig::default(), ReconnectConfig::default());
+    let handler = MessageHandler::new(Arc::new(KrakenDataParser::new()), Arc::new(EventDispatcher::new()))
+        .with_connection_manager(connection_manager.clone());
+    
+    connection_manager.create_ping_message(ProtocolVersion::V1);
+    // Frames that merely mention "pong" are not answers
+    handler.handle_message(r#"{"errorMessage":"Unexpected \"pong\"","event":"error"}"#).await.unwrap();
+    handler.handle_message(r#"[0,[["50000.0","0.1","1534614057.321597","s","l","\"pong\""]],"trade","XBT/USD"]"#).await.unwrap();
+    assert!(!connection_manager.is_healthy());
+    
+    handler.handle_message(r#"{"event":"pong","reqid":0}"#).await.unwrap();
+    assert!(connection_manager.is_healthy());
+    
+    connection_manager.create_ping_message(ProtocolVersion::V2);
+    assert!(!connection_manager.is_healthy());
+    handler.handle_message(r#"{"method":"pong","req_id":1,"time_in":"2023-09-25T09:04:31.742599Z","time_out":"2023-09-25T09:04:31.742648Z"}"#).await.unwrap();
+    assert!(connection_manager.is_healthy());
+}
+
+#[tokio::test]
+async fn test_v1_frames_routed_by_channel_id() {
+    let parser: Arc<dyn DataParser> = Arc::new(KrakenDataParser::new());
+    let dispatcher = Arc::new(EventDispatcher::new());
+    let orderbook_manager = OrderBookManager::new();
+    let subscription_manager = SubscriptionManager::new();
+    let handler = MessageHandler::new(parser, dispatcher.clone())
+        .with_orderbook_manager(orderbook_manager.clone())
+        .with_subscription_manager(subscription_manager.clone());
+    let mut events = dispatcher.create_event_stream();
+    
+    subscription_manager.create_subscription_messages(&[
+        Channel::new("book").with_symbol("XBT/USD").with_depth(10),
+        Channel::new("trade").with_symbol("XBT/USD"),
+    ]).unwrap();
+    handler.handle_message(r#"{"channelID":336,"channelName":"book-10","event":"subscriptionStatus","pair":"XBT/USD","status":"subscribed","subscription":{"depth":10,"name":"book"}}"#).await.unwrap();
+    handler.handle_message(r#"{"channelID":337,"channelName":"trade","event":"subscriptionStatus","pair":"XBT/USD","status":"subscribed","subscription":{"name":"trade"}}"#).await.unwrap();
+    assert_eq!(subscription_manager.route_count(), 2);
+    
+    // A trade whose order type field mentions "book" is still just a trade
+    let trade = r#"[337,[["50000.0","0.1","1534614057.321597","s","l","book"],["50001.0","0.2","1534614057.324998","b","l",""]],"trade","XBT/USD"]"#;
+    handler.handle_message(trade).await.unwrap();
+    
+    let snapshot = r#"[336,{"as":[["50001.00000","0.50000000","1534614248.765567"]],"bs":[["50000.00000","1.00000000","1534614248.654321"]]},"book-10","XBT/USD"]"#;
+    handler.handle_message(snapshot).await.unwrap();
+    let (best_bid, best_ask) = orderbook_manager.get_best_bid_ask("XBT/USD").unwrap();
+    assert_eq!(best_bid, Some(Decimal::from(50000)));
+    assert_eq!(best_ask, Some(Decimal::from(50001)));
+    
+    let received: Vec<_> = std::iter::from_fn(|| events.try_recv().ok()).collect();
+    let trades = received.iter().filter(|e| matches!(e, SdkEvent::Trade(_))).count();
+    let books = received.iter().filter(|e| matches!(e, SdkEvent::OrderBook(_))).count();
+    assert_eq!(trades, 2);
+    assert_eq!(books, 1);
+}
+
+#[tokio::test]
+async fn test_trades_build_candle_events() {
+    let clock = Clock::manual(chrono::DateTime::from_timestamp(1534614057, 0).unwrap());
+    let candles = Arc::new(CandleBuilder::with_config(CandleConfig {
+        bars: vec![BarSpec::seconds(15)],
+        ..Default::default()
+    }));
+    let parser: Arc<dyn DataParser> = Arc::new(KrakenDataParser::new().with_clock(clock.clone()));
+    let dispatcher = Arc::new(EventDispatcher::new());
+    let handler = MessageHandler::new(parser, dispatcher.clone())
+        .with_candle_builder(Arc::clone(&candles))
+        .with_clock(clock.clone());
+    let mut events = dispatcher.create_event_stream();
+    
+    let trade = r#"[337,[["50000.0","0.1","1534614057.321597","s","l",""],["50001.0","0.2","1534614057.324998","b","l",""]],"trade","XBT/USD"]"#;
+    handler.handle_message(trade).await.unwrap();
+    let received: Vec<_> = std::iter::from_fn(|| events.try_recv().ok()).collect();
+    let kinds: Vec<_> = received.iter().map(|event| event.kind()).collect();
+    assert_eq!(kinds, vec![EventKind::Trade, EventKind::Candle, EventKind::Trade, EventKind::Candle]);
+    assert!(matches!(&received[3], SdkEvent::Candle(CandleEvent::Updated(candle)) if candle.trade_count == 2));
+    
+    // The bucket ends at :60; the next frame after that closes it
+    clock.advance(chrono::Duration::seconds(3));
+    handler.handle_message(r#"{"event":"heartbeat"}"#).await.unwrap();
+    let closed = std::iter::from_fn(|| events.try_recv().ok())
+        .find_map(|event| match event {
+            SdkEvent::Candle(CandleEvent::Closed(candle)) => Some(candle),
+            _ => None,
+        })
+        .expect("candle closed");
+    assert_eq!(closed.volume, Decimal::from_str("0.3").unwrap());
+    assert_eq!(closed.close, Decimal::from(50001));
+    assert_eq!(candles.closed("XBT/USD", &BarSpec::seconds(15), 10), vec![closed]);
+}
+
+#[tokio::test]
+async fn test_v1_spread_frames_dispatched() {
+    let parser: Arc<dyn DataParser> = Arc::new(KrakenDataParser::new());
+    let dispatcher = Arc::new(EventDispatcher::new());
+    let handler = MessageHandler::new(parser.clone(), dispatcher.clone());
+    let mut events = dispatcher.create_event_stream();
+    
+    let spread_message = r#"[0,["5698.40000","5700.00000","1542057299.545897","1.01234567","0.98765432"],"spread","XBT/USD"]"#;
+    let spread = parser.parse_spread(spread_message).unwrap();
+    assert_eq!(spread.symbol, "XBT/USD");
+    assert_eq!(spread.bid, Decimal::from_str("5698.4").unwrap());
+    assert_eq!(spread.ask, Decimal::from(5700));
+    assert_eq!(spread.bid_volume, Decimal::from_str("1.01234567").unwrap());
+    assert_eq!(spread.ask_volume, Decimal::from_str("0.98765432").unwrap());
+    assert_eq!(spread.timestamp.timestamp(), 1542057299);
+    assert_eq!(spread.spread(), Decimal::from_str("1.6").unwrap());
+    
+    handler.handle_message(spread_message).await.unwrap();
+    match events.try_recv() {
+        Ok(SdkEvent::Spread(dispatched)) => assert_eq!(dispatched, spread),
+        other => panic!("Expected spread event, got {:?}", other),
+    }
+    
+    assert!(parser.parse_spread(r#"[0,["5698.4"],"spread","XBT/USD"]"#).is_err());
+}
+
+#[tokio::test]
+async fn test_rejected_subscription_dispatches_error() {
+    let parser: Arc<dyn DataParser> = Arc::new(KrakenDataParser::new());
+    let dispatcher = Arc::new(EventDispatcher::new());
+    let subscription_manager = SubscriptionManager::new();
+    let handler = MessageHandler::new(parser, dispatcher.clone())
+        .with_subscription_manager(subscription_manager.clone());
+    let mut events = dispatcher.create_event_stream();
+    
+    subscription_manager.create_subscription_messages(&[Channel::new("ticker").with_symbol("XBT/EUR")]).unwrap();
+    let rejected = r#"{"errorMessage":"Currency pair not supported","event":"subscriptionStatus","pair":"XBT/EUR","status":"error","subscription":{"name":"ticker"}}"#;
+    handler.handle_message(rejected).await.unwrap();
+    
+    assert_eq!(subscription_manager.pending_count(), 0);
+    assert!(matches!(events.try_recv(), Ok(SdkEvent::Error(_))));
+}
+
+#[test]
+fn test_parse_v1_frame_uses_routed_channel() {
+    let parser = KrakenDataParser::new();
+    let frame: serde_json::Value = serde_json::from_str(
+        r#"[42,["1542057314.748456","1542057360.435743","3586.70000","3586.70000","3586.60000","3586.60000","3586.68894","0.03373000",2],"ohlc-5","XBT/USD"]"#
+    ).unwrap();
+    let frame = frame.as_array().unwrap();
+    
+    match parser.parse_v1_frame(frame, "ohlc-5").unwrap() {
+        V1Message::Ohlc(candle) => {
+            assert_eq!(candle.symbol, "XBT/USD");
+            assert_eq!(candle.interval, "5");
+        }
+        other => panic!("Expected OHLC, got {:?}", other),
+    }
+    assert!(parser.parse_v1_frame(frame, "ticker").is_err());
+}
+
+#[test]
+fn test_v1_trade_ids_match_across_parse_paths() {
+    let parser = KrakenDataParser::new();
+    let text = r#"[337,[["50000.0","0.1","1534614057.321597","s","l",""],["50001.0","0.2","1534614057.324998","b","l",""]],"trade","XBT/USD"]"#;
+    let frame: serde_json::Value = serde_json::from_str(text).unwrap();
+    let ids = |message: V1Message| match message {
+        V1Message::Trade(trades) => trades.into_iter().map(|trade| trade.trade_id).collect::<Vec<_>>(),
+        other => panic!("Expected trades, got {:?}", other),
+    };
+    
+    let tree = ids(parser.parse_v1_frame(frame.as_array().unwrap(), "trade").unwrap());
+    let raw = ids(parser.parse_v1_raw(&V1Frame::parse(text).unwrap(), "trade").unwrap());
+    assert_eq!(tree, vec![
+        "trade:XBT/USD:1534614057.321597:50000.0:0.1:s:0",
+        "trade:XBT/USD:1534614057.324998:50001.0:0.2:b:1",
+    ]);
+    assert_eq!(tree, raw);
+    
+    // A level without a usable timestamp is an error, not "now"
+    let book = r#"[336,{"a":[["5541.30000","2.50700000","soon"]]},"book-10","XBT/USD"]"#;
+    let frame: serde_json::Value = serde_json::from_str(book).unwrap();
+    assert!(parser.parse_v1_frame(frame.as_array().unwrap(), "book-10").is_err());
+    assert!(parser.parse_v1_raw(&V1Frame::parse(book).unwrap(), "book-10").is_err());
+}
+
+#[test]
+fn test_v1_decoding_errors_are_not_papered_over() {
+    let parser = KrakenDataParser::new();
+    // An unreadable price is an error on every path, not a zero-priced trade
+    let text = r#"[337,[["n/a","0.1","1534614057.321597","s","l",""]],"trade","XBT/USD"]"#;
+    let frame: serde_json::Value = serde_json::from_str(text).unwrap();
+    
+    assert!(matches!(
+        parser.parse_v1_raw(&V1Frame::parse(text).unwrap(), "trade"),
+        Err(ParseError::InvalidJson(_))
+    ));
+    assert!(parser.parse_v1_frame(frame.as_array().unwrap(), "trade").is_err());
+    assert!(parser.parse_trade(text).is_err());
+}
+
+#[tokio::test]
+async fn test_v2_book_sequence_gap_triggers_resync() {
+    let parser: Arc<dyn DataParser> = Arc::new(KrakenDataParser::new());
+    let dispatcher = Arc::new(EventDispatcher::new());
+    let orderbook_manager = OrderBookManager::new();
+    let sequence_manager = Arc::new(SequenceManager::with_config(SequenceConfig::strict()));
+    {
+        let d = dispatcher.clone();
+        sequence_manager.on_gap(move |gap| d.dispatch_gap(gap));
+        let d = dispatcher.clone();
+        sequence_manager.on_resync(move |resync| d.dispatch_resync(resync));
+    }
+    let handler = MessageHandler::new(parser, dispatcher.clone())
+        .with_orderbook_manager(orderbook_manager.clone())
+        .with_sequence_manager(sequence_manager.clone());
+    let mut events = dispatcher.create_event_stream();
+    
+    let book = |kind: &str, sequence: u64, price: f64| format!(
+        r#"{{"channel":"book","type":"{}","sequence":{},"data":[{{"symbol":"BTC/USD","bids":[{{"price":{},"qty":1.0}}],"asks":[{{"price":101.0,"qty":1.0}}],"checksum":0}}]}}"#,
+        kind, sequence, price
+    );
+    
+    handler.handle_message(&book("snapshot", 1, 100.0)).await.unwrap();
+    handler.handle_message(&book("update", 2, 99.0)).await.unwrap();
+    assert!(!orderbook_manager.is_resyncing("BTC/USD"));
+    
+    // Sequence 3 was lost
+    handler.handle_message(&book("update", 4, 98.0)).await.unwrap();
+    assert!(orderbook_manager.is_resyncing("BTC/USD"));
+    assert_eq!(orderbook_manager.take_resync_requests(), vec!["BTC/USD".to_string()]);
+    
+    let received: Vec<_> = std::iter::from_fn(|| events.try_recv().ok()).collect();
+    let gap = received.iter().find_map(|e| match e {
+        SdkEvent::Gap(gap) => Some(gap.clone()),
+        _ => None,
+    }).expect("Expected gap event");
+    assert_eq!(gap.channel, "book:BTC/USD");
+    assert_eq!(gap.expected_sequence, 3);
+    assert!(received.iter().any(|e| matches!(e, SdkEvent::Resync(r) if r.last_good_sequence == 2)));
+    
+    // A fresh snapshot starts a new baseline
+    handler.handle_message(&book("snapshot", 10, 97.0)).await.unwrap();
+    handler.handle_message(&book("update", 11, 96.0)).await.unwrap();
+    assert!(!orderbook_manager.is_resyncing("BTC/USD"));
+    assert_eq!(sequence_manager.get_state("book:BTC/USD").unwrap().last_sequence, 11);
+}
+
+#[tokio::test]
+async fn test_dispatched_messages_record_latency() {
+    let parser: Arc<dyn DataParser> = Arc::new(KrakenDataParser::new());
+    let dispatcher = Arc::new(EventDispatcher::new());
+    let tracker = Arc::new(LatencyTracker::new());
+    let alerts = Arc::new(std::sync::Mutex::new(Vec::new()));
+    {
+        let alerts = alerts.clone();
+        tracker.set_alerts(&LatencyAlertConfig::new(move |alert| alerts.lock().unwrap().push(alert)));
+    }
+    let handler = MessageHandler::new(parser, dispatcher.clone())
+        .with_latency_tracker(tracker.clone());
+    
+    // 2018 trade timestamps: network latency is years, which trips the alerts
+    let trade = r#"[337,[["50000.0","0.1","1534614057.321597","s","l",""],["50001.0","0.2","1534614057.324998","b","l",""]],"trade","XBT/USD"]"#;
+    handler.handle_message(trade).await.unwrap();
+    let ticker = r#"[340,{"a":["5525.40000",1,"1.000"],"b":["5525.10000",1,"1.000"],"c":["5525.10000","0.00398963"],"v":["2634.11501494","3591.17907851"]},"ticker","ETH/USD"]"#;
+    handler.handle_message(ticker).await.unwrap();
+    
+    let trades = tracker.channel_stats("trade").unwrap();
+    assert_eq!(trades.sample_count, 2);
+    assert!(trades.network.min > 1_000_000.0);
+    // Sub-second trade timestamps are kept
+    assert_eq!(trades.last_measurement.unwrap().exchange_timestamp.timestamp_subsec_micros(), 324998);
+    
+    // Tickers carry no exchange time, so only receive -> dispatch counts
+    let tickers = tracker.channel_stats("ticker").unwrap();
+    assert_eq!(tickers.sample_count, 1);
+    assert!(tickers.processing.max >= 0.0);
+    assert!(tracker.symbol_stats("ETH/USD").is_some());
+    assert_eq!(tracker.symbol_stats("XBT/USD").unwrap().sample_count, 2);
+    assert_eq!(tracker.stats().sample_count, 3);
+    
+    let alerts = alerts.lock().unwrap();
+    assert!(alerts.iter().all(|alert| alert.channel == "trade"));
+    assert!(alerts.iter().any(|alert| matches!(alert.alert_type, LatencyAlertType::HighNetworkLatency)));
+}
+
+#[tokio::test]
+async fn test_handler_stamps_frames_by_its_clock() {
+    let clock = Clock::manual(chrono::DateTime::from_timestamp(1534614059, 0).unwrap());
+    let parser: Arc<dyn DataParser> = Arc::new(KrakenDataParser::new().with_clock(clock.clone()));
+    let dispatcher = Arc::new(EventDispatcher::new());
+    let tracker = Arc::new(LatencyTracker::new());
+    let handler = MessageHandler::new(parser, dispatcher.clone())
+        .with_latency_tracker(tracker.clone())
+        .with_clock(clock.clone());
+    let mut events = dispatcher.create_event_stream();
+    
+    handler.handle_message(r#"{"event":"heartbeat"}"#).await.unwrap();
+    let Ok(SdkEvent::Heartbeat(heartbeat)) = events.try_recv() else {
+        panic!("Expected heartbeat event");
+    };
+    assert_eq!(heartbeat.received_at, clock.now());
+    
+    let trade = r#"[337,[["50000.0","0.1","1534614057.000000","s","l",""]],"trade","XBT/USD"]"#;
+    handler.handle_message(trade).await.unwrap();
+    let measurement = tracker.channel_stats("trade").unwrap().last_measurement.unwrap();
+    assert_eq!((measurement.receive_timestamp, measurement.process_timestamp), (clock.now(), clock.now()));
+    assert_eq!(measurement.network_latency_us, 2_000_000);
+    assert_eq!(measurement.processing_latency_us, 0);
+}
+
+#[tokio::test]
+async fn test_system_status_and_heartbeat_events() {
+    let parser: Arc<dyn DataParser> = Arc::new(KrakenDataParser::new());
+    let dispatcher = Arc::new(EventDispatcher::new());
+    let monitor = SystemStatusMonitor::new();
+    let handler = MessageHandler::new(parser, dispatcher.clone())
+        .with_status_monitor(monitor.clone());
+    let mut events = dispatcher.create_event_stream();
+    
+    handler.handle_message(r#"{"connectionID":8628615390848610000,"event":"systemStatus","status":"cancel_only","version":"1.9.1"}"#).await.unwrap();
+    let Ok(SdkEvent::SystemStatus(status)) = events.try_recv() else {
+        panic!("Expected system status event");
+    };
+    assert_eq!(status.status, ExchangeStatus::CancelOnly);
+    assert_eq!(status.version.as_deref(), Some("1.9.1"));
+    assert_eq!(status.connection_id, Some(8628615390848610000));
+    assert_eq!(monitor.status(), Some(ExchangeStatus::CancelOnly));
+    
+    handler.handle_message(r#"{"event":"heartbeat"}"#).await.unwrap();
+    assert!(matches!(events.try_recv(), Ok(SdkEvent::Heartbeat(_))));
+    
+    // v2 status channel and heartbeat
+    handler.handle_message(r#"{"channel":"status","type":"update","data":[{"api_version":"v2","connection_id":42,"system":"post_only","version":"2.0.0"}]}"#).await.unwrap();
+    let Ok(SdkEvent::SystemStatus(status)) = events.try_recv() else {
+        panic!("Expected system status event");
+    };
+    assert_eq!(status.status, ExchangeStatus::PostOnly);
+    assert_eq!(status.connection_id, Some(42));
+    assert_eq!(monitor.status(), Some(ExchangeStatus::PostOnly));
+    
+    handler.handle_message(r#"{"channel":"heartbeat"}"#).await.unwrap();
+    assert!(matches!(events.try_recv(), Ok(SdkEvent::Heartbeat(_))));
+}
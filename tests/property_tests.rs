@@ -3,9 +3,8 @@
 use kraken_ws_sdk::{
     data::*,
     orderbook::*,
-    parser::*,
 };
-use quickcheck::{quickcheck, TestResult};
+use quickcheck::TestResult;
 use quickcheck_macros::quickcheck;
 use rust_decimal::Decimal;
 use std::str::FromStr;
@@ -172,6 +171,7 @@ fn prop_ticker_data_display_contains_symbol(symbol: String) -> TestResult {
         last_price: Decimal::from_str("50000.5").unwrap(),
         volume: Decimal::from_str("100.0").unwrap(),
         timestamp: Utc::now(),
+        ..Default::default()
     };
     
     let display_str = format!("{}", ticker);
@@ -196,6 +196,7 @@ fn prop_trade_data_volume_positive(volume: f64) -> TestResult {
         side: TradeSide::Buy,
         timestamp: Utc::now(),
         trade_id: "test".to_string(),
+        backfilled: false,
     };
     
     TestResult::from_bool(trade.volume > Decimal::ZERO)
@@ -313,14 +314,12 @@ fn prop_price_levels_maintain_order(prices: Vec<f64>) -> TestResult {
     }
     
     // Check that bids are in descending order (BTreeMap should handle this)
-    let bid_prices: Vec<_> = order_book.bids.keys().collect();
+    let bid_prices: Vec<_> = order_book.bids.keys().rev().collect();
     let mut sorted_prices = bid_prices.clone();
     sorted_prices.sort_by(|a, b| b.cmp(a)); // Descending order
     
     TestResult::from_bool(bid_prices == sorted_prices)
 }
 
-// Run property tests
-#[cfg(test)]
 // Note: Property tests are run automatically via #[quickcheck] attribute
 // No need for manual test runner - quickcheck_macros handles it
\ No newline at end of file
@@ -6,11 +6,13 @@ use kraken_ws_sdk::{
     events::*,
     orderbook::*,
     parser::*,
+    pool::*,
     subscription::*,
 };
 use rust_decimal::Decimal;
 use std::sync::Arc;
 use std::str::FromStr;
+use std::time::Duration;
 use chrono::Utc;
 
 // Test EventDispatcher
@@ -135,6 +137,180 @@ async fn test_orderbook_manager_update() {
     assert_eq!(best_ask, Some(Decimal::from_str("50001.0").unwrap()));
 }
 
+fn level(price: &str, volume: &str) -> PriceLevel {
+    PriceLevel {
+        price: Decimal::from_str(price).unwrap(),
+        volume: Decimal::from_str(volume).unwrap(),
+        timestamp: Utc::now(),
+    }
+}
+
+fn book_update(symbol: &str, bids: Vec<PriceLevel>, asks: Vec<PriceLevel>, checksum: Option<u32>) -> OrderBookUpdate {
+    OrderBookUpdate {
+        symbol: symbol.to_string(),
+        bids,
+        asks,
+        timestamp: Utc::now(),
+        checksum,
+    }
+}
+
+#[tokio::test]
+async fn test_orderbook_checksum() {
+    let manager = OrderBookManager::new();
+    
+    let snapshot = book_update(
+        "XBT/USD",
+        vec![level("50000.00000", "1.00000000"), level("49999.00000", "2.00000000")],
+        vec![level("50001.00000", "0.50000000"), level("50002.00000", "1.50000000")],
+        Some(1523196916),
+    );
+    assert!(manager.apply_snapshot(snapshot).is_ok());
+    assert_eq!(manager.calculate_checksum("XBT/USD"), Some(1523196916));
+    
+    let update = book_update(
+        "XBT/USD",
+        vec![level("50000.00000", "0.00000000")],
+        vec![level("50001.50000", "0.25000000")],
+        Some(1737712024),
+    );
+    assert!(manager.apply_update(update).is_ok());
+    assert!(!manager.is_resyncing("XBT/USD"));
+    assert!(manager.take_resync_requests().is_empty());
+}
+
+#[tokio::test]
+async fn test_orderbook_checksum_with_precision() {
+    let manager = OrderBookManager::new();
+    manager.set_precision("BTC/USD", 1, 8);
+    
+    // v2 levels come from JSON numbers and lose trailing zeros
+    let snapshot = book_update(
+        "BTC/USD",
+        vec![level("50000", "1.5"), level("49999.9", "0.00000001")],
+        vec![level("50000.1", "2")],
+        Some(314293462),
+    );
+    assert!(manager.apply_snapshot(snapshot).is_ok());
+    assert_eq!(manager.calculate_checksum("BTC/USD"), Some(314293462));
+}
+
+#[tokio::test]
+async fn test_orderbook_checksum_mismatch_resync() {
+    let manager = OrderBookManager::new();
+    
+    let snapshot = book_update(
+        "XBT/USD",
+        vec![level("50000.00000", "1.00000000")],
+        vec![level("50001.00000", "0.50000000")],
+        None,
+    );
+    manager.apply_snapshot(snapshot.clone()).unwrap();
+    
+    let bad_update = book_update("XBT/USD", vec![level("50000.00000", "3.00000000")], vec![], Some(42));
+    match manager.apply_update(bad_update) {
+        Err(ParseError::ChecksumMismatch { symbol, expected, .. }) => {
+            assert_eq!(symbol, "XBT/USD");
+            assert_eq!(expected, 42);
+        }
+        other => panic!("Expected checksum mismatch, got {:?}", other),
+    }
+    assert!(manager.is_resyncing("XBT/USD"));
+    assert_eq!(manager.take_resync_requests(), vec!["XBT/USD".to_string()]);
+    assert!(manager.take_resync_requests().is_empty());
+    
+    // Deltas are ignored until a new snapshot arrives
+    let ignored = book_update("XBT/USD", vec![level("49000.00000", "1.00000000")], vec![], None);
+    let book = manager.apply_update(ignored).unwrap();
+    assert_eq!(book.status, BookStatus::Resyncing);
+    assert!(!book.bids.contains_key(&Decimal::from(49000)));
+    
+    let book = manager.apply_snapshot(snapshot).unwrap();
+    assert_eq!(book.status, BookStatus::Synced);
+    assert_eq!(book.bids.len(), 1);
+}
+
+#[tokio::test]
+async fn test_orderbook_recovery_replays_buffered_deltas() {
+    let manager = OrderBookManager::new();
+    let at = |price: &str, volume: &str, seconds: i64| PriceLevel {
+        timestamp: chrono::DateTime::from_timestamp(1_700_000_000 + seconds, 0).unwrap(),
+        ..level(price, volume)
+    };
+    
+    manager.apply_snapshot(book_update("XBT/USD", vec![at("100.0", "1.0", 0)], vec![at("101.0", "1.0", 0)], None)).unwrap();
+    manager.begin_recovery("XBT/USD");
+    assert!(manager.is_recovering("XBT/USD"));
+    
+    // Already reflected in the REST snapshot, then newer than it
+    manager.apply_update(book_update("XBT/USD", vec![at("99.0", "5.0", 5)], vec![], Some(1))).unwrap();
+    manager.apply_update(book_update("XBT/USD", vec![at("100.0", "0.0", 20)], vec![], None)).unwrap();
+    
+    let expected = OrderBookManager::new();
+    expected.apply_snapshot(book_update("XBT/USD", vec![at("99.0", "2.0", 10)], vec![at("101.0", "1.0", 10)], None)).unwrap();
+    let checksum = expected.calculate_checksum("XBT/USD");
+    manager.apply_update(book_update("XBT/USD", vec![], vec![at("101.0", "1.0", 20)], checksum)).unwrap();
+    
+    let rest = book_update(
+        "XBT/USD",
+        vec![at("100.0", "3.0", 10), at("99.0", "2.0", 8)],
+        vec![at("101.0", "1.0", 0)],
+        None,
+    );
+    let book = manager.apply_recovery_snapshot(rest.clone()).unwrap();
+    assert_eq!(book.status, BookStatus::Synced);
+    assert!(!manager.is_recovering("XBT/USD"));
+    assert_eq!(manager.calculate_checksum("XBT/USD"), checksum);
+    assert_eq!(book.bids.get(&Decimal::from(99)).unwrap().volume, Decimal::from(2));
+    
+    // A WebSocket snapshot supersedes a recovery still in flight
+    manager.begin_recovery("XBT/USD");
+    manager.apply_snapshot(book_update("XBT/USD", vec![at("98.0", "1.0", 30)], vec![], None)).unwrap();
+    let book = manager.apply_recovery_snapshot(rest).unwrap();
+    assert_eq!(book.bids.keys().copied().collect::<Vec<_>>(), vec![Decimal::from(98)]);
+}
+
+#[tokio::test]
+async fn test_orderbook_snapshots_are_copy_on_write() {
+    let manager = OrderBookManager::new();
+    manager.apply_snapshot(book_update("XBT/USD", vec![level("100.0", "1.0")], vec![level("101.0", "1.0")], None)).unwrap();
+    manager.apply_snapshot(book_update("ETH/USD", vec![level("10.0", "1.0")], vec![level("11.0", "1.0")], None)).unwrap();
+    
+    let held = manager.get_order_book("XBT/USD").unwrap();
+    let updated = manager.apply_update(book_update("XBT/USD", vec![level("100.5", "2.0")], vec![], None)).unwrap();
+    
+    // The held snapshot is untouched; the update went to a copy
+    assert_eq!(held.bids.len(), 1);
+    assert_eq!(updated.bids.len(), 2);
+    assert!(Arc::ptr_eq(&updated, &manager.get_order_book("XBT/USD").unwrap()));
+    
+    // Unheld books are updated in place
+    drop(updated);
+    let before = Arc::as_ptr(&manager.get_order_book("ETH/USD").unwrap());
+    let after = manager.apply_update(book_update("ETH/USD", vec![level("10.5", "1.0")], vec![], None)).unwrap();
+    assert_eq!(Arc::as_ptr(&after), before);
+    assert_eq!(manager.get_best_bid_ask("ETH/USD"), Some((Some(Decimal::from_str("10.5").unwrap()), Some(Decimal::from(11)))));
+}
+
+#[tokio::test]
+async fn test_orderbook_truncated_to_depth() {
+    let manager = OrderBookManager::new();
+    manager.set_depth("XBT/USD", 2);
+    
+    let snapshot = book_update(
+        "XBT/USD",
+        vec![level("100.0", "1.0"), level("99.0", "1.0"), level("98.0", "1.0")],
+        vec![level("101.0", "1.0"), level("102.0", "1.0"), level("103.0", "1.0")],
+        None,
+    );
+    let book = manager.apply_snapshot(snapshot).unwrap();
+    
+    let bids: Vec<_> = book.bids.keys().rev().map(|p| p.to_string()).collect();
+    let asks: Vec<_> = book.asks.keys().map(|p| p.to_string()).collect();
+    assert_eq!(bids, vec!["100.0", "99.0"]);
+    assert_eq!(asks, vec!["101.0", "102.0"]);
+}
+
 #[tokio::test]
 async fn test_orderbook_spread_calculation() {
     let mut order_book = OrderBook::new("BTC/USD");
@@ -217,10 +393,294 @@ async fn test_subscription_validation() {
     assert!(result.is_err());
 }
 
+#[tokio::test]
+async fn test_v2_subscription_message_format() {
+    let manager = SubscriptionManager::with_protocol(ProtocolVersion::V2);
+    
+    let channels = vec![
+        Channel::new("book").with_symbol("BTC/USD").with_depth(25),
+    ];
+    
+    let message = manager.create_subscription_message(&channels).unwrap();
+    let tokio_tungstenite::tungstenite::Message::Text(text) = message else {
+        panic!("Expected text message");
+    };
+    
+    let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(json["method"], "subscribe");
+    assert_eq!(json["params"]["channel"], "book");
+    assert_eq!(json["params"]["symbol"][0], "BTC/USD");
+    assert_eq!(json["params"]["depth"], 25);
+    assert!(json["req_id"].as_u64().is_some());
+    assert!(json.get("event").is_none());
+}
+
+#[tokio::test]
+async fn test_subscription_messages_grouped_by_channel() {
+    let manager = SubscriptionManager::with_protocol(ProtocolVersion::V2);
+    
+    let channels = vec![
+        Channel::new("ticker").with_symbol("BTC/USD"),
+        Channel::new("ticker").with_symbol("ETH/USD"),
+        Channel::new("ohlc").with_symbol("BTC/USD").with_interval("5"),
+        Channel::new("instrument"),
+    ];
+    
+    let messages = manager.create_subscription_messages(&channels).unwrap();
+    assert_eq!(messages.len(), 3);
+    
+    let parsed: Vec<serde_json::Value> = messages.iter()
+        .map(|m| serde_json::from_str(m.to_text().unwrap()).unwrap())
+        .collect();
+    
+    assert_eq!(parsed[0]["params"]["symbol"], serde_json::json!(["BTC/USD", "ETH/USD"]));
+    assert_eq!(parsed[1]["params"]["interval"], 5);
+    assert_eq!(parsed[2]["params"]["channel"], "instrument");
+    assert!(parsed[2]["params"].get("symbol").is_none());
+    
+    // Every request gets its own req_id
+    assert_ne!(parsed[0]["req_id"], parsed[1]["req_id"]);
+}
+
+#[tokio::test]
+async fn test_subscription_channels_per_protocol() {
+    let v1 = SubscriptionManager::new();
+    let v2 = SubscriptionManager::with_protocol(ProtocolVersion::V2);
+    
+    // instrument only exists in v2, spread only in v1
+    assert!(v1.create_subscription_message(&[Channel::new("instrument")]).is_err());
+    assert!(v2.create_subscription_message(&[Channel::new("instrument")]).is_ok());
+    assert!(v1.create_subscription_message(&[Channel::new("spread").with_symbol("BTC/USD")]).is_ok());
+    assert!(v2.create_subscription_message(&[Channel::new("spread").with_symbol("BTC/USD")]).is_err());
+    
+    // Invalid book depth
+    let invalid_depth = vec![Channel::new("book").with_symbol("BTC/USD").with_depth(7)];
+    assert!(v2.create_subscription_message(&invalid_depth).is_err());
+}
+
+#[tokio::test]
+async fn test_v2_subscription_confirmation() {
+    let manager = SubscriptionManager::with_protocol(ProtocolVersion::V2);
+    let channel = Channel::new("ticker").with_symbol("BTC/USD");
+    manager.create_subscription_message(std::slice::from_ref(&channel)).unwrap();
+    
+    let ack = r#"{"method":"subscribe","req_id":1,"result":{"channel":"ticker","symbol":"BTC/USD"},"success":true}"#;
+    assert!(manager.handle_subscription_confirmation(ack).is_ok());
+    assert!(manager.is_subscribed(&channel));
+    
+    let rejected = r#"{"method":"subscribe","req_id":2,"error":"Currency pair not supported","success":false}"#;
+    assert!(manager.handle_subscription_confirmation(rejected).is_err());
+}
+
+/// Request id of a generated (un)subscribe message
+fn request_id(message: &tokio_tungstenite::tungstenite::Message) -> u64 {
+    let json: serde_json::Value = serde_json::from_str(message.to_text().unwrap()).unwrap();
+    json.get("reqid").or_else(|| json.get("req_id")).and_then(|v| v.as_u64()).unwrap()
+}
+
+#[tokio::test]
+async fn test_subscription_ack_waits_for_every_pair() {
+    let manager = SubscriptionManager::new();
+    let channels = vec![
+        Channel::new("ticker").with_symbol("XBT/USD"),
+        Channel::new("ticker").with_symbol("ETH/USD"),
+    ];
+    
+    let (messages, ack) = manager.create_subscription_request(&channels).unwrap();
+    let req_id = request_id(&messages[0]);
+    assert_eq!(ack.req_ids(), vec![req_id]);
+    
+    // Kraken confirms each pair separately
+    for (channel_id, pair) in [(1, "XBT/USD"), (2, "ETH/USD")] {
+        assert_eq!(manager.outstanding_requests(), 1);
+        let status = format!(
+            r#"{{"channelID":{},"channelName":"ticker","event":"subscriptionStatus","pair":"{}","reqid":{},"status":"subscribed","subscription":{{"name":"ticker"}}}}"#,
+            channel_id, pair, req_id
+        );
+        manager.handle_subscription_confirmation(&status).unwrap();
+    }
+    
+    assert!(ack.wait(Duration::from_secs(1)).await.is_ok());
+    assert_eq!(manager.outstanding_requests(), 0);
+}
+
+#[tokio::test]
+async fn test_subscription_ack_typed_errors() {
+    // Unknown pair (v1)
+    let v1 = SubscriptionManager::new();
+    let (messages, ack) = v1.create_subscription_request(&[Channel::new("ticker").with_symbol("XBT/USDX")]).unwrap();
+    let rejected = format!(
+        r#"{{"errorMessage":"Currency pair not supported XBT/USDX","event":"subscriptionStatus","pair":"XBT/USDX","reqid":{},"status":"error","subscription":{{"name":"ticker"}}}}"#,
+        request_id(&messages[0])
+    );
+    assert!(v1.handle_subscription_confirmation(&rejected).is_err());
+    assert!(matches!(
+        ack.wait(Duration::from_secs(1)).await,
+        Err(SubscriptionError::UnknownPair(pair)) if pair == "XBT/USDX"
+    ));
+    
+    // Invalid depth, known from the request since v2 doesn't echo it
+    let v2 = SubscriptionManager::with_protocol(ProtocolVersion::V2);
+    let book = Channel::new("book").with_symbol("BTC/USD").with_depth(1000);
+    let (messages, ack) = v2.create_subscription_request(&[book]).unwrap();
+    let rejected = format!(
+        r#"{{"error":"Subscription depth not supported","method":"subscribe","req_id":{},"success":false,"symbol":"BTC/USD"}}"#,
+        request_id(&messages[0])
+    );
+    assert!(v2.handle_subscription_confirmation(&rejected).is_err());
+    assert!(matches!(ack.wait(Duration::from_secs(1)).await, Err(SubscriptionError::InvalidDepth(1000))));
+    
+    // Depths Kraken never accepts are rejected before sending
+    let invalid = Channel::new("book").with_symbol("BTC/USD").with_depth(7);
+    assert!(matches!(v2.create_subscription_request(&[invalid]), Err(SubscriptionError::InvalidDepth(7))));
+    
+    // No answer at all
+    let (messages, ack) = v2.create_subscription_request(&[Channel::new("trade").with_symbol("BTC/USD")]).unwrap();
+    let expected = request_id(&messages[0]);
+    assert!(matches!(
+        ack.wait(Duration::from_millis(20)).await,
+        Err(SubscriptionError::Timeout { req_id, .. }) if req_id == expected
+    ));
+    assert_eq!(v2.outstanding_requests(), 0);
+}
+
+#[test]
+fn test_v2_rejection_clears_pending() {
+    let manager = SubscriptionManager::with_protocol(ProtocolVersion::V2);
+    let channels = [
+        Channel::new("ticker").with_symbol("BTC/USD"),
+        Channel::new("ticker").with_symbol("BTC/FOO"),
+    ];
+    let (messages, _ack) = manager.create_subscription_request(&channels).unwrap();
+    let req_id = request_id(&messages[0]);
+    assert_eq!(manager.pending_count(), 2);
+    
+    let rejected = format!(
+        r#"{{"error":"Currency pair not supported BTC/FOO","method":"subscribe","req_id":{},"success":false,"symbol":"BTC/FOO"}}"#,
+        req_id
+    );
+    assert!(manager.handle_subscription_confirmation(&rejected).is_err());
+    assert_eq!(manager.pending_count(), 1);
+    
+    let confirmed = format!(
+        r#"{{"method":"subscribe","req_id":{},"result":{{"channel":"ticker","symbol":"BTC/USD"}},"success":true}}"#,
+        req_id
+    );
+    manager.handle_subscription_confirmation(&confirmed).unwrap();
+    assert_eq!(manager.pending_count(), 0);
+    
+    // Without an echoed symbol the whole request is rejected
+    let (messages, _ack) = manager.create_subscription_request(&[Channel::new("trade").with_symbol("ETH/USD")]).unwrap();
+    let rejected = format!(
+        r#"{{"error":"Subscription limit reached","method":"subscribe","req_id":{},"success":false}}"#,
+        request_id(&messages[0])
+    );
+    assert!(manager.handle_subscription_confirmation(&rejected).is_err());
+    assert_eq!(manager.pending_count(), 0);
+}
+
+#[tokio::test]
+async fn test_pool_places_subscriptions_within_limits() {
+    let pool_config = PoolConfig::new()
+        .with_max_connections(2)
+        .with_max_subscriptions_per_connection(2)
+        .with_max_books_per_connection(1);
+    assert!(pool_config.validate().is_ok());
+    assert!(PoolConfig::new().with_max_connections(0).validate().is_err());
+    let mut pool = KrakenWsPool::new(ClientConfig::default(), pool_config);
+    
+    // Offline, channels are only queued on their connection
+    let ticker = |symbol: &str| Channel::new("ticker").with_symbol(symbol);
+    pool.subscribe(vec![ticker("XBT/USD"), ticker("ETH/USD"), ticker("SOL/USD")]).await.unwrap();
+    assert_eq!(pool.connection_count().await, 2);
+    let first = pool.connection_for(&ticker("XBT/USD")).await.unwrap();
+    assert_eq!(pool.connection_for(&ticker("ETH/USD")).await, Some(first));
+    let second = pool.connection_for(&ticker("SOL/USD")).await.unwrap();
+    assert_ne!(first, second);
+    
+    // Resubscribing goes to the same connection
+    pool.subscribe(vec![ticker("SOL/USD")]).await.unwrap();
+    let book = |symbol: &str| Channel::new("book").with_symbol(symbol).with_depth(100);
+    pool.subscribe(vec![book("XBT/USD")]).await.unwrap();
+    assert_eq!(pool.connection_for(&book("XBT/USD")).await, Some(second));
+    
+    // Every connection is full: nothing of the request is subscribed
+    let result = pool.subscribe(vec![ticker("ADA/USD"), book("ETH/USD")]).await;
+    assert!(matches!(
+        result,
+        Err(SdkError::Subscription(SubscriptionError::PoolExhausted { connections: 2, .. }))
+    ));
+    assert_eq!(pool.connection_for(&ticker("ADA/USD")).await, None);
+    
+    let stats = pool.connection_stats().await;
+    assert_eq!(stats.iter().map(|c| c.channels.len()).collect::<Vec<_>>(), vec![2, 2]);
+    assert_eq!(stats.iter().map(|c| c.books).collect::<Vec<_>>(), vec![0, 1]);
+    
+    // A connection left without subscriptions is closed
+    pool.unsubscribe(vec![ticker("SOL/USD"), book("XBT/USD")]).await.unwrap();
+    assert_eq!(pool.connection_count().await, 1);
+    assert!(matches!(
+        pool.unsubscribe(vec![ticker("SOL/USD")]).await,
+        Err(SdkError::Subscription(SubscriptionError::NotSubscribed(_)))
+    ));
+}
+
+#[tokio::test]
+async fn test_subscription_replay_messages() {
+    let manager = SubscriptionManager::new();
+    assert!(manager.create_replay_messages().unwrap().is_empty());
+    
+    let book = Channel::new("book").with_symbol("XBT/USD").with_depth(25);
+    let ticker = Channel::new("ticker").with_symbol("ETH/USD");
+    manager.create_subscription_messages(&[book.clone(), ticker.clone()]).unwrap();
+    
+    let ack = r#"{"channelID":10,"channelName":"book-25","event":"subscriptionStatus","pair":"XBT/USD","status":"subscribed","subscription":{"depth":25,"name":"book"}}"#;
+    manager.handle_subscription_confirmation(ack).unwrap();
+    assert!(manager.is_subscribed(&book));
+    
+    // Confirmed and still-pending channels are both replayed, keeping their depth
+    assert_eq!(manager.get_subscribed_channels(), vec![book.clone(), ticker.clone()]);
+    let messages = manager.create_replay_messages().unwrap();
+    assert_eq!(messages.len(), 2);
+    let book_request: serde_json::Value = serde_json::from_str(messages[0].to_text().unwrap()).unwrap();
+    assert_eq!(book_request["event"], "subscribe");
+    assert_eq!(book_request["subscription"]["depth"], 25);
+    
+    // Replayed subscriptions wait for a fresh confirmation
+    assert!(!manager.is_subscribed(&book));
+    assert_eq!(manager.get_subscribed_channels().len(), 2);
+}
+
+#[tokio::test]
+async fn test_subscription_channel_routes() {
+    let manager = SubscriptionManager::new();
+    let book = Channel::new("book").with_symbol("XBT/USD").with_depth(10);
+    manager.create_subscription_messages(std::slice::from_ref(&book)).unwrap();
+    
+    let ack = r#"{"channelID":336,"channelName":"book-10","event":"subscriptionStatus","pair":"XBT/USD","status":"subscribed","subscription":{"depth":10,"name":"book"}}"#;
+    manager.handle_subscription_confirmation(ack).unwrap();
+    
+    let route = manager.route(336).expect("Expected a route for channel 336");
+    assert_eq!(route.channel_name, "book-10");
+    assert_eq!(route.channel, book);
+    assert!(manager.route(337).is_none());
+    
+    let unsubscribed = r#"{"channelID":336,"channelName":"book-10","event":"subscriptionStatus","pair":"XBT/USD","status":"unsubscribed","subscription":{"depth":10,"name":"book"}}"#;
+    manager.handle_unsubscription_confirmation(unsubscribed).unwrap();
+    assert!(manager.route(336).is_none());
+    
+    // Channel IDs don't survive a reconnect
+    manager.create_subscription_messages(&[book]).unwrap();
+    manager.handle_subscription_confirmation(ack).unwrap();
+    assert_eq!(manager.route_count(), 1);
+    manager.create_replay_messages().unwrap();
+    assert_eq!(manager.route_count(), 0);
+}
+
 // Test KrakenDataParser
 #[tokio::test]
 async fn test_kraken_parser_creation() {
-    let parser = KrakenDataParser::new();
+    let _parser = KrakenDataParser::new();
     // Parser should be created successfully
     // We can't test much without actual data, but creation should work
 }
@@ -263,7 +723,7 @@ async fn test_message_handler_creation() {
     let parser: Arc<dyn DataParser> = Arc::new(KrakenDataParser::new());
     let dispatcher = Arc::new(EventDispatcher::new());
     
-    let handler = MessageHandler::new(parser, dispatcher);
+    let _handler = MessageHandler::new(parser, dispatcher);
     // Handler should be created successfully
 }
 
@@ -309,6 +769,7 @@ async fn test_ticker_data_display() {
         last_price: Decimal::from_str("50000.5").unwrap(),
         volume: Decimal::from_str("100.0").unwrap(),
         timestamp: Utc::now(),
+        ..Default::default()
     };
     
     let display_str = format!("{}", ticker);
@@ -325,6 +786,7 @@ async fn test_trade_data_display() {
         side: TradeSide::Buy,
         timestamp: Utc::now(),
         trade_id: "test-123".to_string(),
+        backfilled: false,
     };
     
     let display_str = format!("{}", trade);
@@ -384,4 +846,206 @@ async fn test_contextual_error() {
     let display_str = format!("{}", contextual_error);
     assert!(display_str.contains("test error"));
     assert!(display_str.contains("test_operation"));
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_order_book_update_merge() {
+    let mut pending = book_update("BTC/USD", vec![level("100", "1"), level("101", "2")], vec![], None);
+    let later = book_update("BTC/USD", vec![level("100", "0"), level("99", "3")], vec![], Some(42));
+    
+    pending.merge(later);
+    
+    let bids: Vec<(String, String)> = pending
+        .bids
+        .iter()
+        .map(|level| (level.price.to_string(), level.volume.to_string()))
+        .collect();
+    assert_eq!(bids, vec![
+        ("100".to_string(), "0".to_string()),
+        ("101".to_string(), "2".to_string()),
+        ("99".to_string(), "3".to_string()),
+    ]);
+    assert_eq!(pending.checksum, Some(42));
+}
+
+#[tokio::test]
+async fn test_full_stream_coalesces_book_updates() {
+    let dispatcher = EventDispatcher::new().with_stream_buffer(2);
+    let mut events = dispatcher.create_event_stream();
+    
+    dispatcher.dispatch_orderbook(book_update("BTC/USD", vec![level("100", "1")], vec![], None));
+    dispatcher.dispatch_orderbook(book_update("BTC/USD", vec![level("101", "2")], vec![], None));
+    dispatcher.dispatch_orderbook(book_update("BTC/USD", vec![level("100", "0")], vec![], None));
+    
+    // The stream was full, so the third update merged into the second
+    assert_eq!(dispatcher.backpressure_stats().total_received, 3);
+    assert_eq!(events.coalesced(), 1);
+    assert_eq!(events.missed(), 0);
+    
+    match events.recv().await {
+        Some(SdkEvent::OrderBook(book)) => assert_eq!(book.bids.len(), 1),
+        other => panic!("expected order book, got {:?}", other),
+    }
+    match events.recv().await {
+        Some(SdkEvent::OrderBook(book)) => {
+            let prices: Vec<String> = book.bids.iter().map(|l| l.price.to_string()).collect();
+            assert_eq!(prices, vec!["101", "100"]);
+        }
+        other => panic!("expected order book, got {:?}", other),
+    }
+    assert!(events.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_delivery_task_bounds_and_orders_a_backlog() {
+    let dispatcher = Arc::new(EventDispatcher::new().with_backpressure(kraken_ws_sdk::backpressure::BackpressureConfig {
+        max_messages_per_second: 0,
+        max_buffer_size: 4,
+        ..Default::default()
+    }));
+    dispatcher.start_delivery();
+    let mut events = dispatcher.create_event_stream();
+    let trade = |symbol: &str, trade_id: &str| TradeData {
+        symbol: symbol.to_string(),
+        price: Decimal::from(100),
+        volume: Decimal::ONE,
+        side: TradeSide::Buy,
+        timestamp: Utc::now(),
+        trade_id: trade_id.to_string(),
+        backfilled: false,
+    };
+    
+    // The delivery task only runs once this test yields, so the publisher
+    // outpaces it and everything queues up
+    dispatcher.dispatch_orderbook(book_update("BTC/USD", vec![level("100", "1")], vec![], None));
+    dispatcher.dispatch_orderbook(book_update("BTC/USD", vec![level("101", "1")], vec![], None));
+    dispatcher.dispatch_trade(trade("BTC/USD", "1"));
+    // Merges into the queued book, which moves behind the trade
+    dispatcher.dispatch_orderbook(book_update("BTC/USD", vec![level("102", "1")], vec![], None));
+    dispatcher.dispatch_trade(trade("ETH/USD", "2"));
+    dispatcher.dispatch_trade(trade("ETH/USD", "3"));
+    let stats = dispatcher.backpressure_stats();
+    assert_eq!((stats.total_coalesced, stats.current_queue_depth), (2, 4));
+    assert!(events.is_empty());
+    
+    // Full: the oldest book makes room, and trades are queued regardless
+    dispatcher.dispatch_orderbook(book_update("ETH/USD", vec![level("200", "1")], vec![], None));
+    dispatcher.dispatch_trade(trade("ETH/USD", "4"));
+    assert_eq!(dispatcher.backpressure_stats().total_dropped, 1);
+    
+    let mut received = Vec::new();
+    while received.len() < 5 {
+        let event = tokio::time::timeout(Duration::from_secs(1), events.recv()).await.unwrap().unwrap();
+        received.push(match event {
+            SdkEvent::Trade(trade) => format!("trade {} {}", trade.symbol, trade.trade_id),
+            SdkEvent::OrderBook(book) => format!("book {} {}", book.symbol, book.bids[0].price),
+            other => panic!("unexpected event {:?}", other),
+        });
+    }
+    assert_eq!(received, vec!["trade BTC/USD 1", "trade ETH/USD 2", "trade ETH/USD 3", "book ETH/USD 200", "trade ETH/USD 4"]);
+    assert_eq!(dispatcher.backpressure_stats().current_queue_depth, 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_delivery_under_load_only_sheds_books() {
+    let dispatcher = Arc::new(EventDispatcher::new());
+    dispatcher.start_delivery();
+    let mut events = dispatcher.subscribe_with_buffer(EventFilter::new(), 10_000);
+    
+    // Well past the default 1000/s plus burst
+    for i in 0..1500u32 {
+        dispatcher.dispatch_orderbook(book_update("BTC/USD", vec![level(&(100 + i).to_string(), "1")], vec![], None));
+        dispatcher.dispatch_trade(TradeData {
+            symbol: "BTC/USD".to_string(),
+            price: Decimal::from(100),
+            volume: Decimal::ONE,
+            side: TradeSide::Buy,
+            timestamp: Utc::now(),
+            trade_id: i.to_string(),
+            backfilled: false,
+        });
+        if i % 100 == 0 {
+            dispatcher.dispatch_connection_state_change(ConnectionState::Connected);
+        }
+    }
+    
+    let (mut trades, mut states) = (Vec::new(), 0);
+    while trades.len() < 1500 || states < 15 {
+        match tokio::time::timeout(Duration::from_secs(2), events.recv()).await.unwrap().unwrap() {
+            SdkEvent::Trade(trade) => trades.push(trade.trade_id.parse::<u32>().unwrap()),
+            SdkEvent::State(_) => states += 1,
+            SdkEvent::OrderBook(_) => {}
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+    assert_eq!(trades, (0..1500).collect::<Vec<_>>());
+    assert_eq!(events.missed(), 0);
+}
+
+#[tokio::test]
+async fn test_slow_stream_reports_lag_without_blocking_others() {
+    let dispatcher = EventDispatcher::new();
+    let mut slow = dispatcher.subscribe_with_buffer(EventFilter::new(), 2);
+    let mut fast = dispatcher.create_event_stream();
+    
+    for i in 0..5 {
+        dispatcher.dispatch_error(SdkError::Network(format!("boom {}", i)));
+    }
+    
+    let fast_events: Vec<_> = std::iter::from_fn(|| fast.try_recv().ok()).collect();
+    assert_eq!(fast_events.len(), 5);
+    
+    assert_eq!(slow.missed(), 3);
+    assert!(matches!(slow.try_recv(), Ok(SdkEvent::Lagged(3))));
+    match slow.try_recv() {
+        Ok(SdkEvent::Error(SdkError::Network(message))) => assert_eq!(message, "boom 3"),
+        other => panic!("expected the fourth error, got {:?}", other),
+    }
+    assert!(matches!(slow.try_recv(), Ok(SdkEvent::Error(_))));
+    assert!(slow.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_filtered_streams() {
+    use futures_util::StreamExt;
+    
+    let dispatcher = EventDispatcher::new();
+    let mut btc_books = dispatcher.subscribe(
+        EventFilter::new().symbol("BTC/USD").data_type(DataType::OrderBook),
+    );
+    let mut errors = dispatcher.subscribe(EventFilter::new().kind(EventKind::Error));
+    let everything = dispatcher.create_event_stream();
+    assert_eq!(dispatcher.stream_count(), 3);
+    
+    dispatcher.dispatch_orderbook(book_update("ETH/USD", vec![level("10", "1")], vec![], None));
+    dispatcher.dispatch_orderbook(book_update("BTC/USD", vec![level("100", "1")], vec![], None));
+    dispatcher.dispatch_connection_state_change(ConnectionState::Connected);
+    dispatcher.dispatch_error(SdkError::Network("boom".to_string()));
+    
+    // Symbol-less events still reach a symbol-filtered stream
+    match btc_books.next().await {
+        Some(SdkEvent::OrderBook(book)) => assert_eq!(book.symbol, "BTC/USD"),
+        other => panic!("expected BTC book, got {:?}", other),
+    }
+    assert!(matches!(btc_books.next().await, Some(SdkEvent::State(ConnectionState::Connected))));
+    assert!(matches!(btc_books.next().await, Some(SdkEvent::Error(_))));
+    assert!(btc_books.try_recv().is_err());
+    
+    assert!(matches!(errors.try_recv(), Ok(SdkEvent::Error(_))));
+    assert!(errors.try_recv().is_err());
+    assert_eq!(everything.len(), 4);
+    
+    drop(everything);
+    assert_eq!(dispatcher.stream_count(), 2);
+}
+
+#[tokio::test]
+async fn test_stream_ends_when_dispatcher_dropped() {
+    let dispatcher = EventDispatcher::new();
+    let mut events = dispatcher.create_event_stream();
+    dispatcher.dispatch_error(SdkError::Network("last".to_string()));
+    drop(dispatcher);
+    
+    assert!(matches!(events.recv().await, Some(SdkEvent::Error(_))));
+    assert!(events.recv().await.is_none());
+}